@@ -0,0 +1,192 @@
+//! Structured API errors shared by the STT and LLM providers.
+//!
+//! Keeps the HTTP status, the provider's own error code and any server-provided retry hint
+//! next to the human-readable message, so retry decisions never have to parse message text.
+
+use std::fmt;
+use std::time::Duration;
+
+/// An error reported by a provider API.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ApiError {
+    /// HTTP status code, when the error came from an HTTP response.
+    pub status: Option<u16>,
+    /// Provider-specific error code (e.g. `rate_limit_exceeded`, `RESOURCE_EXHAUSTED`).
+    pub code: Option<String>,
+    pub message: String,
+    /// How long the server asked us to wait before retrying (`Retry-After`).
+    pub retry_after: Option<Duration>,
+}
+
+impl ApiError {
+    /// An error without HTTP details (malformed responses, refusals, ...).
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            ..Default::default()
+        }
+    }
+
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn with_status(mut self, status: u16) -> Self {
+        self.status = Some(status);
+        self
+    }
+
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn with_retry_after(mut self, retry_after: Duration) -> Self {
+        self.retry_after = Some(retry_after);
+        self
+    }
+
+    /// Build an error from a non-success response, consuming its body.
+    ///
+    /// The message reads `"<label> (<status>): <detail>"`, where the detail is the provider's
+    /// error message when the body is a recognised JSON error, or the raw body otherwise.
+    pub async fn from_response(label: &str, response: reqwest::Response) -> Self {
+        let status = response.status();
+        let retry_after = retry_after_from_headers(response.headers());
+        let body = response
+            .text()
+            .await
+            .unwrap_or_else(|_| "Unknown error".to_string());
+        let (code, detail) = parse_error_body(&body);
+
+        Self {
+            status: Some(status.as_u16()),
+            code,
+            message: format!("{} ({}): {}", label, status, detail.unwrap_or(body)),
+            retry_after,
+        }
+    }
+
+    /// 429 Too Many Requests.
+    pub fn is_rate_limited(&self) -> bool {
+        self.status == Some(429)
+    }
+
+    /// Transient server-side failures (timeouts, overload, bad gateways).
+    pub fn is_transient_server_error(&self) -> bool {
+        matches!(self.status, Some(408 | 500 | 502 | 503 | 504 | 529))
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl From<String> for ApiError {
+    fn from(message: String) -> Self {
+        Self::new(message)
+    }
+}
+
+impl From<&str> for ApiError {
+    fn from(message: &str) -> Self {
+        Self::new(message)
+    }
+}
+
+/// Read the server's retry hint: `retry-after-ms` (OpenAI/Azure) or the standard
+/// `Retry-After` (delta-seconds or an HTTP date).
+fn retry_after_from_headers(headers: &reqwest::header::HeaderMap) -> Option<Duration> {
+    let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok());
+
+    if let Some(ms) = header("retry-after-ms").and_then(|v| v.trim().parse::<f64>().ok()) {
+        if ms.is_finite() && ms >= 0.0 {
+            return Some(Duration::from_secs_f64(ms / 1000.0));
+        }
+    }
+    header("retry-after").and_then(|v| parse_retry_after(v, chrono::Utc::now()))
+}
+
+/// Parse a `Retry-After` value relative to `now`.
+fn parse_retry_after(value: &str, now: chrono::DateTime<chrono::Utc>) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(secs) = value.parse::<f64>() {
+        return (secs.is_finite() && secs >= 0.0).then(|| Duration::from_secs_f64(secs));
+    }
+
+    let date = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    // A date in the past means "now".
+    Some((date.with_timezone(&chrono::Utc) - now).to_std().unwrap_or(Duration::ZERO))
+}
+
+/// Extract `(code, message)` from the common JSON error shapes:
+/// - `{"error": {"message": ..., "code"/"status"/"type": ...}}` (OpenAI, Groq, Anthropic, Gemini)
+/// - `{"error": "..."}` (AssemblyAI, Ollama)
+/// - `{"err_code": ..., "err_msg": ...}` (Deepgram)
+fn parse_error_body(body: &str) -> (Option<String>, Option<String>) {
+    let Ok(value) = serde_json::from_str::<serde_json::Value>(body) else {
+        return (None, None);
+    };
+    let string = |v: &serde_json::Value| v.as_str().map(str::to_string);
+
+    match &value["error"] {
+        serde_json::Value::String(message) => (None, Some(message.clone())),
+        error @ serde_json::Value::Object(_) => {
+            let code = string(&error["code"])
+                .or_else(|| string(&error["status"]))
+                .or_else(|| string(&error["type"]));
+            (code, string(&error["message"]))
+        }
+        _ => (string(&value["err_code"]), string(&value["err_msg"])),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_retry_after_seconds_and_date() {
+        let now = chrono::DateTime::parse_from_rfc2822("Wed, 21 Oct 2015 07:28:00 GMT")
+            .unwrap()
+            .with_timezone(&chrono::Utc);
+
+        assert_eq!(parse_retry_after("3", now), Some(Duration::from_secs(3)));
+        assert_eq!(parse_retry_after("0.5", now), Some(Duration::from_millis(500)));
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:28:10 GMT", now),
+            Some(Duration::from_secs(10))
+        );
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:27:00 GMT", now),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(parse_retry_after("soon", now), None);
+        assert_eq!(parse_retry_after("-1", now), None);
+    }
+
+    #[test]
+    fn test_retry_after_ms_takes_precedence() {
+        let mut headers = reqwest::header::HeaderMap::new();
+        headers.insert("retry-after", "20".parse().unwrap());
+        headers.insert("retry-after-ms", "1500".parse().unwrap());
+
+        assert_eq!(retry_after_from_headers(&headers), Some(Duration::from_millis(1500)));
+    }
+
+    #[test]
+    fn test_parse_error_body_shapes() {
+        assert_eq!(
+            parse_error_body(r#"{"error":{"message":"Slow down","type":"requests","code":"rate_limit_exceeded"}}"#),
+            (Some("rate_limit_exceeded".to_string()), Some("Slow down".to_string()))
+        );
+        assert_eq!(
+            parse_error_body(r#"{"error":{"code":429,"message":"Quota","status":"RESOURCE_EXHAUSTED"}}"#),
+            (Some("RESOURCE_EXHAUSTED".to_string()), Some("Quota".to_string()))
+        );
+        assert_eq!(
+            parse_error_body(r#"{"error":"model not found"}"#),
+            (None, Some("model not found".to_string()))
+        );
+        assert_eq!(
+            parse_error_body(r#"{"err_code":"INVALID_AUTH","err_msg":"Bad key"}"#),
+            (Some("INVALID_AUTH".to_string()), Some("Bad key".to_string()))
+        );
+        assert_eq!(parse_error_body("<html>502</html>"), (None, None));
+    }
+}
@@ -5,12 +5,14 @@
 //!
 //! Supports optional Voice Activity Detection (VAD) for auto-stop functionality.
 
-use crate::vad::{VadConfig, VadEvent, VadFrameProcessor};
+use crate::stt::{f32_to_pcm16, AudioChunkSender, AudioEncoding, AudioFormat};
+use crate::vad::{find_pause_midpoints, VadConfig, VadEvent, VadFrameProcessor};
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
 use cpal::SampleFormat;
 use hound::{WavSpec, WavWriter};
 use serde::{Deserialize, Serialize};
 use std::io::Cursor;
+use std::ops::Range;
 use std::sync::mpsc;
 use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
 use std::sync::{Arc, Mutex as StdMutex};
@@ -241,6 +243,9 @@ pub struct AudioEncodeConfig {
     pub noise_suppression_enabled: bool,
     /// If enabled, compute a best-effort speech presence boolean using WebRTC VAD.
     pub detect_speech_presence: bool,
+    /// Output encoding. Ogg/Opus is always mono at an Opus sample rate (16kHz unless the
+    /// audio already has a supported rate).
+    pub encoding: AudioEncoding,
 }
 
 impl Default for AudioEncodeConfig {
@@ -253,10 +258,42 @@ impl Default for AudioEncodeConfig {
             agc_enabled: false,
             noise_suppression_enabled: false,
             detect_speech_presence: false,
+            encoding: AudioEncoding::Wav,
         }
     }
 }
 
+impl AudioEncodeConfig {
+    /// Encode samples as-is (for audio that was already preprocessed).
+    pub fn passthrough() -> Self {
+        Self {
+            noise_gate_threshold_dbfs: None,
+            downmix_to_mono: false,
+            resample_to_16khz: false,
+            highpass_enabled: false,
+            agc_enabled: false,
+            noise_suppression_enabled: false,
+            detect_speech_presence: false,
+            encoding: AudioEncoding::Wav,
+        }
+    }
+}
+
+/// Audio encoded for upload, with the format providers need to label it.
+#[derive(Debug, Clone)]
+pub struct EncodedAudio {
+    pub bytes: Vec<u8>,
+    pub format: AudioFormat,
+}
+
+/// A piece of a longer recording, encoded as WAV.
+#[derive(Debug, Clone)]
+pub struct WavChunk {
+    pub wav_bytes: Vec<u8>,
+    /// Start of the chunk within the recording, in seconds.
+    pub start_secs: f32,
+}
+
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct AudioCaptureDiagnostics {
     pub stats: AudioLevelStats,
@@ -302,7 +339,9 @@ pub struct AudioBuffer {
 impl AudioBuffer {
     /// Create a new audio buffer with the specified parameters
     pub fn new(sample_rate: u32, channels: u16, max_duration_secs: f32) -> Self {
-        let capacity = (sample_rate as f32 * max_duration_secs * channels as f32) as usize;
+        // Pre-allocate at most a minute; long recordings (up to an hour or more) grow as needed.
+        let capacity =
+            (sample_rate as f32 * max_duration_secs.min(60.0) * channels as f32) as usize;
         Self {
             samples: Vec::with_capacity(capacity),
             sample_rate,
@@ -411,6 +450,18 @@ impl AudioBuffer {
         &self,
         cfg: AudioEncodeConfig,
     ) -> Result<(Vec<u8>, AudioCaptureDiagnostics), AudioCaptureError> {
+        let (encoded, diagnostics) = self.encode_with_config(AudioEncodeConfig {
+            encoding: AudioEncoding::Wav,
+            ..cfg
+        })?;
+        Ok((encoded.bytes, diagnostics))
+    }
+
+    /// Process the buffer contents and encode them as `cfg.encoding`.
+    pub fn encode_with_config(
+        &self,
+        cfg: AudioEncodeConfig,
+    ) -> Result<(EncodedAudio, AudioCaptureDiagnostics), AudioCaptureError> {
         let diagnostics = if cfg.detect_speech_presence {
             Some(detect_speech_presence(
                 &self.samples,
@@ -465,32 +516,49 @@ impl AudioBuffer {
             );
         }
 
-        let spec = WavSpec {
-            channels: out_channels,
-            sample_rate: out_sample_rate,
-            bits_per_sample: 16,
-            sample_format: hound::SampleFormat::Int,
-        };
-
-        let mut cursor = Cursor::new(Vec::new());
-        {
-            let mut writer = WavWriter::new(&mut cursor, spec)
-                .map_err(|e| AudioCaptureError::Encoding(e.to_string()))?;
-
-            for &sample in &processed_samples {
-                let sample_i16 = (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16;
-                writer
-                    .write_sample(sample_i16)
-                    .map_err(|e| AudioCaptureError::Encoding(e.to_string()))?;
+        let (bytes, out_sample_rate, out_channels) = match cfg.encoding {
+            AudioEncoding::Wav => (
+                encode_wav(&processed_samples, out_sample_rate, out_channels)?,
+                out_sample_rate,
+                out_channels,
+            ),
+            AudioEncoding::Pcm16 => (
+                f32_to_pcm16(&processed_samples)
+                    .iter()
+                    .flat_map(|s| s.to_le_bytes())
+                    .collect(),
+                out_sample_rate,
+                out_channels,
+            ),
+            AudioEncoding::Flac => (
+                crate::audio_codec::encode_flac(&processed_samples, out_sample_rate, out_channels)?,
+                out_sample_rate,
+                out_channels,
+            ),
+            AudioEncoding::OggOpus => {
+                let mut mono = if out_channels > 1 {
+                    downmix_interleaved_to_mono(&processed_samples, out_channels as usize)
+                } else {
+                    processed_samples
+                };
+                let mut opus_rate = out_sample_rate;
+                if !crate::audio_codec::OPUS_SAMPLE_RATES.contains(&opus_rate) {
+                    mono = crate::vad::resample_to_16khz(&mono, opus_rate);
+                    opus_rate = 16000;
+                }
+                (crate::audio_codec::encode_ogg_opus(&mono, opus_rate)?, opus_rate, 1)
             }
-
-            writer
-                .finalize()
-                .map_err(|e| AudioCaptureError::Encoding(e.to_string()))?;
-        }
+        };
 
         Ok((
-            cursor.into_inner(),
+            EncodedAudio {
+                bytes,
+                format: AudioFormat {
+                    sample_rate: out_sample_rate,
+                    channels: out_channels as u8,
+                    encoding: cfg.encoding,
+                },
+            },
             AudioCaptureDiagnostics {
                 stats: self.level_stats(),
                 speech_detected: diagnostics,
@@ -498,6 +566,64 @@ impl AudioBuffer {
         ))
     }
 
+    /// Decode 16-bit PCM WAV bytes (e.g. a finished or persisted recording).
+    pub fn from_wav_bytes(wav_bytes: &[u8]) -> Result<Self, AudioCaptureError> {
+        let mut reader = hound::WavReader::new(Cursor::new(wav_bytes))
+            .map_err(|e| AudioCaptureError::Encoding(e.to_string()))?;
+        let spec = reader.spec();
+        if spec.sample_format != hound::SampleFormat::Int || spec.bits_per_sample != 16 {
+            return Err(AudioCaptureError::Encoding(format!(
+                "Unsupported WAV format ({:?}, {} bits)",
+                spec.sample_format, spec.bits_per_sample
+            )));
+        }
+
+        let samples = reader
+            .samples::<i16>()
+            .map(|s| s.map(|s| s as f32 / i16::MAX as f32))
+            .collect::<Result<Vec<f32>, _>>()
+            .map_err(|e| AudioCaptureError::Encoding(e.to_string()))?;
+
+        let channels = spec.channels.max(1);
+        let frames = samples.len() / channels as usize;
+        Ok(Self {
+            samples,
+            sample_rate: spec.sample_rate,
+            channels,
+            max_duration_secs: frames as f32 / spec.sample_rate.max(1) as f32,
+        })
+    }
+
+    /// Split into WAV chunks of at most `max_chunk_secs`, cutting in pauses between speech where
+    /// possible (VAD), and mid-word only when a stretch of speech has no usable pause.
+    ///
+    /// Samples are encoded as-is; apply preprocessing before splitting.
+    pub fn split_at_pauses(&self, max_chunk_secs: f32) -> Result<Vec<WavChunk>, AudioCaptureError> {
+        let channels = self.channels.max(1) as usize;
+        let sample_rate = self.sample_rate.max(1);
+        let mono = downmix_interleaved_to_mono(&self.samples, channels);
+        let pauses = find_pause_midpoints(&mono, sample_rate, VadConfig::default());
+        let max_frames = ((max_chunk_secs.max(1.0) * sample_rate as f32) as usize).max(1);
+
+        plan_chunks(mono.len(), &pauses, max_frames)
+            .into_iter()
+            .map(|frames| {
+                let chunk = AudioBuffer {
+                    samples: self.samples[frames.start * channels..frames.end * channels].to_vec(),
+                    sample_rate,
+                    channels: self.channels,
+                    max_duration_secs: self.max_duration_secs,
+                };
+                let (wav_bytes, _diagnostics) =
+                    chunk.to_wav_bytes_with_config(AudioEncodeConfig::passthrough())?;
+                Ok(WavChunk {
+                    wav_bytes,
+                    start_secs: frames.start as f32 / sample_rate as f32,
+                })
+            })
+            .collect()
+    }
+
     /// Get the sample rate
     #[cfg_attr(not(test), allow(dead_code))]
     pub fn sample_rate(&self) -> u32 {
@@ -511,6 +637,81 @@ impl AudioBuffer {
     }
 }
 
+/// Plan chunk boundaries (in frames) no longer than `max_frames`, preferring the latest pause
+/// in each window. Pauses in the first quarter of a window are ignored to avoid tiny chunks.
+fn plan_chunks(total_frames: usize, pauses: &[usize], max_frames: usize) -> Vec<Range<usize>> {
+    let mut chunks = Vec::new();
+    let mut start = 0;
+
+    while total_frames - start > max_frames {
+        let window_end = start + max_frames;
+        let min_end = start + max_frames / 4;
+        let end = pauses
+            .iter()
+            .copied()
+            .filter(|&p| p > min_end && p <= window_end)
+            .max()
+            .unwrap_or(window_end);
+        chunks.push(start..end);
+        start = end;
+    }
+
+    if start < total_frames || chunks.is_empty() {
+        chunks.push(start..total_frames);
+    }
+    chunks
+}
+
+/// Re-encode 16-bit WAV bytes (e.g. a processed recording) as `encoding`, without further
+/// preprocessing.
+pub fn encode_wav_as(
+    wav_bytes: &[u8],
+    encoding: AudioEncoding,
+) -> Result<EncodedAudio, AudioCaptureError> {
+    let (encoded, _diagnostics) = AudioBuffer::from_wav_bytes(wav_bytes)?.encode_with_config(
+        AudioEncodeConfig {
+            encoding,
+            ..AudioEncodeConfig::passthrough()
+        },
+    )?;
+    Ok(encoded)
+}
+
+fn encode_wav(samples: &[f32], sample_rate: u32, channels: u16) -> Result<Vec<u8>, AudioCaptureError> {
+    let spec = WavSpec {
+        channels,
+        sample_rate,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+
+    let mut cursor = Cursor::new(Vec::new());
+    {
+        let mut writer = WavWriter::new(&mut cursor, spec)
+            .map_err(|e| AudioCaptureError::Encoding(e.to_string()))?;
+
+        for &sample in samples {
+            let sample_i16 = (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16;
+            writer
+                .write_sample(sample_i16)
+                .map_err(|e| AudioCaptureError::Encoding(e.to_string()))?;
+        }
+
+        writer
+            .finalize()
+            .map_err(|e| AudioCaptureError::Encoding(e.to_string()))?;
+    }
+
+    Ok(cursor.into_inner())
+}
+
+/// Duration of WAV audio in seconds, read from the header.
+pub fn wav_duration_secs(wav_bytes: &[u8]) -> Option<f32> {
+    let reader = hound::WavReader::new(Cursor::new(wav_bytes)).ok()?;
+    let sample_rate = reader.spec().sample_rate;
+    (sample_rate > 0).then(|| reader.duration() as f32 / sample_rate as f32)
+}
+
 /// Basic audio level metrics for gating/diagnostics.
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct AudioLevelStats {
@@ -750,6 +951,9 @@ pub struct AudioCapture {
 
     // Most recent realtime waveform buckets (for true waveform rendering).
     waveform_meter: Arc<AudioWaveformMeter>,
+
+    // Optional sink for mono PCM16 chunks (streaming STT). Consumed by the next `start`.
+    chunk_sink: Option<AudioChunkSender>,
 }
 
 impl AudioCapture {
@@ -763,6 +967,7 @@ impl AudioCapture {
             vad_config: VadAutoStopConfig::default(),
             level_meter: Arc::new(AudioLevelMeter::default()),
             waveform_meter: Arc::new(AudioWaveformMeter::default()),
+            chunk_sink: None,
         }
     }
 
@@ -776,6 +981,7 @@ impl AudioCapture {
             vad_config,
             level_meter: Arc::new(AudioLevelMeter::default()),
             waveform_meter: Arc::new(AudioWaveformMeter::default()),
+            chunk_sink: None,
         }
     }
 
@@ -811,6 +1017,14 @@ impl AudioCapture {
         &self.vad_config
     }
 
+    /// Stream mono PCM16 chunks of the next recording into `sink`.
+    ///
+    /// The sink is consumed by the next `start`/`start_with_device_name` call and is
+    /// dropped when that recording stops, which closes the channel.
+    pub fn set_chunk_sink(&mut self, sink: Option<AudioChunkSender>) {
+        self.chunk_sink = sink;
+    }
+
     /// Start recording audio from the default input device.
     ///
     /// Prefer `start_with_device_name` when you need to honor a user-selected mic.
@@ -899,6 +1113,7 @@ impl AudioCapture {
         let stream_config: cpal::StreamConfig = config.into();
         let vad_config = self.vad_config.clone();
         let sample_rate = self.sample_rate;
+        let chunk_tx = self.chunk_sink.take();
 
         // Spawn capture thread
         let thread_handle = thread::spawn(move || {
@@ -913,6 +1128,7 @@ impl AudioCapture {
                 event_tx,
                 vad_config,
                 sample_rate,
+                chunk_tx,
             )
         });
 
@@ -1030,6 +1246,7 @@ impl AudioCapture {
             agc_enabled: false,
             noise_suppression_enabled: false,
             detect_speech_presence: false,
+            encoding: AudioEncoding::Wav,
         })?;
 
         // "After": apply current user settings.
@@ -1120,6 +1337,7 @@ fn run_capture_thread(
     event_tx: mpsc::Sender<AudioCaptureEvent>,
     vad_config: VadAutoStopConfig,
     sample_rate: u32,
+    chunk_tx: Option<AudioChunkSender>,
 ) -> Result<(), AudioCaptureError> {
     use cpal::Sample;
 
@@ -1166,6 +1384,7 @@ fn run_capture_thread(
             let meter = meter.clone();
             let waveform_meter = waveform_meter.clone();
             let vad_tx = if vad_config.enabled { Some(vad_samples_tx.clone()) } else { None };
+            let chunk_tx = chunk_tx.clone();
             let channels = config.channels as usize;
             device.build_input_stream(
                 &config,
@@ -1193,14 +1412,19 @@ fn run_capture_thread(
                         buf.append(data);
                     }
 
-                    // Send samples to VAD thread if enabled
-                    if let Some(ref tx) = vad_tx {
+                    // Send mono samples to the VAD thread and/or streaming STT session
+                    if vad_tx.is_some() || chunk_tx.is_some() {
                         let mono = if channels > 1 {
                             downmix_interleaved_chunk_to_mono(data, channels)
                         } else {
                             data.to_vec()
                         };
-                        let _ = tx.send(mono);
+                        if let Some(ref tx) = chunk_tx {
+                            let _ = tx.send(f32_to_pcm16(&mono));
+                        }
+                        if let Some(ref tx) = vad_tx {
+                            let _ = tx.send(mono);
+                        }
                     }
                 },
                 err_fn,
@@ -1212,6 +1436,7 @@ fn run_capture_thread(
             let meter = meter.clone();
             let waveform_meter = waveform_meter.clone();
             let vad_tx = if vad_config.enabled { Some(vad_samples_tx.clone()) } else { None };
+            let chunk_tx = chunk_tx.clone();
             let channels = config.channels as usize;
             device.build_input_stream(
                 &config,
@@ -1242,14 +1467,19 @@ fn run_capture_thread(
                         buf.append(&samples);
                     }
 
-                    // Send samples to VAD thread if enabled
-                    if let Some(ref tx) = vad_tx {
+                    // Send mono samples to the VAD thread and/or streaming STT session
+                    if vad_tx.is_some() || chunk_tx.is_some() {
                         let mono = if channels > 1 {
                             downmix_interleaved_chunk_to_mono(&samples, channels)
                         } else {
                             samples
                         };
-                        let _ = tx.send(mono);
+                        if let Some(ref tx) = chunk_tx {
+                            let _ = tx.send(f32_to_pcm16(&mono));
+                        }
+                        if let Some(ref tx) = vad_tx {
+                            let _ = tx.send(mono);
+                        }
                     }
                 },
                 err_fn,
@@ -1261,6 +1491,7 @@ fn run_capture_thread(
             let meter = meter.clone();
             let waveform_meter = waveform_meter.clone();
             let vad_tx = if vad_config.enabled { Some(vad_samples_tx.clone()) } else { None };
+            let chunk_tx = chunk_tx.clone();
             let channels = config.channels as usize;
             device.build_input_stream(
                 &config,
@@ -1291,14 +1522,19 @@ fn run_capture_thread(
                         buf.append(&samples);
                     }
 
-                    // Send samples to VAD thread if enabled
-                    if let Some(ref tx) = vad_tx {
+                    // Send mono samples to the VAD thread and/or streaming STT session
+                    if vad_tx.is_some() || chunk_tx.is_some() {
                         let mono = if channels > 1 {
                             downmix_interleaved_chunk_to_mono(&samples, channels)
                         } else {
                             samples
                         };
-                        let _ = tx.send(mono);
+                        if let Some(ref tx) = chunk_tx {
+                            let _ = tx.send(f32_to_pcm16(&mono));
+                        }
+                        if let Some(ref tx) = vad_tx {
+                            let _ = tx.send(mono);
+                        }
                     }
                 },
                 err_fn,
@@ -1402,6 +1638,44 @@ mod tests {
         assert_eq!(&wav_bytes[0..4], b"RIFF");
     }
 
+    #[test]
+    fn test_plan_chunks_prefers_latest_pause() {
+        assert_eq!(plan_chunks(100, &[], 200), vec![0..100]);
+        assert_eq!(plan_chunks(250, &[30, 80, 150, 190], 100), vec![0..80, 80..150, 150..250]);
+        // No pause in range: hard cut at the window end.
+        assert_eq!(plan_chunks(250, &[10], 100), vec![0..100, 100..200, 200..250]);
+    }
+
+    #[test]
+    fn test_wav_round_trip_and_split() {
+        let mut buffer = AudioBuffer::new(16000, 1, 60.0);
+        buffer.append(&vec![0.0; 16000 * 5]);
+        let wav_bytes = buffer.to_wav_bytes().expect("Failed to encode WAV");
+        assert_eq!(wav_duration_secs(&wav_bytes), Some(5.0));
+
+        let decoded = AudioBuffer::from_wav_bytes(&wav_bytes).expect("Failed to decode WAV");
+        assert_eq!(decoded.len(), 16000 * 5);
+
+        let chunks = decoded.split_at_pauses(2.0).expect("Failed to split");
+        let starts: Vec<f32> = chunks.iter().map(|c| c.start_secs).collect();
+        assert_eq!(starts, vec![0.0, 2.0, 4.0]);
+        assert_eq!(wav_duration_secs(&chunks[2].wav_bytes), Some(1.0));
+    }
+
+    #[test]
+    fn test_encode_wav_as_flac() {
+        let mut buffer = AudioBuffer::new(48000, 2, 60.0);
+        buffer.append(&vec![0.0; 48000 * 2]);
+        let wav_bytes = buffer.to_wav_bytes().expect("Failed to encode WAV");
+
+        let encoded = encode_wav_as(&wav_bytes, AudioEncoding::Flac).expect("Failed to encode FLAC");
+        assert_eq!(&encoded.bytes[..4], b"fLaC");
+        assert!(encoded.bytes.len() < wav_bytes.len());
+        assert_eq!(encoded.format.sample_rate, 48000);
+        assert_eq!(encoded.format.channels, 1);
+        assert_eq!(encoded.format.encoding, AudioEncoding::Flac);
+    }
+
     #[test]
     fn test_audio_buffer_max_duration() {
         let mut buffer = AudioBuffer::new(1000, 1, 1.0); // 1 second max
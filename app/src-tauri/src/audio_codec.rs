@@ -0,0 +1,173 @@
+//! Compressed audio encoders for STT uploads.
+//!
+//! - FLAC (lossless, pure Rust via `flacenc`) roughly halves the size of a speech WAV.
+//! - Ogg/Opus (lossy, libopus) shrinks it by an order of magnitude. It needs the
+//!   `opus-encoding` feature; without it, encoding fails and callers fall back to WAV.
+//!
+//! Samples are interleaved f32 in [-1, 1], quantized to 16 bits like the WAV encoder.
+
+use crate::audio_capture::AudioCaptureError;
+
+fn to_i16(sample: f32) -> i16 {
+    (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16
+}
+
+/// Encode interleaved samples as a 16-bit FLAC stream.
+pub fn encode_flac(
+    samples: &[f32],
+    sample_rate: u32,
+    channels: u16,
+) -> Result<Vec<u8>, AudioCaptureError> {
+    use flacenc::component::BitRepr;
+    use flacenc::error::Verify;
+
+    let config = flacenc::config::Encoder::default()
+        .into_verified()
+        .map_err(|(_, e)| AudioCaptureError::Encoding(format!("FLAC config: {:?}", e)))?;
+
+    let samples: Vec<i32> = samples.iter().map(|&s| to_i16(s) as i32).collect();
+    let source = flacenc::source::MemSource::from_samples(
+        &samples,
+        channels.max(1) as usize,
+        16,
+        sample_rate as usize,
+    );
+
+    let stream = flacenc::encode_with_fixed_block_size(&config, source, config.block_size)
+        .map_err(|e| AudioCaptureError::Encoding(format!("FLAC: {:?}", e)))?;
+
+    let mut sink = flacenc::bitsink::ByteSink::new();
+    stream
+        .write(&mut sink)
+        .map_err(|e| AudioCaptureError::Encoding(format!("FLAC: {:?}", e)))?;
+    Ok(sink.as_slice().to_vec())
+}
+
+/// Sample rates Opus encodes natively.
+pub const OPUS_SAMPLE_RATES: [u32; 5] = [8000, 12000, 16000, 24000, 48000];
+
+/// Target bitrate for speech; well above what Whisper-class models need.
+#[cfg(feature = "opus-encoding")]
+const OPUS_BITRATE: i32 = 24_000;
+
+/// Encode mono samples as Ogg/Opus (RFC 7845). `sample_rate` must be in `OPUS_SAMPLE_RATES`.
+#[cfg(feature = "opus-encoding")]
+pub fn encode_ogg_opus(samples: &[f32], sample_rate: u32) -> Result<Vec<u8>, AudioCaptureError> {
+    use ogg::writing::{PacketWriteEndInfo, PacketWriter};
+
+    const SERIAL: u32 = 0x5456_4f50;
+    const MAX_PACKET_BYTES: usize = 4000;
+
+    let opus_error = |e: opus::Error| AudioCaptureError::Encoding(format!("Opus: {}", e));
+    let io_error = |e: std::io::Error| AudioCaptureError::Encoding(format!("Ogg: {}", e));
+
+    if !OPUS_SAMPLE_RATES.contains(&sample_rate) {
+        return Err(AudioCaptureError::Encoding(format!(
+            "Opus does not support {} Hz audio",
+            sample_rate
+        )));
+    }
+
+    let mut encoder = opus::Encoder::new(sample_rate, opus::Channels::Mono, opus::Application::Voip)
+        .map_err(opus_error)?;
+    encoder
+        .set_bitrate(opus::Bitrate::Bits(OPUS_BITRATE))
+        .map_err(opus_error)?;
+
+    // Ogg granule positions and the pre-skip are always counted at 48 kHz.
+    let granule_scale = (48000 / sample_rate) as u64;
+    let pre_skip = encoder.get_lookahead().map_err(opus_error)? as u64 * granule_scale;
+
+    let mut head = Vec::with_capacity(19);
+    head.extend_from_slice(b"OpusHead");
+    head.push(1); // version
+    head.push(1); // channel count
+    head.extend_from_slice(&(pre_skip as u16).to_le_bytes());
+    head.extend_from_slice(&sample_rate.to_le_bytes());
+    head.extend_from_slice(&0i16.to_le_bytes()); // output gain
+    head.push(0); // channel mapping family
+
+    let vendor = concat!("tangerine-voice ", env!("CARGO_PKG_VERSION"));
+    let mut tags = Vec::new();
+    tags.extend_from_slice(b"OpusTags");
+    tags.extend_from_slice(&(vendor.len() as u32).to_le_bytes());
+    tags.extend_from_slice(vendor.as_bytes());
+    tags.extend_from_slice(&0u32.to_le_bytes()); // no user comments
+
+    let mut writer = PacketWriter::new(Vec::new());
+    writer
+        .write_packet(head, SERIAL, PacketWriteEndInfo::EndPage, 0)
+        .map_err(io_error)?;
+    writer
+        .write_packet(tags, SERIAL, PacketWriteEndInfo::EndPage, 0)
+        .map_err(io_error)?;
+
+    // 20 ms frames; the last one is zero-padded and trimmed again by its granule position.
+    let frame_len = (sample_rate / 50) as usize;
+    let pcm: Vec<i16> = samples.iter().map(|&s| to_i16(s)).collect();
+    let frame_count = pcm.len().div_ceil(frame_len).max(1);
+    let mut frame = vec![0i16; frame_len];
+
+    for index in 0..frame_count {
+        let start = index * frame_len;
+        let end = (start + frame_len).min(pcm.len());
+        frame.fill(0);
+        if start < end {
+            frame[..end - start].copy_from_slice(&pcm[start..end]);
+        }
+
+        let packet = encoder
+            .encode_vec(&frame, MAX_PACKET_BYTES)
+            .map_err(opus_error)?;
+        let last = index + 1 == frame_count;
+        let granule = pre_skip + end as u64 * granule_scale;
+        let end_info = if last {
+            PacketWriteEndInfo::EndStream
+        } else {
+            PacketWriteEndInfo::NormalPacket
+        };
+        writer
+            .write_packet(packet, SERIAL, end_info, granule)
+            .map_err(io_error)?;
+    }
+
+    Ok(writer.into_inner())
+}
+
+#[cfg(not(feature = "opus-encoding"))]
+pub fn encode_ogg_opus(_samples: &[f32], _sample_rate: u32) -> Result<Vec<u8>, AudioCaptureError> {
+    Err(AudioCaptureError::Encoding(
+        "Ogg/Opus encoding requires building with the `opus-encoding` feature".to_string(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flac_is_smaller_than_wav() {
+        let samples: Vec<f32> = (0..16000)
+            .map(|i| (i as f32 * 440.0 * std::f32::consts::TAU / 16000.0).sin() * 0.3)
+            .collect();
+
+        let flac = encode_flac(&samples, 16000, 1).unwrap();
+        assert_eq!(&flac[..4], b"fLaC");
+        assert!(flac.len() < samples.len() * 2);
+    }
+
+    #[cfg(feature = "opus-encoding")]
+    #[test]
+    fn test_ogg_opus_stream_header() {
+        let samples = vec![0.0f32; 16000];
+        let ogg = encode_ogg_opus(&samples, 16000).unwrap();
+        assert_eq!(&ogg[..4], b"OggS");
+        assert!(ogg.windows(8).any(|w| w == b"OpusHead"));
+    }
+
+    #[cfg(not(feature = "opus-encoding"))]
+    #[test]
+    fn test_ogg_opus_requires_feature() {
+        assert!(encode_ogg_opus(&[0.0; 320], 16000).is_err());
+    }
+}
@@ -6,7 +6,11 @@
 use serde::Serialize;
 use tauri::AppHandle;
 
+use crate::dictionary::DictionaryEntry;
+use crate::dictionary_learning::DictionarySuggestionStore;
+use crate::llm::TRANSFORM_PROMPT_DEFAULT;
 use crate::request_log::RequestLogStore;
+use crate::snippets::SnippetStore;
 
 #[cfg(desktop)]
 use tauri_plugin_store::StoreExt;
@@ -113,20 +117,10 @@ Example:
 pub const DICTIONARY_PROMPT_DEFAULT: &str = r#"## Personal Dictionary
 Apply these corrections for technical terms, proper nouns, and custom words.
 
-Entries can be in various formats - interpret flexibly:
-- Explicit mappings: "ant row pic = Anthropic"
-- Single terms to recognize: Just "LLM" (correct phonetic mismatches)
-- Natural descriptions: "The name 'Claude' should always be capitalized"
+Entries are listed below, either as "spoken = Term" mappings (what the speech recognition
+heard and the correct spelling) or as a single term to recognize.
 
-When you hear terms that sound like entries below, use the correct spelling/form.
-
-### Entries:
-Tangerine
-LLM
-ant row pick = Anthropic
-Claude
-Pipecat
-Tauri"#;
+When you hear terms that sound like an entry, use the correct spelling/form."#;
 
 /// Response containing default prompt sections
 #[derive(Debug, Serialize)]
@@ -134,6 +128,8 @@ pub struct DefaultSectionsResponse {
     pub main: String,
     pub advanced: String,
     pub dictionary: String,
+    /// System prompt for the transform-selection hotkey
+    pub transform: String,
 }
 
 /// Get default prompts for each section
@@ -143,6 +139,7 @@ pub fn get_default_sections() -> DefaultSectionsResponse {
         main: MAIN_PROMPT_DEFAULT.to_string(),
         advanced: ADVANCED_PROMPT_DEFAULT.to_string(),
         dictionary: DICTIONARY_PROMPT_DEFAULT.to_string(),
+        transform: TRANSFORM_PROMPT_DEFAULT.to_string(),
     }
 }
 
@@ -170,6 +167,8 @@ const STT_PROVIDERS: &[(&str, &str, bool)] = &[
     ("openai", "OpenAI", false),
     ("groq", "Groq", false),
     ("deepgram", "Deepgram", false),
+    ("assemblyai", "AssemblyAI", false),
+    ("openai-compatible", "OpenAI-Compatible", false),
     ("whisper", "Local Whisper", true),
 ];
 
@@ -180,6 +179,7 @@ const LLM_PROVIDERS: &[(&str, &str, bool)] = &[
     ("anthropic", "Anthropic", false),
     ("groq", "Groq", false),
     ("ollama", "Ollama", true),
+    ("openai-compatible", "OpenAI-Compatible", false),
 ];
 
 /// Helper to check if an API key is configured in the store
@@ -192,6 +192,28 @@ fn has_api_key(app: &AppHandle, key: &str) -> bool {
         .unwrap_or(false)
 }
 
+/// Helper to check if the OpenAI-compatible STT endpoint has a base URL configured
+#[cfg(desktop)]
+fn has_openai_compatible_stt(app: &AppHandle) -> bool {
+    app.store("settings.json")
+        .ok()
+        .and_then(|store| store.get("openai_compatible_stt"))
+        .and_then(|v| serde_json::from_value::<crate::stt::OpenAiCompatibleSttConfig>(v).ok())
+        .map(|cfg| cfg.is_configured())
+        .unwrap_or(false)
+}
+
+/// Helper to check if the OpenAI-compatible LLM endpoint has a base URL configured
+#[cfg(desktop)]
+fn has_openai_compatible_llm(app: &AppHandle) -> bool {
+    app.store("settings.json")
+        .ok()
+        .and_then(|store| store.get("openai_compatible_llm"))
+        .and_then(|v| serde_json::from_value::<crate::llm::OpenAiCompatibleLlmConfig>(v).ok())
+        .map(|cfg| cfg.is_configured())
+        .unwrap_or(false)
+}
+
 /// Get list of available STT and LLM providers (those with API keys configured)
 #[cfg(desktop)]
 #[tauri::command]
@@ -202,8 +224,14 @@ pub fn get_available_providers(app: AppHandle) -> AvailableProvidersResponse {
     // Check which STT providers have API keys
     for (id, label, is_local) in STT_PROVIDERS {
         let key_name = format!("{}_api_key", id);
-        // Local providers don't need API keys, remote ones do
-        if *is_local || has_api_key(&app, &key_name) {
+        // Local providers don't need API keys, remote ones do.
+        // The OpenAI-compatible endpoint only needs a base URL (its key is optional).
+        let configured = if *id == "openai-compatible" {
+            has_openai_compatible_stt(&app)
+        } else {
+            has_api_key(&app, &key_name)
+        };
+        if *is_local || configured {
             stt_providers.push(ProviderInfo {
                 value: id.to_string(),
                 label: label.to_string(),
@@ -215,8 +243,14 @@ pub fn get_available_providers(app: AppHandle) -> AvailableProvidersResponse {
     // Check which LLM providers have API keys
     for (id, label, is_local) in LLM_PROVIDERS {
         let key_name = format!("{}_api_key", id);
-        // Local providers don't need API keys, remote ones do
-        if *is_local || has_api_key(&app, &key_name) {
+        // Local providers don't need API keys, remote ones do.
+        // The OpenAI-compatible endpoint only needs a base URL (its key is optional).
+        let configured = if *id == "openai-compatible" {
+            has_openai_compatible_llm(&app)
+        } else {
+            has_api_key(&app, &key_name)
+        };
+        if *is_local || configured {
             llm_providers.push(ProviderInfo {
                 value: id.to_string(),
                 label: label.to_string(),
@@ -276,6 +310,63 @@ pub fn sync_pipeline_config(app: AppHandle) -> Result<(), String> {
         .and_then(|store| store.get("stt_transcription_prompt"))
         .and_then(|v| serde_json::from_value(v).ok());
 
+    // Self-hosted OpenAI-compatible STT endpoint (base URL, optional key, model, extra fields)
+    let stt_openai_compatible: crate::stt::OpenAiCompatibleSttConfig = app
+        .store("settings.json")
+        .ok()
+        .and_then(|store| store.get("openai_compatible_stt"))
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or_default();
+
+    // Ordered STT providers to try when the effective provider fails or times out
+    let stt_fallback_chain: Vec<crate::pipeline::SttFallbackEntry> = app
+        .store("settings.json")
+        .ok()
+        .and_then(|store| store.get("stt_fallback_chain"))
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or_default();
+
+    // Hedged STT requests (race a delayed second request against a slow primary)
+    let stt_hedge: crate::pipeline::SttHedgeConfig = app
+        .store("settings.json")
+        .ok()
+        .and_then(|store| store.get("stt_hedge"))
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or_default();
+
+    // Split long recordings at pauses and transcribe the chunks concurrently
+    let stt_chunking: crate::pipeline::SttChunkingConfig = app
+        .store("settings.json")
+        .ok()
+        .and_then(|store| store.get("stt_chunking"))
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or_default();
+
+    // Maximum recording length (long dictations rely on chunked transcription)
+    let max_duration_secs_raw: f32 = app
+        .store("settings.json")
+        .ok()
+        .and_then(|store| store.get("max_recording_duration_secs"))
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or(300.0);
+    let max_duration_secs: f32 = if max_duration_secs_raw.is_finite() && max_duration_secs_raw > 0.0 {
+        max_duration_secs_raw
+    } else {
+        log::warn!(
+            "Invalid max_recording_duration_secs value in store ({}); falling back to 300s",
+            max_duration_secs_raw
+        );
+        300.0
+    };
+
+    // Stream audio to the STT provider while recording
+    let stt_streaming_enabled: bool = app
+        .store("settings.json")
+        .ok()
+        .and_then(|store| store.get("stt_streaming_enabled"))
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or(false);
+
     // Get the appropriate API key based on provider
     let stt_api_key: String = {
         let key_name = format!("{}_api_key", stt_provider);
@@ -289,7 +380,7 @@ pub fn sync_pipeline_config(app: AppHandle) -> Result<(), String> {
     // Read all available STT API keys (for per-profile provider overrides at runtime)
     let mut stt_api_keys: std::collections::HashMap<String, String> =
         std::collections::HashMap::new();
-    for provider in ["openai", "groq", "deepgram"] {
+    for provider in ["openai", "groq", "deepgram", "assemblyai"] {
         let key_name = format!("{}_api_key", provider);
         let key: String = app
             .store("settings.json")
@@ -366,6 +457,51 @@ pub fn sync_pipeline_config(app: AppHandle) -> Result<(), String> {
         .and_then(|store| store.get("anthropic_thinking_budget"))
         .and_then(|v| serde_json::from_value(v).ok());
 
+    // Ordered LLM providers to try when the effective provider fails (within the LLM timeout)
+    let llm_fallback_chain: Vec<crate::llm::LlmFallbackEntry> = app
+        .store("settings.json")
+        .ok()
+        .and_then(|store| store.get("llm_fallback_chain"))
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or_default();
+
+    // Checks that reject rewrites which answer or chat instead of formatting the transcript
+    let llm_output_guardrail: crate::llm::OutputGuardrail = app
+        .store("settings.json")
+        .ok()
+        .and_then(|store| store.get("llm_output_guardrail"))
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or_default();
+
+    // The user's name for the `{{user_name}}` prompt variable
+    let user_name: Option<String> = app
+        .store("settings.json")
+        .ok()
+        .and_then(|store| store.get("user_name"))
+        .and_then(|v| serde_json::from_value(v).ok());
+
+    // Custom system prompt for the transform-selection hotkey (None = built-in default)
+    let transform_prompt: Option<String> = app
+        .store("settings.json")
+        .ok()
+        .and_then(|store| store.get("transform_prompt"))
+        .and_then(|v| serde_json::from_value(v).ok());
+
+    // Personal dictionary (terms and their spoken forms, optionally per profile)
+    let dictionary: Vec<DictionaryEntry> = app
+        .store("settings.json")
+        .ok()
+        .and_then(|store| store.get("dictionary_entries"))
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or_default();
+
+    let llm_openai_compatible: crate::llm::OpenAiCompatibleLlmConfig = app
+        .store("settings.json")
+        .ok()
+        .and_then(|store| store.get("openai_compatible_llm"))
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or_default();
+
     // If the user never explicitly selected a model, treat "default" as the provider's
     // concrete default model so request logs can display the exact model used.
     let llm_provider_effective = llm_provider_setting
@@ -449,6 +585,9 @@ pub fn sync_pipeline_config(app: AppHandle) -> Result<(), String> {
                 stt_timeout_seconds: p.stt_timeout_seconds,
                 llm_provider: p.llm_provider,
                 llm_model: p.llm_model,
+                llm_fallback_chain: p.llm_fallback_chain,
+                translation: p.translation,
+                stt_language: p.stt_language,
             })
             .collect();
 
@@ -559,6 +698,42 @@ pub fn sync_pipeline_config(app: AppHandle) -> Result<(), String> {
         .and_then(|store| store.get("audio_noise_suppression_enabled"))
         .and_then(|v| serde_json::from_value(v).ok())
         .unwrap_or(default_pipeline_config.audio_noise_suppression_enabled);
+    let audio_upload_encoding: crate::stt::AudioEncoding = app
+        .store("settings.json")
+        .ok()
+        .and_then(|store| store.get("audio_upload_encoding"))
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or(default_pipeline_config.audio_upload_encoding);
+    let hallucination_filter: crate::stt::HallucinationFilterConfig = app
+        .store("settings.json")
+        .ok()
+        .and_then(|store| store.get("hallucination_filter"))
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or_else(|| default_pipeline_config.hallucination_filter.clone());
+    let provider_health: crate::provider_health::ProviderHealthConfig = app
+        .store("settings.json")
+        .ok()
+        .and_then(|store| store.get("provider_health"))
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or_else(|| default_pipeline_config.provider_health.clone());
+    let translation: crate::translation::TranslationConfig = app
+        .store("settings.json")
+        .ok()
+        .and_then(|store| store.get("translation"))
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or_else(|| default_pipeline_config.translation.clone());
+    let stt_language: crate::stt::SttLanguage = app
+        .store("settings.json")
+        .ok()
+        .and_then(|store| store.get("stt_language"))
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or_else(|| default_pipeline_config.stt_language.clone());
+    let llm_streaming_enabled: bool = app
+        .store("settings.json")
+        .ok()
+        .and_then(|store| store.get("llm_streaming_enabled"))
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or(default_pipeline_config.llm_streaming_enabled);
 
     // Extra hallucination protection
     let quiet_audio_require_speech: bool = app
@@ -575,7 +750,12 @@ pub fn sync_pipeline_config(app: AppHandle) -> Result<(), String> {
         stt_api_keys,
         stt_model: stt_model.clone(),
         stt_transcription_prompt,
-        max_duration_secs: 300.0,
+        stt_openai_compatible,
+        stt_fallback_chain,
+        stt_hedge,
+        stt_chunking,
+        stt_streaming_enabled,
+        max_duration_secs,
         retry_config: RetryConfig::default(),
         vad_config: vad_settings.to_vad_auto_stop_config(),
         transcription_timeout: std::time::Duration::from_secs_f64(stt_timeout_seconds),
@@ -593,6 +773,12 @@ pub fn sync_pipeline_config(app: AppHandle) -> Result<(), String> {
         audio_highpass_enabled,
         audio_agc_enabled,
         audio_noise_suppression_enabled,
+        audio_upload_encoding,
+        hallucination_filter,
+        provider_health,
+        translation,
+        stt_language,
+        llm_streaming_enabled,
 
         quiet_audio_require_speech,
 
@@ -601,18 +787,28 @@ pub fn sync_pipeline_config(app: AppHandle) -> Result<(), String> {
             provider: llm_provider_effective,
             api_key: llm_api_key,
             model: llm_model_effective.clone(),
+            openai_compatible: llm_openai_compatible,
             openai_reasoning_effort,
             gemini_thinking_budget,
             gemini_thinking_level,
             anthropic_thinking_budget,
             prompts: base_prompts,
             program_prompt_profiles,
+            fallback_chain: llm_fallback_chain,
+            guardrail: llm_output_guardrail,
+            user_name,
+            transform_prompt,
             ..Default::default()
         },
         llm_api_keys,
 
         // Preserve provider payload logging across config sync.
         request_log_store: app.try_state::<RequestLogStore>().map(|s| s.inner().clone()),
+        dictionary,
+        snippet_store: app.try_state::<SnippetStore>().map(|s| s.inner().clone()),
+        dictionary_suggestions: app
+            .try_state::<DictionarySuggestionStore>()
+            .map(|s| s.inner().clone()),
     };
 
     // Update the pipeline
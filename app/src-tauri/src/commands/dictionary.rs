@@ -0,0 +1,85 @@
+//! Auto-learning dictionary: review the dictionary entries proposed from user corrections.
+
+use crate::dictionary_learning::{DictionarySuggestion, DictionarySuggestionStore};
+use tauri::{AppHandle, State};
+
+#[cfg(desktop)]
+use crate::dictionary::DictionaryEntry;
+#[cfg(desktop)]
+use crate::dictionary_learning::add_to_dictionary;
+#[cfg(desktop)]
+use tauri_plugin_store::StoreExt;
+
+/// List the pending dictionary suggestions (newest first)
+#[tauri::command]
+pub async fn list_dictionary_suggestions(
+    suggestions: State<'_, DictionarySuggestionStore>,
+) -> Result<Vec<DictionarySuggestion>, String> {
+    suggestions.list()
+}
+
+/// Approve a suggestion: add it to the dictionary entries (scoped to the profile it was
+/// learned in), and apply the updated dictionary to the pipeline.
+///
+/// Returns false when the dictionary already had the entry.
+#[cfg(desktop)]
+#[tauri::command]
+pub async fn approve_dictionary_suggestion(
+    app: AppHandle,
+    id: String,
+    suggestions: State<'_, DictionarySuggestionStore>,
+) -> Result<bool, String> {
+    let suggestion = suggestions
+        .get(&id)?
+        .ok_or_else(|| format!("Dictionary suggestion not found: {}", id))?;
+
+    let store = app
+        .store("settings.json")
+        .map_err(|e| format!("Failed to get store: {}", e))?;
+    let mut entries: Vec<DictionaryEntry> = store
+        .get("dictionary_entries")
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or_default();
+
+    let added = add_to_dictionary(
+        &mut entries,
+        suggestion.profile_id.as_deref(),
+        &suggestion.correction,
+    );
+    if added {
+        let value = serde_json::to_value(&entries)
+            .map_err(|e| format!("Failed to serialize dictionary: {}", e))?;
+        store.set("dictionary_entries", value);
+        store
+            .save()
+            .map_err(|e| format!("Failed to save store: {}", e))?;
+        crate::commands::config::sync_pipeline_config(app.clone())?;
+        log::info!(
+            "Dictionary entry added: {}",
+            suggestion.correction.dictionary_entry()
+        );
+    }
+
+    suggestions.remove(&id)?;
+    Ok(added)
+}
+
+/// Stub for non-desktop platforms
+#[cfg(not(desktop))]
+#[tauri::command]
+pub async fn approve_dictionary_suggestion(
+    _app: AppHandle,
+    _id: String,
+    _suggestions: State<'_, DictionarySuggestionStore>,
+) -> Result<bool, String> {
+    Err("Dictionary suggestions are not supported on this platform".to_string())
+}
+
+/// Reject a suggestion; the same correction won't be suggested again
+#[tauri::command]
+pub async fn reject_dictionary_suggestion(
+    id: String,
+    suggestions: State<'_, DictionarySuggestionStore>,
+) -> Result<bool, String> {
+    suggestions.reject(&id)
+}
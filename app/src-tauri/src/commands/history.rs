@@ -1,4 +1,8 @@
+use crate::dictionary_learning::{
+    corrections_from_edit, CorrectionSource, DictionarySuggestion, DictionarySuggestionStore,
+};
 use crate::history::{HistoryEntry, HistoryStorage};
+use crate::retry_queue::RetryQueue;
 use tauri::{AppHandle, State};
 
 #[cfg(desktop)]
@@ -45,17 +49,50 @@ pub async fn get_history(
     history.get_all(limit)
 }
 
+/// Correct the text of a history entry.
+///
+/// Returns the dictionary suggestions learned from the correction (words the STT misheard).
+#[tauri::command]
+pub async fn update_history_entry(
+    id: String,
+    text: String,
+    history: State<'_, HistoryStorage>,
+    suggestions: State<'_, DictionarySuggestionStore>,
+) -> Result<Vec<DictionarySuggestion>, String> {
+    let before = history
+        .update_text(&id, text.clone())?
+        .ok_or_else(|| format!("History entry not found: {}", id))?;
+
+    let raw = before.stt_text.as_deref().unwrap_or(&before.text);
+    let mut proposed = Vec::new();
+    for correction in corrections_from_edit(raw, &before.text, &text) {
+        proposed.extend(suggestions.propose(
+            correction,
+            before.profile_id.as_deref(),
+            CorrectionSource::HistoryEdit,
+        )?);
+    }
+    Ok(proposed)
+}
+
 /// Delete a history entry by ID
 #[tauri::command]
 pub async fn delete_history_entry(
     id: String,
     history: State<'_, HistoryStorage>,
+    retry_queue: State<'_, RetryQueue>,
 ) -> Result<bool, String> {
+    // Don't resurrect the entry from a pending offline retry.
+    retry_queue.remove(&id)?;
     history.delete(&id)
 }
 
 /// Clear all history entries
 #[tauri::command]
-pub async fn clear_history(history: State<'_, HistoryStorage>) -> Result<(), String> {
+pub async fn clear_history(
+    history: State<'_, HistoryStorage>,
+    retry_queue: State<'_, RetryQueue>,
+) -> Result<(), String> {
+    retry_queue.clear()?;
     history.clear()
 }
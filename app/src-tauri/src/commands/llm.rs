@@ -1,12 +1,14 @@
 //! Tauri commands for LLM formatting configuration.
 
+use crate::dictionary::{entries_for_profile, with_dictionary};
 use crate::llm::{
-    LlmConfig, PromptSections, ADVANCED_PROMPT_DEFAULT, DICTIONARY_PROMPT_DEFAULT,
-    MAIN_PROMPT_DEFAULT,
+    combine_prompt_sections, render_prompt_sections, unknown_prompt_variables,
+    validate_prompt_sections, LlmConfig, PromptContext, PromptSections, ADVANCED_PROMPT_DEFAULT,
+    DICTIONARY_PROMPT_DEFAULT, MAIN_PROMPT_DEFAULT, PROMPT_VARIABLES, TRANSFORM_PROMPT_DEFAULT,
 };
 use crate::llm::{
     format_text, AnthropicLlmProvider, GroqLlmProvider, LlmProvider, OllamaLlmProvider,
-    OpenAiLlmProvider, GeminiLlmProvider,
+    OpenAiCompatibleLlmProvider, OpenAiLlmProvider, GeminiLlmProvider,
 };
 use crate::pipeline::SharedPipeline;
 use std::sync::Arc;
@@ -30,7 +32,7 @@ impl From<String> for LlmCommandError {
 pub struct LlmConfigPayload {
     /// Whether LLM formatting is enabled
     pub enabled: bool,
-    /// Provider name: "openai", "anthropic", or "ollama"
+    /// Provider name: "openai", "anthropic", "ollama", or "openai-compatible"
     pub provider: String,
     /// API key (not needed for ollama)
     pub api_key: Option<String>,
@@ -49,6 +51,18 @@ pub struct TestLlmRewriteResponse {
     pub model_used: String,
 }
 
+#[derive(Debug, serde::Serialize)]
+pub struct PromptPreviewResponse {
+    /// System prompt as it would be sent right now (variables filled in)
+    pub prompt: String,
+    /// Referenced variables that don't exist (left as is in `prompt`)
+    pub unknown_variables: Vec<String>,
+    /// Variables prompts may use
+    pub available_variables: Vec<String>,
+    /// Values used for the variables
+    pub context: PromptContext,
+}
+
 #[derive(Debug, serde::Serialize)]
 pub struct LlmCompleteResponse {
     pub output: String,
@@ -114,6 +128,11 @@ fn create_llm_provider(config: &LlmConfig) -> Arc<dyn LlmProvider> {
             );
             Arc::new(provider.with_timeout(config.timeout))
         }
+        "openai-compatible" => {
+            let provider =
+                OpenAiCompatibleLlmProvider::new(&config.openai_compatible, config.model.clone());
+            Arc::new(provider.with_timeout(config.timeout))
+        }
         _ => {
             // Default to OpenAI
             let provider = if let Some(model) = &config.model {
@@ -180,6 +199,11 @@ fn create_llm_provider_unstructured(config: &LlmConfig) -> Arc<dyn LlmProvider>
             );
             Arc::new(provider.with_timeout(config.timeout))
         }
+        "openai-compatible" => {
+            let provider =
+                OpenAiCompatibleLlmProvider::new(&config.openai_compatible, config.model.clone());
+            Arc::new(provider.with_timeout(config.timeout))
+        }
         _ => {
             // Default to OpenAI
             let provider = if let Some(model) = &config.model {
@@ -243,6 +267,11 @@ fn create_llm_provider_without_timeout(config: &LlmConfig) -> Arc<dyn LlmProvide
             );
             Arc::new(provider.without_timeout())
         }
+        "openai-compatible" => {
+            let provider =
+                OpenAiCompatibleLlmProvider::new(&config.openai_compatible, config.model.clone());
+            Arc::new(provider.without_timeout())
+        }
         _ => {
             // Default to OpenAI
             let provider = if let Some(model) = &config.model {
@@ -282,6 +311,9 @@ impl From<PromptConfigPayload> for PromptSections {
             advanced_custom: payload.advanced_custom,
             dictionary_enabled: payload.dictionary_enabled,
             dictionary_custom: payload.dictionary_custom,
+            translation: None,
+            strict: false,
+            snippet_markers: false,
         }
     }
 }
@@ -305,6 +337,7 @@ pub fn get_llm_default_prompts() -> DefaultPromptsResponse {
         main: MAIN_PROMPT_DEFAULT.to_string(),
         advanced: ADVANCED_PROMPT_DEFAULT.to_string(),
         dictionary: DICTIONARY_PROMPT_DEFAULT.to_string(),
+        transform: TRANSFORM_PROMPT_DEFAULT.to_string(),
     }
 }
 
@@ -314,6 +347,7 @@ pub struct DefaultPromptsResponse {
     pub main: String,
     pub advanced: String,
     pub dictionary: String,
+    pub transform: String,
 }
 
 /// Get available LLM providers
@@ -396,6 +430,14 @@ pub fn get_llm_providers() -> Vec<LlmProviderInfo> {
                 "codellama".to_string(),
             ],
         },
+        LlmProviderInfo {
+            id: "openai-compatible".to_string(),
+            name: "OpenAI-Compatible".to_string(),
+            requires_api_key: false,
+            // Model names depend on the server (LM Studio, llama.cpp, vLLM, OpenRouter, ...).
+            default_model: String::new(),
+            models: Vec::new(),
+        },
     ]
 }
 
@@ -415,29 +457,15 @@ pub async fn test_llm_rewrite(
     // IMPORTANT: This is a *test* endpoint. It intentionally ignores the
     // "Rewrite Transcription" enable toggle so users can validate prompts/
     // provider/model without changing runtime behavior.
-    let (desired_provider, desired_model, prompts) = if let Some(id) = profile_id {
-        if id != "default" {
-            let profile = config
-                .llm_config
-                .program_prompt_profiles
-                .iter()
-                .find(|p| p.id == id)
-                .ok_or_else(|| LlmCommandError::from(format!("Unknown profile_id: {}", id)))?;
-
-            let provider = profile
-                .llm_provider
-                .clone()
-                .unwrap_or_else(|| config.llm_config.provider.clone());
-            let model = profile.llm_model.clone().or_else(|| config.llm_config.model.clone());
-
-            (provider, model, profile.prompts.clone())
-        } else {
-            (
-                config.llm_config.provider.clone(),
-                config.llm_config.model.clone(),
-                config.llm_config.prompts.clone(),
-            )
-        }
+    let profile = find_profile(&config.llm_config, profile_id.as_deref())?;
+    let (desired_provider, desired_model, prompts) = if let Some(profile) = &profile {
+        let provider = profile
+            .llm_provider
+            .clone()
+            .unwrap_or_else(|| config.llm_config.provider.clone());
+        let model = profile.llm_model.clone().or_else(|| config.llm_config.model.clone());
+
+        (provider, model, profile.prompts.clone())
     } else {
         (
             config.llm_config.provider.clone(),
@@ -445,8 +473,10 @@ pub async fn test_llm_rewrite(
             config.llm_config.prompts.clone(),
         )
     };
+    let context = pipeline.prompt_context(profile.as_ref(), &prompts);
+    let prompts = render_prompt_sections(&prompts, &context);
 
-    let api_key = if desired_provider == "ollama" {
+    let api_key = if desired_provider == "ollama" || desired_provider == "openai-compatible" {
         String::new()
     } else {
         config
@@ -462,18 +492,28 @@ pub async fn test_llm_rewrite(
         api_key,
         model: desired_model,
         ollama_url: config.llm_config.ollama_url.clone(),
+        openai_compatible: config.llm_config.openai_compatible.clone(),
         openai_reasoning_effort: config.llm_config.openai_reasoning_effort.clone(),
         gemini_thinking_budget: config.llm_config.gemini_thinking_budget,
         gemini_thinking_level: config.llm_config.gemini_thinking_level.clone(),
         anthropic_thinking_budget: config.llm_config.anthropic_thinking_budget,
         prompts: PromptSections::default(),
         program_prompt_profiles: Vec::new(),
+        fallback_chain: Vec::new(),
+        guardrail: config.llm_config.guardrail.clone(),
+        user_name: config.llm_config.user_name.clone(),
+        transform_prompt: config.llm_config.transform_prompt.clone(),
         timeout: config.llm_config.timeout,
     };
 
     // This is a *test* endpoint: do not enforce request timeouts.
     let provider = create_llm_provider_without_timeout(&provider_cfg);
-    let output = format_text(provider.as_ref(), &transcript, &prompts)
+    let output = format_text(
+        provider.as_ref(),
+        &transcript,
+        &prompts,
+        &config.llm_config.guardrail,
+    )
         .await
         .map_err(|e| LlmCommandError::from(e.to_string()))?;
 
@@ -484,6 +524,56 @@ pub async fn test_llm_rewrite(
     })
 }
 
+/// Program prompt profile for `profile_id` (None or "default" = the Default profile).
+fn find_profile(
+    llm_config: &LlmConfig,
+    profile_id: Option<&str>,
+) -> Result<Option<crate::llm::ProgramPromptProfile>, LlmCommandError> {
+    match profile_id {
+        None | Some("default") => Ok(None),
+        Some(id) => llm_config
+            .program_prompt_profiles
+            .iter()
+            .find(|p| p.id == id)
+            .cloned()
+            .map(Some)
+            .ok_or_else(|| LlmCommandError::from(format!("Unknown profile_id: {}", id))),
+    }
+}
+
+/// Render the system prompt that a rewrite would use right now.
+///
+/// `prompts` previews unsaved prompt edits; otherwise the profile's (or Default) prompts are
+/// used. The dictionary entries are included, and variables are filled with current values,
+/// so `{{app_name}}` shows this app.
+#[tauri::command]
+pub fn preview_llm_system_prompt(
+    pipeline: State<'_, SharedPipeline>,
+    prompts: Option<PromptConfigPayload>,
+    profile_id: Option<String>,
+) -> Result<PromptPreviewResponse, LlmCommandError> {
+    let config = pipeline.config();
+    let profile = find_profile(&config.llm_config, profile_id.as_deref())?;
+    let prompts: PromptSections = match prompts {
+        Some(payload) => payload.into(),
+        None => profile
+            .as_ref()
+            .map(|p| p.prompts.clone())
+            .unwrap_or_else(|| config.llm_config.prompts.clone()),
+    };
+    let dictionary =
+        entries_for_profile(&config.dictionary, profile.as_ref().map(|p| p.id.as_str()));
+    let prompts = with_dictionary(&prompts, &dictionary);
+
+    let context = pipeline.prompt_context(profile.as_ref(), &prompts);
+    Ok(PromptPreviewResponse {
+        prompt: combine_prompt_sections(&render_prompt_sections(&prompts, &context)),
+        unknown_variables: unknown_prompt_variables(&prompts),
+        available_variables: PROMPT_VARIABLES.iter().map(|v| v.to_string()).collect(),
+        context,
+    })
+}
+
 /// Run a one-off LLM completion with explicit provider/model and explicit prompts.
 ///
 /// This is used by the History UI to send analysis instructions as the *system prompt*
@@ -498,7 +588,7 @@ pub async fn llm_complete(
     let desired_provider = args.provider;
     let desired_model = args.model;
 
-    let api_key = if desired_provider == "ollama" {
+    let api_key = if desired_provider == "ollama" || desired_provider == "openai-compatible" {
         String::new()
     } else {
         config
@@ -508,7 +598,10 @@ pub async fn llm_complete(
             .unwrap_or_default()
     };
 
-    if desired_provider != "ollama" && api_key.trim().is_empty() {
+    if desired_provider != "ollama"
+        && desired_provider != "openai-compatible"
+        && api_key.trim().is_empty()
+    {
         return Err(LlmCommandError::from(format!(
             "No API key configured for provider: {}",
             desired_provider
@@ -521,12 +614,17 @@ pub async fn llm_complete(
         api_key,
         model: desired_model,
         ollama_url: config.llm_config.ollama_url.clone(),
+        openai_compatible: config.llm_config.openai_compatible.clone(),
         openai_reasoning_effort: config.llm_config.openai_reasoning_effort.clone(),
         gemini_thinking_budget: config.llm_config.gemini_thinking_budget,
         gemini_thinking_level: config.llm_config.gemini_thinking_level.clone(),
         anthropic_thinking_budget: config.llm_config.anthropic_thinking_budget,
         prompts: PromptSections::default(),
         program_prompt_profiles: Vec::new(),
+        fallback_chain: Vec::new(),
+        guardrail: config.llm_config.guardrail.clone(),
+        user_name: config.llm_config.user_name.clone(),
+        transform_prompt: config.llm_config.transform_prompt.clone(),
         timeout: config.llm_config.timeout,
     };
 
@@ -568,12 +666,17 @@ pub fn update_llm_config(
         api_key: config.api_key.unwrap_or_default(),
         model: config.model,
         ollama_url: config.ollama_url,
+        openai_compatible: Default::default(),
         openai_reasoning_effort: None,
         gemini_thinking_budget: None,
         gemini_thinking_level: None,
         anthropic_thinking_budget: None,
         prompts: PromptSections::default(),
         program_prompt_profiles: Vec::new(),
+        fallback_chain: Vec::new(),
+        guardrail: Default::default(),
+        user_name: None,
+        transform_prompt: None,
         timeout: Duration::from_secs(config.timeout_secs.unwrap_or(30)),
     };
 
@@ -598,9 +701,12 @@ pub fn update_llm_prompts(
     pipeline: State<'_, SharedPipeline>,
     prompts: PromptConfigPayload,
 ) -> Result<(), LlmCommandError> {
+    let prompts: PromptSections = prompts.into();
+    validate_prompt_sections(&prompts).map_err(|e| LlmCommandError::from(e.to_string()))?;
+
     let current_config = get_current_pipeline_config(&pipeline)?;
     let mut llm_config = current_config.llm_config.clone();
-    llm_config.prompts = prompts.into();
+    llm_config.prompts = prompts;
 
     let new_config = crate::pipeline::PipelineConfig {
         llm_config,
@@ -657,12 +763,13 @@ mod tests {
     #[test]
     fn test_get_llm_providers() {
         let providers = get_llm_providers();
-        assert_eq!(providers.len(), 5);
+        assert_eq!(providers.len(), 6);
         assert!(providers.iter().any(|p| p.id == "openai"));
         assert!(providers.iter().any(|p| p.id == "gemini"));
         assert!(providers.iter().any(|p| p.id == "anthropic"));
         assert!(providers.iter().any(|p| p.id == "groq"));
         assert!(providers.iter().any(|p| p.id == "ollama"));
+        assert!(providers.iter().any(|p| p.id == "openai-compatible"));
     }
 
     #[test]
@@ -671,5 +778,6 @@ mod tests {
         assert!(!prompts.main.is_empty());
         assert!(!prompts.advanced.is_empty());
         assert!(!prompts.dictionary.is_empty());
+        assert!(!prompts.transform.is_empty());
     }
 }
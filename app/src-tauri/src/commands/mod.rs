@@ -1,11 +1,14 @@
 pub mod audio;
 pub mod config;
+pub mod dictionary;
 pub mod history;
 pub mod llm;
 pub mod logs;
+pub mod offline_retry;
 pub mod overlay;
 pub mod recording;
 pub mod settings;
+pub mod snippets;
 pub mod text;
 pub mod windows;
 pub mod whisper;
@@ -0,0 +1,328 @@
+//! Offline retry queue: dictations that failed because the STT provider was unreachable are
+//! queued and re-transcribed in the background once connectivity is back.
+
+use crate::history::HistoryStorage;
+use crate::pipeline::{PipelineError, PipelineState, SharedPipeline};
+use crate::recordings::RecordingStore;
+use crate::retry_queue::{
+    connectivity_probe_url, probe_connectivity, OfflineRetryConfig, QueuedRetry, RetryQueue,
+};
+use std::collections::HashMap;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+#[cfg(desktop)]
+use tauri_plugin_store::StoreExt;
+
+fn get_offline_retry_config(app: &AppHandle) -> OfflineRetryConfig {
+    #[cfg(desktop)]
+    {
+        return app
+            .store("settings.json")
+            .ok()
+            .and_then(|store| store.get("offline_retry"))
+            .and_then(|v| serde_json::from_value(v).ok())
+            .unwrap_or_default();
+    }
+
+    #[cfg(not(desktop))]
+    {
+        let _ = app;
+        OfflineRetryConfig::default()
+    }
+}
+
+fn pipeline_is_idle(pipeline: &SharedPipeline) -> bool {
+    matches!(
+        pipeline.try_state(),
+        Some(PipelineState::Idle | PipelineState::Error)
+    )
+}
+
+/// Queue a failed dictation for automatic retry if it failed on a network error.
+///
+/// The WAV must already be saved in the `RecordingStore` under `request_id`; `profile_id` is
+/// the program profile it was recorded with, reused for the retry. Returns true if the request
+/// was queued (its history entry is then marked `queued`).
+pub fn queue_if_network_error(
+    app: &AppHandle,
+    request_id: &str,
+    profile_id: Option<String>,
+    error: &PipelineError,
+) -> bool {
+    if !error.is_network_error() || !get_offline_retry_config(app).enabled {
+        return false;
+    }
+
+    let (Some(queue), Some(recordings)) = (
+        app.try_state::<RetryQueue>(),
+        app.try_state::<RecordingStore>(),
+    ) else {
+        return false;
+    };
+    if !recordings.has(request_id) {
+        return false;
+    }
+
+    if let Err(e) = queue.enqueue(request_id, profile_id, error.to_string()) {
+        log::warn!("Failed to queue dictation {} for retry: {}", request_id, e);
+        return false;
+    }
+    log::info!("Queued dictation {} for retry when back online", request_id);
+
+    if let Some(history) = app.try_state::<HistoryStorage>() {
+        let _ = history.mark_request_queued(request_id, error.to_string());
+        let _ = app.emit("history-changed", ());
+    }
+    let _ = app.emit("offline-retry-queued", request_id);
+    true
+}
+
+/// Take a request out of the queue (e.g. the user retried or deleted it by hand).
+///
+/// Returns the entry if it was queued.
+pub fn dequeue(app: &AppHandle, request_id: &str) -> Option<QueuedRetry> {
+    let queue = app.try_state::<RetryQueue>()?;
+    let entry = queue
+        .entries()
+        .ok()?
+        .into_iter()
+        .find(|e| e.request_id == request_id)?;
+    queue.remove(request_id).ok()?;
+    Some(entry)
+}
+
+/// Start the background task that drains the queue whenever the pipeline is idle. Each queued
+/// dictation is retried once an STT provider of its profile answers a connectivity probe.
+pub fn start_worker(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let client = reqwest::Client::new();
+
+        loop {
+            let config = get_offline_retry_config(&app);
+            tokio::time::sleep(config.check_interval()).await;
+
+            if !config.enabled {
+                continue;
+            }
+            let Some(queue) = app.try_state::<RetryQueue>() else {
+                continue;
+            };
+            if queue.is_empty() {
+                continue;
+            }
+            let Some(pipeline) = app.try_state::<SharedPipeline>() else {
+                continue;
+            };
+            if !pipeline_is_idle(&pipeline) {
+                continue;
+            }
+
+            drain_queue(&app, &config, &client).await;
+        }
+    });
+}
+
+/// Whether an STT provider that a retry under `profile_id` would try is reachable.
+///
+/// Local providers need no network; cloud providers are probed first so we don't burn retry
+/// attempts while still offline. Probe results are kept in `probes` for the rest of the round.
+async fn stt_reachable(
+    pipeline: &SharedPipeline,
+    profile_id: Option<&str>,
+    client: &reqwest::Client,
+    probes: &mut HashMap<&'static str, bool>,
+) -> bool {
+    // Without a usable provider the retry fails right away and reports why.
+    let Ok(providers) = pipeline.stt_providers_for_profile(profile_id) else {
+        return true;
+    };
+
+    for provider in providers {
+        let Some(url) = connectivity_probe_url(&provider) else {
+            return true;
+        };
+        let reachable = match probes.get(url) {
+            Some(&reachable) => reachable,
+            None => {
+                let reachable = probe_connectivity(client, url).await;
+                if !reachable {
+                    log::debug!("Offline retry: {} still unreachable", url);
+                }
+                probes.insert(url, reachable);
+                reachable
+            }
+        };
+        if reachable {
+            return true;
+        }
+    }
+    false
+}
+
+async fn drain_queue(app: &AppHandle, config: &OfflineRetryConfig, client: &reqwest::Client) {
+    let (Some(queue), Some(pipeline), Some(recordings)) = (
+        app.try_state::<RetryQueue>(),
+        app.try_state::<SharedPipeline>(),
+        app.try_state::<RecordingStore>(),
+    ) else {
+        return;
+    };
+    let history = app.try_state::<HistoryStorage>();
+    let entries = queue.entries().unwrap_or_default();
+    let mut probes = HashMap::new();
+
+    for entry in entries {
+        // Retries run alongside new dictations, but don't compete with one for bandwidth;
+        // leave the rest for the next round.
+        if !pipeline_is_idle(&pipeline) {
+            break;
+        }
+        let request_id = entry.request_id.as_str();
+
+        // Deleted or pruned from history in the meantime: nothing to update anymore.
+        if history.as_ref().is_some_and(|h| !h.contains(request_id)) {
+            let _ = queue.remove(request_id);
+            continue;
+        }
+
+        // Dictations of other profiles may use a provider that is back already.
+        if !stt_reachable(&pipeline, entry.profile_id.as_deref(), client, &mut probes).await {
+            continue;
+        }
+
+        let wav = match recordings.load_wav(request_id) {
+            Ok(wav) => wav,
+            Err(e) => {
+                let _ = queue.remove(request_id);
+                if let Some(history) = &history {
+                    let _ = history.complete_request_error(
+                        request_id,
+                        format!("Recording no longer available for retry: {}", e),
+                    );
+                    let _ = app.emit("history-changed", ());
+                }
+                continue;
+            }
+        };
+
+        log::info!(
+            "Offline retry: re-transcribing {} (attempt {}/{})",
+            request_id,
+            entry.attempts + 1,
+            config.max_attempts
+        );
+        if let Some(history) = &history {
+            let _ = history.mark_request_in_progress(request_id);
+            let _ = app.emit("history-changed", ());
+        }
+
+        match pipeline
+            .retranscribe_in_background(request_id, wav, entry.profile_id.as_deref())
+            .await
+        {
+            Ok(result) => {
+                let _ = queue.remove(request_id);
+                let text = result.final_text.clone();
+
+                if let Some(history) = &history {
+                    let _ = history.complete_request_success_detailed(
+                        request_id,
+                        text.clone(),
+                        Some(result.stt_transcript.clone()),
+                        result.models_used(),
+                    );
+                    let _ = app.emit("history-changed", ());
+                }
+
+                let copied = config.copy_to_clipboard
+                    && !text.trim().is_empty()
+                    && match crate::commands::text::copy_to_clipboard(&text) {
+                        Ok(()) => true,
+                        Err(e) => {
+                            log::warn!("Offline retry: failed to copy text to clipboard: {}", e);
+                            false
+                        }
+                    };
+
+                let payload = serde_json::json!({
+                    "request_id": request_id,
+                    "text": text,
+                    "copied_to_clipboard": copied,
+                });
+                let _ = app.emit("offline-retry-completed", payload);
+            }
+            Err(e @ (PipelineError::Cancelled | PipelineError::Lock(_))) => {
+                // Not the provider's fault, so keep the attempt count.
+                log::info!("Offline retry: {} interrupted ({})", request_id, e);
+                if let Some(history) = &history {
+                    let error_message = entry.last_error.clone().unwrap_or_else(|| e.to_string());
+                    let _ = history.mark_request_queued(request_id, error_message);
+                    let _ = app.emit("history-changed", ());
+                }
+                break;
+            }
+            Err(e) if e.is_network_error() => {
+                let updated = queue
+                    .record_failure(request_id, e.to_string(), config.max_attempts)
+                    .ok()
+                    .flatten();
+                let gave_up = updated.is_none_or(|u| u.attempts >= config.max_attempts.max(1));
+
+                if let Some(history) = &history {
+                    if gave_up {
+                        let _ = history.complete_request_error(request_id, e.to_string());
+                    } else {
+                        let _ = history.mark_request_queued(request_id, e.to_string());
+                    }
+                    let _ = app.emit("history-changed", ());
+                }
+                if gave_up {
+                    emit_retry_failed(app, request_id, &e);
+                }
+
+                // Still (or again) offline: wait for the next connectivity check.
+                break;
+            }
+            Err(e) => {
+                let _ = queue.remove(request_id);
+                if let Some(history) = &history {
+                    let _ = history.complete_request_error(request_id, e.to_string());
+                    let _ = app.emit("history-changed", ());
+                }
+                emit_retry_failed(app, request_id, &e);
+            }
+        }
+    }
+}
+
+fn emit_retry_failed(app: &AppHandle, request_id: &str, error: &PipelineError) {
+    log::warn!("Offline retry: giving up on {}: {}", request_id, error);
+    let payload = serde_json::json!({
+        "request_id": request_id,
+        "message": error.to_string(),
+    });
+    let _ = app.emit("offline-retry-failed", payload);
+}
+
+/// List dictations waiting to be retried (oldest first)
+#[tauri::command]
+pub fn offline_retry_queue_list(queue: State<'_, RetryQueue>) -> Result<Vec<QueuedRetry>, String> {
+    queue.entries()
+}
+
+/// Stop automatically retrying a dictation; its history entry is marked as failed.
+#[tauri::command]
+pub fn offline_retry_queue_remove(app: AppHandle, request_id: String) -> Result<bool, String> {
+    let Some(entry) = dequeue(&app, &request_id) else {
+        return Ok(false);
+    };
+
+    if let Some(history) = app.try_state::<HistoryStorage>() {
+        let message = entry
+            .last_error
+            .unwrap_or_else(|| "Removed from retry queue".to_string());
+        let _ = history.complete_request_error(&request_id, message);
+        let _ = app.emit("history-changed", ());
+    }
+    Ok(true)
+}
@@ -5,6 +5,7 @@
 
 use crate::audio_capture::{AudioCaptureDiagnostics, VadAutoStopConfig};
 use crate::pipeline::{LlmOutcome, PipelineConfig, PipelineError, PipelineState, SharedPipeline};
+use crate::provider_health::ProviderHealthSnapshot;
 use crate::recordings::{RecordingStore, RecordingsStats};
 use crate::request_log::RequestLogStore;
 use crate::history::{HistoryStorage, RequestModelInfo};
@@ -211,6 +212,7 @@ impl From<PipelineError> for CommandError {
             PipelineError::Cancelled => "cancelled",
             PipelineError::Timeout(_) => "timeout",
             PipelineError::RecordingTooLarge(_, _) => "size",
+            PipelineError::Transform(_) => "llm",
         };
         Self {
             message: err.to_string(),
@@ -377,6 +379,7 @@ pub async fn pipeline_stop_and_transcribe(
                 None
             },
             llm_model: config.llm_config.model.clone(),
+            profile_id: None,
         }
     };
 
@@ -434,6 +437,11 @@ pub async fn pipeline_stop_and_transcribe(
         });
     }
 
+    // Show the streamed LLM rewrite in the overlay; the final text is returned as usual.
+    if let Some(deltas) = pipeline.take_llm_deltas() {
+        crate::forward_llm_deltas(&app, deltas, None);
+    }
+
     let result = match pipeline.stop_and_transcribe_detailed().await {
         Ok(r) => r,
         Err(PipelineError::Cancelled) => {
@@ -488,6 +496,16 @@ pub async fn pipeline_stop_and_transcribe(
                 }
             }
 
+            // Network failures are retried automatically once back online.
+            if let Some(req_id) = active_request_id.as_deref() {
+                crate::commands::offline_retry::queue_if_network_error(
+                    &app,
+                    req_id,
+                    pipeline.last_profile_id(),
+                    &e,
+                );
+            }
+
             // Emit pipeline-error event with request_id so the overlay can show a retry button.
             let payload = serde_json::json!({
                 "message": e.to_string(),
@@ -505,8 +523,13 @@ pub async fn pipeline_stop_and_transcribe(
     if let Some(log_store) = app.try_state::<RequestLogStore>() {
         log_store.with_current(|log| {
             log.raw_transcript = Some(result.stt_text.clone());
+            log.stt_transcript = Some(result.stt_transcript.clone()).filter(|t| t.has_details());
             log.formatted_transcript = Some(result.final_text.clone());
             log.stt_duration_ms = Some(result.stt_duration_ms);
+            if let Some(provider) = &result.stt_provider_used {
+                log.stt_provider = provider.clone();
+                log.stt_model = result.stt_model_used.clone();
+            }
             log.llm_duration_ms = result.llm_duration_ms;
 
             // Use the provider instance's model (includes provider defaults) so the UI can show
@@ -526,7 +549,13 @@ pub async fn pipeline_stop_and_transcribe(
                 LlmOutcome::NotAttempted => {
                     log.info("LLM formatting not attempted (disabled or unavailable)");
                 }
-                LlmOutcome::Succeeded => {
+                LlmOutcome::Succeeded { provider, attempts, .. } => {
+                    if *attempts > 1 {
+                        log.info(format!(
+                            "LLM fallback provider {} succeeded after {} attempts",
+                            provider, attempts
+                        ));
+                    }
                     if let Some(ms) = result.llm_duration_ms {
                         log.info(format!(
                             "LLM formatting succeeded in {}ms ({} -> {} chars)",
@@ -548,6 +577,21 @@ pub async fn pipeline_stop_and_transcribe(
                         log.warn("LLM formatting timed out; fell back to STT transcript");
                     }
                 }
+                LlmOutcome::SucceededAfterStrictRetry { provider, reason, .. } => {
+                    log.info(format!(
+                        "LLM output from {} rejected ({}); strict retry succeeded ({} -> {} chars)",
+                        provider,
+                        reason,
+                        result.stt_text.len(),
+                        result.final_text.len()
+                    ));
+                }
+                LlmOutcome::Rejected { provider, reason } => {
+                    log.warn(format!(
+                        "LLM output from {} rejected; fell back to STT transcript ({})",
+                        provider, reason
+                    ));
+                }
                 LlmOutcome::Failed(err) => {
                     log.warn(format!(
                         "LLM formatting failed; fell back to STT transcript ({})",
@@ -560,6 +604,7 @@ pub async fn pipeline_stop_and_transcribe(
         });
         log_store.complete_current();
     }
+    crate::notify_llm_output_rejected(&app, &result.llm_outcome);
 
     // Persist audio for retry (best-effort)
     if let (Some(req_id), Some(store)) = (
@@ -576,7 +621,12 @@ pub async fn pipeline_stop_and_transcribe(
     // Update history entry with success text
     if let Some(req_id) = active_request_id.as_deref() {
         if let Some(history) = app.try_state::<HistoryStorage>() {
-            let _ = history.complete_request_success(req_id, final_text.clone());
+            let _ = history.complete_request_success_detailed(
+                req_id,
+                final_text.clone(),
+                Some(result.stt_transcript.clone()),
+                result.models_used(),
+            );
             let _ = app.emit("history-changed", ());
         }
     }
@@ -618,6 +668,17 @@ pub async fn pipeline_retry_transcription(
         .load_wav(&request_id)
         .map_err(CommandError::from)?;
 
+    // A manual retry supersedes any pending automatic one for the original request.
+    if let Some(queued) = crate::commands::offline_retry::dequeue(&app, &request_id) {
+        if let Some(history) = app.try_state::<HistoryStorage>() {
+            let message = queued
+                .last_error
+                .unwrap_or_else(|| "Retried manually".to_string());
+            let _ = history.complete_request_error(&request_id, message);
+            let _ = app.emit("history-changed", ());
+        }
+    }
+
     // Start a *new* request log for the retry attempt.
     let config = pipeline.config();
     let new_request_id: Option<String> = app.try_state::<RequestLogStore>().map(|log_store| {
@@ -634,6 +695,7 @@ pub async fn pipeline_retry_transcription(
             None
         },
         llm_model: config.llm_config.model.clone(),
+        profile_id: None,
     };
 
     // Create a history entry for the retry attempt.
@@ -676,6 +738,17 @@ pub async fn pipeline_retry_transcription(
                     let _ = history.complete_request_error(req_id, e.to_string());
                     let _ = app.emit("history-changed", ());
                 }
+
+                // Keep the audio under the new id so this attempt can be retried as well.
+                if recording_store.save_wav(req_id, &wav).is_ok() {
+                    let _ = recording_store.prune_to_max_files(max_saved_recordings);
+                }
+                crate::commands::offline_retry::queue_if_network_error(
+                    &app,
+                    req_id,
+                    pipeline.last_profile_id(),
+                    &e,
+                );
             }
 
             // Also emit pipeline-error so the overlay can present the always-on-top retry UI.
@@ -702,8 +775,13 @@ pub async fn pipeline_retry_transcription(
     if let Some(log_store) = app.try_state::<RequestLogStore>() {
         log_store.with_current(|log| {
             log.raw_transcript = Some(result.stt_text.clone());
+            log.stt_transcript = Some(result.stt_transcript.clone()).filter(|t| t.has_details());
             log.formatted_transcript = Some(result.final_text.clone());
             log.stt_duration_ms = Some(result.stt_duration_ms);
+            if let Some(provider) = &result.stt_provider_used {
+                log.stt_provider = provider.clone();
+                log.stt_model = result.stt_model_used.clone();
+            }
             log.llm_duration_ms = result.llm_duration_ms;
 
             if result.llm_attempted() {
@@ -724,7 +802,12 @@ pub async fn pipeline_retry_transcription(
     // Update history on success
     if let Some(req_id) = new_request_id.as_deref() {
         if let Some(history) = app.try_state::<HistoryStorage>() {
-            let _ = history.complete_request_success(req_id, final_text.clone());
+            let _ = history.complete_request_success_detailed(
+                req_id,
+                final_text.clone(),
+                Some(result.stt_transcript.clone()),
+                result.models_used(),
+            );
             let _ = app.emit("history-changed", ());
         }
     }
@@ -902,6 +985,11 @@ pub async fn pipeline_dictate(
         });
     }
 
+    // Show the streamed LLM rewrite in the overlay; the final text is returned as usual.
+    if let Some(deltas) = pipeline.take_llm_deltas() {
+        crate::forward_llm_deltas(&app, deltas, None);
+    }
+
     let result = match pipeline.stop_and_transcribe_detailed().await {
         Ok(r) => r,
         Err(PipelineError::Cancelled) => {
@@ -954,8 +1042,13 @@ pub async fn pipeline_dictate(
     if let Some(log_store) = app.try_state::<RequestLogStore>() {
         log_store.with_current(|log| {
             log.raw_transcript = Some(result.stt_text.clone());
+            log.stt_transcript = Some(result.stt_transcript.clone()).filter(|t| t.has_details());
             log.formatted_transcript = Some(result.final_text.clone());
             log.stt_duration_ms = Some(result.stt_duration_ms);
+            if let Some(provider) = &result.stt_provider_used {
+                log.stt_provider = provider.clone();
+                log.stt_model = result.stt_model_used.clone();
+            }
             log.llm_duration_ms = result.llm_duration_ms;
 
             log.info(format!(
@@ -968,7 +1061,13 @@ pub async fn pipeline_dictate(
                 LlmOutcome::NotAttempted => {
                     log.info("LLM formatting not attempted (disabled or unavailable)");
                 }
-                LlmOutcome::Succeeded => {
+                LlmOutcome::Succeeded { provider, attempts, .. } => {
+                    if *attempts > 1 {
+                        log.info(format!(
+                            "LLM fallback provider {} succeeded after {} attempts",
+                            provider, attempts
+                        ));
+                    }
                     if let Some(ms) = result.llm_duration_ms {
                         log.info(format!(
                             "LLM formatting succeeded in {}ms ({} -> {} chars)",
@@ -990,6 +1089,21 @@ pub async fn pipeline_dictate(
                         log.warn("LLM formatting timed out; fell back to STT transcript");
                     }
                 }
+                LlmOutcome::SucceededAfterStrictRetry { provider, reason, .. } => {
+                    log.info(format!(
+                        "LLM output from {} rejected ({}); strict retry succeeded ({} -> {} chars)",
+                        provider,
+                        reason,
+                        result.stt_text.len(),
+                        result.final_text.len()
+                    ));
+                }
+                LlmOutcome::Rejected { provider, reason } => {
+                    log.warn(format!(
+                        "LLM output from {} rejected; fell back to STT transcript ({})",
+                        provider, reason
+                    ));
+                }
                 LlmOutcome::Failed(err) => {
                     log.warn(format!(
                         "LLM formatting failed; fell back to STT transcript ({})",
@@ -1002,6 +1116,7 @@ pub async fn pipeline_dictate(
         });
         log_store.complete_current();
     }
+    crate::notify_llm_output_rejected(&app, &result.llm_outcome);
 
     #[cfg(desktop)]
     crate::set_escape_cancel_shortcut_enabled(&app, false);
@@ -1071,6 +1186,23 @@ pub fn pipeline_get_last_recording_diagnostics(
     Ok(pipeline.last_recording_diagnostics())
 }
 
+/// Get the rolling health (success rate, latency, circuit state) of each STT/LLM provider used.
+#[tauri::command]
+pub fn pipeline_get_provider_health(
+    pipeline: State<'_, SharedPipeline>,
+) -> Result<Vec<ProviderHealthSnapshot>, CommandError> {
+    Ok(pipeline.provider_health())
+}
+
+/// Forget recorded provider health so every provider is tried normally again.
+#[tauri::command]
+pub fn pipeline_reset_provider_health(
+    pipeline: State<'_, SharedPipeline>,
+) -> Result<(), CommandError> {
+    pipeline.reset_provider_health();
+    Ok(())
+}
+
 /// Full pipeline helper: Start recording if not recording, or stop and transcribe if recording
 #[tauri::command]
 pub async fn pipeline_toggle(
@@ -57,18 +57,23 @@ pub async fn register_shortcuts(app: AppHandle) -> Result<(), String> {
         "paste_last_hotkey",
         HotkeyConfig::default_paste_last(),
     );
+    let transform_hotkey: HotkeyConfig =
+        get_setting_from_store(&app, "transform_hotkey", HotkeyConfig::default_transform());
 
     // Convert to shortcuts with validation (fall back to defaults if invalid)
     let toggle_shortcut = toggle_hotkey.to_shortcut_or_default(HotkeyConfig::default_toggle);
     let hold_shortcut = hold_hotkey.to_shortcut_or_default(HotkeyConfig::default_hold);
     let paste_last_shortcut =
         paste_last_hotkey.to_shortcut_or_default(HotkeyConfig::default_paste_last);
+    let transform_shortcut =
+        transform_hotkey.to_shortcut_or_default(HotkeyConfig::default_transform);
 
     log::info!(
-        "Re-registering shortcuts - Toggle: {}, Hold: {}, PasteLast: {}",
+        "Re-registering shortcuts - Toggle: {}, Hold: {}, PasteLast: {}, Transform: {}",
         toggle_hotkey.to_shortcut_string(),
         hold_hotkey.to_shortcut_string(),
-        paste_last_hotkey.to_shortcut_string()
+        paste_last_hotkey.to_shortcut_string(),
+        transform_hotkey.to_shortcut_string()
     );
 
     // Get the global shortcut manager
@@ -80,7 +85,12 @@ pub async fn register_shortcuts(app: AppHandle) -> Result<(), String> {
         .map_err(|e| format!("Failed to unregister shortcuts: {}", e))?;
 
     // Collect shortcuts to register
-    let shortcuts: Vec<Shortcut> = vec![toggle_shortcut, hold_shortcut, paste_last_shortcut];
+    let shortcuts: Vec<Shortcut> = vec![
+        toggle_shortcut,
+        hold_shortcut,
+        paste_last_shortcut,
+        transform_shortcut,
+    ];
 
     // Register new shortcuts with handler
     shortcut_manager
@@ -0,0 +1,35 @@
+//! Voice snippet management: trigger phrases the pipeline expands right after STT.
+
+use crate::snippets::{Snippet, SnippetInput, SnippetStore};
+use tauri::State;
+
+/// List all voice snippets
+#[tauri::command]
+pub async fn list_snippets(snippets: State<'_, SnippetStore>) -> Result<Vec<Snippet>, String> {
+    snippets.list()
+}
+
+/// Add a voice snippet
+#[tauri::command]
+pub async fn add_snippet(
+    snippet: SnippetInput,
+    snippets: State<'_, SnippetStore>,
+) -> Result<Snippet, String> {
+    snippets.add(snippet)
+}
+
+/// Update a voice snippet by ID
+#[tauri::command]
+pub async fn update_snippet(
+    id: String,
+    snippet: SnippetInput,
+    snippets: State<'_, SnippetStore>,
+) -> Result<Snippet, String> {
+    snippets.update(&id, snippet)
+}
+
+/// Delete a voice snippet by ID
+#[tauri::command]
+pub async fn delete_snippet(id: String, snippets: State<'_, SnippetStore>) -> Result<bool, String> {
+    snippets.delete(&id)
+}
@@ -1,4 +1,5 @@
-use arboard::Clipboard;
+use crate::llm::StreamedRemainder;
+use arboard::{Clipboard, ImageData};
 use enigo::{Direction, Enigo, Key, Keyboard, Settings};
 use std::sync::{Mutex, OnceLock};
 use std::sync::mpsc;
@@ -69,6 +70,11 @@ impl OutputMode {
             _ => OutputMode::Paste,
         }
     }
+
+    /// Whether text can be pasted piece by piece while the LLM streams it.
+    pub fn can_stream(&self) -> bool {
+        !matches!(self, OutputMode::Clipboard)
+    }
 }
 
 #[tauri::command]
@@ -114,6 +120,58 @@ pub fn output_text_with_mode(text: &str, mode: OutputMode, hit_enter: bool) -> R
     }
 }
 
+/// Finish outputting text whose first sentences were pasted while the LLM streamed it.
+///
+/// Pastes the part of `text` after `streamed`; in `PasteAndClipboard` mode the full text is
+/// left in the clipboard, as if it had been pasted in one go. If `text` doesn't continue the
+/// streamed output (the rewrite failed after it started streaming), the pasted sentences are
+/// deleted and `text` is output in full instead; returns true in that case.
+pub fn output_streamed_remainder(
+    text: &str,
+    streamed: &str,
+    mode: OutputMode,
+    hit_enter: bool,
+) -> Result<bool, String> {
+    let _guard = output_injection_lock()
+        .lock()
+        .map_err(|_| "Output lock poisoned".to_string())?;
+
+    let (rest, replaced) = match crate::llm::streamed_remainder(text, streamed) {
+        StreamedRemainder::Append(rest) => (rest, false),
+        StreamedRemainder::Replace { delete_chars } => {
+            delete_before_cursor(delete_chars)?;
+            (text, true)
+        }
+    };
+
+    if rest.is_empty() {
+        let mut enigo = Enigo::new(&Settings::default()).map_err(|e| e.to_string())?;
+        maybe_hit_enter(&mut enigo, hit_enter)?;
+    } else if mode == OutputMode::PasteAndClipboard {
+        paste_and_keep_clipboard(rest, hit_enter)?;
+    } else {
+        type_text_blocking(rest, hit_enter)?;
+    }
+
+    if mode == OutputMode::PasteAndClipboard {
+        copy_to_clipboard(text)?;
+    }
+    Ok(replaced)
+}
+
+/// Press Backspace `count` times, removing text this app just pasted.
+fn delete_before_cursor(count: usize) -> Result<(), String> {
+    let mut enigo = Enigo::new(&Settings::default()).map_err(|e| e.to_string())?;
+    for _ in 0..count {
+        enigo
+            .key(Key::Backspace, Direction::Click)
+            .map_err(|e| e.to_string())?;
+    }
+    // Let the target app catch up before the replacement is pasted.
+    thread::sleep(Duration::from_millis(KEY_EVENT_DELAY_MS));
+    Ok(())
+}
+
 /// Copy text to clipboard and paste, keeping text in clipboard (no restore)
 pub fn paste_and_keep_clipboard(text: &str, hit_enter: bool) -> Result<(), String> {
     let mut clipboard = Clipboard::new().map_err(|e| e.to_string())?;
@@ -159,6 +217,89 @@ pub fn copy_to_clipboard(text: &str) -> Result<(), String> {
     Ok(())
 }
 
+/// Clipboard content saved before the clipboard is used to copy a selection.
+enum SavedClipboard {
+    Text(String),
+    Image(ImageData<'static>),
+    Empty,
+}
+
+impl SavedClipboard {
+    fn save(clipboard: &mut Clipboard) -> Self {
+        if let Ok(text) = clipboard.get_text() {
+            return SavedClipboard::Text(text);
+        }
+        match clipboard.get_image() {
+            Ok(image) => SavedClipboard::Image(image),
+            Err(_) => SavedClipboard::Empty,
+        }
+    }
+
+    /// Put the saved content back. An empty clipboard is left as is.
+    fn restore(self, clipboard: &mut Clipboard) {
+        let result = match self {
+            SavedClipboard::Text(text) => clipboard.set_text(text),
+            SavedClipboard::Image(image) => clipboard.set_image(image),
+            SavedClipboard::Empty => Ok(()),
+        };
+        if let Err(e) = result {
+            log::warn!("Failed to restore clipboard: {}", e);
+        }
+    }
+}
+
+/// Copy the current selection of the focused app via simulated Ctrl+C / Cmd+C.
+///
+/// The previous clipboard content (text or image) is restored afterwards. Returns `None` when
+/// nothing is selected (the copy left the clipboard empty).
+///
+/// Blocks while it waits for the target app; call it from a blocking task.
+pub fn capture_selection() -> Result<Option<String>, String> {
+    let _guard = output_injection_lock()
+        .lock()
+        .map_err(|_| "Output lock poisoned".to_string())?;
+
+    let mut clipboard = Clipboard::new().map_err(|e| e.to_string())?;
+
+    // Save previous clipboard content, then clear it so an empty selection is detectable
+    let previous = SavedClipboard::save(&mut clipboard);
+    clipboard.clear().map_err(|e| e.to_string())?;
+    thread::sleep(Duration::from_millis(CLIPBOARD_STABILIZATION_DELAY_MS));
+
+    let mut enigo = Enigo::new(&Settings::default()).map_err(|e| e.to_string())?;
+
+    // The hotkey's other modifiers may still be held; Ctrl+Alt+C or Win+C is not a copy.
+    for key in [Key::Alt, Key::Shift, Key::Control, Key::Meta] {
+        enigo.key(key, Direction::Release).map_err(|e| e.to_string())?;
+    }
+
+    #[cfg(target_os = "macos")]
+    let modifier = Key::Meta;
+    #[cfg(not(target_os = "macos"))]
+    let modifier = Key::Control;
+
+    enigo
+        .key(modifier, Direction::Press)
+        .map_err(|e| e.to_string())?;
+    thread::sleep(Duration::from_millis(KEY_EVENT_DELAY_MS));
+    enigo
+        .key(Key::Unicode('c'), Direction::Click)
+        .map_err(|e| e.to_string())?;
+    thread::sleep(Duration::from_millis(KEY_EVENT_DELAY_MS));
+    enigo
+        .key(modifier, Direction::Release)
+        .map_err(|e| e.to_string())?;
+
+    // Give the target app time to fill the clipboard
+    thread::sleep(Duration::from_millis(CLIPBOARD_RESTORE_DELAY_MS));
+    let selection = clipboard.get_text().unwrap_or_default();
+
+    previous.restore(&mut clipboard);
+
+    log::info!("Captured selection ({} chars)", selection.len());
+    Ok(Some(selection).filter(|text| !text.trim().is_empty()))
+}
+
 // Keystrokes mode intentionally disabled.
 // (Kept as a stub in case any legacy call sites remain in downstream forks.)
 #[allow(dead_code)]
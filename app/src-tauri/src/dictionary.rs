@@ -0,0 +1,572 @@
+//! Structured personal dictionary.
+//!
+//! Entries (canonical term + spoken variants) are the single source of truth for the
+//! dictionary: a deterministic replacement pass fixes the STT output with them (also when the
+//! LLM rewrite is off), and the same entries are written into the LLM dictionary section and
+//! the STT vocabulary.
+
+use crate::llm::{PromptSections, DICTIONARY_PROMPT_DEFAULT};
+use crate::stt::Vocabulary;
+use crate::text_normalize::{is_word_char, word_ranges};
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::ops::Range;
+
+/// A dictionary term and the spoken forms it replaces.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DictionaryEntry {
+    #[serde(default = "new_entry_id")]
+    pub id: String,
+    /// Canonical spelling (e.g. "Anthropic").
+    pub term: String,
+    /// Spoken or misheard forms replaced by the term (e.g. "ant row pic").
+    #[serde(default)]
+    pub spoken: Vec<String>,
+    /// Match the spoken forms with exact case.
+    #[serde(default)]
+    pub case_sensitive: bool,
+    /// Also match the term itself (case-insensitively, fixing its capitalization). Off by
+    /// default: terms that are also common words ("Cursor") would be recapitalized everywhere.
+    #[serde(default)]
+    pub match_term: bool,
+    /// Only match whole words (never inside a longer word).
+    #[serde(default = "default_true")]
+    pub whole_word: bool,
+    /// Program profile the entry is limited to (None = every profile).
+    #[serde(default)]
+    pub profile_id: Option<String>,
+}
+
+fn new_entry_id() -> String {
+    uuid::Uuid::new_v4().to_string()
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl DictionaryEntry {
+    pub fn new(term: impl Into<String>, spoken: Vec<String>, profile_id: Option<String>) -> Self {
+        Self {
+            id: new_entry_id(),
+            term: term.into(),
+            spoken,
+            case_sensitive: false,
+            match_term: false,
+            whole_word: true,
+            profile_id,
+        }
+    }
+
+    /// Lines for the LLM dictionary section: one "spoken = Term" mapping per spoken form, or
+    /// just the term when it has none.
+    pub fn prompt_lines(&self) -> Vec<String> {
+        let lines: Vec<String> = self
+            .spoken
+            .iter()
+            .map(|s| s.trim())
+            .filter(|s| !s.is_empty())
+            .map(|spoken| format!("{} = {}", spoken, self.term))
+            .collect();
+        if lines.is_empty() {
+            vec![self.term.clone()]
+        } else {
+            lines
+        }
+    }
+
+    /// Forms replaced by the term: the spoken forms, plus the term itself when opted in.
+    fn patterns(&self) -> impl Iterator<Item = &str> {
+        let term = (self.match_term && !self.case_sensitive).then_some(self.term.as_str());
+        self.spoken
+            .iter()
+            .map(|s| s.trim())
+            .chain(term)
+            .filter(|s| !s.is_empty())
+    }
+}
+
+/// A replacement made by the dictionary pass, as recorded in the request log.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DictionaryReplacement {
+    /// Text as transcribed.
+    pub spoken: String,
+    pub term: String,
+}
+
+/// The entries that apply to a profile (None = Default): global ones plus the profile's own.
+pub fn entries_for_profile(
+    entries: &[DictionaryEntry],
+    profile_id: Option<&str>,
+) -> Vec<DictionaryEntry> {
+    entries
+        .iter()
+        .filter(|e| !e.term.trim().is_empty())
+        .filter(|e| e.profile_id.is_none() || e.profile_id.as_deref() == profile_id)
+        .cloned()
+        .collect()
+}
+
+fn same(a: &str, b: &str, case_sensitive: bool) -> bool {
+    if case_sensitive {
+        a == b
+    } else {
+        a.to_lowercase() == b.to_lowercase()
+    }
+}
+
+/// Whole-word occurrences of `pattern`: its words in sequence, separated by spaces or hyphens.
+fn find_whole_words(
+    text: &str,
+    text_words: &[Range<usize>],
+    pattern: &str,
+    case_sensitive: bool,
+) -> Vec<Range<usize>> {
+    let pattern_words: Vec<&str> = word_ranges(pattern)
+        .into_iter()
+        .map(|r| &pattern[r])
+        .collect();
+    let punctuated = pattern
+        .chars()
+        .any(|c| !is_word_char(c) && !c.is_whitespace() && c != '-');
+    if pattern_words.is_empty() || punctuated {
+        // Punctuation inside the pattern ("Node.js"): only an exact substring can match.
+        return find_substrings(text, pattern, case_sensitive)
+            .into_iter()
+            .filter(|range| {
+                let before = text[..range.start].chars().next_back();
+                let after = text[range.end..].chars().next();
+                !before.is_some_and(is_word_char) && !after.is_some_and(is_word_char)
+            })
+            .collect();
+    }
+
+    let mut found = Vec::new();
+    let mut index = 0;
+    while index + pattern_words.len() <= text_words.len() {
+        let candidate = &text_words[index..index + pattern_words.len()];
+        let matches = candidate
+            .iter()
+            .zip(&pattern_words)
+            .all(|(range, word)| same(&text[range.clone()], word, case_sensitive))
+            && candidate.windows(2).all(|pair| {
+                text[pair[0].end..pair[1].start]
+                    .chars()
+                    .all(|c| c.is_whitespace() || c == '-')
+            });
+        if matches {
+            found.push(candidate[0].start..candidate[candidate.len() - 1].end);
+            index += pattern_words.len();
+        } else {
+            index += 1;
+        }
+    }
+    found
+}
+
+/// Occurrences of `pattern` anywhere in `text` (ASCII case folding when not case-sensitive).
+fn find_substrings(text: &str, pattern: &str, case_sensitive: bool) -> Vec<Range<usize>> {
+    let mut found = Vec::new();
+    let mut index = 0;
+    while index + pattern.len() <= text.len() {
+        let matches = text
+            .get(index..index + pattern.len())
+            .is_some_and(|candidate| {
+                if case_sensitive {
+                    candidate == pattern
+                } else {
+                    candidate.eq_ignore_ascii_case(pattern)
+                }
+            });
+        if matches {
+            found.push(index..index + pattern.len());
+            index += pattern.len();
+        } else {
+            index += text[index..].chars().next().map_or(1, char::len_utf8);
+        }
+    }
+    found
+}
+
+/// Replace the spoken forms of the dictionary terms in `text`.
+///
+/// Longer matches win over shorter overlapping ones; text that already reads as the term is
+/// left alone.
+pub fn apply_dictionary(
+    text: &str,
+    entries: &[DictionaryEntry],
+) -> (String, Vec<DictionaryReplacement>) {
+    let text_words = word_ranges(text);
+
+    let mut matches: Vec<(Range<usize>, &str)> = Vec::new();
+    for entry in entries {
+        for pattern in entry.patterns() {
+            let found = if entry.whole_word {
+                find_whole_words(text, &text_words, pattern, entry.case_sensitive)
+            } else {
+                find_substrings(text, pattern, entry.case_sensitive)
+            };
+            matches.extend(found.into_iter().map(|range| (range, entry.term.as_str())));
+        }
+    }
+    matches.sort_by_key(|(range, _)| (range.start, std::cmp::Reverse(range.end)));
+
+    let mut output = String::with_capacity(text.len());
+    let mut replacements = Vec::new();
+    let mut last = 0;
+    for (range, term) in matches {
+        if range.start < last || text[range.clone()] == *term {
+            continue;
+        }
+        output.push_str(&text[last..range.start]);
+        output.push_str(term);
+        replacements.push(DictionaryReplacement {
+            spoken: text[range.clone()].to_string(),
+            term: term.to_string(),
+        });
+        last = range.end;
+    }
+    output.push_str(&text[last..]);
+
+    (output, replacements)
+}
+
+/// The prompts with the dictionary entries listed in the dictionary section.
+///
+/// The entries are appended to the section's own instructions (the built-in ones when the
+/// section is off); the section is enabled whenever there are entries. Without entries the
+/// prompts are unchanged, so an enabled section keeps its instructions.
+pub fn with_dictionary(prompts: &PromptSections, entries: &[DictionaryEntry]) -> PromptSections {
+    if entries.is_empty() {
+        return prompts.clone();
+    }
+
+    let mut section = if prompts.dictionary_enabled {
+        prompts.dictionary_prompt().trim_end().to_string()
+    } else {
+        DICTIONARY_PROMPT_DEFAULT.to_string()
+    };
+    if !section.contains("### Entries") {
+        section.push_str("\n\n### Entries:");
+    }
+    for line in entries.iter().flat_map(DictionaryEntry::prompt_lines) {
+        section.push('\n');
+        section.push_str(&line);
+    }
+
+    PromptSections {
+        dictionary_enabled: true,
+        dictionary_custom: Some(section.trim_start().to_string()),
+        ..prompts.clone()
+    }
+}
+
+/// A dictionary section's text before its `### Entries` list.
+fn section_instructions(section: &str) -> &str {
+    section
+        .split_once("### Entries")
+        .map_or(section, |(instructions, _)| instructions)
+        .trim_end()
+}
+
+/// Move the entry lines of a dictionary prompt section into `entries`.
+///
+/// Dictionaries used to be written into the section as lines under `### Entries:` ("ant row
+/// pic = Anthropic" or just a term). Each line becomes a `DictionaryEntry` limited to
+/// `profile_id`, merged into an existing entry for the same term. Returns the section text
+/// left: its instructions, plus any free-form lines that weren't entries.
+pub fn migrate_dictionary_section(
+    section: &str,
+    profile_id: Option<&str>,
+    entries: &mut Vec<DictionaryEntry>,
+) -> String {
+    let Some((_, listed)) = section.split_once("### Entries") else {
+        return section.to_string();
+    };
+    let listed = listed.split_once('\n').map_or("", |(_, rest)| rest);
+
+    let mut kept = section_instructions(section).to_string();
+    for line in listed.lines() {
+        let Some(parsed) = Vocabulary::parse_dictionary_line(line) else {
+            if !line.trim().is_empty() && !line.trim().starts_with('#') {
+                kept.push('\n');
+                kept.push_str(line.trim());
+            }
+            continue;
+        };
+
+        let existing = entries
+            .iter_mut()
+            .find(|e| e.term == parsed.term && e.profile_id.as_deref() == profile_id);
+        match existing {
+            Some(entry) => {
+                for spoken in parsed.sounds_like {
+                    if !entry
+                        .spoken
+                        .iter()
+                        .any(|s| s.trim().eq_ignore_ascii_case(&spoken))
+                    {
+                        entry.spoken.push(spoken);
+                    }
+                }
+            }
+            None => entries.push(DictionaryEntry::new(
+                parsed.term,
+                parsed.sounds_like,
+                profile_id.map(str::to_string),
+            )),
+        }
+    }
+    kept
+}
+
+/// One-time migration of the dictionary prompt sections in the settings into entries.
+///
+/// `global` is the `cleanup_prompt_sections` setting and `profiles` the
+/// `rewrite_program_prompt_profiles` setting. Only enabled sections with custom text are
+/// migrated: a disabled section's entries never applied, and the built-in text only lists
+/// examples. Returns whether any section changed.
+pub fn migrate_dictionary_settings(
+    global: &mut JsonValue,
+    profiles: &mut JsonValue,
+    entries: &mut Vec<DictionaryEntry>,
+) -> bool {
+    let mut migrate = |sections: Option<&mut JsonValue>, profile_id: Option<&str>| {
+        let Some(dictionary) = sections.and_then(|s| s.get_mut("dictionary")) else {
+            return false;
+        };
+        let enabled = dictionary.get("enabled").and_then(JsonValue::as_bool) == Some(true);
+        let Some(content) = dictionary.get("content").and_then(JsonValue::as_str) else {
+            return false;
+        };
+        if !enabled {
+            return false;
+        }
+
+        let migrated = migrate_dictionary_section(content, profile_id, entries);
+        if migrated == content {
+            return false;
+        }
+        dictionary["content"] = JsonValue::String(migrated);
+        true
+    };
+
+    let mut changed = migrate(Some(global), None);
+    for profile in profiles.as_array_mut().into_iter().flatten() {
+        let id = profile
+            .get("id")
+            .and_then(JsonValue::as_str)
+            .map(str::to_string);
+        changed |= migrate(profile.get_mut("cleanup_prompt_sections"), id.as_deref());
+    }
+    changed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(term: &str, spoken: &[&str]) -> DictionaryEntry {
+        DictionaryEntry::new(term, spoken.iter().map(|s| s.to_string()).collect(), None)
+    }
+
+    #[test]
+    fn test_apply_dictionary_whole_words() {
+        let mut anthropic = entry("Anthropic", &["ant row pic", "ant row pick"]);
+        anthropic.match_term = true;
+        let entries = vec![anthropic, entry("Claude", &["cloud"])];
+        let (text, replacements) = apply_dictionary(
+            "I joined Ant-Row-Pic to work on cloud, not clouds. anthropic rocks",
+            &entries,
+        );
+        assert_eq!(
+            text,
+            "I joined Anthropic to work on Claude, not clouds. Anthropic rocks"
+        );
+        assert_eq!(
+            replacements,
+            vec![
+                DictionaryReplacement {
+                    spoken: "Ant-Row-Pic".to_string(),
+                    term: "Anthropic".to_string()
+                },
+                DictionaryReplacement {
+                    spoken: "cloud".to_string(),
+                    term: "Claude".to_string()
+                },
+                DictionaryReplacement {
+                    spoken: "anthropic".to_string(),
+                    term: "Anthropic".to_string()
+                },
+            ]
+        );
+
+        // Already correct text is not reported.
+        assert!(apply_dictionary("Anthropic and Claude", &entries)
+            .1
+            .is_empty());
+    }
+
+    #[test]
+    fn test_apply_dictionary_flags() {
+        let mut acronym = entry("LLM", &["LLMs"]);
+        acronym.case_sensitive = true;
+        let mut suffix = entry("GmbH", &["gmbh"]);
+        suffix.whole_word = false;
+        let mut dotted = entry("Node.js", &["node js"]);
+        dotted.match_term = true;
+
+        let (text, _) = apply_dictionary(
+            "llm LLMs AcmeGMBH node js and node.js",
+            &[acronym, suffix, dotted],
+        );
+        assert_eq!(text, "llm LLM AcmeGmbH Node.js and Node.js");
+    }
+
+    #[test]
+    fn test_apply_dictionary_leaves_common_word_terms_alone() {
+        let cursor = entry("Cursor", &["cursor editor"]);
+        let (text, replacements) = apply_dictionary(
+            "Move the cursor, then open the cursor editor.",
+            &[cursor.clone()],
+        );
+        assert_eq!(text, "Move the cursor, then open the Cursor.");
+        assert_eq!(replacements.len(), 1);
+
+        // Matching the term itself is opt-in.
+        let cursor = DictionaryEntry {
+            match_term: true,
+            ..cursor
+        };
+        let (text, _) = apply_dictionary("Move the cursor.", &[cursor]);
+        assert_eq!(text, "Move the Cursor.");
+    }
+
+    #[test]
+    fn test_entries_for_profile() {
+        let mut slack = entry("Slackbot", &[]);
+        slack.profile_id = Some("slack".to_string());
+        let entries = vec![entry("Claude", &[]), slack, entry(" ", &["blank"])];
+
+        let terms = |profile| -> Vec<String> {
+            entries_for_profile(&entries, profile)
+                .into_iter()
+                .map(|e| e.term)
+                .collect()
+        };
+        assert_eq!(terms(None), vec!["Claude"]);
+        assert_eq!(terms(Some("slack")), vec!["Claude", "Slackbot"]);
+    }
+
+    #[test]
+    fn test_with_dictionary_generates_prompt_section() {
+        let entries = vec![
+            entry("Anthropic", &["ant row pic", "ant row pick"]),
+            entry("LLM", &[]),
+        ];
+
+        let prompts = with_dictionary(&PromptSections::default(), &entries);
+        assert!(prompts.dictionary_enabled);
+        let section = prompts.dictionary_prompt();
+        assert!(section.starts_with("## Personal Dictionary"));
+        assert!(section
+            .ends_with("### Entries:\nant row pic = Anthropic\nant row pick = Anthropic\nLLM"));
+        assert_eq!(
+            prompts.vocabulary().prioritized_terms(),
+            vec!["Anthropic".to_string(), "LLM".to_string()]
+        );
+
+        let custom = PromptSections {
+            dictionary_enabled: true,
+            dictionary_custom: Some("Always write Tangerine.".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            with_dictionary(&custom, &entries).dictionary_prompt(),
+            "Always write Tangerine.\n\n### Entries:\nant row pic = Anthropic\nant row pick = Anthropic\nLLM"
+        );
+        // Without entries an enabled section keeps its instructions.
+        let unchanged = with_dictionary(&custom, &[]);
+        assert!(unchanged.dictionary_enabled);
+        assert_eq!(unchanged.dictionary_custom, custom.dictionary_custom);
+
+        // Terms the section lists itself stay; the entries are added to them.
+        let listed = PromptSections {
+            dictionary_enabled: true,
+            dictionary_custom: Some(
+                "Always write Tangerine.\n\n### Entries:\nold = Old".to_string(),
+            ),
+            ..Default::default()
+        };
+        assert_eq!(
+            with_dictionary(&listed, &entries[1..]).dictionary_prompt(),
+            "Always write Tangerine.\n\n### Entries:\nold = Old\nLLM"
+        );
+    }
+
+    #[test]
+    fn test_migrate_dictionary_settings() {
+        let mut global = serde_json::json!({
+            "main": { "enabled": true, "content": null },
+            "dictionary": {
+                "enabled": true,
+                "content": "Be careful with names.\n\n### Entries:\nLLM\nant row pic = Anthropic\nThe name 'Claude' should always be capitalized in every sentence"
+            },
+        });
+        let mut profiles = serde_json::json!([
+            {
+                "id": "slack",
+                "cleanup_prompt_sections": {
+                    "dictionary": { "enabled": true, "content": "### Entries:\nant row pick = Anthropic" }
+                }
+            },
+            {
+                "id": "off",
+                "cleanup_prompt_sections": {
+                    "dictionary": { "enabled": false, "content": "### Entries:\nIgnored" }
+                }
+            },
+            { "id": "inherit", "cleanup_prompt_sections": { "dictionary": null } },
+        ]);
+        let mut entries = vec![entry("LLM", &[])];
+
+        assert!(migrate_dictionary_settings(
+            &mut global,
+            &mut profiles,
+            &mut entries
+        ));
+        assert_eq!(
+            global["dictionary"]["content"],
+            "Be careful with names.\nThe name 'Claude' should always be capitalized in every sentence"
+        );
+        assert_eq!(
+            profiles[0]["cleanup_prompt_sections"]["dictionary"]["content"],
+            ""
+        );
+        assert_eq!(
+            profiles[1]["cleanup_prompt_sections"]["dictionary"]["content"],
+            "### Entries:\nIgnored"
+        );
+
+        let migrated: Vec<(&str, Vec<String>, Option<&str>)> = entries
+            .iter()
+            .map(|e| (e.term.as_str(), e.spoken.clone(), e.profile_id.as_deref()))
+            .collect();
+        assert_eq!(
+            migrated,
+            vec![
+                ("LLM", vec![], None),
+                ("Anthropic", vec!["ant row pic".to_string()], None),
+                ("Anthropic", vec!["ant row pick".to_string()], Some("slack")),
+            ]
+        );
+
+        // Running it again changes nothing.
+        assert!(!migrate_dictionary_settings(
+            &mut global,
+            &mut profiles,
+            &mut entries
+        ));
+        assert_eq!(entries.len(), 3);
+    }
+}
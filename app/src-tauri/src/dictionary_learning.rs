@@ -0,0 +1,541 @@
+//! Auto-learning dictionary: dictionary entries proposed from the user's corrections.
+//!
+//! Corrections come from history entries the user edited and from a dictation repeated right
+//! after the previous one. The raw STT text is diffed word by word against the corrected
+//! text; short replaced spans ("ant row pic" -> "Anthropic") become suggestions. Nothing is
+//! added to the dictionary until the user approves a suggestion.
+
+use crate::dictionary::DictionaryEntry;
+use crate::text_normalize::{normalize_word, word_ranges};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::ops::Range;
+use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
+use uuid::Uuid;
+
+/// Longer replaced spans are rewrites, not misrecognitions.
+const MAX_SPAN_WORDS: usize = 4;
+
+/// Texts are diffed word by word; longer ones are skipped (quadratic diff).
+const MAX_DIFF_WORDS: usize = 300;
+
+/// Pending suggestions kept (the oldest are dropped first).
+const MAX_SUGGESTIONS: usize = 100;
+
+/// Rejected corrections remembered so they aren't proposed again.
+const MAX_REJECTED: usize = 500;
+
+/// A replaced span: what the STT heard and what the user meant.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Correction {
+    /// Misrecognized words, lowercased without punctuation (e.g. "ant row pic").
+    pub heard: String,
+    /// Correct spelling (e.g. "Anthropic").
+    pub correct: String,
+}
+
+impl Correction {
+    fn same_as(&self, other: &Correction) -> bool {
+        self.heard.eq_ignore_ascii_case(&other.heard) && self.correct == other.correct
+    }
+
+    /// Dictionary line for this correction ("ant row pic = Anthropic").
+    pub fn dictionary_entry(&self) -> String {
+        format!("{} = {}", self.heard, self.correct)
+    }
+}
+
+/// Where a correction was observed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CorrectionSource {
+    /// The user edited a history entry.
+    HistoryEdit,
+    /// The user dictated the same thing again right after.
+    Redictation,
+}
+
+/// A proposed dictionary entry waiting for approval.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DictionarySuggestion {
+    pub id: String,
+    #[serde(flatten)]
+    pub correction: Correction,
+    /// Program profile the dictation used (None = Default); its dictionary gets the entry.
+    pub profile_id: Option<String>,
+    pub source: CorrectionSource,
+    /// How often the correction was observed.
+    pub times_seen: u32,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+struct SuggestionData {
+    suggestions: Vec<DictionarySuggestion>,
+    #[serde(default)]
+    rejected: Vec<Correction>,
+}
+
+/// Manages loading and saving of pending dictionary suggestions.
+///
+/// Clones share the same data (the pipeline keeps one to record re-dictations).
+#[derive(Debug, Clone)]
+pub struct DictionarySuggestionStore {
+    data: Arc<RwLock<SuggestionData>>,
+    file_path: Option<PathBuf>,
+}
+
+impl DictionarySuggestionStore {
+    /// Create a store persisted to `dictionary_suggestions.json` in the given app data directory
+    pub fn new(app_data_dir: PathBuf) -> Self {
+        let file_path = app_data_dir.join("dictionary_suggestions.json");
+
+        if let Some(parent) = file_path.parent() {
+            let _ = fs::create_dir_all(parent);
+        }
+
+        let data = fs::read_to_string(&file_path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+
+        Self {
+            data: Arc::new(RwLock::new(data)),
+            file_path: Some(file_path),
+        }
+    }
+
+    /// Create a store that is never written to disk
+    #[cfg(test)]
+    pub fn in_memory() -> Self {
+        Self {
+            data: Arc::new(RwLock::new(SuggestionData::default())),
+            file_path: None,
+        }
+    }
+
+    fn save(&self) -> Result<(), String> {
+        let Some(file_path) = &self.file_path else {
+            return Ok(());
+        };
+
+        let data = self
+            .data
+            .read()
+            .map_err(|e| format!("Failed to read dictionary suggestions: {}", e))?;
+
+        let content = serde_json::to_string_pretty(&*data)
+            .map_err(|e| format!("Failed to serialize dictionary suggestions: {}", e))?;
+
+        fs::write(file_path, content)
+            .map_err(|e| format!("Failed to write dictionary suggestions file: {}", e))?;
+
+        Ok(())
+    }
+
+    /// Pending suggestions, newest first
+    pub fn list(&self) -> Result<Vec<DictionarySuggestion>, String> {
+        let data = self
+            .data
+            .read()
+            .map_err(|e| format!("Failed to read dictionary suggestions: {}", e))?;
+        Ok(data.suggestions.clone())
+    }
+
+    /// A pending suggestion by ID
+    pub fn get(&self, id: &str) -> Result<Option<DictionarySuggestion>, String> {
+        Ok(self.list()?.into_iter().find(|s| s.id == id))
+    }
+
+    /// Record an observed correction.
+    ///
+    /// A correction already pending for the same profile is counted again instead of added
+    /// twice. Returns None for corrections the user rejected before.
+    pub fn propose(
+        &self,
+        correction: Correction,
+        profile_id: Option<&str>,
+        source: CorrectionSource,
+    ) -> Result<Option<DictionarySuggestion>, String> {
+        let suggestion = {
+            let mut data = self
+                .data
+                .write()
+                .map_err(|e| format!("Failed to write dictionary suggestions: {}", e))?;
+
+            if data.rejected.iter().any(|r| r.same_as(&correction)) {
+                return Ok(None);
+            }
+
+            let existing = data.suggestions.iter_mut().find(|s| {
+                s.correction.same_as(&correction) && s.profile_id.as_deref() == profile_id
+            });
+            match existing {
+                Some(existing) => {
+                    existing.times_seen += 1;
+                    existing.clone()
+                }
+                None => {
+                    let suggestion = DictionarySuggestion {
+                        id: Uuid::new_v4().to_string(),
+                        correction,
+                        profile_id: profile_id.map(str::to_string),
+                        source,
+                        times_seen: 1,
+                        created_at: Utc::now(),
+                    };
+                    data.suggestions.insert(0, suggestion.clone());
+                    data.suggestions.truncate(MAX_SUGGESTIONS);
+                    suggestion
+                }
+            }
+        };
+
+        self.save()?;
+        Ok(Some(suggestion))
+    }
+
+    /// Remove a suggestion (after it was approved). Returns false if it didn't exist.
+    pub fn remove(&self, id: &str) -> Result<bool, String> {
+        let removed = {
+            let mut data = self
+                .data
+                .write()
+                .map_err(|e| format!("Failed to write dictionary suggestions: {}", e))?;
+            let before = data.suggestions.len();
+            data.suggestions.retain(|s| s.id != id);
+            data.suggestions.len() != before
+        };
+
+        if removed {
+            self.save()?;
+        }
+        Ok(removed)
+    }
+
+    /// Reject a suggestion; the same correction won't be proposed again.
+    pub fn reject(&self, id: &str) -> Result<bool, String> {
+        {
+            let mut data = self
+                .data
+                .write()
+                .map_err(|e| format!("Failed to write dictionary suggestions: {}", e))?;
+            let Some(index) = data.suggestions.iter().position(|s| s.id == id) else {
+                return Ok(false);
+            };
+            let rejected = data.suggestions.remove(index);
+            data.rejected.insert(0, rejected.correction);
+            data.rejected.truncate(MAX_REJECTED);
+        }
+
+        self.save()?;
+        Ok(true)
+    }
+}
+
+/// A word of a text: where it was written, and normalized for comparison.
+struct Token {
+    range: Range<usize>,
+    key: String,
+}
+
+fn tokens(text: &str) -> Vec<Token> {
+    word_ranges(text)
+        .into_iter()
+        .map(|range| Token {
+            key: normalize_word(&text[range.clone()]),
+            range,
+        })
+        .filter(|token| !token.key.is_empty())
+        .collect()
+}
+
+/// Matched word pairs (index in `a`, index in `b`) of the longest common subsequence.
+fn common_words(a: &[Token], b: &[Token]) -> Vec<(usize, usize)> {
+    // lengths[i][j]: LCS length of a[i..] and b[j..]
+    let mut lengths = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lengths[i][j] = if a[i].key == b[j].key {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+
+    let mut pairs = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        if a[i].key == b[j].key {
+            pairs.push((i, j));
+            i += 1;
+            j += 1;
+        } else if lengths[i + 1][j] >= lengths[i][j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    pairs
+}
+
+/// Whether `current` repeats `previous` with a few words changed.
+pub fn is_redictation(previous: &str, current: &str) -> bool {
+    let (a, b) = (tokens(previous), tokens(current));
+    if a.is_empty() || b.is_empty() || a.len().max(b.len()) > MAX_DIFF_WORDS {
+        return false;
+    }
+    let common = common_words(&a, &b).len();
+    common < a.len().max(b.len()) && common * 2 >= a.len().max(b.len())
+}
+
+/// Replaced spans between the raw transcript and the corrected text.
+///
+/// Only short replacements count (inserted or deleted words are rewording, not
+/// misrecognition), and only when most of the text is unchanged or the whole text is a single
+/// short replacement.
+pub fn propose_corrections(raw: &str, corrected: &str) -> Vec<Correction> {
+    let (a, b) = (tokens(raw), tokens(corrected));
+    if a.is_empty() || b.is_empty() || a.len().max(b.len()) > MAX_DIFF_WORDS {
+        return Vec::new();
+    }
+
+    let mut pairs = common_words(&a, &b);
+    let whole_text_replaced = pairs.is_empty() && a.len().max(b.len()) <= MAX_SPAN_WORDS;
+    if !whole_text_replaced && pairs.len() * 2 < a.len().max(b.len()) {
+        return Vec::new();
+    }
+
+    let mut corrections: Vec<Correction> = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    pairs.push((a.len(), b.len()));
+    for (next_i, next_j) in pairs {
+        let heard = &a[i..next_i];
+        let correct = &b[j..next_j];
+        if !heard.is_empty()
+            && !correct.is_empty()
+            && heard.len() <= MAX_SPAN_WORDS
+            && correct.len() <= MAX_SPAN_WORDS
+        {
+            let correction = Correction {
+                heard: heard
+                    .iter()
+                    .map(|t| t.key.as_str())
+                    .collect::<Vec<_>>()
+                    .join(" "),
+                // As written, so terms like "Node.js" keep their punctuation.
+                correct: corrected[correct[0].range.start..correct[correct.len() - 1].range.end]
+                    .to_string(),
+            };
+            if !corrections.iter().any(|c| c.same_as(&correction)) {
+                corrections.push(correction);
+            }
+        }
+        (i, j) = (next_i + 1, next_j + 1);
+    }
+    corrections
+}
+
+/// Corrections from a history edit of `before` (the output) into `after`.
+///
+/// `raw` is the STT text the output was made from. Replacements the rewrite already made
+/// (the corrected words are in `before`) are not the user's corrections and are skipped.
+pub fn corrections_from_edit(raw: &str, before: &str, after: &str) -> Vec<Correction> {
+    let before: Vec<String> = tokens(before).into_iter().map(|t| t.key).collect();
+    propose_corrections(raw, after)
+        .into_iter()
+        .filter(|correction| {
+            let correct: Vec<String> = tokens(&correction.correct)
+                .into_iter()
+                .map(|t| t.key)
+                .collect();
+            !before
+                .windows(correct.len())
+                .any(|window| window == correct.as_slice())
+        })
+        .collect()
+}
+
+/// Add an approved correction to the dictionary entries.
+///
+/// The heard words become a spoken form of an existing entry for the correct spelling when
+/// one applies to the profile the correction was learned in; otherwise a new entry limited to
+/// that profile (or global for the Default profile) is added. Returns false when the entry
+/// already had the spoken form.
+pub fn add_to_dictionary(
+    entries: &mut Vec<DictionaryEntry>,
+    profile_id: Option<&str>,
+    correction: &Correction,
+) -> bool {
+    let existing = entries.iter_mut().find(|entry| {
+        entry.term == correction.correct
+            && (entry.profile_id.is_none() || entry.profile_id.as_deref() == profile_id)
+    });
+
+    match existing {
+        Some(entry) => {
+            if entry
+                .spoken
+                .iter()
+                .any(|spoken| spoken.trim().eq_ignore_ascii_case(&correction.heard))
+            {
+                return false;
+            }
+            entry.spoken.push(correction.heard.clone());
+        }
+        None => entries.push(DictionaryEntry::new(
+            correction.correct.clone(),
+            vec![correction.heard.clone()],
+            profile_id.map(str::to_string),
+        )),
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn correction(heard: &str, correct: &str) -> Correction {
+        Correction {
+            heard: heard.to_string(),
+            correct: correct.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_propose_corrections() {
+        assert_eq!(
+            propose_corrections(
+                "I work at ant row pic on the cloud team.",
+                "I work at Anthropic, on the Claude team."
+            ),
+            vec![
+                correction("ant row pic", "Anthropic"),
+                correction("cloud", "Claude")
+            ]
+        );
+
+        // A single short replacement of the whole text counts, a rewrite doesn't.
+        assert_eq!(
+            propose_corrections("ant row pic", "Anthropic"),
+            vec![correction("ant row pic", "Anthropic")]
+        );
+        assert!(propose_corrections("so basically we should ship it", "Ship it today.").is_empty());
+
+        // Case and punctuation changes are not corrections.
+        assert!(propose_corrections("hello world", "Hello, world!").is_empty());
+
+        // The correct spelling is kept as written.
+        assert_eq!(
+            propose_corrections("we use note jay ess here", "We use Node.js here."),
+            vec![correction("note jay ess", "Node.js")]
+        );
+    }
+
+    #[test]
+    fn test_corrections_from_edit_skip_rewrite_changes() {
+        let raw = "um send it to ant row pic tomorrow gonna be great";
+        let before = "Send it to ant row pic tomorrow; going to be great.";
+        let after = "Send it to Anthropic tomorrow; going to be great.";
+        assert_eq!(
+            corrections_from_edit(raw, before, after),
+            vec![correction("ant row pic", "Anthropic")]
+        );
+    }
+
+    #[test]
+    fn test_is_redictation() {
+        assert!(is_redictation(
+            "email the cloud team",
+            "email the Claude team"
+        ));
+        assert!(!is_redictation("email the team", "Email the team."));
+        assert!(!is_redictation("yes", "sounds good to me"));
+    }
+
+    #[test]
+    fn test_store_dedupes_and_remembers_rejections() {
+        let store = DictionarySuggestionStore::in_memory();
+        let first = store
+            .propose(
+                correction("cloud", "Claude"),
+                None,
+                CorrectionSource::Redictation,
+            )
+            .unwrap()
+            .unwrap();
+        let again = store
+            .propose(
+                correction("Cloud", "Claude"),
+                None,
+                CorrectionSource::HistoryEdit,
+            )
+            .unwrap()
+            .unwrap();
+        assert_eq!(again.id, first.id);
+        assert_eq!(again.times_seen, 2);
+
+        // Another profile gets its own suggestion.
+        store
+            .propose(
+                correction("cloud", "Claude"),
+                Some("slack"),
+                CorrectionSource::HistoryEdit,
+            )
+            .unwrap();
+        assert_eq!(store.list().unwrap().len(), 2);
+
+        assert!(store.reject(&first.id).unwrap());
+        assert!(!store.reject(&first.id).unwrap());
+        assert_eq!(
+            store
+                .propose(
+                    correction("cloud", "Claude"),
+                    None,
+                    CorrectionSource::Redictation
+                )
+                .unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_add_to_dictionary_merges_spoken_forms() {
+        let mut entries = vec![DictionaryEntry::new("Anthropic", vec![], None)];
+
+        assert!(add_to_dictionary(
+            &mut entries,
+            Some("slack"),
+            &correction("ant row pic", "Anthropic")
+        ));
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].spoken, vec!["ant row pic"]);
+        assert!(!add_to_dictionary(
+            &mut entries,
+            None,
+            &correction("Ant Row Pic", "Anthropic")
+        ));
+
+        // A new term is limited to the profile it was learned in.
+        assert!(add_to_dictionary(
+            &mut entries,
+            Some("slack"),
+            &correction("cloud", "Claude")
+        ));
+        assert_eq!(entries[1].term, "Claude");
+        assert_eq!(entries[1].spoken, vec!["cloud"]);
+        assert_eq!(entries[1].profile_id.as_deref(), Some("slack"));
+
+        // The Slack entry doesn't apply to other profiles, which get their own.
+        assert!(add_to_dictionary(
+            &mut entries,
+            Some("code"),
+            &correction("cloud", "Claude")
+        ));
+        assert_eq!(entries.len(), 3);
+    }
+}
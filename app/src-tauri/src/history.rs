@@ -1,3 +1,5 @@
+use crate::stt::{Transcript, TranscriptWord};
+use crate::translation::normalize_language;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::fs;
@@ -12,6 +14,8 @@ pub enum HistoryStatus {
     InProgress,
     Success,
     Error,
+    /// Failed on a network error; waiting in the offline retry queue.
+    Queued,
 }
 
 impl Default for HistoryStatus {
@@ -43,6 +47,21 @@ pub struct HistoryEntry {
     /// LLM model used for rewriting (if enabled).
     #[serde(default)]
     pub llm_model: Option<String>,
+    /// Raw STT result with word timings/confidence (only kept when the provider returned detail).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stt_transcript: Option<Transcript>,
+    /// Spoken language reported by the STT provider (ISO 639-1 code when known).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    /// Words the STT provider was unsure about (below `LOW_CONFIDENCE_THRESHOLD`), for review.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub low_confidence_words: Vec<TranscriptWord>,
+    /// Raw STT text, when the output differs from it (rewritten, expanded snippets, ...).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stt_text: Option<String>,
+    /// Program profile that was active for this dictation (None = Default).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile_id: Option<String>,
 }
 
 /// Metadata about which models were used for a transcription request.
@@ -52,6 +71,8 @@ pub struct RequestModelInfo {
     pub stt_model: Option<String>,
     pub llm_provider: Option<String>,
     pub llm_model: Option<String>,
+    /// Program profile whose prompts and overrides were applied (None = Default).
+    pub profile_id: Option<String>,
 }
 
 impl HistoryEntry {
@@ -66,6 +87,11 @@ impl HistoryEntry {
             stt_model: None,
             llm_provider: None,
             llm_model: None,
+            stt_transcript: None,
+            language: None,
+            low_confidence_words: Vec::new(),
+            stt_text: None,
+            profile_id: None,
         }
     }
 
@@ -80,6 +106,11 @@ impl HistoryEntry {
             stt_model: model_info.stt_model,
             llm_provider: model_info.llm_provider,
             llm_model: model_info.llm_model,
+            stt_transcript: None,
+            language: None,
+            low_confidence_words: Vec::new(),
+            stt_text: None,
+            profile_id: model_info.profile_id,
         }
     }
 }
@@ -235,23 +266,64 @@ impl HistoryStorage {
 
     /// Mark an existing request entry as successful and set the final text.
     pub fn complete_request_success(&self, request_id: &str, text: String) -> Result<(), String> {
+        self.complete_request_success_detailed(request_id, text, None, RequestModelInfo::default())
+    }
+
+    /// Mark an existing request entry as successful, keeping the detailed STT result and the
+    /// providers that actually served the request.
+    ///
+    /// Text-only transcripts are not stored (the entry already has the text). Providers in
+    /// `models_used` replace the ones recorded at request start (e.g. after an STT fallback);
+    /// `None` providers leave the recorded values untouched.
+    pub fn complete_request_success_detailed(
+        &self,
+        request_id: &str,
+        text: String,
+        stt_transcript: Option<Transcript>,
+        models_used: RequestModelInfo,
+    ) -> Result<(), String> {
+        let language = stt_transcript
+            .as_ref()
+            .and_then(|t| t.language.as_deref())
+            .filter(|l| !l.trim().is_empty())
+            .map(normalize_language);
+        let stt_text = stt_transcript
+            .as_ref()
+            .map(|t| t.text.clone())
+            .filter(|raw| !raw.trim().is_empty() && *raw != text);
+        let low_confidence_words: Vec<TranscriptWord> = stt_transcript
+            .as_ref()
+            .map(|t| t.low_confidence_words().into_iter().cloned().collect())
+            .unwrap_or_default();
+        let stt_transcript = stt_transcript.filter(|t| t.has_details());
         {
             let mut data = self
                 .data
                 .write()
                 .map_err(|e| format!("Failed to write history: {}", e))?;
 
+            if !data.entries.iter().any(|e| e.id == request_id) {
+                // If we somehow missed creating an in-progress entry, fall back to inserting.
+                data.entries.insert(0, HistoryEntry::new_request_in_progress(request_id.to_string(), RequestModelInfo::default()));
+            }
             if let Some(entry) = data.entries.iter_mut().find(|e| e.id == request_id) {
                 entry.text = text;
                 entry.status = HistoryStatus::Success;
                 entry.error_message = None;
-            } else {
-                // If we somehow missed creating an in-progress entry, fall back to inserting.
-                data.entries.insert(0, HistoryEntry::new_request_in_progress(request_id.to_string(), RequestModelInfo::default()));
-                if let Some(entry) = data.entries.iter_mut().find(|e| e.id == request_id) {
-                    entry.text = text;
-                    entry.status = HistoryStatus::Success;
-                    entry.error_message = None;
+                entry.stt_transcript = stt_transcript;
+                entry.language = language;
+                entry.low_confidence_words = low_confidence_words;
+                entry.stt_text = stt_text;
+                if models_used.profile_id.is_some() {
+                    entry.profile_id = models_used.profile_id;
+                }
+                if models_used.stt_provider.is_some() {
+                    entry.stt_provider = models_used.stt_provider;
+                    entry.stt_model = models_used.stt_model;
+                }
+                if models_used.llm_provider.is_some() {
+                    entry.llm_provider = models_used.llm_provider;
+                    entry.llm_model = models_used.llm_model;
                 }
             }
         }
@@ -280,6 +352,47 @@ impl HistoryStorage {
         self.save()
     }
 
+    /// Mark an existing request entry as waiting for an automatic retry.
+    pub fn mark_request_queued(&self, request_id: &str, error_message: String) -> Result<(), String> {
+        self.set_request_status(request_id, HistoryStatus::Queued, Some(error_message))
+    }
+
+    /// Mark a queued request entry as running again (error message is kept until it completes).
+    pub fn mark_request_in_progress(&self, request_id: &str) -> Result<(), String> {
+        self.set_request_status(request_id, HistoryStatus::InProgress, None)
+    }
+
+    fn set_request_status(
+        &self,
+        request_id: &str,
+        status: HistoryStatus,
+        error_message: Option<String>,
+    ) -> Result<(), String> {
+        {
+            let mut data = self
+                .data
+                .write()
+                .map_err(|e| format!("Failed to write history: {}", e))?;
+
+            let Some(entry) = data.entries.iter_mut().find(|e| e.id == request_id) else {
+                return Err(format!("History entry not found: {}", request_id));
+            };
+            entry.status = status;
+            if error_message.is_some() {
+                entry.error_message = error_message;
+            }
+        }
+        self.save()
+    }
+
+    /// Whether an entry with the given id exists
+    pub fn contains(&self, id: &str) -> bool {
+        self.data
+            .read()
+            .map(|data| data.entries.iter().any(|e| e.id == id))
+            .unwrap_or(false)
+    }
+
     /// Get all history entries (newest first), optionally limited
     pub fn get_all(&self, limit: Option<usize>) -> Result<Vec<HistoryEntry>, String> {
         let data = self
@@ -295,6 +408,31 @@ impl HistoryStorage {
         Ok(entries)
     }
 
+    /// Replace the text of an entry (a user correction). Returns the entry as it was before
+    /// the edit, or None if it doesn't exist.
+    pub fn update_text(&self, id: &str, text: String) -> Result<Option<HistoryEntry>, String> {
+        let before = {
+            let mut data = self
+                .data
+                .write()
+                .map_err(|e| format!("Failed to write history: {}", e))?;
+
+            let Some(entry) = data.entries.iter_mut().find(|e| e.id == id) else {
+                return Ok(None);
+            };
+            let before = entry.clone();
+            // Keep what the STT heard; it is what the correction applies to.
+            if entry.stt_text.is_none() && entry.text != text {
+                entry.stt_text = Some(std::mem::take(&mut entry.text));
+            }
+            entry.text = text;
+            before
+        };
+
+        self.save()?;
+        Ok(Some(before))
+    }
+
     /// Delete an entry by ID
     pub fn delete(&self, id: &str) -> Result<bool, String> {
         let deleted = {
@@ -7,18 +7,28 @@ use tauri::{
 };
 use tauri_utils::config::BackgroundThrottlingPolicy;
 
+mod api_error;
 mod audio;
 mod audio_capture;
+mod audio_codec;
 mod audio_mute;
 mod commands;
+mod dictionary;
+mod dictionary_learning;
 mod history;
 mod llm;
 mod pipeline;
+mod provider_health;
 mod recordings;
 mod request_log;
+mod retry;
+mod retry_queue;
 mod settings;
+mod snippets;
 mod state;
 mod stt;
+mod text_normalize;
+mod translation;
 mod vad;
 mod windows_apps;
 
@@ -26,10 +36,14 @@ mod windows_apps;
 mod tests;
 
 use audio_mute::AudioMuteManager;
+use dictionary::DictionaryEntry;
+use dictionary_learning::DictionarySuggestionStore;
 use history::{HistoryStorage, RequestModelInfo};
 use recordings::RecordingStore;
 use request_log::{RequestLogStore, RequestLogsRetentionConfig, RequestLogsRetentionMode};
+use retry_queue::RetryQueue;
 use settings::HotkeyConfig;
+use snippets::SnippetStore;
 use state::AppState;
 
 #[cfg(desktop)]
@@ -103,6 +117,38 @@ fn ensure_default_settings(app: &AppHandle) -> Result<(), Box<dyn std::error::Er
     };
 
     let mut dirty = false;
+
+    // Before the spoken-language setting existed, local Whisper always transcribed in English
+    // (its provider default). Keep existing installs that use it on English instead of seeding
+    // auto-detection, both globally and for profiles that pick local Whisper.
+    if is_missing(store.get("stt_language")) && !is_missing(store.get("stt_provider")) {
+        let uses_local_whisper =
+            |provider: Option<&Value>| provider.and_then(Value::as_str) == Some("local-whisper");
+        let english = json!(stt::SttLanguage::Fixed {
+            language: "en".to_string(),
+        });
+
+        if uses_local_whisper(store.get("stt_provider").as_ref()) {
+            store.set("stt_language", english.clone());
+            dirty = true;
+        }
+        if let Some(Value::Array(mut profiles)) = store.get("rewrite_program_prompt_profiles") {
+            let mut changed = false;
+            for profile in &mut profiles {
+                if uses_local_whisper(profile.get("stt_provider"))
+                    && is_missing(profile.get("stt_language").cloned())
+                {
+                    profile["stt_language"] = english.clone();
+                    changed = true;
+                }
+            }
+            if changed {
+                store.set("rewrite_program_prompt_profiles", Value::Array(profiles));
+                dirty = true;
+            }
+        }
+    }
+
     let mut set_if_missing = |key: &str, value: Value| {
         if is_missing(store.get(key)) {
             store.set(key.to_string(), value);
@@ -113,6 +159,17 @@ fn ensure_default_settings(app: &AppHandle) -> Result<(), Box<dyn std::error::Er
     set_if_missing("stt_provider", json!("groq"));
     set_if_missing("stt_transcription_prompt", json!(null));
     set_if_missing("stt_timeout_seconds", json!(10.0));
+    set_if_missing("stt_fallback_chain", json!([]));
+    set_if_missing("stt_hedge", json!(default_pipeline_config.stt_hedge));
+    set_if_missing("stt_chunking", json!(default_pipeline_config.stt_chunking));
+    set_if_missing(
+        "max_recording_duration_secs",
+        json!(default_pipeline_config.max_duration_secs),
+    );
+    set_if_missing(
+        "stt_streaming_enabled",
+        json!(default_pipeline_config.stt_streaming_enabled),
+    );
     // How many recordings/history items to retain (impacts disk usage).
     // Keep this aligned with the UI default.
     set_if_missing("max_saved_recordings", json!(1000));
@@ -139,6 +196,11 @@ fn ensure_default_settings(app: &AppHandle) -> Result<(), Box<dyn std::error::Er
     set_if_missing("sound_enabled", json!(true));
     set_if_missing("rewrite_llm_enabled", json!(false));
     set_if_missing("rewrite_program_prompt_profiles", json!([]));
+    set_if_missing("llm_fallback_chain", json!([]));
+    set_if_missing("llm_output_guardrail", json!(llm::OutputGuardrail::default()));
+    set_if_missing("user_name", json!(null));
+    set_if_missing("transform_prompt", json!(null));
+    set_if_missing("dictionary_entries", json!([]));
 
     // Hotkeys: seed explicit defaults so both Rust and UI see the same persisted values.
     set_if_missing(
@@ -153,6 +215,10 @@ fn ensure_default_settings(app: &AppHandle) -> Result<(), Box<dyn std::error::Er
         "paste_last_hotkey",
         serde_json::to_value(HotkeyConfig::default_paste_last())?,
     );
+    set_if_missing(
+        "transform_hotkey",
+        serde_json::to_value(HotkeyConfig::default_transform())?,
+    );
 
     // VAD settings are used by the pipeline.
     set_if_missing(
@@ -207,6 +273,60 @@ fn ensure_default_settings(app: &AppHandle) -> Result<(), Box<dyn std::error::Er
         "audio_noise_suppression_enabled",
         json!(default_pipeline_config.audio_noise_suppression_enabled),
     );
+    set_if_missing(
+        "audio_upload_encoding",
+        json!(default_pipeline_config.audio_upload_encoding),
+    );
+    set_if_missing(
+        "hallucination_filter",
+        json!(default_pipeline_config.hallucination_filter),
+    );
+    set_if_missing(
+        "provider_health",
+        json!(default_pipeline_config.provider_health),
+    );
+    set_if_missing(
+        "translation",
+        json!(default_pipeline_config.translation),
+    );
+    set_if_missing(
+        "stt_language",
+        json!(default_pipeline_config.stt_language),
+    );
+    set_if_missing(
+        "llm_streaming_enabled",
+        json!(default_pipeline_config.llm_streaming_enabled),
+    );
+    set_if_missing("output_streaming_chunks", json!(false));
+    set_if_missing(
+        "offline_retry",
+        serde_json::to_value(retry_queue::OfflineRetryConfig::default())?,
+    );
+
+    // One-time move of the entries written into the dictionary prompt sections (by hand or by
+    // approved suggestions) into `dictionary_entries`, the only source of the dictionary now.
+    if store.get("dictionary_sections_migrated") != Some(json!(true)) {
+        let mut global = store.get("cleanup_prompt_sections").unwrap_or(Value::Null);
+        let mut profiles = store
+            .get("rewrite_program_prompt_profiles")
+            .unwrap_or_else(|| json!([]));
+        let mut entries: Vec<dictionary::DictionaryEntry> = store
+            .get("dictionary_entries")
+            .and_then(|v| serde_json::from_value(v).ok())
+            .unwrap_or_default();
+
+        if dictionary::migrate_dictionary_settings(&mut global, &mut profiles, &mut entries) {
+            store.set("cleanup_prompt_sections", global);
+            store.set("rewrite_program_prompt_profiles", profiles);
+            store.set("dictionary_entries", serde_json::to_value(&entries)?);
+            log::info!(
+                "Moved the dictionary prompt entries into the dictionary ({} entries)",
+                entries.len()
+            );
+        }
+        store.set("dictionary_sections_migrated", json!(true));
+        dirty = true;
+    }
 
     if dirty {
         // Persist seeded defaults.
@@ -240,6 +360,86 @@ fn emit_system_event(app: &AppHandle, event_type: &str, message: &str, details:
     let _ = app.emit("system-event", event);
 }
 
+/// Forward streamed LLM rewrite text to the UI as `llm-rewrite-delta` events.
+///
+/// With `chunk_output`, complete sentences are also pasted as they arrive. The task ends when
+/// the pipeline closes the delta stream and returns the text it pasted, so the caller only
+/// outputs the rest of the final text.
+pub(crate) fn forward_llm_deltas(
+    app: &AppHandle,
+    mut deltas: llm::LlmDeltaReceiver,
+    chunk_output: Option<commands::text::OutputMode>,
+) -> tauri::async_runtime::JoinHandle<String> {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut text = String::new();
+        let mut chunker = llm::SentenceChunker::default();
+        let mut pasted = String::new();
+        let mut chunk_output = chunk_output;
+
+        while let Some(delta) = deltas.recv().await {
+            text.push_str(&delta);
+            let payload = serde_json::json!({
+                "delta": delta,
+                "text": text,
+            });
+            let _ = app.emit("llm-rewrite-delta", payload);
+
+            let Some(mode) = chunk_output else {
+                continue;
+            };
+            for chunk in chunker.push(&delta) {
+                if let Err(e) = commands::text::output_text_with_mode(&chunk, mode, false) {
+                    // Stop pasting; the final output covers everything not pasted yet.
+                    log::error!("Failed to output streamed text: {}", e);
+                    chunk_output = None;
+                    break;
+                }
+                pasted.push_str(&chunk);
+            }
+        }
+
+        pasted
+    })
+}
+
+/// Tell the user that the output guardrail discarded the rewrite, so the transcript was used.
+pub(crate) fn notify_llm_output_rejected(app: &AppHandle, outcome: &pipeline::LlmOutcome) {
+    if let pipeline::LlmOutcome::Rejected { provider, reason } = outcome {
+        let payload = serde_json::json!({
+            "kind": "llm_output_rejected",
+            "message": format!(
+                "The rewrite from {} was discarded ({}); the transcript was used instead",
+                provider, reason
+            ),
+        });
+        let _ = app.emit("pipeline-notice", payload);
+    }
+}
+
+/// Tell the user that sentences pasted while the rewrite streamed were replaced, because the
+/// rewrite failed afterwards and the transcript was output instead.
+fn notify_streamed_output_replaced(app: &AppHandle, outcome: &pipeline::LlmOutcome) {
+    let cause = match outcome {
+        pipeline::LlmOutcome::TimedOut => "the rewrite timed out".to_string(),
+        pipeline::LlmOutcome::Failed(err) => format!("the rewrite failed: {}", err),
+        pipeline::LlmOutcome::Rejected { reason, .. } => {
+            format!("the rewrite was rejected: {}", reason)
+        }
+        _ => "the final text differs from the streamed text".to_string(),
+    };
+    let message = format!("Replaced the streamed rewrite with the transcript ({})", cause);
+    log::warn!("{}", message);
+    if let Some(log_store) = app.try_state::<RequestLogStore>() {
+        log_store.with_current(|log| log.warn(message.clone()));
+    }
+    let payload = serde_json::json!({
+        "kind": "streamed_output_replaced",
+        "message": message,
+    });
+    let _ = app.emit("pipeline-notice", payload);
+}
+
 /// Normalize transcript text for output.
 ///
 /// We intentionally keep this conservative: the pipeline now performs a
@@ -424,6 +624,11 @@ fn start_recording(
     log::info!("{}: starting recording (current pipeline state: {:?})", source, current_state);
     emit_system_event(app, "shortcut", &format!("{}: starting recording", source), Some(&format!("Pipeline state: {:?}", current_state)));
 
+    // A plain dictation; the transform hotkey sets its selection once recording has started.
+    if let Ok(mut pending) = state.transform_selection.lock() {
+        *pending = None;
+    }
+
     // Start pipeline recording FIRST - if it fails, don't do anything else
     if let Some(pipeline) = app.try_state::<pipeline::SharedPipeline>() {
         if let Err(e) = pipeline.start_recording() {
@@ -438,6 +643,16 @@ fn start_recording(
             return;
         }
 
+        // Forward live transcript events (streaming STT) to the UI.
+        if let Some(mut events) = pipeline.take_transcript_events() {
+            let app_for_events = app.clone();
+            tauri::async_runtime::spawn(async move {
+                while let Some(event) = events.recv().await {
+                    let _ = app_for_events.emit("stt-transcript-event", event);
+                }
+            });
+        }
+
         // Pipeline started successfully - now start request logging.
         if let Some(log_store) = app.try_state::<RequestLogStore>() {
             let config = pipeline.config();
@@ -574,6 +789,15 @@ fn stop_recording(
     log::info!("{}: stopping recording", source);
     emit_system_event(app, "shortcut", &format!("{}: stopping recording", source), None);
 
+    // Set when the recording was started by the transform-selection hotkey: the instruction's
+    // result replaces the selection instead of being dictated.
+    let transform_selection = state
+        .transform_selection
+        .lock()
+        .ok()
+        .and_then(|mut pending| pending.take());
+    let is_transform = transform_selection.is_some();
+
     // If hallucination protection (quiet-audio gate) is enabled and the recording is considered
     // effectively quiet, the pipeline will skip STT and immediately return to Idle.
     // In that case, playing the stop sound is misleading, so we only play it if we actually
@@ -611,15 +835,32 @@ fn stop_recording(
         get_setting_from_store(app, "overlay_mode", "recording_only".to_string());
 
     // Get output mode for how to output text
+    // (A transform always pastes over the selection and restores the clipboard.)
     let output_mode_str: String = get_setting_from_store(app, "output_mode", "paste".to_string());
-    let output_mode = commands::text::OutputMode::from_str(&output_mode_str);
+    let output_mode = if is_transform {
+        commands::text::OutputMode::Paste
+    } else {
+        commands::text::OutputMode::from_str(&output_mode_str)
+    };
 
     // Optional: after pasting, press Enter.
-    let output_hit_enter: bool = get_setting_from_store(app, "output_hit_enter", false);
+    let output_hit_enter: bool =
+        !is_transform && get_setting_from_store(app, "output_hit_enter", false);
+
+    // Optional: paste sentences while the LLM rewrite is still streaming. Not with the output
+    // guardrail on: it judges the complete rewrite and may still discard it.
+    let guardrail_enabled = app
+        .try_state::<pipeline::SharedPipeline>()
+        .is_some_and(|pipeline| pipeline.config().llm_config.guardrail.enabled);
+    let output_streaming_chunks: bool =
+        get_setting_from_store(app, "output_streaming_chunks", false) && !guardrail_enabled;
+    let chunk_output = Some(output_mode).filter(|mode| output_streaming_chunks && mode.can_stream());
 
     // Stop pipeline and trigger transcription in background
     if let Some(pipeline) = app.try_state::<pipeline::SharedPipeline>() {
         let pipeline_clone = (*pipeline).clone();
+        // Transforms don't stream: the selection is replaced in one paste.
+        let llm_deltas = pipeline.take_llm_deltas().filter(|_| !is_transform);
         let app_clone = app.clone();
         let overlay_mode_clone = overlay_mode.clone();
 
@@ -640,6 +881,7 @@ fn stop_recording(
                     None
                 },
                 llm_model: config.llm_config.model.clone(),
+                profile_id: None,
             }
         };
 
@@ -710,10 +952,24 @@ fn stop_recording(
                 });
             }
 
-            match pipeline_clone.stop_and_transcribe_detailed().await {
+            let llm_delta_forwarder =
+                llm_deltas.map(|deltas| forward_llm_deltas(&app_clone, deltas, chunk_output));
+
+            let result = match transform_selection {
+                Some(selection) => pipeline_clone.stop_and_transform_selection(selection).await,
+                None => pipeline_clone.stop_and_transcribe_detailed().await,
+            };
+
+            match result {
                 Ok(result) => {
                     log::info!("Transcription complete: {} chars", result.final_text.len());
 
+                    // Text already pasted while the rewrite streamed (the stream has ended).
+                    let streamed_output = match llm_delta_forwarder {
+                        Some(forwarder) => forwarder.await.unwrap_or_default(),
+                        None => String::new(),
+                    };
+
                     // Final output after pipeline (STT + optional LLM) normalization.
                     // Quiet recordings should already have been skipped in the pipeline.
                     let filtered_transcript = sanitize_transcript(&result.final_text);
@@ -723,6 +979,7 @@ fn stop_recording(
                         log_store.with_current(|log| {
                             // Raw STT output (pre-LLM)
                             log.raw_transcript = Some(result.stt_text.clone());
+                            log.stt_transcript = Some(result.stt_transcript.clone()).filter(|t| t.has_details());
 
                             // Final output after pipeline + hallucination filtering (if any)
                             if let Some(ref text) = filtered_transcript {
@@ -730,6 +987,10 @@ fn stop_recording(
                             }
 
                             log.stt_duration_ms = Some(result.stt_duration_ms);
+                            if let Some(provider) = &result.stt_provider_used {
+                                log.stt_provider = provider.clone();
+                                log.stt_model = result.stt_model_used.clone();
+                            }
                             log.llm_duration_ms = result.llm_duration_ms;
 
                             log.info(format!(
@@ -742,7 +1003,13 @@ fn stop_recording(
                                 pipeline::LlmOutcome::NotAttempted => {
                                     log.info("LLM formatting not attempted (disabled or unavailable)");
                                 }
-                                pipeline::LlmOutcome::Succeeded => {
+                                pipeline::LlmOutcome::Succeeded { provider, attempts, .. } => {
+                                    if *attempts > 1 {
+                                        log.info(format!(
+                                            "LLM fallback provider {} succeeded after {} attempts",
+                                            provider, attempts
+                                        ));
+                                    }
                                     if let Some(ms) = result.llm_duration_ms {
                                         log.info(format!(
                                             "LLM formatting succeeded in {}ms ({} -> {} chars)",
@@ -764,6 +1031,21 @@ fn stop_recording(
                                         log.warn("LLM formatting timed out; fell back to STT transcript");
                                     }
                                 }
+                                pipeline::LlmOutcome::SucceededAfterStrictRetry { provider, reason, .. } => {
+                                    log.info(format!(
+                                        "LLM output from {} rejected ({}); strict retry succeeded ({} -> {} chars)",
+                                        provider,
+                                        reason,
+                                        result.stt_text.len(),
+                                        result.final_text.len()
+                                    ));
+                                }
+                                pipeline::LlmOutcome::Rejected { provider, reason } => {
+                                    log.warn(format!(
+                                        "LLM output from {} rejected; fell back to STT transcript ({})",
+                                        provider, reason
+                                    ));
+                                }
                                 pipeline::LlmOutcome::Failed(err) => {
                                     log.warn(format!(
                                         "LLM formatting failed; fell back to STT transcript ({})",
@@ -780,10 +1062,12 @@ fn stop_recording(
                         });
                         log_store.complete_current();
                     }
+                    notify_llm_output_rejected(&app_clone, &result.llm_outcome);
 
-                    // Persist audio for retry (best-effort)
+                    // Persist audio for retry (best-effort). Not for transforms: retrying the
+                    // instruction without its selection would dictate it instead.
                     if let (Some(ref req_id), Some(store)) = (
-                        request_id.as_ref(),
+                        request_id.as_ref().filter(|_| !is_transform),
                         app_clone.try_state::<RecordingStore>(),
                     ) {
                         if let Some(wav) = pipeline_clone.clone_last_wav_bytes() {
@@ -804,7 +1088,22 @@ fn stop_recording(
                         let _ = app_clone.emit("pipeline-transcript-ready", text);
 
                         // Output the transcript based on mode
-                        if let Err(e) = commands::text::output_text_with_mode(text, output_mode, output_hit_enter) {
+                        let output_result = if streamed_output.is_empty() {
+                            commands::text::output_text_with_mode(text, output_mode, output_hit_enter)
+                        } else {
+                            commands::text::output_streamed_remainder(
+                                text,
+                                &streamed_output,
+                                output_mode,
+                                output_hit_enter,
+                            )
+                            .map(|replaced| {
+                                if replaced {
+                                    notify_streamed_output_replaced(&app_clone, &result.llm_outcome);
+                                }
+                            })
+                        };
+                        if let Err(e) = output_result {
                             log::error!("Failed to output transcript: {}", e);
 
                             if let Some(log_store) = app_clone.try_state::<RequestLogStore>() {
@@ -817,7 +1116,12 @@ fn stop_recording(
                         // Save to history
                         if let Some(ref req_id) = request_id {
                             if let Some(history) = app_clone.try_state::<HistoryStorage>() {
-                                if let Err(e) = history.complete_request_success(req_id, text.clone()) {
+                                if let Err(e) = history.complete_request_success_detailed(
+                                    req_id,
+                                    text.clone(),
+                                    Some(result.stt_transcript.clone()),
+                                    result.models_used(),
+                                ) {
                                     log::warn!("Failed to update history: {}", e);
                                 }
                                 let _ = app_clone.emit("history-changed", ());
@@ -910,9 +1214,10 @@ fn stop_recording(
                         log_store.complete_current();
                     }
 
-                    // Persist audio for retry (best-effort)
+                    // Persist audio for retry (best-effort). Not for transforms: retrying the
+                    // instruction without its selection would dictate it instead.
                     if let (Some(ref req_id), Some(store)) = (
-                        request_id.as_ref(),
+                        request_id.as_ref().filter(|_| !is_transform),
                         app_clone.try_state::<RecordingStore>(),
                     ) {
                         if let Some(wav) = pipeline_clone.clone_last_wav_bytes() {
@@ -935,6 +1240,16 @@ fn stop_recording(
                             let _ = history.complete_request_error(req_id, e.to_string());
                             let _ = app_clone.emit("history-changed", ());
                         }
+
+                        // Network failures are retried automatically once back online.
+                        if !is_transform {
+                            commands::offline_retry::queue_if_network_error(
+                                &app_clone,
+                                req_id,
+                                pipeline_clone.last_profile_id(),
+                                &e,
+                            );
+                        }
                     }
 
                     // Time-based retention (best-effort). Still apply even on failures.
@@ -1046,6 +1361,10 @@ pub(crate) fn cancel_pipeline_session(app: &AppHandle, source: &str) {
     state.is_recording.store(false, Ordering::SeqCst);
     state.toggle_key_held.store(false, Ordering::SeqCst);
     state.ptt_key_held.store(false, Ordering::SeqCst);
+    state.transform_key_held.store(false, Ordering::SeqCst);
+    if let Ok(mut pending) = state.transform_selection.lock() {
+        *pending = None;
+    }
 
     // Restore audio side effects (unmute + resume playback if we paused).
     let sound_enabled: bool = get_setting_from_store(app, "sound_enabled", true);
@@ -1148,6 +1467,8 @@ pub fn handle_shortcut_event(app: &AppHandle, shortcut: &Shortcut, event: &Short
         get_setting_from_store(app, "hold_hotkey", HotkeyConfig::default_hold());
     let paste_last_hotkey: HotkeyConfig =
         get_setting_from_store(app, "paste_last_hotkey", HotkeyConfig::default_paste_last());
+    let transform_hotkey: HotkeyConfig =
+        get_setting_from_store(app, "transform_hotkey", HotkeyConfig::default_transform());
 
     // Validate hotkeys - if they can't be parsed as shortcuts, use defaults
     let toggle_shortcut_str = normalize_shortcut_string(
@@ -1168,6 +1489,12 @@ pub fn handle_shortcut_event(app: &AppHandle, shortcut: &Shortcut, event: &Short
             .map(|_| paste_last_hotkey.to_shortcut_string())
             .unwrap_or_else(|_| HotkeyConfig::default_paste_last().to_shortcut_string()),
     );
+    let transform_shortcut_str = normalize_shortcut_string(
+        &transform_hotkey
+            .to_shortcut()
+            .map(|_| transform_hotkey.to_shortcut_string())
+            .unwrap_or_else(|_| HotkeyConfig::default_transform().to_shortcut_string()),
+    );
 
     // Get audio mute manager if available
     let audio_mute_manager = app.try_state::<AudioMuteManager>();
@@ -1176,6 +1503,7 @@ pub fn handle_shortcut_event(app: &AppHandle, shortcut: &Shortcut, event: &Short
     let is_toggle = shortcut_str == toggle_shortcut_str;
     let is_hold = shortcut_str == hold_shortcut_str;
     let is_paste_last = shortcut_str == paste_last_shortcut_str;
+    let is_transform = shortcut_str == transform_shortcut_str;
 
     if is_toggle {
         // Toggle mode: action happens on key release (debounced)
@@ -1303,6 +1631,83 @@ pub fn handle_shortcut_event(app: &AppHandle, shortcut: &Shortcut, event: &Short
                 }
             }
         }
+    } else if is_transform {
+        // Transform selection: copy the selection, record an instruction, paste the rewrite
+        // over the selection. Toggle-style: action happens on key release.
+        match event.state {
+            ShortcutState::Pressed => {
+                state.transform_key_held.swap(true, Ordering::SeqCst);
+            }
+            ShortcutState::Released => {
+                if state.transform_key_held.swap(false, Ordering::SeqCst) {
+                    let pipeline_state = app
+                        .try_state::<pipeline::SharedPipeline>()
+                        .map(|p| p.state());
+
+                    log::info!("Transform released: pipeline state = {:?}", pipeline_state);
+                    emit_system_event(app, "shortcut", "Transform key released", Some(&format!("Pipeline state: {:?}", pipeline_state)));
+
+                    if pipeline_state == Some(pipeline::PipelineState::Recording) {
+                        stop_recording(
+                            app,
+                            &state,
+                            sound_enabled,
+                            audio_cue,
+                            &audio_mute_manager,
+                            playing_audio_handling,
+                            "Transform",
+                        );
+                    } else if pipeline_state.map(|s| s.can_start_recording()).unwrap_or(false) {
+                        // Copying the selection waits on the target app; keep it off the
+                        // shortcut handler.
+                        let app = app.clone();
+                        tauri::async_runtime::spawn(async move {
+                            let captured =
+                                tauri::async_runtime::spawn_blocking(commands::text::capture_selection)
+                                    .await
+                                    .unwrap_or_else(|e| Err(e.to_string()));
+
+                            let state = app.state::<AppState>();
+                            let audio_mute_manager = app.try_state::<AudioMuteManager>();
+                            let error_msg = match captured {
+                                Ok(Some(selection)) => {
+                                    start_recording(
+                                        &app,
+                                        &state,
+                                        sound_enabled,
+                                        audio_cue,
+                                        &audio_mute_manager,
+                                        playing_audio_handling,
+                                        "Transform",
+                                    );
+                                    let started = app
+                                        .try_state::<pipeline::SharedPipeline>()
+                                        .map(|p| p.state() == pipeline::PipelineState::Recording)
+                                        .unwrap_or(false);
+                                    if started {
+                                        if let Ok(mut pending) = state.transform_selection.lock() {
+                                            *pending = Some(selection);
+                                        }
+                                    }
+                                    None
+                                }
+                                Ok(None) => Some("No text selected to transform".to_string()),
+                                Err(e) => Some(format!("Failed to capture selection: {}", e)),
+                            };
+
+                            if let Some(error_msg) = error_msg {
+                                log::warn!("Transform: {}", error_msg);
+                                let payload = serde_json::json!({
+                                    "message": error_msg,
+                                    "request_id": null,
+                                });
+                                let _ = app.emit("pipeline-error", payload);
+                            }
+                        });
+                    }
+                }
+            }
+        }
     } else {
         log::warn!("Unknown shortcut: {}", shortcut_str);
     }
@@ -1346,6 +1751,7 @@ pub fn run() {
             is_audio_mute_supported,
             commands::history::add_history_entry,
             commands::history::get_history,
+            commands::history::update_history_entry,
             commands::history::delete_history_entry,
             commands::history::clear_history,
             commands::overlay::resize_overlay,
@@ -1370,6 +1776,19 @@ pub fn run() {
             commands::recording::pipeline_test_audio_settings_start_recording,
             commands::recording::pipeline_test_audio_settings_stop_recording,
             commands::recording::pipeline_retry_transcription,
+            commands::recording::pipeline_get_provider_health,
+            commands::recording::pipeline_reset_provider_health,
+            commands::offline_retry::offline_retry_queue_list,
+            commands::offline_retry::offline_retry_queue_remove,
+            // Voice snippet commands
+            commands::snippets::list_snippets,
+            commands::snippets::add_snippet,
+            commands::snippets::update_snippet,
+            commands::snippets::delete_snippet,
+            // Auto-learning dictionary commands
+            commands::dictionary::list_dictionary_suggestions,
+            commands::dictionary::approve_dictionary_suggestion,
+            commands::dictionary::reject_dictionary_suggestion,
             // Recording file access (for playback)
             commands::recording::recording_get_wav_path,
             commands::recording::recording_get_wav_base64,
@@ -1391,6 +1810,7 @@ pub fn run() {
             commands::llm::update_llm_prompts,
             commands::llm::get_llm_config,
             commands::llm::test_llm_rewrite,
+            commands::llm::preview_llm_system_prompt,
             commands::llm::llm_complete,
             // Local Whisper model management commands
             commands::whisper::is_local_whisper_available,
@@ -1425,6 +1845,18 @@ pub fn run() {
             let recording_store = RecordingStore::new(app_data_dir.clone());
             app.manage(recording_store);
 
+            // Dictations waiting for an automatic retry after a network failure
+            let retry_queue = RetryQueue::new(app_data_dir.clone());
+            app.manage(retry_queue);
+
+            // Voice snippets (trigger phrases expanded after STT)
+            let snippet_store = SnippetStore::new(app_data_dir.clone());
+            app.manage(snippet_store);
+
+            // Dictionary entries learned from corrections, waiting for approval
+            let dictionary_suggestions = DictionarySuggestionStore::new(app_data_dir.clone());
+            app.manage(dictionary_suggestions);
+
             let history_storage = HistoryStorage::new(app_data_dir);
             app.manage(history_storage);
 
@@ -1490,6 +1922,9 @@ pub fn run() {
             {
                 let pipeline = initialize_pipeline_from_settings(app.handle());
                 app.manage(pipeline);
+
+                // Re-transcribe queued dictations once the network is back.
+                commands::offline_retry::start_worker(app.handle().clone());
             }
 
             // Backend-driven overlay waveform: publish realtime mic levels to the overlay.
@@ -1793,6 +2228,40 @@ fn initialize_pipeline_from_settings(app: &AppHandle) -> pipeline::SharedPipelin
     let stt_transcription_prompt: Option<String> =
         get_setting_from_store(app, "stt_transcription_prompt", None);
 
+    // Self-hosted OpenAI-compatible STT endpoint (base URL, optional key, model, extra fields)
+    let stt_openai_compatible: stt::OpenAiCompatibleSttConfig = get_setting_from_store(
+        app,
+        "openai_compatible_stt",
+        stt::OpenAiCompatibleSttConfig::default(),
+    );
+
+    // Ordered STT providers to try when the effective provider fails or times out
+    let stt_fallback_chain: Vec<pipeline::SttFallbackEntry> =
+        get_setting_from_store(app, "stt_fallback_chain", Vec::new());
+
+    // Hedged STT requests (race a delayed second request against a slow primary)
+    let stt_hedge: pipeline::SttHedgeConfig =
+        get_setting_from_store(app, "stt_hedge", pipeline::SttHedgeConfig::default());
+
+    // Split long recordings at pauses and transcribe the chunks concurrently
+    let stt_chunking: pipeline::SttChunkingConfig =
+        get_setting_from_store(app, "stt_chunking", pipeline::SttChunkingConfig::default());
+
+    // Maximum recording length (long dictations rely on chunked transcription)
+    let max_duration_secs_raw: f32 = get_setting_from_store(app, "max_recording_duration_secs", 300.0);
+    let max_duration_secs: f32 = if max_duration_secs_raw.is_finite() && max_duration_secs_raw > 0.0 {
+        max_duration_secs_raw
+    } else {
+        log::warn!(
+            "Invalid max_recording_duration_secs value in store ({}); falling back to 300s",
+            max_duration_secs_raw
+        );
+        300.0
+    };
+
+    // Stream audio to the STT provider while recording
+    let stt_streaming_enabled: bool = get_setting_from_store(app, "stt_streaming_enabled", false);
+
     // Read STT timeout from store (seconds)
     let stt_timeout_seconds_raw: f64 = get_setting_from_store(app, "stt_timeout_seconds", 10.0);
     let stt_timeout_seconds: f64 = if stt_timeout_seconds_raw.is_finite() && stt_timeout_seconds_raw > 0.0 {
@@ -1807,7 +2276,7 @@ fn initialize_pipeline_from_settings(app: &AppHandle) -> pipeline::SharedPipelin
 
     // Read all available STT API keys (for per-profile provider overrides at runtime)
     let mut stt_api_keys: HashMap<String, String> = HashMap::new();
-    for provider in ["openai", "groq", "deepgram"] {
+    for provider in ["openai", "groq", "deepgram", "assemblyai"] {
         let key_name = format!("{}_api_key", provider);
         let key: String = get_setting_from_store(app, &key_name, String::new());
         if !key.is_empty() {
@@ -1820,6 +2289,7 @@ fn initialize_pipeline_from_settings(app: &AppHandle) -> pipeline::SharedPipelin
         "openai" => get_setting_from_store(app, "openai_api_key", String::new()),
         "groq" => get_setting_from_store(app, "groq_api_key", String::new()),
         "deepgram" => get_setting_from_store(app, "deepgram_api_key", String::new()),
+        "assemblyai" => get_setting_from_store(app, "assemblyai_api_key", String::new()),
         _ => String::new(),
     };
 
@@ -1937,6 +2407,36 @@ fn initialize_pipeline_from_settings(app: &AppHandle) -> pipeline::SharedPipelin
         "audio_noise_suppression_enabled",
         default_pipeline_config.audio_noise_suppression_enabled,
     );
+    let audio_upload_encoding: stt::AudioEncoding = get_setting_from_store(
+        app,
+        "audio_upload_encoding",
+        default_pipeline_config.audio_upload_encoding,
+    );
+    let hallucination_filter: stt::HallucinationFilterConfig = get_setting_from_store(
+        app,
+        "hallucination_filter",
+        default_pipeline_config.hallucination_filter.clone(),
+    );
+    let provider_health: provider_health::ProviderHealthConfig = get_setting_from_store(
+        app,
+        "provider_health",
+        default_pipeline_config.provider_health.clone(),
+    );
+    let translation: translation::TranslationConfig = get_setting_from_store(
+        app,
+        "translation",
+        default_pipeline_config.translation.clone(),
+    );
+    let stt_language: stt::SttLanguage = get_setting_from_store(
+        app,
+        "stt_language",
+        default_pipeline_config.stt_language.clone(),
+    );
+    let llm_streaming_enabled: bool = get_setting_from_store(
+        app,
+        "llm_streaming_enabled",
+        default_pipeline_config.llm_streaming_enabled,
+    );
 
     let quiet_audio_require_speech: bool = get_setting_from_store(
         app,
@@ -1960,6 +2460,31 @@ fn initialize_pipeline_from_settings(app: &AppHandle) -> pipeline::SharedPipelin
     let anthropic_thinking_budget: Option<i64> =
         get_setting_from_store(app, "anthropic_thinking_budget", None);
 
+    // Ordered LLM providers to try when the effective provider fails (within the LLM timeout)
+    let llm_fallback_chain: Vec<llm::LlmFallbackEntry> =
+        get_setting_from_store(app, "llm_fallback_chain", Vec::new());
+
+    // Checks that reject rewrites which answer or chat instead of formatting the transcript
+    let llm_output_guardrail: llm::OutputGuardrail =
+        get_setting_from_store(app, "llm_output_guardrail", llm::OutputGuardrail::default());
+
+    // The user's name for the `{{user_name}}` prompt variable
+    let user_name: Option<String> = get_setting_from_store(app, "user_name", None);
+
+    // Custom system prompt for the transform-selection hotkey (None = built-in default)
+    let transform_prompt: Option<String> = get_setting_from_store(app, "transform_prompt", None);
+
+    // Personal dictionary (terms and their spoken forms, optionally per profile)
+    let dictionary: Vec<DictionaryEntry> =
+        get_setting_from_store(app, "dictionary_entries", Vec::new());
+
+    // Self-hosted/third-party OpenAI-compatible LLM endpoint (base URL, optional key, model, headers)
+    let llm_openai_compatible: llm::OpenAiCompatibleLlmConfig = get_setting_from_store(
+        app,
+        "openai_compatible_llm",
+        llm::OpenAiCompatibleLlmConfig::default(),
+    );
+
     // If the user never explicitly selected a model, treat "default" as the provider's
     // concrete default model so request logs can display the exact model used.
     let llm_provider_effective = llm_provider_setting
@@ -2027,6 +2552,9 @@ fn initialize_pipeline_from_settings(app: &AppHandle) -> pipeline::SharedPipelin
             stt_timeout_seconds: p.stt_timeout_seconds,
             llm_provider: p.llm_provider,
             llm_model: p.llm_model,
+            llm_fallback_chain: p.llm_fallback_chain,
+            translation: p.translation,
+            stt_language: p.stt_language,
         })
         .collect();
 
@@ -2052,7 +2580,12 @@ fn initialize_pipeline_from_settings(app: &AppHandle) -> pipeline::SharedPipelin
         stt_api_keys,
         stt_model,
         stt_transcription_prompt,
-        max_duration_secs: 300.0,
+        stt_openai_compatible,
+        stt_fallback_chain,
+        stt_hedge,
+        stt_chunking,
+        stt_streaming_enabled,
+        max_duration_secs,
         retry_config: stt::RetryConfig::default(),
         vad_config: vad_settings.to_vad_auto_stop_config(),
         transcription_timeout: Duration::from_secs_f64(stt_timeout_seconds),
@@ -2070,6 +2603,12 @@ fn initialize_pipeline_from_settings(app: &AppHandle) -> pipeline::SharedPipelin
         audio_highpass_enabled,
         audio_agc_enabled,
         audio_noise_suppression_enabled,
+        audio_upload_encoding,
+        hallucination_filter,
+        provider_health,
+        translation,
+        stt_language,
+        llm_streaming_enabled,
 
         quiet_audio_require_speech,
 
@@ -2078,18 +2617,28 @@ fn initialize_pipeline_from_settings(app: &AppHandle) -> pipeline::SharedPipelin
             provider: llm_provider_effective,
             api_key: llm_api_key,
             model: llm_model_effective,
+            openai_compatible: llm_openai_compatible,
             openai_reasoning_effort,
             gemini_thinking_budget,
             gemini_thinking_level,
             anthropic_thinking_budget,
             prompts: base_prompts,
             program_prompt_profiles,
+            fallback_chain: llm_fallback_chain,
+            guardrail: llm_output_guardrail,
+            user_name,
+            transform_prompt,
             ..Default::default()
         },
         llm_api_keys,
 
         // Allow providers to enrich the active RequestLog with request/response payloads.
         request_log_store: app.try_state::<RequestLogStore>().map(|s| s.inner().clone()),
+        dictionary,
+        snippet_store: app.try_state::<SnippetStore>().map(|s| s.inner().clone()),
+        dictionary_suggestions: app
+            .try_state::<DictionarySuggestionStore>()
+            .map(|s| s.inner().clone()),
     };
 
     log::info!(
@@ -2113,21 +2662,31 @@ fn register_initial_shortcuts(app: &AppHandle) -> Result<(), Box<dyn std::error:
         get_setting_from_store(app, "hold_hotkey", HotkeyConfig::default_hold());
     let paste_last_hotkey: HotkeyConfig =
         get_setting_from_store(app, "paste_last_hotkey", HotkeyConfig::default_paste_last());
+    let transform_hotkey: HotkeyConfig =
+        get_setting_from_store(app, "transform_hotkey", HotkeyConfig::default_transform());
 
     // Convert to shortcuts with validation (fall back to defaults if invalid)
     let toggle_shortcut = toggle_hotkey.to_shortcut_or_default(HotkeyConfig::default_toggle);
     let hold_shortcut = hold_hotkey.to_shortcut_or_default(HotkeyConfig::default_hold);
     let paste_last_shortcut =
         paste_last_hotkey.to_shortcut_or_default(HotkeyConfig::default_paste_last);
+    let transform_shortcut =
+        transform_hotkey.to_shortcut_or_default(HotkeyConfig::default_transform);
 
     log::info!(
-        "Registering shortcuts - Toggle: {}, Hold: {}, PasteLast: {}",
+        "Registering shortcuts - Toggle: {}, Hold: {}, PasteLast: {}, Transform: {}",
         toggle_hotkey.to_shortcut_string(),
         hold_hotkey.to_shortcut_string(),
-        paste_last_hotkey.to_shortcut_string()
+        paste_last_hotkey.to_shortcut_string(),
+        transform_hotkey.to_shortcut_string()
     );
 
-    let shortcuts: Vec<Shortcut> = vec![toggle_shortcut, hold_shortcut, paste_last_shortcut];
+    let shortcuts: Vec<Shortcut> = vec![
+        toggle_shortcut,
+        hold_shortcut,
+        paste_last_shortcut,
+        transform_shortcut,
+    ];
 
     app.global_shortcut()
         .on_shortcuts(shortcuts, |app, shortcut, event| {
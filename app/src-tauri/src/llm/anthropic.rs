@@ -1,7 +1,9 @@
 //! Anthropic (Claude) LLM provider for text formatting.
 
-use super::{LlmError, LlmProvider, DEFAULT_LLM_TIMEOUT};
+use super::streaming::{parse_event_json, read_sse_events, request_error, StreamControl};
+use super::{LlmDeltaSender, LlmError, LlmProvider, DEFAULT_LLM_TIMEOUT};
 use async_trait::async_trait;
+use crate::api_error::ApiError;
 use crate::request_log::RequestLogStore;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
@@ -135,6 +137,68 @@ impl AnthropicLlmProvider {
             budget_tokens: capped as u32,
         })
     }
+
+    fn build_request(
+        &self,
+        system_prompt: &str,
+        user_message: &str,
+        stream: bool,
+    ) -> Result<MessagesRequest, LlmError> {
+        if self.api_key.is_empty() {
+            return Err(LlmError::NoApiKey("anthropic".to_string()));
+        }
+
+        Ok(MessagesRequest {
+            model: self.model.clone(),
+            max_tokens: 4096,
+            system: system_prompt.to_string(),
+            messages: vec![Message {
+                role: "user".to_string(),
+                content: vec![MessageContent {
+                    content_type: "text".to_string(),
+                    text: user_message.to_string(),
+                }],
+            }],
+            thinking: self.effective_thinking(),
+            stream: stream.then_some(true),
+        })
+    }
+
+    /// Log and send the request, returning the successful response.
+    async fn send(&self, request: &MessagesRequest) -> Result<reqwest::Response, LlmError> {
+        if let Some(store) = &self.request_log_store {
+            let request_json = serde_json::to_value(request).unwrap_or_else(|_| {
+                json!({
+                    "provider": "anthropic",
+                    "error": "failed to serialize request",
+                })
+            });
+            store.with_current(|log| {
+                log.llm_request_json = Some(request_json);
+            });
+        }
+
+        let mut req = self
+            .client
+            .post(ANTHROPIC_API_URL)
+            .header("x-api-key", &self.api_key)
+            .header("anthropic-version", API_VERSION)
+            .header("content-type", "application/json")
+            .json(request);
+        if let Some(timeout) = self.timeout {
+            req = req.timeout(timeout);
+        }
+
+        // Without a configured timeout, a timeout is treated as a generic network error.
+        let response = req.send().await.map_err(|e| request_error(e, self.timeout))?;
+
+        if !response.status().is_success() {
+            let error = ApiError::from_response("Anthropic API error", response).await;
+            return Err(LlmError::Api(error));
+        }
+
+        Ok(response)
+    }
 }
 
 #[derive(Debug, Serialize)]
@@ -159,6 +223,9 @@ struct MessagesRequest {
 
     #[serde(skip_serializing_if = "Option::is_none")]
     thinking: Option<ThinkingParam>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stream: Option<bool>,
 }
 
 #[derive(Debug, Serialize)]
@@ -181,88 +248,11 @@ struct MessagesResponse {
     content: Vec<ContentBlock>,
 }
 
-#[derive(Debug, Deserialize)]
-struct ErrorResponse {
-    error: ErrorDetail,
-}
-
-#[derive(Debug, Deserialize)]
-struct ErrorDetail {
-    message: String,
-}
-
 #[async_trait]
 impl LlmProvider for AnthropicLlmProvider {
     async fn complete(&self, system_prompt: &str, user_message: &str) -> Result<String, LlmError> {
-        if self.api_key.is_empty() {
-            return Err(LlmError::NoApiKey("anthropic".to_string()));
-        }
-
-        let request = MessagesRequest {
-            model: self.model.clone(),
-            max_tokens: 4096,
-            system: system_prompt.to_string(),
-            messages: vec![Message {
-                role: "user".to_string(),
-                content: vec![MessageContent {
-                    content_type: "text".to_string(),
-                    text: user_message.to_string(),
-                }],
-            }],
-            thinking: self.effective_thinking(),
-        };
-
-        if let Some(store) = &self.request_log_store {
-            let request_json = serde_json::to_value(&request).unwrap_or_else(|_| {
-                json!({
-                    "provider": "anthropic",
-                    "error": "failed to serialize request",
-                })
-            });
-            store.with_current(|log| {
-                log.llm_request_json = Some(request_json);
-            });
-        }
-
-        let mut req = self
-            .client
-            .post(ANTHROPIC_API_URL)
-            .header("x-api-key", &self.api_key)
-            .header("anthropic-version", API_VERSION)
-            .header("content-type", "application/json")
-            .json(&request);
-        if let Some(timeout) = self.timeout {
-            req = req.timeout(timeout);
-        }
-
-        let response = req.send().await.map_err(|e| {
-            if e.is_timeout() {
-                if let Some(timeout) = self.timeout {
-                    LlmError::Timeout(timeout)
-                } else {
-                    // If we didn't configure a timeout, treat this as a generic network error.
-                    LlmError::Network(e)
-                }
-            } else {
-                LlmError::Network(e)
-            }
-        })?;
-
-        let status = response.status();
-        if !status.is_success() {
-            let error_text = response.text().await.unwrap_or_default();
-            // Try to parse as error response
-            if let Ok(error_response) = serde_json::from_str::<ErrorResponse>(&error_text) {
-                return Err(LlmError::Api(format!(
-                    "Anthropic API error ({}): {}",
-                    status, error_response.error.message
-                )));
-            }
-            return Err(LlmError::Api(format!(
-                "Anthropic API error ({}): {}",
-                status, error_text
-            )));
-        }
+        let request = self.build_request(system_prompt, user_message, false)?;
+        let response = self.send(&request).await?;
 
         let response_json: serde_json::Value = response.json().await.map_err(|e| {
             LlmError::InvalidResponse(format!("Failed to parse response: {}", e))
@@ -291,6 +281,67 @@ impl LlmProvider for AnthropicLlmProvider {
             .ok_or_else(|| LlmError::InvalidResponse("No text content in response".to_string()))
     }
 
+    async fn complete_stream(
+        &self,
+        system_prompt: &str,
+        user_message: &str,
+        deltas: &LlmDeltaSender,
+    ) -> Result<String, LlmError> {
+        let request = self.build_request(system_prompt, user_message, true)?;
+        let response = self.send(&request).await?;
+
+        let mut text = String::new();
+        let mut stop_reason: Option<String> = None;
+
+        read_sse_events(response, self.timeout, |event| {
+            let value = parse_event_json(&event)?;
+            match value.get("type").and_then(|t| t.as_str()) {
+                // Thinking blocks stream `thinking_delta`s, which are not part of the output.
+                Some("content_block_delta")
+                    if value.pointer("/delta/type").and_then(|t| t.as_str()) == Some("text_delta") =>
+                {
+                    if let Some(delta) = value.pointer("/delta/text").and_then(|t| t.as_str()) {
+                        text.push_str(delta);
+                        let _ = deltas.send(delta.to_string());
+                    }
+                }
+                Some("message_delta") => {
+                    stop_reason = value
+                        .pointer("/delta/stop_reason")
+                        .and_then(|r| r.as_str())
+                        .map(|r| r.to_string());
+                }
+                Some("message_stop") => return Ok(StreamControl::Stop),
+                Some("error") => {
+                    let message = value
+                        .pointer("/error/message")
+                        .and_then(|m| m.as_str())
+                        .unwrap_or("stream failed");
+                    return Err(LlmError::Api(format!("Anthropic API error: {}", message).into()));
+                }
+                _ => {}
+            }
+            Ok(StreamControl::Continue)
+        })
+        .await?;
+
+        if let Some(store) = &self.request_log_store {
+            let response_for_log = json!({
+                "stream": true,
+                "content": [{ "type": "text", "text": text }],
+                "stop_reason": stop_reason,
+            });
+            store.with_current(|log| {
+                log.llm_response_json = Some(response_for_log);
+            });
+        }
+
+        if text.is_empty() {
+            return Err(LlmError::InvalidResponse("No text content in response".to_string()));
+        }
+        Ok(text)
+    }
+
     fn name(&self) -> &'static str {
         "anthropic"
     }
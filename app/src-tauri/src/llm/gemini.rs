@@ -1,7 +1,11 @@
 //! Google Gemini (AI Studio / Gemini Developer API) LLM provider for text formatting.
 
-use super::{LlmError, LlmProvider, DEFAULT_LLM_TIMEOUT};
+use super::streaming::{
+    parse_event_json, read_sse_events, request_error, JsonStringFieldDecoder, StreamControl,
+};
+use super::{LlmDeltaSender, LlmError, LlmProvider, DEFAULT_LLM_TIMEOUT};
 use async_trait::async_trait;
+use crate::api_error::ApiError;
 use crate::request_log::RequestLogStore;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
@@ -212,6 +216,127 @@ impl GeminiLlmProvider {
         None
     }
 
+    fn build_request(
+        &self,
+        system_prompt: &str,
+        user_message: &str,
+    ) -> Result<GenerateContentRequest, LlmError> {
+        if self.api_key.trim().is_empty() {
+            return Err(LlmError::NoApiKey("gemini".to_string()));
+        }
+
+        // For deterministic formatting/rewrite.
+        // Gemini docs note that for Gemini 3 models it's recommended to keep temperature at the
+        // default value to avoid unexpected behavior. For older models we keep temperature low.
+        let temperature = if self.model.contains("gemini-3") {
+            None
+        } else {
+            Some(0.0)
+        };
+
+        let generation_config = GenerationConfig {
+            max_output_tokens: 4096,
+            temperature,
+            response_mime_type: if self.structured_outputs {
+                "application/json".to_string()
+            } else {
+                "text/plain".to_string()
+            },
+            response_json_schema: if self.structured_outputs {
+                Some(Self::rewrite_response_schema())
+            } else {
+                None
+            },
+            thinking_config: self.effective_thinking_config(),
+        };
+
+        Ok(GenerateContentRequest {
+            system_instruction: Some(Content {
+                role: None,
+                parts: vec![Part {
+                    text: Some(if self.structured_outputs {
+                        format!(
+                            "{}\n\nReturn ONLY valid JSON that matches the provided JSON Schema (no markdown, no extra keys).",
+                            system_prompt
+                        )
+                    } else {
+                        system_prompt.to_string()
+                    }),
+                }],
+            }),
+            contents: vec![Content {
+                role: Some("user".to_string()),
+                parts: vec![Part {
+                    text: Some(user_message.to_string()),
+                }],
+            }],
+            generation_config: Some(generation_config),
+        })
+    }
+
+    /// Log and send the request to `method` (`generateContent` or `streamGenerateContent`).
+    async fn send(
+        &self,
+        request: &GenerateContentRequest,
+        method: &str,
+    ) -> Result<reqwest::Response, LlmError> {
+        let model = Self::normalize_model_name(&self.model);
+        let url = format!("{}/{model}:{method}", GEMINI_API_ROOT);
+
+        if let Some(store) = &self.request_log_store {
+            let request_json = serde_json::to_value(request).unwrap_or_else(|_| {
+                json!({
+                    "provider": "gemini",
+                    "error": "failed to serialize request",
+                })
+            });
+            store.with_current(|log| {
+                log.llm_request_json = Some(request_json);
+            });
+        }
+
+        let mut req = self
+            .client
+            .post(url)
+            .header("x-goog-api-key", self.api_key.trim())
+            .json(request);
+
+        if let Some(timeout) = self.timeout {
+            req = req.timeout(timeout);
+        }
+
+        let response = req.send().await.map_err(|e| request_error(e, self.timeout))?;
+
+        if !response.status().is_success() {
+            let error = ApiError::from_response("Gemini API error", response).await;
+            return Err(LlmError::Api(error));
+        }
+
+        Ok(response)
+    }
+
+    /// Response is JSON-mode; unwrap our schema.
+    fn unwrap_structured_output(output_text: &str) -> Result<String, LlmError> {
+        let v: serde_json::Value = serde_json::from_str(output_text).map_err(|e| {
+            LlmError::InvalidResponse(format!(
+                "Gemini structured output was not valid JSON: {} (content: {})",
+                e, output_text
+            ))
+        })?;
+
+        let rewritten = v
+            .get("rewritten_text")
+            .and_then(|t| t.as_str())
+            .ok_or_else(|| {
+                LlmError::InvalidResponse(format!(
+                    "Gemini structured output missing required field 'rewritten_text' (content: {})",
+                    output_text
+                ))
+            })?;
+
+        Ok(rewritten.to_string())
+    }
+
     fn extract_text(response: &GenerateContentResponse) -> Result<String, LlmError> {
         let candidate = response
             .candidates
@@ -309,162 +434,105 @@ struct Candidate {
     content: Option<Content>,
 }
 
-#[derive(Debug, Deserialize)]
-struct GeminiErrorResponse {
-    error: GeminiErrorDetail,
-}
-
-#[derive(Debug, Deserialize)]
-struct GeminiErrorDetail {
-    message: String,
-}
-
 #[async_trait]
 impl LlmProvider for GeminiLlmProvider {
     async fn complete(&self, system_prompt: &str, user_message: &str) -> Result<String, LlmError> {
-        if self.api_key.trim().is_empty() {
-            return Err(LlmError::NoApiKey("gemini".to_string()));
-        }
+        let request = self.build_request(system_prompt, user_message)?;
+        let response = self.send(&request, "generateContent").await?;
 
-        let model = Self::normalize_model_name(&self.model);
-        let url = format!("{}/{model}:generateContent", GEMINI_API_ROOT);
-
-        // For deterministic formatting/rewrite.
-        // Gemini docs note that for Gemini 3 models it's recommended to keep temperature at the
-        // default value to avoid unexpected behavior. For older models we keep temperature low.
-        let temperature = if self.model.contains("gemini-3") {
-            None
-        } else {
-            Some(0.0)
-        };
-
-        let generation_config = GenerationConfig {
-            max_output_tokens: 4096,
-            temperature,
-            response_mime_type: if self.structured_outputs {
-                "application/json".to_string()
-            } else {
-                "text/plain".to_string()
-            },
-            response_json_schema: if self.structured_outputs {
-                Some(Self::rewrite_response_schema())
-            } else {
-                None
-            },
-            thinking_config: self.effective_thinking_config(),
-        };
-
-        let request = GenerateContentRequest {
-            system_instruction: Some(Content {
-                role: None,
-                parts: vec![Part {
-                    text: Some(if self.structured_outputs {
-                        format!(
-                            "{}\n\nReturn ONLY valid JSON that matches the provided JSON Schema (no markdown, no extra keys).",
-                            system_prompt
-                        )
-                    } else {
-                        system_prompt.to_string()
-                    }),
-                }],
-            }),
-            contents: vec![Content {
-                role: Some("user".to_string()),
-                parts: vec![Part {
-                    text: Some(user_message.to_string()),
-                }],
-            }],
-            generation_config: Some(generation_config),
-        };
+        let response_value: serde_json::Value = response.json().await.map_err(|e| {
+            LlmError::InvalidResponse(format!("Failed to parse Gemini response: {}", e))
+        })?;
 
         if let Some(store) = &self.request_log_store {
-            let request_json = serde_json::to_value(&request).unwrap_or_else(|_| {
-                json!({
-                    "provider": "gemini",
-                    "error": "failed to serialize request",
-                })
-            });
+            let response_for_log = response_value.clone();
             store.with_current(|log| {
-                log.llm_request_json = Some(request_json);
+                log.llm_response_json = Some(response_for_log);
             });
         }
 
-        let mut req = self
-            .client
-            .post(url)
-            .header("x-goog-api-key", self.api_key.trim())
-            .json(&request);
+        let response_json: GenerateContentResponse = serde_json::from_value(response_value)
+            .map_err(|e| LlmError::InvalidResponse(format!("Failed to parse Gemini response: {}", e)))?;
 
-        if let Some(timeout) = self.timeout {
-            req = req.timeout(timeout);
+        let output_text = Self::extract_text(&response_json)?;
+
+        if !self.structured_outputs {
+            return Ok(output_text);
         }
 
-        let response = req.send().await.map_err(|e| {
-            if e.is_timeout() {
-                if let Some(timeout) = self.timeout {
-                    LlmError::Timeout(timeout)
-                } else {
-                    LlmError::Network(e)
-                }
-            } else {
-                LlmError::Network(e)
+        Self::unwrap_structured_output(&output_text)
+    }
+
+    async fn complete_stream(
+        &self,
+        system_prompt: &str,
+        user_message: &str,
+        deltas: &LlmDeltaSender,
+    ) -> Result<String, LlmError> {
+        let request = self.build_request(system_prompt, user_message)?;
+        let response = self.send(&request, "streamGenerateContent?alt=sse").await?;
+
+        let mut output_text = String::new();
+        let mut field = JsonStringFieldDecoder::new("rewritten_text");
+        let mut last_chunk: Option<serde_json::Value> = None;
+
+        // Every event is a partial `GenerateContentResponse`; there is no end-of-stream marker.
+        read_sse_events(response, self.timeout, |event| {
+            let value = parse_event_json(&event)?;
+            if let Some(message) = value.pointer("/error/message").and_then(|m| m.as_str()) {
+                return Err(LlmError::Api(format!("Gemini API error: {}", message).into()));
             }
-        })?;
 
-        let status = response.status();
-        if !status.is_success() {
-            let error_text = response.text().await.unwrap_or_default();
-            if let Ok(error_response) = serde_json::from_str::<GeminiErrorResponse>(&error_text) {
-                return Err(LlmError::Api(format!(
-                    "Gemini API error ({}): {}",
-                    status, error_response.error.message
-                )));
+            let chunk: GenerateContentResponse = serde_json::from_value(value.clone())
+                .map_err(|e| LlmError::InvalidResponse(format!("Failed to parse Gemini response: {}", e)))?;
+            let parts = chunk
+                .candidates
+                .iter()
+                .flatten()
+                .take(1)
+                .filter_map(|c| c.content.as_ref())
+                .flat_map(|c| c.parts.iter())
+                .filter_map(|p| p.text.as_deref());
+
+            for delta in parts {
+                output_text.push_str(delta);
+                let text = if self.structured_outputs {
+                    field.push(delta)
+                } else {
+                    delta.to_string()
+                };
+                if !text.is_empty() {
+                    let _ = deltas.send(text);
+                }
             }
-            return Err(LlmError::Api(format!(
-                "Gemini API error ({}): {}",
-                status, error_text
-            )));
-        }
 
-        let response_value: serde_json::Value = response.json().await.map_err(|e| {
-            LlmError::InvalidResponse(format!("Failed to parse Gemini response: {}", e))
-        })?;
+            last_chunk = Some(value);
+            Ok(StreamControl::Continue)
+        })
+        .await?;
 
         if let Some(store) = &self.request_log_store {
-            let response_for_log = response_value.clone();
+            let response_for_log = json!({
+                "stream": true,
+                "text": output_text,
+                "last_chunk": last_chunk,
+            });
             store.with_current(|log| {
                 log.llm_response_json = Some(response_for_log);
             });
         }
 
-        let response_json: GenerateContentResponse = serde_json::from_value(response_value)
-            .map_err(|e| LlmError::InvalidResponse(format!("Failed to parse Gemini response: {}", e)))?;
-
-        let output_text = Self::extract_text(&response_json)?;
+        if output_text.trim().is_empty() {
+            return Err(LlmError::InvalidResponse(
+                "Gemini API returned empty candidate text".to_string(),
+            ));
+        }
 
         if !self.structured_outputs {
             return Ok(output_text);
         }
 
-        // Response is JSON-mode; unwrap our schema.
-        let v: serde_json::Value = serde_json::from_str(&output_text).map_err(|e| {
-            LlmError::InvalidResponse(format!(
-                "Gemini structured output was not valid JSON: {} (content: {})",
-                e, output_text
-            ))
-        })?;
-
-        let rewritten = v
-            .get("rewritten_text")
-            .and_then(|t| t.as_str())
-            .ok_or_else(|| {
-                LlmError::InvalidResponse(format!(
-                    "Gemini structured output missing required field 'rewritten_text' (content: {})",
-                    output_text
-                ))
-            })?;
-
-        Ok(rewritten.to_string())
+        Self::unwrap_structured_output(&output_text)
     }
 
     fn name(&self) -> &'static str {
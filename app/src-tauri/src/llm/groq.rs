@@ -4,8 +4,10 @@
 //! This provider uses the same request/response shape as OpenAI, but targets
 //! Groq's base URL.
 
-use super::{LlmError, LlmProvider, DEFAULT_LLM_TIMEOUT};
+use super::streaming::{read_chat_completion_stream, request_error};
+use super::{LlmDeltaSender, LlmError, LlmProvider, DEFAULT_LLM_TIMEOUT};
 use async_trait::async_trait;
+use crate::api_error::ApiError;
 use crate::request_log::RequestLogStore;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
@@ -65,55 +67,18 @@ impl GroqLlmProvider {
         self.timeout = None;
         self
     }
-}
-
-#[derive(Debug, Serialize)]
-struct ChatMessage {
-    role: String,
-    content: String,
-}
-
-#[derive(Debug, Serialize)]
-struct ChatRequest {
-    model: String,
-    messages: Vec<ChatMessage>,
-    max_tokens: u32,
-    temperature: f32,
-}
-
-#[derive(Debug, Deserialize)]
-struct ChatChoice {
-    message: ChatResponseMessage,
-}
-
-#[derive(Debug, Deserialize)]
-struct ChatResponseMessage {
-    content: String,
-}
-
-#[derive(Debug, Deserialize)]
-struct ChatResponse {
-    choices: Vec<ChatChoice>,
-}
-
-#[derive(Debug, Deserialize)]
-struct ErrorResponse {
-    error: ErrorDetail,
-}
 
-#[derive(Debug, Deserialize)]
-struct ErrorDetail {
-    message: String,
-}
-
-#[async_trait]
-impl LlmProvider for GroqLlmProvider {
-    async fn complete(&self, system_prompt: &str, user_message: &str) -> Result<String, LlmError> {
+    fn build_request(
+        &self,
+        system_prompt: &str,
+        user_message: &str,
+        stream: bool,
+    ) -> Result<ChatRequest, LlmError> {
         if self.api_key.is_empty() {
             return Err(LlmError::NoApiKey("groq".to_string()));
         }
 
-        let request = ChatRequest {
+        Ok(ChatRequest {
             model: self.model.clone(),
             messages: vec![
                 ChatMessage {
@@ -127,10 +92,14 @@ impl LlmProvider for GroqLlmProvider {
             ],
             max_tokens: 4096,
             temperature: 0.3,
-        };
+            stream: stream.then_some(true),
+        })
+    }
 
+    /// Log and send the request, returning the successful response.
+    async fn send(&self, request: &ChatRequest) -> Result<reqwest::Response, LlmError> {
         if let Some(store) = &self.request_log_store {
-            let request_json = serde_json::to_value(&request).unwrap_or_else(|_| {
+            let request_json = serde_json::to_value(request).unwrap_or_else(|_| {
                 json!({
                     "provider": "groq",
                     "error": "failed to serialize request",
@@ -145,39 +114,60 @@ impl LlmProvider for GroqLlmProvider {
             .client
             .post(GROQ_API_URL)
             .bearer_auth(&self.api_key)
-            .json(&request);
+            .json(request);
         if let Some(timeout) = self.timeout {
             req = req.timeout(timeout);
         }
 
-        let response = req.send().await.map_err(|e| {
-            if e.is_timeout() {
-                if let Some(timeout) = self.timeout {
-                    LlmError::Timeout(timeout)
-                } else {
-                    // If we didn't configure a timeout, treat this as a generic network error.
-                    LlmError::Network(e)
-                }
-            } else {
-                LlmError::Network(e)
-            }
-        })?;
-
-        let status = response.status();
-        if !status.is_success() {
-            let error_text = response.text().await.unwrap_or_default();
-            if let Ok(error_response) = serde_json::from_str::<ErrorResponse>(&error_text) {
-                return Err(LlmError::Api(format!(
-                    "Groq API error ({}): {}",
-                    status, error_response.error.message
-                )));
-            }
-            return Err(LlmError::Api(format!(
-                "Groq API error ({}): {}",
-                status, error_text
-            )));
+        // Without a configured timeout, a timeout is treated as a generic network error.
+        let response = req.send().await.map_err(|e| request_error(e, self.timeout))?;
+
+        if !response.status().is_success() {
+            let error = ApiError::from_response("Groq API error", response).await;
+            return Err(LlmError::Api(error));
         }
 
+        Ok(response)
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct ChatMessage {
+    role: String,
+    content: String,
+}
+
+#[derive(Debug, Serialize)]
+struct ChatRequest {
+    model: String,
+    messages: Vec<ChatMessage>,
+    max_tokens: u32,
+    temperature: f32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stream: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatChoice {
+    message: ChatResponseMessage,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatResponseMessage {
+    content: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatResponse {
+    choices: Vec<ChatChoice>,
+}
+
+#[async_trait]
+impl LlmProvider for GroqLlmProvider {
+    async fn complete(&self, system_prompt: &str, user_message: &str) -> Result<String, LlmError> {
+        let request = self.build_request(system_prompt, user_message, false)?;
+        let response = self.send(&request).await?;
+
         let response_json: serde_json::Value = response
             .json()
             .await
@@ -201,6 +191,30 @@ impl LlmProvider for GroqLlmProvider {
             .ok_or_else(|| LlmError::InvalidResponse("No response choices returned".to_string()))
     }
 
+    async fn complete_stream(
+        &self,
+        system_prompt: &str,
+        user_message: &str,
+        deltas: &LlmDeltaSender,
+    ) -> Result<String, LlmError> {
+        let request = self.build_request(system_prompt, user_message, true)?;
+        let response = self.send(&request).await?;
+
+        let text = read_chat_completion_stream(response, self.timeout, deltas).await?;
+
+        if let Some(store) = &self.request_log_store {
+            let response_for_log = json!({
+                "stream": true,
+                "choices": [{ "message": { "role": "assistant", "content": text } }],
+            });
+            store.with_current(|log| {
+                log.llm_response_json = Some(response_for_log);
+            });
+        }
+
+        Ok(text)
+    }
+
     fn name(&self) -> &'static str {
         "groq"
     }
@@ -0,0 +1,225 @@
+//! Post-LLM validation: catch rewrites that answer, summarize or chat instead of formatting.
+//!
+//! The prompts ask the model to only clean up the transcript; these checks verify that the
+//! result still looks like it. They are deliberately loose: filler removal, punctuation and
+//! backtrack corrections change the text a little, an answer or a summary changes it a lot.
+
+use crate::text_normalize::{edit_distance, words};
+use serde::{Deserialize, Serialize};
+
+/// Openers of a conversational reply. Only a violation when the transcript doesn't start with
+/// the same words (the speaker may well dictate "Sure, ...").
+const CHATTY_PREFIXES: &[&str] = &[
+    "here is",
+    "here's",
+    "here are",
+    "sure",
+    "certainly",
+    "of course",
+    "absolutely",
+    "i've formatted",
+    "i have formatted",
+    "i've cleaned",
+    "formatted text",
+    "formatted version",
+    "cleaned text",
+    "cleaned up",
+    "corrected text",
+    "the formatted",
+    "the cleaned",
+    "the corrected",
+    "as an ai",
+    "i'm sorry",
+    "i am sorry",
+];
+
+/// Words of leading filler ("um, so, ...") tolerated before a prefix in the transcript.
+const TRANSCRIPT_PREFIX_SLACK: usize = 3;
+
+/// Shorter transcripts only get the prefix check; a few words can legitimately change a lot.
+const MIN_WORDS_FOR_RATIOS: usize = 6;
+
+/// What the pipeline does when the guardrail rejects an output.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GuardrailAction {
+    /// Use the raw transcript.
+    #[default]
+    Fallback,
+    /// Ask the same provider again with a stricter prompt, then use the raw transcript.
+    RetryStrict,
+}
+
+/// Thresholds for accepting an LLM rewrite.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OutputGuardrail {
+    pub enabled: bool,
+    pub action: GuardrailAction,
+    /// Allowed output/transcript length ratio (in characters).
+    pub min_length_ratio: f64,
+    pub max_length_ratio: f64,
+    /// Minimum share of the output's words that also appear in the transcript.
+    pub min_token_overlap: f64,
+    /// Maximum word-level edit distance, relative to the longer of the two texts.
+    pub max_edit_ratio: f64,
+}
+
+impl Default for OutputGuardrail {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            action: GuardrailAction::Fallback,
+            min_length_ratio: 0.3,
+            max_length_ratio: 1.8,
+            min_token_overlap: 0.6,
+            max_edit_ratio: 0.7,
+        }
+    }
+}
+
+/// Why an output was rejected.
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum GuardrailViolation {
+    #[error("output starts with a conversational prefix (\"{0}\")")]
+    ChattyPrefix(String),
+
+    #[error("output is {0:.2}x the transcript length")]
+    LengthRatio(f64),
+
+    #[error("only {:.0}% of the output's words appear in the transcript", .0 * 100.0)]
+    TokenOverlap(f64),
+
+    #[error("output differs from the transcript in {:.0}% of its words", .0 * 100.0)]
+    EditDistance(f64),
+}
+
+impl OutputGuardrail {
+    /// Check `output` against the `transcript` it was produced from.
+    ///
+    /// With `translated`, only the prefix check applies: the words are meant to differ.
+    pub fn check(
+        &self,
+        transcript: &str,
+        output: &str,
+        translated: bool,
+    ) -> Result<(), GuardrailViolation> {
+        if !self.enabled {
+            return Ok(());
+        }
+
+        let transcript_words = words(transcript);
+        let output_words = words(output);
+
+        if let Some(prefix) = chatty_prefix(&transcript_words, &output_words) {
+            return Err(GuardrailViolation::ChattyPrefix(prefix.to_string()));
+        }
+        if translated || transcript_words.len() < MIN_WORDS_FOR_RATIOS {
+            return Ok(());
+        }
+
+        let length_ratio =
+            output.trim().chars().count() as f64 / transcript.trim().chars().count().max(1) as f64;
+        if length_ratio < self.min_length_ratio || length_ratio > self.max_length_ratio {
+            return Err(GuardrailViolation::LengthRatio(length_ratio));
+        }
+
+        if !output_words.is_empty() {
+            let known = output_words
+                .iter()
+                .filter(|w| transcript_words.contains(w))
+                .count();
+            let overlap = known as f64 / output_words.len() as f64;
+            if overlap < self.min_token_overlap {
+                return Err(GuardrailViolation::TokenOverlap(overlap));
+            }
+        }
+
+        let edit_ratio = edit_distance(&transcript_words, &output_words) as f64
+            / transcript_words.len().max(output_words.len()) as f64;
+        if edit_ratio > self.max_edit_ratio {
+            return Err(GuardrailViolation::EditDistance(edit_ratio));
+        }
+
+        Ok(())
+    }
+}
+
+fn chatty_prefix(transcript_words: &[String], output_words: &[String]) -> Option<&'static str> {
+    CHATTY_PREFIXES.iter().copied().find(|prefix| {
+        let prefix_words = words(prefix);
+        let transcript_head =
+            &transcript_words[..transcript_words.len().min(prefix_words.len() + TRANSCRIPT_PREFIX_SLACK)];
+        output_words.starts_with(&prefix_words)
+            && !transcript_head
+                .windows(prefix_words.len())
+                .any(|window| window == prefix_words.as_slice())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TRANSCRIPT: &str =
+        "um so basically I was like thinking we should uh you know update the readme file";
+
+    fn enabled() -> OutputGuardrail {
+        OutputGuardrail {
+            enabled: true,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_accepts_formatted_transcript() {
+        let guardrail = enabled();
+        assert!(guardrail
+            .check(TRANSCRIPT, "So basically, I was thinking we should update the readme file.", false)
+            .is_ok());
+        assert!(guardrail
+            .check("sure thing I'll be there", "Sure thing, I'll be there.", false)
+            .is_ok());
+        assert!(guardrail
+            .check("um sure I can do that tomorrow", "Sure, I can do that tomorrow.", false)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_rejects_chatty_prefix() {
+        let result = enabled().check(
+            TRANSCRIPT,
+            "Here is the formatted text: So basically, I was thinking we should update the readme file.",
+            false,
+        );
+        assert_eq!(result, Err(GuardrailViolation::ChattyPrefix("here is".to_string())));
+    }
+
+    #[test]
+    fn test_rejects_answers_and_summaries() {
+        let guardrail = enabled();
+        let transcript = "what is the difference between a process and a thread in an operating system";
+
+        let answer = "A process is an independent program with its own memory space, while a thread \
+            is a lightweight unit of execution that shares memory with other threads of the same \
+            process.";
+        assert!(matches!(
+            guardrail.check(transcript, answer, false),
+            Err(GuardrailViolation::LengthRatio(_))
+        ));
+
+        let reworded = "Processes own memory; threads share it within one program.";
+        assert!(matches!(
+            guardrail.check(transcript, reworded, false),
+            Err(GuardrailViolation::TokenOverlap(_))
+        ));
+
+        assert!(guardrail.check(transcript, answer, true).is_ok());
+    }
+
+    #[test]
+    fn test_disabled_by_default() {
+        let guardrail = OutputGuardrail::default();
+        assert!(guardrail.check(TRANSCRIPT, "Here is a poem instead.", false).is_ok());
+    }
+}
@@ -1,29 +1,47 @@
 //! LLM providers for text formatting.
 //!
 //! This module provides LLM-based text formatting for dictation transcripts.
-//! It supports multiple providers (OpenAI, Anthropic, Ollama) and uses
-//! configurable prompts to clean up and format transcribed speech.
+//! It supports multiple providers (OpenAI, Anthropic, Ollama, OpenAI-compatible servers)
+//! and uses configurable prompts to clean up and format transcribed speech.
 
 mod anthropic;
 mod defaults;
 mod gemini;
 mod groq;
+mod guardrail;
 mod ollama;
 mod openai;
+mod openai_compatible;
 mod prompts;
+mod streaming;
+mod template;
 
 pub use anthropic::AnthropicLlmProvider;
 pub use gemini::GeminiLlmProvider;
 pub use groq::GroqLlmProvider;
+pub use guardrail::{GuardrailAction, GuardrailViolation, OutputGuardrail};
 pub use ollama::OllamaLlmProvider;
 pub use openai::OpenAiLlmProvider;
+pub use openai_compatible::{OpenAiCompatibleLlmConfig, OpenAiCompatibleLlmProvider};
 pub use defaults::default_llm_model_for_provider;
 pub use prompts::{
-    combine_prompt_sections, PromptSections, ADVANCED_PROMPT_DEFAULT, DICTIONARY_PROMPT_DEFAULT,
-    MAIN_PROMPT_DEFAULT,
+    combine_prompt_sections, transform_user_message, PromptSections, ADVANCED_PROMPT_DEFAULT,
+    DICTIONARY_PROMPT_DEFAULT, MAIN_PROMPT_DEFAULT, TRANSFORM_PROMPT_DEFAULT,
+};
+pub use streaming::{
+    llm_delta_channel, streamed_remainder, LlmDeltaReceiver, LlmDeltaSender, SentenceChunker,
+    StreamedRemainder,
+};
+pub use template::{
+    app_name_from_path, prompt_variables, render_prompt_sections, render_template,
+    template_variables, truncate_context_value, unknown_prompt_variables,
+    validate_prompt_sections, PromptContext, PROMPT_VARIABLES,
 };
 
+use crate::api_error::ApiError;
+use crate::retry::RetryableError;
 use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -37,7 +55,7 @@ pub enum LlmError {
     Network(#[from] reqwest::Error),
 
     #[error("API error: {0}")]
-    Api(String),
+    Api(ApiError),
 
     #[error("Invalid response: {0}")]
     InvalidResponse(String),
@@ -50,6 +68,24 @@ pub enum LlmError {
 
     #[error("Provider not available: {0}")]
     ProviderNotAvailable(String),
+
+    #[error("Output rejected: {0}")]
+    Rejected(GuardrailViolation),
+}
+
+impl RetryableError for LlmError {
+    const KIND: &'static str = "LLM";
+
+    fn is_transient(&self) -> bool {
+        matches!(self, LlmError::Network(_) | LlmError::Timeout(_))
+    }
+
+    fn api_error(&self) -> Option<&ApiError> {
+        match self {
+            LlmError::Api(error) => Some(error),
+            _ => None,
+        }
+    }
 }
 
 /// Trait for LLM providers that can format text
@@ -58,6 +94,21 @@ pub trait LlmProvider: Send + Sync {
     /// Complete a prompt and return the response
     async fn complete(&self, system_prompt: &str, user_message: &str) -> Result<String, LlmError>;
 
+    /// Complete a prompt, sending text deltas to `deltas` as they are generated.
+    ///
+    /// Returns the full response, like `complete`. Providers without a streaming API send the
+    /// whole response as a single delta.
+    async fn complete_stream(
+        &self,
+        system_prompt: &str,
+        user_message: &str,
+        deltas: &LlmDeltaSender,
+    ) -> Result<String, LlmError> {
+        let text = self.complete(system_prompt, user_message).await?;
+        let _ = deltas.send(text.clone());
+        Ok(text)
+    }
+
     /// Get the provider name
     fn name(&self) -> &'static str;
 
@@ -144,6 +195,8 @@ pub struct LlmConfig {
     pub model: Option<String>,
     /// Base URL for Ollama (default: http://localhost:11434)
     pub ollama_url: Option<String>,
+    /// Endpoint settings for the "openai-compatible" provider
+    pub openai_compatible: OpenAiCompatibleLlmConfig,
 
     /// OpenAI reasoning effort (gpt-5 and o-series models only).
     /// Examples: "low", "medium", "high".
@@ -166,10 +219,28 @@ pub struct LlmConfig {
     pub prompts: PromptSections,
     /// Optional per-program prompt overrides (matched against the foreground executable path)
     pub program_prompt_profiles: Vec<ProgramPromptProfile>,
-    /// Request timeout
+    /// Providers to try, in order, when the effective provider fails
+    pub fallback_chain: Vec<LlmFallbackEntry>,
+    /// Validation applied to every rewrite before it replaces the transcript
+    pub guardrail: OutputGuardrail,
+    /// The user's name, available to prompts as `{{user_name}}`
+    pub user_name: Option<String>,
+    /// Custom system prompt for the transform-selection hotkey (None = built-in default)
+    pub transform_prompt: Option<String>,
+    /// Request timeout (also the time budget shared by the whole fallback chain)
     pub timeout: Duration,
 }
 
+/// An extra LLM provider to try when the ones before it fail.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LlmFallbackEntry {
+    /// Provider id (e.g. "groq", "ollama").
+    pub provider: String,
+    /// Optional model override (None = provider default).
+    #[serde(default)]
+    pub model: Option<String>,
+}
+
 /// Per-program prompt override profile.
 ///
 /// If the active/foreground executable path matches any entry in `program_paths`, `prompts` is used instead of
@@ -191,6 +262,13 @@ pub struct ProgramPromptProfile {
     pub stt_timeout_seconds: Option<f64>,
     pub llm_provider: Option<String>,
     pub llm_model: Option<String>,
+    /// Optional per-profile LLM fallback chain (falls back to LlmConfig.fallback_chain)
+    pub llm_fallback_chain: Option<Vec<LlmFallbackEntry>>,
+
+    /// Optional per-profile translation mode (falls back to the global setting)
+    pub translation: Option<crate::translation::TranslationConfig>,
+    /// Optional per-profile spoken language (falls back to the global setting)
+    pub stt_language: Option<crate::stt::SttLanguage>,
 }
 
 impl Default for LlmConfig {
@@ -201,22 +279,30 @@ impl Default for LlmConfig {
             api_key: String::new(),
             model: None,
             ollama_url: None,
+            openai_compatible: OpenAiCompatibleLlmConfig::default(),
             openai_reasoning_effort: None,
             gemini_thinking_budget: None,
             gemini_thinking_level: None,
             anthropic_thinking_budget: None,
             prompts: PromptSections::default(),
             program_prompt_profiles: Vec::new(),
+            fallback_chain: Vec::new(),
+            guardrail: OutputGuardrail::default(),
+            user_name: None,
+            transform_prompt: None,
             timeout: DEFAULT_LLM_TIMEOUT,
         }
     }
 }
 
 /// Format text using an LLM provider
+///
+/// Outputs that fail `guardrail` are returned as `LlmError::Rejected`.
 pub async fn format_text(
     provider: &dyn LlmProvider,
     transcript: &str,
     prompts: &PromptSections,
+    guardrail: &OutputGuardrail,
 ) -> Result<String, LlmError> {
     if transcript.trim().is_empty() {
         return Ok(String::new());
@@ -225,13 +311,68 @@ pub async fn format_text(
     let system_prompt = combine_prompt_sections(prompts);
     let result = provider.complete(&system_prompt, transcript).await?;
 
-    Ok(result.trim().to_string())
+    checked_output(transcript, result.trim(), prompts, guardrail)
+}
+
+/// Format text using an LLM provider, streaming the response into `deltas`.
+///
+/// Deltas are the raw response text; the returned text is trimmed like `format_text`.
+pub async fn format_text_stream(
+    provider: &dyn LlmProvider,
+    transcript: &str,
+    prompts: &PromptSections,
+    deltas: &LlmDeltaSender,
+    guardrail: &OutputGuardrail,
+) -> Result<String, LlmError> {
+    if transcript.trim().is_empty() {
+        return Ok(String::new());
+    }
+
+    let system_prompt = combine_prompt_sections(prompts);
+    let result = provider
+        .complete_stream(&system_prompt, transcript, deltas)
+        .await?;
+
+    checked_output(transcript, result.trim(), prompts, guardrail)
+}
+
+fn checked_output(
+    transcript: &str,
+    output: &str,
+    prompts: &PromptSections,
+    guardrail: &OutputGuardrail,
+) -> Result<String, LlmError> {
+    // The checks expect a clean-up of the transcript; a custom main prompt may ask for a
+    // rewrite that legitimately changes it a lot.
+    if prompts.main_custom.is_none() {
+        guardrail
+            .check(transcript, output, prompts.translation.is_some())
+            .map_err(LlmError::Rejected)?;
+    }
+    Ok(output.to_string())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_guardrail_skips_custom_main_prompt() {
+        let guardrail = OutputGuardrail {
+            enabled: true,
+            ..Default::default()
+        };
+        let transcript = "remind me to call the dentist tomorrow and pick up the dry cleaning";
+        let bullets = "Here are your tasks:\n- Call dentist\n- Dry cleaning";
+
+        assert!(checked_output(transcript, bullets, &PromptSections::default(), &guardrail).is_err());
+        let custom = PromptSections {
+            main_custom: Some("Turn the dictation into a task list.".to_string()),
+            ..Default::default()
+        };
+        assert!(checked_output(transcript, bullets, &custom, &guardrail).is_ok());
+    }
+
     #[test]
     fn test_llm_registry() {
         let registry = LlmRegistry::new();
@@ -1,7 +1,9 @@
 //! Ollama LLM provider for local text formatting.
 
-use super::{LlmError, LlmProvider};
+use super::streaming::{read_json_lines, request_error, StreamControl};
+use super::{LlmDeltaSender, LlmError, LlmProvider};
 use async_trait::async_trait;
+use crate::api_error::ApiError;
 use crate::request_log::RequestLogStore;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
@@ -124,6 +126,68 @@ impl OllamaLlmProvider {
 
         Ok(tags_response.models.into_iter().map(|m| m.name).collect())
     }
+
+    fn build_request(&self, system_prompt: &str, user_message: &str, stream: bool) -> ChatRequest {
+        ChatRequest {
+            model: self.model.clone(),
+            messages: vec![
+                ChatMessage {
+                    role: "system".to_string(),
+                    content: system_prompt.to_string(),
+                },
+                ChatMessage {
+                    role: "user".to_string(),
+                    content: user_message.to_string(),
+                },
+            ],
+            stream,
+            options: Some(ChatOptions {
+                temperature: 0.3,
+                num_predict: 4096,
+            }),
+        }
+    }
+
+    /// Log and send the chat request, returning the successful response.
+    async fn send(&self, request: &ChatRequest) -> Result<reqwest::Response, LlmError> {
+        let url = format!("{}/api/chat", self.base_url);
+
+        if let Some(store) = &self.request_log_store {
+            let request_json = serde_json::to_value(request).unwrap_or_else(|_| {
+                json!({
+                    "provider": "ollama",
+                    "error": "failed to serialize request",
+                })
+            });
+            store.with_current(|log| {
+                log.llm_request_json = Some(request_json);
+            });
+        }
+
+        let mut req = self.client.post(&url).json(request);
+        if let Some(timeout) = self.timeout {
+            req = req.timeout(timeout);
+        }
+
+        let response = req.send().await.map_err(|e| {
+            if e.is_connect() && !e.is_timeout() {
+                LlmError::ProviderNotAvailable(format!(
+                    "Ollama not reachable at {}: {}",
+                    self.base_url, e
+                ))
+            } else {
+                // Without a configured timeout, a timeout is treated as a generic network error.
+                request_error(e, self.timeout)
+            }
+        })?;
+
+        if !response.status().is_success() {
+            let error = ApiError::from_response("Ollama error", response).await;
+            return Err(LlmError::Api(error));
+        }
+
+        Ok(response)
+    }
 }
 
 impl Default for OllamaLlmProvider {
@@ -174,103 +238,75 @@ struct ModelInfo {
     name: String,
 }
 
-#[derive(Debug, Deserialize)]
-struct ErrorResponse {
-    error: String,
-}
-
 #[async_trait]
 impl LlmProvider for OllamaLlmProvider {
     async fn complete(&self, system_prompt: &str, user_message: &str) -> Result<String, LlmError> {
-        let url = format!("{}/api/chat", self.base_url);
+        let request = self.build_request(system_prompt, user_message, false);
+        let response = self.send(&request).await?;
 
-        let request = ChatRequest {
-            model: self.model.clone(),
-            messages: vec![
-                ChatMessage {
-                    role: "system".to_string(),
-                    content: system_prompt.to_string(),
-                },
-                ChatMessage {
-                    role: "user".to_string(),
-                    content: user_message.to_string(),
-                },
-            ],
-            stream: false,
-            options: Some(ChatOptions {
-                temperature: 0.3,
-                num_predict: 4096,
-            }),
-        };
+        let response_json: serde_json::Value = response.json().await.map_err(|e| {
+            LlmError::InvalidResponse(format!("Failed to parse response: {}", e))
+        })?;
 
         if let Some(store) = &self.request_log_store {
-            let request_json = serde_json::to_value(&request).unwrap_or_else(|_| {
-                json!({
-                    "provider": "ollama",
-                    "error": "failed to serialize request",
-                })
-            });
+            let response_for_log = response_json.clone();
             store.with_current(|log| {
-                log.llm_request_json = Some(request_json);
+                log.llm_response_json = Some(response_for_log);
             });
         }
 
-        let mut req = self.client.post(&url).json(&request);
-        if let Some(timeout) = self.timeout {
-            req = req.timeout(timeout);
-        }
+        response_json
+            .get("message")
+            .and_then(|m| m.get("content"))
+            .and_then(|c| c.as_str())
+            .map(|s| s.to_string())
+            .ok_or_else(|| LlmError::InvalidResponse("No message content in response".to_string()))
+    }
 
-        let response = req.send().await.map_err(|e| {
-            if e.is_timeout() {
-                if let Some(timeout) = self.timeout {
-                    LlmError::Timeout(timeout)
-                } else {
-                    // If we didn't configure a timeout, treat this as a generic network error.
-                    LlmError::Network(e)
-                }
-            } else if e.is_connect() {
-                LlmError::ProviderNotAvailable(format!(
-                    "Ollama not reachable at {}: {}",
-                    self.base_url, e
-                ))
-            } else {
-                LlmError::Network(e)
+    async fn complete_stream(
+        &self,
+        system_prompt: &str,
+        user_message: &str,
+        deltas: &LlmDeltaSender,
+    ) -> Result<String, LlmError> {
+        let request = self.build_request(system_prompt, user_message, true);
+        let response = self.send(&request).await?;
+
+        let mut text = String::new();
+        let mut final_line: Option<serde_json::Value> = None;
+
+        // One JSON object per line; the last one has `"done": true` and the timing stats.
+        read_json_lines(response, self.timeout, |value| {
+            if let Some(error) = value.get("error").and_then(|e| e.as_str()) {
+                return Err(LlmError::Api(format!("Ollama error: {}", error).into()));
             }
-        })?;
 
-        let status = response.status();
-        if !status.is_success() {
-            let error_text = response.text().await.unwrap_or_default();
-            // Try to parse as error response
-            if let Ok(error_response) = serde_json::from_str::<ErrorResponse>(&error_text) {
-                return Err(LlmError::Api(format!(
-                    "Ollama error ({}): {}",
-                    status, error_response.error
-                )));
+            if let Some(delta) = value
+                .pointer("/message/content")
+                .and_then(|c| c.as_str())
+                .filter(|c| !c.is_empty())
+            {
+                text.push_str(delta);
+                let _ = deltas.send(delta.to_string());
             }
-            return Err(LlmError::Api(format!(
-                "Ollama error ({}): {}",
-                status, error_text
-            )));
-        }
 
-        let response_json: serde_json::Value = response.json().await.map_err(|e| {
-            LlmError::InvalidResponse(format!("Failed to parse response: {}", e))
-        })?;
+            if value.get("done").and_then(|d| d.as_bool()) == Some(true) {
+                final_line = Some(value);
+                return Ok(StreamControl::Stop);
+            }
+            Ok(StreamControl::Continue)
+        })
+        .await?;
 
         if let Some(store) = &self.request_log_store {
-            let response_for_log = response_json.clone();
+            let mut response_for_log = final_line.unwrap_or_else(|| json!({}));
+            response_for_log["message"] = json!({ "role": "assistant", "content": text });
             store.with_current(|log| {
                 log.llm_response_json = Some(response_for_log);
             });
         }
 
-        response_json
-            .get("message")
-            .and_then(|m| m.get("content"))
-            .and_then(|c| c.as_str())
-            .map(|s| s.to_string())
-            .ok_or_else(|| LlmError::InvalidResponse("No message content in response".to_string()))
+        Ok(text)
     }
 
     fn name(&self) -> &'static str {
@@ -1,15 +1,21 @@
 //! OpenAI LLM provider for text formatting.
 
-use super::{LlmError, LlmProvider, DEFAULT_LLM_TIMEOUT};
+use super::streaming::{
+    parse_event_json, read_sse_events, request_error, JsonStringFieldDecoder, StreamControl,
+};
+use super::{LlmDeltaSender, LlmError, LlmProvider, DEFAULT_LLM_TIMEOUT};
 use async_trait::async_trait;
+use crate::api_error::ApiError;
 use crate::request_log::RequestLogStore;
 use reqwest::Client;
-use serde::{Deserialize, Serialize};
+use serde::Serialize;
 use serde_json::json;
 use std::time::Duration;
 
 const OPENAI_API_URL: &str = "https://api.openai.com/v1/responses";
 const DEFAULT_MODEL: &str = "gpt-4o-mini";
+/// Structured Outputs field holding the rewritten text.
+const REWRITTEN_TEXT_FIELD: &str = "rewritten_text";
 
 /// OpenAI LLM provider using the Chat Completions API
 pub struct OpenAiLlmProvider {
@@ -207,6 +213,116 @@ impl OpenAiLlmProvider {
         }
     }
 
+    /// Build the Responses API request (with Structured Outputs when supported).
+    fn build_request(
+        &self,
+        system_prompt: &str,
+        user_message: &str,
+        stream: bool,
+    ) -> Result<ResponsesRequest, LlmError> {
+        if self.api_key.is_empty() {
+            return Err(LlmError::NoApiKey("openai".to_string()));
+        }
+
+        let use_structured_outputs =
+            self.structured_outputs && Self::supports_structured_outputs(&self.model);
+
+        // When using Structured Outputs, a short explicit instruction helps avoid
+        // accidental prose even though the schema is enforced server-side.
+        let system_prompt = if use_structured_outputs {
+            format!(
+                "{}\n\nReturn ONLY valid JSON that matches the provided JSON Schema (no markdown, no extra keys).",
+                system_prompt
+            )
+        } else {
+            system_prompt.to_string()
+        };
+
+        let reasoning_effort = if Self::supports_reasoning_effort(&self.model) {
+            self.validated_reasoning_effort()
+        } else {
+            None
+        };
+
+        Ok(ResponsesRequest {
+            model: self.model.clone(),
+            input: vec![
+                ResponseInputMessage {
+                    role: "system".to_string(),
+                    content: system_prompt,
+                },
+                ResponseInputMessage {
+                    role: "user".to_string(),
+                    content: user_message.to_string(),
+                },
+            ],
+            max_output_tokens: 4096,
+            reasoning: reasoning_effort
+                .clone()
+                .map(|effort| ReasoningConfig { effort }),
+            temperature: Self::supports_temperature_param(&self.model, reasoning_effort.as_deref())
+                .then_some(0.0),
+            text: use_structured_outputs.then(|| TextConfig {
+                format: Some(Self::rewrite_response_format()),
+            }),
+            stream: stream.then_some(true),
+        })
+    }
+
+    /// Log and send the request, returning the successful response.
+    async fn send(&self, request: &ResponsesRequest) -> Result<reqwest::Response, LlmError> {
+        if let Some(store) = &self.request_log_store {
+            let request_json = serde_json::to_value(request).unwrap_or_else(|_| {
+                json!({
+                    "provider": "openai",
+                    "error": "failed to serialize request",
+                })
+            });
+            store.with_current(|log| {
+                log.llm_request_json = Some(request_json);
+            });
+        }
+
+        let mut req = self
+            .client
+            .post(OPENAI_API_URL)
+            .bearer_auth(&self.api_key)
+            .json(request);
+        if let Some(timeout) = self.timeout {
+            req = req.timeout(timeout);
+        }
+
+        // Without a configured timeout, a timeout is treated as a generic network error.
+        let response = req.send().await.map_err(|e| request_error(e, self.timeout))?;
+
+        if !response.status().is_success() {
+            let error = ApiError::from_response("OpenAI API error", response).await;
+            return Err(LlmError::Api(error));
+        }
+
+        Ok(response)
+    }
+
+    /// Extract `rewritten_text` from a Structured Outputs response.
+    fn unwrap_structured_output(output_text: &str) -> Result<String, LlmError> {
+        let v: serde_json::Value = serde_json::from_str(output_text).map_err(|e| {
+            LlmError::InvalidResponse(format!(
+                "Structured output was not valid JSON: {} (content: {})",
+                e, output_text
+            ))
+        })?;
+
+        v.get(REWRITTEN_TEXT_FIELD)
+            .and_then(|t| t.as_str())
+            .map(|t| t.to_string())
+            .ok_or_else(|| {
+                LlmError::InvalidResponse(format!(
+                    "Structured output missing required field 'rewritten_text' (content: {})",
+                    output_text
+                ))
+            })
+    }
+
     fn extract_responses_output_text(value: &serde_json::Value) -> Result<String, LlmError> {
         // Prefer the SDK-style convenience field when present.
         if let Some(s) = value.get("output_text").and_then(|v| v.as_str()) {
@@ -237,10 +353,9 @@ impl OpenAiLlmProvider {
                             .get("refusal")
                             .and_then(|r| r.as_str())
                             .unwrap_or("");
-                        return Err(LlmError::Api(format!(
-                            "OpenAI refusal: {}",
-                            refusal
-                        )));
+                        return Err(LlmError::Api(
+                            format!("OpenAI refusal: {}", refusal).into(),
+                        ));
                     }
                     Some("output_text") => {
                         if let Some(text) = part.get("text").and_then(|t| t.as_str()) {
@@ -269,6 +384,8 @@ struct ResponsesRequest {
     reasoning: Option<ReasoningConfig>,
     #[serde(skip_serializing_if = "Option::is_none")]
     text: Option<TextConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stream: Option<bool>,
 }
 
 #[derive(Debug, Serialize)]
@@ -302,148 +419,92 @@ struct TextFormat {
     description: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
-struct ErrorResponse {
-    error: ErrorDetail,
-}
-
-#[derive(Debug, Deserialize)]
-struct ErrorDetail {
-    message: String,
-}
-
 #[async_trait]
 impl LlmProvider for OpenAiLlmProvider {
     async fn complete(&self, system_prompt: &str, user_message: &str) -> Result<String, LlmError> {
-        if self.api_key.is_empty() {
-            return Err(LlmError::NoApiKey("openai".to_string()));
-        }
+        let request = self.build_request(system_prompt, user_message, false)?;
+        let response = self.send(&request).await?;
 
-        let use_structured_outputs =
-            self.structured_outputs && Self::supports_structured_outputs(&self.model);
-
-        // When using Structured Outputs, a short explicit instruction helps avoid
-        // accidental prose even though the schema is enforced server-side.
-        let system_prompt = if use_structured_outputs {
-            format!(
-                "{}\n\nReturn ONLY valid JSON that matches the provided JSON Schema (no markdown, no extra keys).",
-                system_prompt
-            )
-        } else {
-            system_prompt.to_string()
-        };
-
-        let reasoning_effort = if Self::supports_reasoning_effort(&self.model) {
-            self.validated_reasoning_effort()
-        } else {
-            None
-        };
-
-        let request = ResponsesRequest {
-            model: self.model.clone(),
-            input: vec![
-                ResponseInputMessage {
-                    role: "system".to_string(),
-                    content: system_prompt,
-                },
-                ResponseInputMessage {
-                    role: "user".to_string(),
-                    content: user_message.to_string(),
-                },
-            ],
-            max_output_tokens: 4096,
-            reasoning: reasoning_effort
-                .clone()
-                .map(|effort| ReasoningConfig { effort }),
-            temperature: Self::supports_temperature_param(&self.model, reasoning_effort.as_deref())
-                .then_some(0.0),
-            text: use_structured_outputs.then(|| TextConfig {
-                format: Some(Self::rewrite_response_format()),
-            }),
-        };
+        let response_json: serde_json::Value = response.json().await.map_err(|e| {
+            LlmError::InvalidResponse(format!("Failed to parse response: {}", e))
+        })?;
 
         if let Some(store) = &self.request_log_store {
-            let request_json = serde_json::to_value(&request).unwrap_or_else(|_| {
-                json!({
-                    "provider": "openai",
-                    "error": "failed to serialize request",
-                })
-            });
+            let response_for_log = response_json.clone();
             store.with_current(|log| {
-                log.llm_request_json = Some(request_json);
+                log.llm_response_json = Some(response_for_log);
             });
         }
 
-        let mut req = self
-            .client
-            .post(OPENAI_API_URL)
-            .bearer_auth(&self.api_key)
-            .json(&request);
-        if let Some(timeout) = self.timeout {
-            req = req.timeout(timeout);
+        let output_text = Self::extract_responses_output_text(&response_json)?;
+
+        if request.text.is_some() {
+            Self::unwrap_structured_output(&output_text)
+        } else {
+            Ok(output_text)
         }
+    }
 
-        let response = req.send().await.map_err(|e| {
-            if e.is_timeout() {
-                if let Some(timeout) = self.timeout {
-                    LlmError::Timeout(timeout)
-                } else {
-                    // If we didn't configure a timeout, treat this as a generic network error.
-                    LlmError::Network(e)
+    async fn complete_stream(
+        &self,
+        system_prompt: &str,
+        user_message: &str,
+        deltas: &LlmDeltaSender,
+    ) -> Result<String, LlmError> {
+        let request = self.build_request(system_prompt, user_message, true)?;
+        let structured = request.text.is_some();
+        let response = self.send(&request).await?;
+
+        let mut output_text = String::new();
+        let mut field = JsonStringFieldDecoder::new(REWRITTEN_TEXT_FIELD);
+        let mut completed: Option<serde_json::Value> = None;
+
+        read_sse_events(response, self.timeout, |event| {
+            let value = parse_event_json(&event)?;
+            match value.get("type").and_then(|t| t.as_str()) {
+                Some("response.output_text.delta") => {
+                    let delta = value.get("delta").and_then(|d| d.as_str()).unwrap_or("");
+                    output_text.push_str(delta);
+                    let text = if structured {
+                        field.push(delta)
+                    } else {
+                        delta.to_string()
+                    };
+                    if !text.is_empty() {
+                        let _ = deltas.send(text);
+                    }
                 }
-            } else {
-                LlmError::Network(e)
-            }
-        })?;
-
-        let status = response.status();
-        if !status.is_success() {
-            let error_text = response.text().await.unwrap_or_default();
-            // Try to parse as error response
-            if let Ok(error_response) = serde_json::from_str::<ErrorResponse>(&error_text) {
-                return Err(LlmError::Api(format!(
-                    "OpenAI API error ({}): {}",
-                    status, error_response.error.message
-                )));
+                Some("response.refusal.done") => {
+                    let refusal = value.get("refusal").and_then(|r| r.as_str()).unwrap_or("");
+                    return Err(LlmError::Api(format!("OpenAI refusal: {}", refusal).into()));
+                }
+                Some("response.completed") => {
+                    completed = value.get("response").cloned();
+                    return Ok(StreamControl::Stop);
+                }
+                Some("response.failed") | Some("response.incomplete") | Some("error") => {
+                    let message = value
+                        .pointer("/response/error/message")
+                        .or_else(|| value.pointer("/response/incomplete_details/reason"))
+                        .or_else(|| value.get("message"))
+                        .and_then(|m| m.as_str())
+                        .unwrap_or("stream failed");
+                    return Err(LlmError::Api(format!("OpenAI API error: {}", message).into()));
+                }
+                _ => {}
             }
-            return Err(LlmError::Api(format!(
-                "OpenAI API error ({}): {}",
-                status, error_text
-            )));
-        }
+            Ok(StreamControl::Continue)
+        })
+        .await?;
 
-        let response_json: serde_json::Value = response.json().await.map_err(|e| {
-            LlmError::InvalidResponse(format!("Failed to parse response: {}", e))
-        })?;
-
-        if let Some(store) = &self.request_log_store {
-            let response_for_log = response_json.clone();
+        if let (Some(store), Some(response)) = (&self.request_log_store, completed) {
             store.with_current(|log| {
-                log.llm_response_json = Some(response_for_log);
+                log.llm_response_json = Some(response);
             });
         }
 
-        let output_text = Self::extract_responses_output_text(&response_json)?;
-
-        if use_structured_outputs {
-            let v: serde_json::Value = serde_json::from_str(&output_text).map_err(|e| {
-                LlmError::InvalidResponse(format!(
-                    "Structured output was not valid JSON: {} (content: {})",
-                    e, output_text
-                ))
-            })?;
-
-            let rewritten = v
-                .get("rewritten_text")
-                .and_then(|t| t.as_str())
-                .ok_or_else(|| {
-                    LlmError::InvalidResponse(format!(
-                        "Structured output missing required field 'rewritten_text' (content: {})",
-                        output_text
-                    ))
-                })?;
-
-            Ok(rewritten.to_string())
+        if structured {
+            Self::unwrap_structured_output(&output_text)
         } else {
             Ok(output_text)
         }
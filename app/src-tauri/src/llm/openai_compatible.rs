@@ -0,0 +1,355 @@
+//! Generic OpenAI-compatible LLM provider.
+//!
+//! Targets servers that expose `/v1/chat/completions` with the OpenAI request/response shape
+//! (LM Studio, llama.cpp's `server`, vLLM, OpenRouter, ...).
+
+use super::streaming::{read_chat_completion_stream, request_error};
+use super::{LlmDeltaSender, LlmError, LlmProvider, DEFAULT_LLM_TIMEOUT};
+use async_trait::async_trait;
+use crate::api_error::ApiError;
+use crate::request_log::RequestLogStore;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+/// User settings for the OpenAI-compatible LLM endpoint.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OpenAiCompatibleLlmConfig {
+    /// Server base URL, e.g. "http://localhost:1234/v1" or "https://openrouter.ai/api/v1".
+    pub base_url: String,
+    /// Optional bearer token. Most local servers don't need one.
+    pub api_key: Option<String>,
+    /// Model name to send (profiles and the global model setting take precedence).
+    pub model: Option<String>,
+    /// Extra HTTP headers sent with every request (e.g. OpenRouter's `HTTP-Referer`).
+    pub headers: BTreeMap<String, String>,
+}
+
+impl OpenAiCompatibleLlmConfig {
+    /// Whether enough is configured to make requests.
+    pub fn is_configured(&self) -> bool {
+        !self.base_url.trim().is_empty()
+    }
+}
+
+/// LLM provider for any OpenAI-compatible Chat Completions server
+pub struct OpenAiCompatibleLlmProvider {
+    client: Client,
+    endpoint: String,
+    api_key: Option<String>,
+    model: String,
+    headers: HeaderMap,
+    timeout: Option<Duration>,
+    request_log_store: Option<RequestLogStore>,
+}
+
+impl OpenAiCompatibleLlmProvider {
+    /// Create a new OpenAI-compatible LLM provider
+    ///
+    /// # Arguments
+    /// * `config` - Endpoint settings
+    /// * `model` - Model override (takes precedence over `config.model`)
+    pub fn new(config: &OpenAiCompatibleLlmConfig, model: Option<String>) -> Self {
+        let mut headers = HeaderMap::new();
+        for (name, value) in &config.headers {
+            if name.trim().is_empty() {
+                continue;
+            }
+            match (
+                HeaderName::from_bytes(name.trim().as_bytes()),
+                HeaderValue::from_str(value.trim()),
+            ) {
+                (Ok(name), Ok(value)) => {
+                    headers.insert(name, value);
+                }
+                _ => log::warn!(
+                    "OpenAI-compatible LLM: ignoring invalid header '{}'",
+                    name.trim()
+                ),
+            }
+        }
+
+        Self {
+            client: Client::new(),
+            endpoint: Self::chat_completions_endpoint(&config.base_url),
+            api_key: config
+                .api_key
+                .as_deref()
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(|s| s.to_string()),
+            model: model
+                .or_else(|| config.model.clone())
+                .map(|m| m.trim().to_string())
+                .unwrap_or_default(),
+            headers,
+            timeout: Some(DEFAULT_LLM_TIMEOUT),
+            request_log_store: None,
+        }
+    }
+
+    pub fn with_request_log_store(mut self, store: Option<RequestLogStore>) -> Self {
+        self.request_log_store = store;
+        self
+    }
+
+    /// Set the request timeout.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Disable request timeouts entirely.
+    ///
+    /// This is primarily intended for the Settings UI "Test" actions.
+    pub fn without_timeout(mut self) -> Self {
+        self.timeout = None;
+        self
+    }
+
+    /// Accept either a base URL ("http://host/v1") or the full chat completions endpoint.
+    fn chat_completions_endpoint(base_url: &str) -> String {
+        let base = base_url.trim().trim_end_matches('/');
+        if base.is_empty() || base.ends_with("/chat/completions") {
+            base.to_string()
+        } else {
+            format!("{}/chat/completions", base)
+        }
+    }
+
+    fn build_request(
+        &self,
+        system_prompt: &str,
+        user_message: &str,
+        stream: bool,
+    ) -> Result<ChatRequest, LlmError> {
+        if self.endpoint.is_empty() {
+            return Err(LlmError::ProviderNotAvailable(
+                "OpenAI-compatible LLM requires a base URL".to_string(),
+            ));
+        }
+        if self.model.is_empty() {
+            return Err(LlmError::ProviderNotAvailable(
+                "OpenAI-compatible LLM requires a model name".to_string(),
+            ));
+        }
+
+        Ok(ChatRequest {
+            model: self.model.clone(),
+            messages: vec![
+                ChatMessage {
+                    role: "system".to_string(),
+                    content: system_prompt.to_string(),
+                },
+                ChatMessage {
+                    role: "user".to_string(),
+                    content: user_message.to_string(),
+                },
+            ],
+            max_tokens: 4096,
+            temperature: 0.3,
+            stream: stream.then_some(true),
+        })
+    }
+
+    /// Log and send the request, returning the successful response.
+    async fn send(&self, request: &ChatRequest) -> Result<reqwest::Response, LlmError> {
+        if let Some(store) = &self.request_log_store {
+            let request_json = serde_json::to_value(request).unwrap_or_else(|_| {
+                json!({
+                    "provider": "openai-compatible",
+                    "error": "failed to serialize request",
+                })
+            });
+            store.with_current(|log| {
+                log.llm_request_json = Some(request_json);
+            });
+        }
+
+        let mut req = self.client.post(&self.endpoint);
+        if let Some(api_key) = &self.api_key {
+            req = req.bearer_auth(api_key);
+        }
+        req = req.headers(self.headers.clone()).json(request);
+        if let Some(timeout) = self.timeout {
+            req = req.timeout(timeout);
+        }
+
+        // Without a configured timeout, a timeout is treated as a generic network error.
+        let response = req.send().await.map_err(|e| request_error(e, self.timeout))?;
+
+        if !response.status().is_success() {
+            let error = ApiError::from_response("OpenAI-compatible API error", response).await;
+            return Err(LlmError::Api(error));
+        }
+
+        Ok(response)
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct ChatMessage {
+    role: String,
+    content: String,
+}
+
+#[derive(Debug, Serialize)]
+struct ChatRequest {
+    model: String,
+    messages: Vec<ChatMessage>,
+    max_tokens: u32,
+    temperature: f32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stream: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatChoice {
+    message: ChatResponseMessage,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatResponseMessage {
+    content: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatResponse {
+    choices: Vec<ChatChoice>,
+}
+
+#[async_trait]
+impl LlmProvider for OpenAiCompatibleLlmProvider {
+    async fn complete(&self, system_prompt: &str, user_message: &str) -> Result<String, LlmError> {
+        let request = self.build_request(system_prompt, user_message, false)?;
+        let response = self.send(&request).await?;
+
+        let response_json: serde_json::Value = response
+            .json()
+            .await
+            .map_err(|e| LlmError::InvalidResponse(format!("Failed to parse response: {}", e)))?;
+
+        if let Some(store) = &self.request_log_store {
+            let response_for_log = response_json.clone();
+            store.with_current(|log| {
+                log.llm_response_json = Some(response_for_log);
+            });
+        }
+
+        let response: ChatResponse = serde_json::from_value(response_json)
+            .map_err(|e| LlmError::InvalidResponse(format!("Failed to parse response: {}", e)))?;
+
+        response
+            .choices
+            .into_iter()
+            .next()
+            .and_then(|choice| choice.message.content)
+            .ok_or_else(|| LlmError::InvalidResponse("No response choices returned".to_string()))
+    }
+
+    async fn complete_stream(
+        &self,
+        system_prompt: &str,
+        user_message: &str,
+        deltas: &LlmDeltaSender,
+    ) -> Result<String, LlmError> {
+        let request = self.build_request(system_prompt, user_message, true)?;
+        let response = self.send(&request).await?;
+
+        let text = read_chat_completion_stream(response, self.timeout, deltas).await?;
+
+        if let Some(store) = &self.request_log_store {
+            let response_for_log = json!({
+                "stream": true,
+                "choices": [{ "message": { "role": "assistant", "content": text } }],
+            });
+            store.with_current(|log| {
+                log.llm_response_json = Some(response_for_log);
+            });
+        }
+
+        Ok(text)
+    }
+
+    fn name(&self) -> &'static str {
+        "openai-compatible"
+    }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(base_url: &str) -> OpenAiCompatibleLlmConfig {
+        OpenAiCompatibleLlmConfig {
+            base_url: base_url.to_string(),
+            model: Some("local-model".to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_provider_creation() {
+        let provider = OpenAiCompatibleLlmProvider::new(&config("http://localhost:1234/v1/"), None);
+        assert_eq!(provider.name(), "openai-compatible");
+        assert_eq!(provider.model(), "local-model");
+        assert_eq!(provider.endpoint, "http://localhost:1234/v1/chat/completions");
+        assert!(provider.api_key.is_none());
+    }
+
+    #[test]
+    fn test_full_endpoint_url_accepted() {
+        let provider = OpenAiCompatibleLlmProvider::new(
+            &config("http://127.0.0.1:8080/v1/chat/completions"),
+            None,
+        );
+        assert_eq!(provider.endpoint, "http://127.0.0.1:8080/v1/chat/completions");
+    }
+
+    #[test]
+    fn test_model_override_precedence() {
+        let provider = OpenAiCompatibleLlmProvider::new(
+            &config("http://localhost:1234/v1"),
+            Some("qwen2.5-7b-instruct".to_string()),
+        );
+        assert_eq!(provider.model(), "qwen2.5-7b-instruct");
+    }
+
+    #[test]
+    fn test_invalid_headers_skipped() {
+        let mut cfg = config("https://openrouter.ai/api/v1");
+        cfg.api_key = Some("  ".to_string());
+        cfg.headers.insert("X-Title".to_string(), "Tangerine".to_string());
+        cfg.headers.insert("Bad Header".to_string(), "value".to_string());
+
+        let provider = OpenAiCompatibleLlmProvider::new(&cfg, None);
+        assert!(provider.api_key.is_none());
+        assert_eq!(provider.headers.len(), 1);
+        assert_eq!(provider.headers["x-title"], "Tangerine");
+    }
+
+    #[test]
+    fn test_missing_settings_rejected() {
+        let provider = OpenAiCompatibleLlmProvider::new(&config("  "), None);
+        assert!(matches!(
+            provider.build_request("system", "user", false),
+            Err(LlmError::ProviderNotAvailable(_))
+        ));
+
+        let mut cfg = config("http://localhost:1234/v1");
+        cfg.model = None;
+        let provider = OpenAiCompatibleLlmProvider::new(&cfg, None);
+        assert!(matches!(
+            provider.build_request("system", "user", false),
+            Err(LlmError::ProviderNotAvailable(_))
+        ));
+    }
+}
@@ -0,0 +1,520 @@
+//! Streaming completions.
+//!
+//! Cloud providers stream Server-Sent Events (`data: {json}` blocks separated by blank lines);
+//! Ollama streams newline-delimited JSON. Both are decoded here so each provider only has to
+//! pick the text delta out of its own event shape.
+//!
+//! Structured-output responses arrive as a JSON document (`{"rewritten_text": "..."}`), so
+//! `JsonStringFieldDecoder` unwraps the string value as it is generated.
+
+use super::LlmError;
+use futures_util::StreamExt;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+/// Sending half of an LLM text delta channel.
+pub type LlmDeltaSender = mpsc::UnboundedSender<String>;
+
+/// Receiving half of an LLM text delta channel.
+pub type LlmDeltaReceiver = mpsc::UnboundedReceiver<String>;
+
+/// Create a channel for the text deltas of a streaming completion.
+pub fn llm_delta_channel() -> (LlmDeltaSender, LlmDeltaReceiver) {
+    mpsc::unbounded_channel()
+}
+
+/// Map a request/body error, reporting timeouts as `LlmError::Timeout` when one was configured.
+pub(crate) fn request_error(error: reqwest::Error, timeout: Option<Duration>) -> LlmError {
+    match timeout {
+        Some(timeout) if error.is_timeout() => LlmError::Timeout(timeout),
+        _ => LlmError::Network(error),
+    }
+}
+
+/// Splits a byte stream into lines (`\n` or `\r\n`), tolerating chunks that end mid-line or
+/// mid-UTF-8 sequence.
+#[derive(Debug, Default)]
+struct LineDecoder {
+    buffer: Vec<u8>,
+}
+
+impl LineDecoder {
+    fn push(&mut self, chunk: &[u8]) -> Vec<String> {
+        self.buffer.extend_from_slice(chunk);
+
+        let mut lines = Vec::new();
+        while let Some(pos) = self.buffer.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=pos).collect();
+            let line = String::from_utf8_lossy(&line[..pos]);
+            lines.push(line.strip_suffix('\r').unwrap_or(&line).to_string());
+        }
+        lines
+    }
+
+    /// The unterminated last line, if any.
+    fn finish(&mut self) -> Option<String> {
+        let rest = std::mem::take(&mut self.buffer);
+        let rest = String::from_utf8_lossy(&rest);
+        let rest = rest.strip_suffix('\r').unwrap_or(&rest);
+        (!rest.is_empty()).then(|| rest.to_string())
+    }
+}
+
+/// One Server-Sent Event.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SseEvent {
+    /// `event:` field (Anthropic and the OpenAI Responses API name their events).
+    pub event: Option<String>,
+    /// `data:` lines joined with newlines.
+    pub data: String,
+}
+
+/// Incremental Server-Sent Events decoder.
+#[derive(Debug, Default)]
+pub struct SseDecoder {
+    lines: LineDecoder,
+    event: Option<String>,
+    data: Vec<String>,
+}
+
+impl SseDecoder {
+    /// Feed a chunk of the response body, returning the events it completed.
+    pub fn push(&mut self, chunk: &[u8]) -> Vec<SseEvent> {
+        let lines = self.lines.push(chunk);
+        lines.into_iter().filter_map(|line| self.line(&line)).collect()
+    }
+
+    /// Flush the last event when the body ends without a trailing blank line.
+    pub fn finish(&mut self) -> Option<SseEvent> {
+        let last = self.lines.finish().and_then(|line| self.line(&line));
+        last.or_else(|| self.dispatch())
+    }
+
+    fn line(&mut self, line: &str) -> Option<SseEvent> {
+        if line.is_empty() {
+            return self.dispatch();
+        }
+        // Comment (keep-alive).
+        if line.starts_with(':') {
+            return None;
+        }
+
+        let (field, value) = line.split_once(':').unwrap_or((line, ""));
+        let value = value.strip_prefix(' ').unwrap_or(value);
+        match field {
+            "event" => self.event = Some(value.to_string()),
+            "data" => self.data.push(value.to_string()),
+            _ => {}
+        }
+        None
+    }
+
+    fn dispatch(&mut self) -> Option<SseEvent> {
+        let event = self.event.take();
+        if self.data.is_empty() {
+            return None;
+        }
+        Some(SseEvent {
+            event,
+            data: std::mem::take(&mut self.data).join("\n"),
+        })
+    }
+}
+
+/// Whether a stream handler wants more events.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamControl {
+    Continue,
+    Stop,
+}
+
+/// Read an SSE response body, calling `on_event` for every event until it returns `Stop` or
+/// the body ends.
+pub async fn read_sse_events<F>(
+    response: reqwest::Response,
+    timeout: Option<Duration>,
+    mut on_event: F,
+) -> Result<(), LlmError>
+where
+    F: FnMut(SseEvent) -> Result<StreamControl, LlmError>,
+{
+    let mut decoder = SseDecoder::default();
+    let mut body = response.bytes_stream();
+
+    while let Some(chunk) = body.next().await {
+        let chunk = chunk.map_err(|e| request_error(e, timeout))?;
+        for event in decoder.push(&chunk) {
+            if on_event(event)? == StreamControl::Stop {
+                return Ok(());
+            }
+        }
+    }
+
+    if let Some(event) = decoder.finish() {
+        on_event(event)?;
+    }
+    Ok(())
+}
+
+/// Read a newline-delimited JSON response body (Ollama), calling `on_value` for every line.
+pub async fn read_json_lines<F>(
+    response: reqwest::Response,
+    timeout: Option<Duration>,
+    mut on_value: F,
+) -> Result<(), LlmError>
+where
+    F: FnMut(serde_json::Value) -> Result<StreamControl, LlmError>,
+{
+    let parse = |line: &str| {
+        serde_json::from_str::<serde_json::Value>(line).map_err(|e| {
+            LlmError::InvalidResponse(format!("Invalid JSON line in stream: {} ({})", e, line))
+        })
+    };
+
+    let mut lines = LineDecoder::default();
+    let mut body = response.bytes_stream();
+
+    while let Some(chunk) = body.next().await {
+        let chunk = chunk.map_err(|e| request_error(e, timeout))?;
+        for line in lines.push(&chunk) {
+            if line.trim().is_empty() {
+                continue;
+            }
+            if on_value(parse(&line)?)? == StreamControl::Stop {
+                return Ok(());
+            }
+        }
+    }
+
+    if let Some(line) = lines.finish().filter(|l| !l.trim().is_empty()) {
+        on_value(parse(&line)?)?;
+    }
+    Ok(())
+}
+
+/// Parse an SSE `data:` payload as JSON.
+pub fn parse_event_json(event: &SseEvent) -> Result<serde_json::Value, LlmError> {
+    serde_json::from_str(&event.data).map_err(|e| {
+        LlmError::InvalidResponse(format!("Invalid JSON event in stream: {} ({})", e, event.data))
+    })
+}
+
+/// Read an OpenAI-compatible Chat Completions stream (`choices[0].delta.content` events,
+/// terminated by `data: [DONE]`), sending each delta and returning the full text.
+pub async fn read_chat_completion_stream(
+    response: reqwest::Response,
+    timeout: Option<Duration>,
+    deltas: &LlmDeltaSender,
+) -> Result<String, LlmError> {
+    let mut text = String::new();
+
+    read_sse_events(response, timeout, |event| {
+        if event.data.trim() == "[DONE]" {
+            return Ok(StreamControl::Stop);
+        }
+
+        let value = parse_event_json(&event)?;
+        if let Some(message) = value.pointer("/error/message").and_then(|m| m.as_str()) {
+            return Err(LlmError::Api(format!("Stream error: {}", message).into()));
+        }
+
+        if let Some(delta) = value
+            .pointer("/choices/0/delta/content")
+            .and_then(|c| c.as_str())
+            .filter(|c| !c.is_empty())
+        {
+            text.push_str(delta);
+            let _ = deltas.send(delta.to_string());
+        }
+        Ok(StreamControl::Continue)
+    })
+    .await?;
+
+    Ok(text)
+}
+
+/// Decodes the value of one string field from a JSON document that arrives in pieces.
+///
+/// Used for structured outputs, where the model streams `{"rewritten_text": "..."}`: the
+/// value is yielded as soon as its characters arrive, with escapes resolved.
+#[derive(Debug)]
+pub struct JsonStringFieldDecoder {
+    key: String,
+    raw: String,
+    /// Byte offset into `raw` up to which input has been consumed.
+    pos: usize,
+    state: FieldState,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FieldState {
+    SeekingKey,
+    InValue,
+    Done,
+}
+
+impl JsonStringFieldDecoder {
+    pub fn new(key: &str) -> Self {
+        Self {
+            key: format!("\"{}\"", key),
+            raw: String::new(),
+            pos: 0,
+            state: FieldState::SeekingKey,
+        }
+    }
+
+    /// Feed more of the document, returning newly decoded characters of the value.
+    pub fn push(&mut self, chunk: &str) -> String {
+        self.raw.push_str(chunk);
+        let mut out = String::new();
+
+        if self.state == FieldState::SeekingKey {
+            let Some(key_at) = self.raw.find(&self.key) else {
+                return out;
+            };
+            let after_key = &self.raw[key_at + self.key.len()..];
+            let after_colon = after_key.trim_start();
+            let Some(after_colon) = after_colon.strip_prefix(':') else {
+                return out;
+            };
+            let value = after_colon.trim_start();
+            if !value.starts_with('"') {
+                return out;
+            }
+            self.pos = self.raw.len() - value.len() + 1;
+            self.state = FieldState::InValue;
+        }
+
+        while self.state == FieldState::InValue {
+            let rest = &self.raw[self.pos..];
+            let mut chars = rest.chars();
+            let Some(c) = chars.next() else {
+                break;
+            };
+
+            match c {
+                '"' => {
+                    self.state = FieldState::Done;
+                    self.pos += 1;
+                }
+                '\\' => match Self::unescape(&rest[1..]) {
+                    Some((decoded, len)) => {
+                        out.push(decoded);
+                        self.pos += 1 + len;
+                    }
+                    // Escape sequence not complete yet.
+                    None => break,
+                },
+                c => {
+                    out.push(c);
+                    self.pos += c.len_utf8();
+                }
+            }
+        }
+
+        out
+    }
+
+    /// Decode the escape after a backslash: (character, bytes consumed). `None` if incomplete.
+    fn unescape(rest: &str) -> Option<(char, usize)> {
+        let c = rest.chars().next()?;
+        let simple = match c {
+            'n' => Some('\n'),
+            't' => Some('\t'),
+            'r' => Some('\r'),
+            'b' => Some('\u{8}'),
+            'f' => Some('\u{c}'),
+            'u' => None,
+            other => Some(other),
+        };
+        if let Some(decoded) = simple {
+            return Some((decoded, c.len_utf8()));
+        }
+
+        let hex = |s: &str| s.get(..4).and_then(|h| u32::from_str_radix(h, 16).ok());
+        let unit = hex(&rest[1..])?;
+        if (0xD800..0xDC00).contains(&unit) {
+            // High surrogate: needs the following `\uXXXX` low surrogate.
+            let low_rest = rest.get(5..)?;
+            let low = hex(low_rest.strip_prefix("\\u")?)?;
+            let code = 0x10000 + ((unit - 0xD800) << 10) + (low.wrapping_sub(0xDC00) & 0x3FF);
+            return Some((char::from_u32(code).unwrap_or('\u{FFFD}'), 11));
+        }
+        Some((char::from_u32(unit).unwrap_or('\u{FFFD}'), 5))
+    }
+}
+
+/// Groups streamed text into sentence-sized chunks for typing/pasting while it arrives.
+///
+/// A chunk ends after sentence punctuation (or a line break) once the next word has started,
+/// so the trailing whitespace belongs to the chunk and "3.5" or "v1.2" is never split.
+/// Leading whitespace of the response is dropped, matching the trimmed final text.
+#[derive(Debug, Default)]
+pub struct SentenceChunker {
+    pending: String,
+    started: bool,
+}
+
+impl SentenceChunker {
+    /// Add a delta, returning any chunks it completed.
+    pub fn push(&mut self, delta: &str) -> Vec<String> {
+        let delta = if self.started {
+            delta
+        } else {
+            let trimmed = delta.trim_start();
+            self.started = !trimmed.is_empty();
+            trimmed
+        };
+        self.pending.push_str(delta);
+
+        let mut chunks = Vec::new();
+        while let Some(end) = Self::sentence_end(&self.pending) {
+            let rest = self.pending.split_off(end);
+            chunks.push(std::mem::replace(&mut self.pending, rest));
+        }
+        chunks
+    }
+
+    /// The remaining text once the stream has ended.
+    pub fn finish(&mut self) -> Option<String> {
+        let rest = std::mem::take(&mut self.pending);
+        let rest = rest.trim_end();
+        (!rest.is_empty()).then(|| rest.to_string())
+    }
+
+    /// Byte offset where the first complete sentence (plus trailing whitespace) ends.
+    fn sentence_end(text: &str) -> Option<usize> {
+        let mut boundary = false;
+        let mut seen_space = false;
+
+        for (i, c) in text.char_indices() {
+            if boundary && seen_space && !c.is_whitespace() {
+                return Some(i);
+            }
+            if c == '\n' {
+                boundary = true;
+                seen_space = true;
+            } else if c.is_whitespace() {
+                seen_space = boundary;
+            } else if matches!(c, '.' | '!' | '?' | '…') {
+                boundary = true;
+                seen_space = false;
+            } else if boundary && !seen_space && matches!(c, '"' | '\'' | ')' | ']' | '”' | '’') {
+                // Closing quote/bracket right after the punctuation belongs to the sentence.
+            } else {
+                boundary = false;
+                seen_space = false;
+            }
+        }
+        None
+    }
+}
+
+/// How to finish output that was partly typed while the LLM streamed it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamedRemainder<'a> {
+    /// The final text continues the streamed text: output the rest of it.
+    Append(&'a str),
+    /// The final text doesn't continue the streamed text (e.g. the rewrite failed mid-stream
+    /// and the pipeline fell back to the raw transcript): delete the `delete_chars` characters
+    /// output so far, then output the whole final text.
+    Replace { delete_chars: usize },
+}
+
+/// Compare `final_text` with the text `streamed` chunk by chunk before it was final.
+pub fn streamed_remainder<'a>(final_text: &'a str, streamed: &str) -> StreamedRemainder<'a> {
+    match final_text.strip_prefix(streamed.trim_end()) {
+        Some(rest) => StreamedRemainder::Append(rest.trim_start()),
+        None => StreamedRemainder::Replace {
+            delete_chars: streamed.chars().count(),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sse_decoder_handles_split_chunks() {
+        let mut decoder = SseDecoder::default();
+        assert!(decoder.push(b"event: content_block_delta\ndata: {\"a\":").is_empty());
+
+        let events = decoder.push(b"1}\r\n\r\n: keep-alive\n\ndata: [DONE]");
+        assert_eq!(
+            events,
+            vec![SseEvent {
+                event: Some("content_block_delta".to_string()),
+                data: "{\"a\":1}".to_string(),
+            }]
+        );
+        assert_eq!(
+            decoder.finish(),
+            Some(SseEvent {
+                event: None,
+                data: "[DONE]".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_line_decoder_keeps_split_utf8() {
+        let mut decoder = LineDecoder::default();
+        let bytes = "{\"content\":\"é\"}\n".as_bytes();
+        let split = bytes.len() - 4;
+        assert!(decoder.push(&bytes[..split]).is_empty());
+        assert_eq!(decoder.push(&bytes[split..]), vec!["{\"content\":\"é\"}"]);
+        assert_eq!(decoder.finish(), None);
+    }
+
+    #[test]
+    fn test_json_string_field_decoder() {
+        let mut decoder = JsonStringFieldDecoder::new("rewritten_text");
+        let pieces = [
+            "{\"rewrit", "ten_text\" : \"Hello", ", \\\"wor", "ld\\", "\"\\n\\u00e9",
+            "\\ud83d", "\\ude00 done\"}",
+        ];
+        let decoded: Vec<String> = pieces.iter().map(|p| decoder.push(p)).collect();
+
+        assert_eq!(
+            decoded,
+            vec!["", "Hello", ", \"wor", "ld", "\"\né", "", "😀 done"]
+        );
+        // Text after the closing quote is ignored.
+        assert_eq!(decoder.push("\"x\""), "");
+    }
+
+    #[test]
+    fn test_sentence_chunker() {
+        let mut chunker = SentenceChunker::default();
+        let mut chunks = Vec::new();
+        for delta in ["\n Version 3", ".5 is out. It", " works!\n\nSee \"the", " notes.\" Thanks"] {
+            chunks.extend(chunker.push(delta));
+        }
+        chunks.extend(chunker.finish());
+
+        assert_eq!(
+            chunks,
+            vec![
+                "Version 3.5 is out. ",
+                "It works!\n\n",
+                "See \"the notes.\" ",
+                "Thanks",
+            ]
+        );
+        assert_eq!(chunks.concat(), "Version 3.5 is out. It works!\n\nSee \"the notes.\" Thanks");
+    }
+
+    #[test]
+    fn test_streamed_remainder() {
+        use StreamedRemainder::*;
+
+        assert_eq!(streamed_remainder("One. Two.", "One. "), Append("Two."));
+        assert_eq!(streamed_remainder("One.", "One. "), Append(""));
+        assert_eq!(streamed_remainder("One.", ""), Append("One."));
+        assert_eq!(
+            streamed_remainder("raw transcript", "Één. "),
+            Replace { delete_chars: 5 }
+        );
+    }
+}
@@ -0,0 +1,253 @@
+//! Template variables in custom prompt sections.
+//!
+//! Custom prompts may contain `{{variable}}` placeholders (whitespace inside the braces is
+//! allowed). They are filled from a `PromptContext` right before the rewrite; values that are
+//! unavailable (no clipboard text, no previous dictation, ...) render as an empty string.
+//! Unknown variables are left untouched and reported by `unknown_prompt_variables`.
+
+use super::PromptSections;
+use serde::Serialize;
+use std::collections::BTreeSet;
+
+/// Variables that prompts may reference.
+pub const PROMPT_VARIABLES: &[&str] = &[
+    "app_name",
+    "window_title",
+    "date",
+    "time",
+    "profile",
+    "user_name",
+    "previous_output",
+    "clipboard",
+];
+
+/// Longer values (clipboard, previous dictation) are cut to this many characters.
+const MAX_VALUE_CHARS: usize = 4000;
+
+/// Values for the prompt variables at rewrite time (None renders as an empty string).
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct PromptContext {
+    /// Foreground executable name without directory or extension (e.g. "slack").
+    pub app_name: Option<String>,
+    pub window_title: Option<String>,
+    pub date: Option<String>,
+    pub time: Option<String>,
+    /// Name of the active program profile ("Default" without one).
+    pub profile: Option<String>,
+    pub user_name: Option<String>,
+    /// Final text of the previous dictation.
+    pub previous_output: Option<String>,
+    pub clipboard: Option<String>,
+}
+
+impl PromptContext {
+    /// Value of a known variable (`None` for unknown variables).
+    fn value(&self, name: &str) -> Option<&str> {
+        let value = match name {
+            "app_name" => &self.app_name,
+            "window_title" => &self.window_title,
+            "date" => &self.date,
+            "time" => &self.time,
+            "profile" => &self.profile,
+            "user_name" => &self.user_name,
+            "previous_output" => &self.previous_output,
+            "clipboard" => &self.clipboard,
+            _ => return None,
+        };
+        Some(value.as_deref().unwrap_or(""))
+    }
+}
+
+/// Errors from validating prompt templates.
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum PromptTemplateError {
+    #[error("Unknown prompt variable(s): {}", .0.join(", "))]
+    UnknownVariables(Vec<String>),
+}
+
+/// Placeholders in `text` as (byte range, variable name).
+///
+/// Only identifiers count as variables, so literal braces such as `{{"a": 1}}` are kept as is.
+fn placeholders(text: &str) -> Vec<(std::ops::Range<usize>, &str)> {
+    let mut found = Vec::new();
+    let mut offset = 0;
+
+    while let Some(start) = text[offset..].find("{{").map(|i| offset + i) {
+        let Some(end) = text[start + 2..].find("}}").map(|i| start + 2 + i) else {
+            break;
+        };
+        let name = text[start + 2..end].trim();
+        if !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            found.push((start..end + 2, name));
+            offset = end + 2;
+        } else {
+            offset = start + 2;
+        }
+    }
+
+    found
+}
+
+/// Fill the placeholders in `text`.
+pub fn render_template(text: &str, context: &PromptContext) -> String {
+    let mut rendered = String::with_capacity(text.len());
+    let mut last = 0;
+
+    for (range, name) in placeholders(text) {
+        if let Some(value) = context.value(name) {
+            rendered.push_str(&text[last..range.start]);
+            rendered.push_str(value);
+            last = range.end;
+        }
+    }
+
+    rendered.push_str(&text[last..]);
+    rendered
+}
+
+fn custom_sections(prompts: &PromptSections) -> impl Iterator<Item = &str> {
+    [
+        prompts.main_custom.as_deref(),
+        prompts.advanced_custom.as_deref(),
+        prompts.dictionary_custom.as_deref(),
+    ]
+    .into_iter()
+    .flatten()
+}
+
+/// Every variable referenced by `text`.
+pub fn template_variables(text: &str) -> BTreeSet<String> {
+    placeholders(text)
+        .into_iter()
+        .map(|(_, name)| name.to_string())
+        .collect()
+}
+
+/// Every variable referenced by the custom prompt sections.
+pub fn prompt_variables(prompts: &PromptSections) -> BTreeSet<String> {
+    custom_sections(prompts).flat_map(template_variables).collect()
+}
+
+/// Referenced variables that are not in `PROMPT_VARIABLES`.
+pub fn unknown_prompt_variables(prompts: &PromptSections) -> Vec<String> {
+    prompt_variables(prompts)
+        .into_iter()
+        .filter(|name| !PROMPT_VARIABLES.contains(&name.as_str()))
+        .collect()
+}
+
+/// Reject prompts that reference unknown variables.
+pub fn validate_prompt_sections(prompts: &PromptSections) -> Result<(), PromptTemplateError> {
+    let unknown = unknown_prompt_variables(prompts);
+    if unknown.is_empty() {
+        Ok(())
+    } else {
+        Err(PromptTemplateError::UnknownVariables(unknown))
+    }
+}
+
+/// The prompts with the placeholders in their custom sections filled.
+pub fn render_prompt_sections(prompts: &PromptSections, context: &PromptContext) -> PromptSections {
+    let render = |section: &Option<String>| section.as_deref().map(|text| render_template(text, context));
+    PromptSections {
+        main_custom: render(&prompts.main_custom),
+        advanced_custom: render(&prompts.advanced_custom),
+        dictionary_custom: render(&prompts.dictionary_custom),
+        ..prompts.clone()
+    }
+}
+
+/// Executable name for `{{app_name}}`: the file name without directory and `.exe`.
+pub fn app_name_from_path(path: &str) -> Option<String> {
+    let file_name = path.rsplit(['\\', '/']).next()?.trim();
+    // `get` rather than indexing: the last four bytes may start inside a multibyte character.
+    let stem_len = file_name.len().checked_sub(4).filter(|&stem_len| {
+        file_name
+            .get(stem_len..)
+            .is_some_and(|ext| ext.eq_ignore_ascii_case(".exe"))
+    });
+    let name = stem_len.map_or(file_name, |stem_len| &file_name[..stem_len]);
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+/// Cut a context value to `MAX_VALUE_CHARS` (clipboard contents can be arbitrarily large).
+pub fn truncate_context_value(value: String) -> String {
+    match value.char_indices().nth(MAX_VALUE_CHARS) {
+        Some((index, _)) => value[..index].to_string(),
+        None => value,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context() -> PromptContext {
+        PromptContext {
+            app_name: Some("slack".to_string()),
+            profile: Some("Chat".to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_render_template() {
+        let rendered = render_template(
+            "Writing in {{app_name}} ({{ profile }}). Clipboard: [{{clipboard}}] {{unknown}}",
+            &context(),
+        );
+        assert_eq!(rendered, "Writing in slack (Chat). Clipboard: [] {{unknown}}");
+    }
+
+    #[test]
+    fn test_literal_braces_are_not_variables() {
+        let text = r#"Reply with {{"ok": true}} or {{ }} for {{app_name}}"#;
+        assert_eq!(
+            render_template(text, &context()),
+            r#"Reply with {{"ok": true}} or {{ }} for slack"#
+        );
+    }
+
+    #[test]
+    fn test_unknown_variables_are_reported() {
+        let prompts = PromptSections {
+            main_custom: Some("Hi {{user_name}} in {{app}}".to_string()),
+            dictionary_custom: Some("{{ todays_date }} {{date}}".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(unknown_prompt_variables(&prompts), vec!["app", "todays_date"]);
+        assert_eq!(
+            validate_prompt_sections(&prompts),
+            Err(PromptTemplateError::UnknownVariables(vec![
+                "app".to_string(),
+                "todays_date".to_string()
+            ]))
+        );
+        assert!(validate_prompt_sections(&PromptSections::default()).is_ok());
+    }
+
+    #[test]
+    fn test_render_prompt_sections_keeps_flags() {
+        let prompts = PromptSections {
+            main_custom: Some("Format for {{app_name}}.".to_string()),
+            advanced_enabled: true,
+            ..Default::default()
+        };
+        let rendered = render_prompt_sections(&prompts, &context());
+        assert_eq!(rendered.main_custom.as_deref(), Some("Format for slack."));
+        assert!(rendered.advanced_enabled);
+        assert!(rendered.advanced_custom.is_none());
+    }
+
+    #[test]
+    fn test_app_name_from_path() {
+        assert_eq!(
+            app_name_from_path(r"C:\Program Files\Slack\Slack.EXE").as_deref(),
+            Some("Slack")
+        );
+        assert_eq!(app_name_from_path("/usr/bin/code").as_deref(), Some("code"));
+        assert_eq!(app_name_from_path("/usr/bin/日本").as_deref(), Some("日本"));
+        assert_eq!(app_name_from_path(r"C:\apps\メモ帳.exe").as_deref(), Some("メモ帳"));
+        assert_eq!(app_name_from_path(r"C:\apps\"), None);
+    }
+}
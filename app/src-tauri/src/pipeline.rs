@@ -15,14 +15,34 @@
 //! - Multiple provider support (OpenAI, Anthropic, Ollama)
 //! - Configurable prompts for dictation cleanup
 
-use crate::audio_capture::{AudioCapture, AudioCaptureDiagnostics, AudioCaptureError, AudioCaptureEvent, AudioEncodeConfig, AudioLevelSnapshot, AudioLevelStats, VadAutoStopConfig};
+use crate::audio_capture::{encode_wav_as, wav_duration_secs, AudioBuffer, AudioCapture, AudioCaptureDiagnostics, AudioCaptureError, AudioCaptureEvent, AudioEncodeConfig, AudioLevelSnapshot, AudioLevelStats, VadAutoStopConfig, WavChunk};
 use crate::llm::{
-    format_text, AnthropicLlmProvider, GeminiLlmProvider, GroqLlmProvider, LlmConfig, LlmError,
-    LlmProvider, OllamaLlmProvider, OpenAiLlmProvider,
+    app_name_from_path, format_text, format_text_stream, llm_delta_channel, prompt_variables,
+    render_prompt_sections, render_template, template_variables, transform_user_message,
+    truncate_context_value, unknown_prompt_variables, AnthropicLlmProvider, GeminiLlmProvider, GroqLlmProvider,
+    GuardrailAction, GuardrailViolation, LlmConfig, LlmDeltaReceiver, LlmDeltaSender, LlmError,
+    LlmProvider, OllamaLlmProvider, OpenAiCompatibleLlmProvider, OpenAiLlmProvider,
+    OutputGuardrail, PromptContext, PromptSections,
 };
-use crate::request_log::RequestLogStore;
-use crate::stt::{AudioFormat, RetryConfig, SttError, SttProvider, SttRegistry, with_retry};
-use std::collections::HashMap;
+use crate::dictionary::{apply_dictionary, entries_for_profile, with_dictionary, DictionaryEntry};
+use crate::dictionary_learning::{
+    is_redictation, propose_corrections, CorrectionSource, DictionarySuggestionStore,
+};
+use crate::history::RequestModelInfo;
+use crate::provider_health::{ProviderHealthConfig, ProviderHealthSnapshot, ProviderHealthTracker, ProviderKind};
+use crate::request_log::{RequestLogScratch, RequestLogStore, SttHedgeLog, SttHedgeWinner};
+use crate::retry::RetryableError;
+use crate::snippets::{expand_snippets, Snippet, SnippetExpansion, SnippetStore};
+use crate::translation::{TranslationConfig, TranslationRoute};
+use crate::stt::{
+    audio_chunk_channel, filter_hallucinations, start_streaming_session, with_retry,
+    AudioChunkReceiver, AudioEncoding, AudioFormat, HallucinationFilterConfig,
+    OpenAiCompatibleSttConfig, RetryConfig, SttError, SttLanguage, SttProvider, SttRegistry,
+    SttStream, Transcript, TranscriptEvent, Vocabulary,
+};
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
 use tokio_util::sync::CancellationToken;
@@ -58,6 +78,17 @@ fn select_profile_for_foreground_app(llm_config: &LlmConfig) -> Option<crate::ll
     None
 }
 
+/// Current clipboard text (the `{{clipboard}}` prompt variable).
+fn read_clipboard_text() -> Option<String> {
+    match arboard::Clipboard::new().and_then(|mut clipboard| clipboard.get_text()) {
+        Ok(text) => Some(text),
+        Err(e) => {
+            log::debug!("Pipeline: Clipboard text unavailable for prompt: {}", e);
+            None
+        }
+    }
+}
+
 fn canonicalize_stt_provider_id(id: &str) -> String {
     match id {
         // Historical UI value
@@ -66,6 +97,12 @@ fn canonicalize_stt_provider_id(id: &str) -> String {
     }
 }
 
+/// Normalize the STT text, returning it alongside the (normalized) detailed transcript.
+fn split_stt_transcript(mut transcript: Transcript) -> (String, Transcript) {
+    transcript.text = normalize_stt_text(std::mem::take(&mut transcript.text));
+    (transcript.text.clone(), transcript)
+}
+
 /// Normalize STT output text.
 ///
 /// Some providers (notably Whisper-based APIs) may include a leading space as a
@@ -92,6 +129,14 @@ const DEFAULT_TRANSCRIPTION_TIMEOUT: Duration = Duration::from_secs(10);
 /// Maximum WAV file size in bytes (50MB) to prevent memory issues
 const MAX_WAV_SIZE_BYTES: usize = 50 * 1024 * 1024;
 
+/// A dictation this soon after the previous one may be a corrected re-dictation of it
+const REDICTATION_WINDOW: Duration = Duration::from_secs(30);
+
+/// Template variables that describe what the user is doing right now. A background retry
+/// renders them empty: they would describe the moment of the retry, not of the dictation.
+const LIVE_TEMPLATE_VARIABLES: [&str; 4] =
+    ["app_name", "window_title", "clipboard", "previous_output"];
+
 /// Default values for the quiet-audio gate.
 ///
 /// Thresholds are in dBFS (decibels relative to full scale, where 0 dBFS is max amplitude).
@@ -159,6 +204,21 @@ pub enum PipelineError {
 
     #[error("Recording too large: {0} bytes exceeds limit of {1} bytes")]
     RecordingTooLarge(usize, usize),
+
+    #[error("Transform failed: {0}")]
+    Transform(String),
+}
+
+impl PipelineError {
+    /// Whether the dictation failed only because the STT provider could not be reached
+    /// (connection errors and timeouts), so the same audio is worth retrying once back online.
+    pub fn is_network_error(&self) -> bool {
+        match self {
+            PipelineError::Stt(e) => e.is_transient(),
+            PipelineError::Timeout(_) => true,
+            _ => false,
+        }
+    }
 }
 
 /// Pipeline state machine
@@ -218,11 +278,31 @@ pub enum LlmOutcome {
     /// LLM step was not attempted (not configured or disabled).
     NotAttempted,
     /// LLM step completed successfully and returned formatted text.
-    Succeeded,
-    /// LLM step timed out and the pipeline fell back to the raw STT transcript.
+    Succeeded {
+        /// Provider that produced the text (a fallback provider when `attempts > 1`).
+        provider: String,
+        model: String,
+        /// Providers tried, including the one that succeeded (1 = the effective provider).
+        attempts: u32,
+    },
+    /// The LLM time budget ran out and the pipeline fell back to the raw STT transcript.
     TimedOut,
-    /// LLM step failed and the pipeline fell back to the raw STT transcript.
+    /// Every LLM provider failed and the pipeline fell back to the raw STT transcript.
     Failed(String),
+    /// The first output failed the guardrail; a retry with the strict prompt passed.
+    SucceededAfterStrictRetry {
+        provider: String,
+        model: String,
+        /// Providers tried, including the one that succeeded.
+        attempts: u32,
+        /// Why the first output was rejected.
+        reason: String,
+    },
+    /// The output failed the guardrail and the pipeline fell back to the raw STT transcript.
+    Rejected {
+        provider: String,
+        reason: String,
+    },
 }
 
 /// Detailed result for a transcription request.
@@ -233,12 +313,22 @@ pub enum LlmOutcome {
 pub struct TranscriptionResult {
     /// Raw transcript as returned from the STT provider (before any LLM formatting).
     pub stt_text: String,
+    /// Segment/word timings, confidences and detected language, when the provider returns them.
+    ///
+    /// Empty (text-only) for providers without detail and for skipped (quiet) recordings.
+    pub stt_transcript: Transcript,
     /// Final output text returned by the pipeline.
     /// If LLM formatting was disabled, this will match `stt_text`.
     /// If LLM formatting failed/timed out, this falls back to `stt_text`.
     pub final_text: String,
     /// Duration of the STT phase (including retries), in milliseconds.
     pub stt_duration_ms: u64,
+    /// STT provider id that produced the transcript (None when STT was skipped).
+    ///
+    /// Differs from the configured provider when the fallback chain was used.
+    pub stt_provider_used: Option<String>,
+    /// STT model override of the provider that produced the transcript (None = provider default).
+    pub stt_model_used: Option<String>,
     /// Duration of the LLM phase (including timeout/fallback), in milliseconds.
     pub llm_duration_ms: Option<u64>,
     /// LLM provider id actually used for this transcription (if the LLM step was attempted).
@@ -253,1672 +343,4603 @@ pub struct TranscriptionResult {
     pub llm_model_used: Option<String>,
     /// Outcome of the LLM phase.
     pub llm_outcome: LlmOutcome,
+    /// Program profile that was active (None = Default, or STT was skipped).
+    pub profile_id: Option<String>,
 }
 
 impl TranscriptionResult {
     pub fn llm_attempted(&self) -> bool {
         !matches!(self.llm_outcome, LlmOutcome::NotAttempted)
     }
-}
-
-/// Configuration for the recording pipeline
-#[derive(Debug, Clone)]
-pub struct PipelineConfig {
-    /// Optional backend input device name (CPAL device name).
-    ///
-    /// When set, recording will attempt to use the first input device whose name
-    /// matches exactly, falling back to the system default if not found.
-    pub input_device_name: Option<String>,
-    /// Maximum recording duration in seconds
-    pub max_duration_secs: f32,
-    /// STT provider to use
-    pub stt_provider: String,
-    /// API key for the STT provider
-    pub stt_api_key: String,
-    /// API keys for all configured STT providers (provider id -> key)
-    pub stt_api_keys: HashMap<String, String>,
-    /// Optional model override for STT
-    pub stt_model: Option<String>,
 
-    /// Optional global transcription prompt.
-    ///
-    /// Applied by STT providers that support prompting (currently OpenAI transcription endpoint models).
-    pub stt_transcription_prompt: Option<String>,
-    /// Retry configuration for STT requests
-    pub retry_config: RetryConfig,
-    /// VAD auto-stop configuration
-    pub vad_config: VadAutoStopConfig,
-    /// Timeout for transcription requests
-    pub transcription_timeout: Duration,
-    /// Maximum recording size in bytes (0 = no limit beyond default)
-    pub max_recording_bytes: usize,
+    /// Providers/models that actually served this request (for history).
+    pub fn models_used(&self) -> RequestModelInfo {
+        let llm_attempted = self.llm_attempted();
+        RequestModelInfo {
+            stt_provider: self.stt_provider_used.clone(),
+            stt_model: self.stt_model_used.clone(),
+            llm_provider: self.llm_provider_used.clone().filter(|_| llm_attempted),
+            llm_model: self.llm_model_used.clone().filter(|_| llm_attempted),
+            profile_id: self.profile_id.clone(),
+        }
+    }
+}
 
-    /// Enable a quiet-audio gate to avoid silent-audio hallucinations.
-    pub quiet_audio_gate_enabled: bool,
-    /// Treat recordings shorter than this as effectively quiet.
-    pub quiet_audio_min_duration_secs: f32,
-    /// RMS threshold (in dBFS) below which the audio is considered quiet.
-    pub quiet_audio_rms_dbfs_threshold: f32,
-    /// Peak threshold (in dBFS) below which the audio is considered quiet.
-    pub quiet_audio_peak_dbfs_threshold: f32,
+/// How saved audio is re-transcribed (see `transcribe_saved_wav`).
+#[derive(Debug, Clone, Copy)]
+enum SavedWavRun<'a> {
+    /// A retry the user asked for: it becomes the current operation (state, cancellation)
+    /// and uses the profile of the app in the foreground.
+    Foreground,
+    /// A queued retry run alongside whatever the user is doing, under the profile the
+    /// dictation was recorded with (None = Default).
+    Background { profile_id: Option<&'a str> },
+}
 
-    /// Optional noise gate threshold (dBFS), applied at stop-time before WAV encoding.
-    ///
-    /// Recommended range: -75..-30. `None` disables the noise gate.
-    pub noise_gate_threshold_dbfs: Option<f32>,
+/// An extra STT provider to try when the ones before it fail.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SttFallbackEntry {
+    /// Provider id (e.g. "openai", "local-whisper").
+    pub provider: String,
+    /// Optional model override (None = provider default).
+    #[serde(default)]
+    pub model: Option<String>,
+}
 
-    // ------------------------------------------------------------------------
-    // Voice pickup (preprocessing) options
-    // ------------------------------------------------------------------------
-    /// Convert captured audio to mono before WAV encoding.
-    pub audio_downmix_to_mono: bool,
-    /// Resample to 16kHz before WAV encoding.
-    pub audio_resample_to_16khz: bool,
-    /// Apply a lightweight high-pass (DC/rumble) filter.
-    pub audio_highpass_enabled: bool,
-    /// Apply a lightweight auto-gain/normalization.
-    pub audio_agc_enabled: bool,
-    /// Apply a lightweight noise suppression.
-    pub audio_noise_suppression_enabled: bool,
+/// Hedged STT requests: if the primary request hasn't answered after `delay_ms`, send the same
+/// audio again (to another provider, or the same one) and use whichever answers first.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SttHedgeConfig {
+    pub enabled: bool,
+    /// How long to wait for the primary before sending the hedge request.
+    pub delay_ms: u64,
+    /// Provider for the hedge request (None = same provider as the primary).
+    pub provider: Option<String>,
+    /// Model override for the hedge request (None = the primary's model when racing the same
+    /// provider, otherwise the provider default).
+    pub model: Option<String>,
+}
 
-    // ------------------------------------------------------------------------
-    // Extra hallucination protection
-    // ------------------------------------------------------------------------
-    /// If enabled, run an offline VAD scan at stop-time and skip STT when no speech is detected.
-    pub quiet_audio_require_speech: bool,
-    /// LLM formatting configuration
-    pub llm_config: LlmConfig,
-    /// API keys for all configured LLM providers (provider id -> key)
-    pub llm_api_keys: HashMap<String, String>,
+impl Default for SttHedgeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            delay_ms: 1500,
+            provider: None,
+            model: None,
+        }
+    }
+}
 
-    /// Optional request log store for capturing provider request/response payloads.
-    pub request_log_store: Option<RequestLogStore>,
-    /// Path to local Whisper model (for local-whisper feature)
-    #[cfg(feature = "local-whisper")]
-    pub whisper_model_path: Option<std::path::PathBuf>,
+/// Long recordings are split at pauses and the chunks transcribed concurrently.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SttChunkingConfig {
+    pub enabled: bool,
+    /// Recordings longer than this are split, into chunks no longer than this.
+    pub max_chunk_secs: f32,
+    /// How many chunks are transcribed at the same time.
+    pub max_concurrency: usize,
 }
 
-impl Default for PipelineConfig {
+impl Default for SttChunkingConfig {
     fn default() -> Self {
         Self {
-            input_device_name: None,
-            max_duration_secs: 300.0, // 5 minutes max
-            stt_provider: "groq".to_string(),
-            stt_api_key: String::new(),
-            stt_api_keys: HashMap::new(),
-            stt_model: None,
-            stt_transcription_prompt: None,
-            retry_config: RetryConfig::default(),
-            vad_config: VadAutoStopConfig::default(),
-            transcription_timeout: DEFAULT_TRANSCRIPTION_TIMEOUT,
-            max_recording_bytes: MAX_WAV_SIZE_BYTES,
+            enabled: true,
+            max_chunk_secs: 60.0,
+            max_concurrency: 4,
+        }
+    }
+}
 
-            quiet_audio_gate_enabled: true,
-            quiet_audio_min_duration_secs: DEFAULT_QUIET_AUDIO_MIN_DURATION_SECS,
-            quiet_audio_rms_dbfs_threshold: DEFAULT_QUIET_AUDIO_RMS_DBFS_THRESHOLD,
-            quiet_audio_peak_dbfs_threshold: DEFAULT_QUIET_AUDIO_PEAK_DBFS_THRESHOLD,
+impl SttChunkingConfig {
+    /// Whether a recording of `duration_secs` is split before transcription.
+    pub fn should_split(&self, duration_secs: f32) -> bool {
+        self.enabled && duration_secs > self.max_chunk_secs.max(1.0)
+    }
+}
 
-            noise_gate_threshold_dbfs: None,
+/// A resolved STT provider and the model override it was created with.
+#[derive(Clone)]
+struct SttCandidate {
+    provider: Arc<dyn SttProvider>,
+    model: Option<String>,
+}
 
-            audio_downmix_to_mono: true,
-            audio_resample_to_16khz: false,
-            audio_highpass_enabled: true,
-            audio_agc_enabled: false,
-            audio_noise_suppression_enabled: false,
+/// The resolved hedge request raced against the primary provider.
+struct SttHedge {
+    candidate: SttCandidate,
+    delay: Duration,
+}
 
-            quiet_audio_require_speech: false,
+/// Ordered STT providers for one transcription: the effective provider first, then fallbacks.
+struct SttFallbackChain {
+    candidates: Vec<SttCandidate>,
+    /// Raced against the first candidate only; fallbacks are not hedged.
+    hedge: Option<SttHedge>,
+    chunking: SttChunkingConfig,
+    /// Upload size limit per chunk when a recording is split (0 = no limit).
+    max_chunk_bytes: usize,
+    /// Compressed encoding sent to providers that accept it (WAV = no compression).
+    upload_encoding: AudioEncoding,
+    hallucination_filter: HallucinationFilterConfig,
+    /// Shared with the pipeline; open circuits move providers to the end of the chain.
+    health: ProviderHealthTracker,
+    /// Use the providers' translate task (English output) instead of plain transcription.
+    translate: bool,
+    request_log_store: Option<RequestLogStore>,
+}
 
-            llm_config: LlmConfig::default(),
-            llm_api_keys: HashMap::new(),
-            request_log_store: None,
-            #[cfg(feature = "local-whisper")]
-            whisper_model_path: None,
+/// Audio for one STT request: the WAV, plus a compressed copy when an upload encoding is
+/// configured. Providers that don't accept the compressed encoding are sent the WAV.
+#[derive(Clone)]
+struct SttUpload {
+    wav: Arc<Vec<u8>>,
+    compressed: Option<(Arc<Vec<u8>>, AudioFormat)>,
+}
+
+impl SttUpload {
+    fn wav(wav_bytes: Vec<u8>) -> Self {
+        Self {
+            wav: Arc::new(wav_bytes),
+            compressed: None,
+        }
+    }
+
+    /// Bytes actually uploaded to providers that accept the compressed copy.
+    fn upload_size(&self) -> usize {
+        self.compressed
+            .as_ref()
+            .map_or(self.wav.len(), |(bytes, _)| bytes.len())
+    }
+
+    fn audio_for(&self, provider: &dyn SttProvider) -> (Arc<Vec<u8>>, AudioFormat) {
+        match &self.compressed {
+            Some((bytes, format)) if provider.accepts_encoding(format.encoding) => {
+                (bytes.clone(), format.clone())
+            }
+            _ => (self.wav.clone(), AudioFormat::default()),
         }
     }
 }
 
-/// Internal state for the recording pipeline
-struct PipelineInner {
-    audio_capture: AudioCapture,
-    stt_registry: SttRegistry,
-    stt_provider_cache: HashMap<String, Arc<dyn SttProvider>>,
-    llm_provider_cache: HashMap<String, Arc<dyn LlmProvider>>,
-    state: PipelineState,
-    config: PipelineConfig,
-    /// Cancellation token for the current operation
-    cancel_token: Option<CancellationToken>,
+/// Run `future` unless `token` is cancelled first.
+async fn cancellable<T>(
+    token: &CancellationToken,
+    future: impl std::future::Future<Output = Result<T, SttError>>,
+) -> Result<T, SttError> {
+    tokio::select! {
+        biased;
 
-    /// Last captured audio (WAV bytes). Used for debugging/testing.
-    last_wav_bytes: Option<Vec<u8>>,
+        _ = token.cancelled() => Err(SttError::Cancelled),
+        result = future => result,
+    }
+}
 
-    /// Last recording diagnostics (raw stats + optional speech detection).
-    last_recording_diagnostics: Option<AudioCaptureDiagnostics>,
+async fn transcribe_with_retry(
+    candidate: &SttCandidate,
+    upload: &SttUpload,
+    retry_config: &RetryConfig,
+    health: &ProviderHealthTracker,
+    translate: bool,
+) -> Result<Transcript, SttError> {
+    let (audio, format) = upload.audio_for(candidate.provider.as_ref());
+    let request = with_retry(retry_config, || {
+        let provider = candidate.provider.clone();
+        let audio = audio.clone();
+        let format = format.clone();
+        async move {
+            if translate {
+                provider.translate_detailed(audio.as_slice(), &format).await
+            } else {
+                provider.transcribe_detailed(audio.as_slice(), &format).await
+            }
+        }
+    });
+    health
+        .track(
+            ProviderKind::Stt,
+            candidate.provider.name(),
+            candidate.model.as_deref(),
+            request,
+        )
+        .await
 }
 
-impl PipelineInner {
-    fn new(config: PipelineConfig) -> Self {
-        let audio_capture = AudioCapture::with_vad_config(config.vad_config.clone());
-        let mut inner = Self {
-            audio_capture,
-            stt_registry: SttRegistry::new(),
-            stt_provider_cache: HashMap::new(),
-            llm_provider_cache: HashMap::new(),
-            state: PipelineState::Idle,
-            config: config.clone(),
-            cancel_token: None,
-            last_wav_bytes: None,
-            last_recording_diagnostics: None,
-        };
-        inner.initialize_providers(&config);
-        inner
-    }
+/// Format `text` with the LLM, retrying transient failures and recording the request's health.
+///
+/// With `deltas`, the response is streamed into it as it is generated.
+async fn format_with_llm(
+    llm: &dyn LlmProvider,
+    text: &str,
+    prompts: &PromptSections,
+    guardrail: &OutputGuardrail,
+    retry_config: &RetryConfig,
+    health: &ProviderHealthTracker,
+    deltas: Option<&LlmDeltaSender>,
+) -> Result<String, LlmError> {
+    let Some(deltas) = deltas else {
+        let request = with_retry(retry_config, || format_text(llm, text, prompts, guardrail));
+        return health
+            .track(ProviderKind::Llm, llm.name(), Some(llm.model()), request)
+            .await;
+    };
 
-    fn get_or_create_stt_provider(
-        &mut self,
-        provider_id: &str,
-        model: Option<String>,
-    ) -> Result<Arc<dyn SttProvider>, PipelineError> {
-        let provider_id = canonicalize_stt_provider_id(provider_id);
-        let model_key = model.clone().unwrap_or_else(|| "<default>".to_string());
-        let cache_key = format!("{}::{}", provider_id, model_key);
+    let started = std::time::Instant::now();
+    let interrupted = std::sync::Mutex::new(None);
+    let request = with_retry(retry_config, || async {
+        let (result, streamed) = relay_llm_deltas(deltas, |attempt_tx| async move {
+            format_text_stream(llm, text, prompts, &attempt_tx, guardrail).await
+        })
+        .await;
+        // Retrying would repeat text that has already been shown (or typed), so a stream that
+        // breaks off after its first delta fails for good.
+        result.map_err(|e| match e {
+            e if streamed && e.is_transient() => {
+                let error = format!("Stream interrupted: {}", e);
+                *interrupted.lock().unwrap() = Some(e.to_string());
+                LlmError::InvalidResponse(error)
+            }
+            e => e,
+        })
+    });
+    let result = health
+        .track(ProviderKind::Llm, llm.name(), Some(llm.model()), request)
+        .await;
+    // The interruption is no longer a transient error once rewrapped, but it is still the
+    // provider's fault.
+    if let Some(error) = interrupted.into_inner().unwrap() {
+        health.record_failure(
+            ProviderKind::Llm,
+            llm.name(),
+            Some(llm.model()),
+            started.elapsed(),
+            error,
+        );
+    }
+    result
+}
 
-        if let Some(p) = self.stt_provider_cache.get(&cache_key) {
-            return Ok(p.clone());
+/// Run `request` with its own delta channel, forwarding everything it streams to `deltas`.
+///
+/// Also returns whether any delta was forwarded.
+async fn relay_llm_deltas<Fut>(
+    deltas: &LlmDeltaSender,
+    request: impl FnOnce(LlmDeltaSender) -> Fut,
+) -> (Result<String, LlmError>, bool)
+where
+    Fut: std::future::Future<Output = Result<String, LlmError>>,
+{
+    let (request_tx, mut request_rx) = llm_delta_channel();
+    let forward = async {
+        let mut streamed = false;
+        while let Some(delta) = request_rx.recv().await {
+            streamed = true;
+            let _ = deltas.send(delta);
         }
+        streamed
+    };
 
-        #[cfg(feature = "local-whisper")]
-        if provider_id == "local-whisper" {
-            if let Some(model_path) = &self.config.whisper_model_path {
-                let provider = crate::stt::LocalWhisperProvider::new(model_path.clone())
-                    .map_err(|e| PipelineError::Config(format!("Local Whisper init failed: {}", e)))?;
-                let provider = Arc::new(provider);
-                self.stt_provider_cache.insert(cache_key, provider.clone());
-                return Ok(provider);
+    tokio::join!(request(request_tx), forward)
+}
+
+/// Ordered LLM providers for one dictation: the effective provider first, then fallbacks.
+struct LlmFallbackChain {
+    providers: Vec<Arc<dyn LlmProvider>>,
+    /// Time allowed for the whole chain (all providers, including retries).
+    budget: Duration,
+    guardrail: OutputGuardrail,
+    health: ProviderHealthTracker,
+    request_log_store: Option<RequestLogStore>,
+}
+
+/// One formatting request passed along the LLM fallback chain.
+#[derive(Clone, Copy)]
+struct LlmRequest<'a> {
+    text: &'a str,
+    prompts: &'a PromptSections,
+    retry_config: &'a RetryConfig,
+    deltas: Option<&'a LlmDeltaSender>,
+    /// When the chain started; its time budget counts from here.
+    started: std::time::Instant,
+}
+
+/// What the LLM fallback chain produced for one dictation.
+struct LlmChainOutput {
+    /// Formatted text (None = use the raw transcript).
+    text: Option<String>,
+    outcome: LlmOutcome,
+    /// Provider that produced `text`, or the last one tried.
+    provider: Arc<dyn LlmProvider>,
+}
+
+impl LlmFallbackChain {
+    fn primary(&self) -> &Arc<dyn LlmProvider> {
+        &self.providers[0]
+    }
+
+    /// Format with each provider in turn until one succeeds.
+    ///
+    /// Every provider gets the full retry policy but only what is left of the time budget, so
+    /// the chain never takes longer than the LLM timeout. A provider that already streamed part
+    /// of its response ends the chain, as another provider would repeat that text.
+    ///
+    /// An output rejected by the guardrail also ends the chain: the raw transcript is used, or
+    /// with `GuardrailAction::RetryStrict` the same provider is asked once more with the strict
+    /// prompt (unless the rejected output was already streamed).
+    async fn format(
+        &self,
+        text: &str,
+        prompts: &PromptSections,
+        retry_config: &RetryConfig,
+        deltas: Option<&LlmDeltaSender>,
+    ) -> LlmChainOutput {
+        let request = LlmRequest {
+            text,
+            prompts,
+            retry_config,
+            deltas,
+            started: std::time::Instant::now(),
+        };
+        let mut errors: Vec<String> = Vec::new();
+        let mut last_error: Option<LlmError> = None;
+        let mut timed_out = false;
+        let mut provider = self.primary().clone();
+
+        for (index, llm) in self.providers.iter().enumerate() {
+            if let Some(error) = &last_error {
+                if self.remaining(&request).is_zero() {
+                    timed_out = true;
+                    break;
+                }
+                log::warn!(
+                    "Pipeline: Falling back to LLM provider '{}' ({})",
+                    llm.name(),
+                    error
+                );
+                self.log_warning(format!("LLM failed ({}); falling back to {}", error, llm.name()));
+            }
+            provider = llm.clone();
+            let attempts = index as u32 + 1;
+
+            match self.attempt(llm, &request).await {
+                Some((Ok(formatted), _)) => {
+                    return LlmChainOutput {
+                        text: Some(formatted),
+                        outcome: LlmOutcome::Succeeded {
+                            provider: llm.name().to_string(),
+                            model: llm.model().to_string(),
+                            attempts,
+                        },
+                        provider,
+                    };
+                }
+                Some((Err(LlmError::Rejected(violation)), streamed)) => {
+                    let retry = self.guardrail.action == GuardrailAction::RetryStrict && !streamed;
+                    return self
+                        .handle_rejection(llm, violation, retry, attempts, &request)
+                        .await;
+                }
+                Some((Err(e), streamed)) => {
+                    errors.push(format!("{}: {}", llm.name(), e));
+                    last_error = Some(e);
+                    // Another provider would repeat text that has already been shown (or typed).
+                    if streamed {
+                        break;
+                    }
+                }
+                None => {
+                    timed_out = true;
+                    break;
+                }
             }
+        }
 
-            return Err(PipelineError::Config(
-                "Local Whisper selected but no model path configured".to_string(),
-            ));
+        let outcome = if timed_out {
+            self.timed_out()
+        } else {
+            // Name each provider once fallbacks were tried.
+            let message = match last_error {
+                Some(_) if errors.len() > 1 => errors.join("; "),
+                Some(e) => e.to_string(),
+                None => "No LLM provider available".to_string(),
+            };
+            log::warn!("Pipeline: LLM formatting failed ({}), using raw transcript", message);
+            LlmOutcome::Failed(message)
+        };
+
+        LlmChainOutput {
+            text: None,
+            outcome,
+            provider,
         }
+    }
 
-        let api_key = self
-            .config
-            .stt_api_keys
-            .get(&provider_id)
-            .cloned()
-            .unwrap_or_default();
+    /// Decide what to do with an output rejected by the guardrail.
+    async fn handle_rejection(
+        &self,
+        llm: &Arc<dyn LlmProvider>,
+        violation: GuardrailViolation,
+        retry: bool,
+        attempts: u32,
+        request: &LlmRequest<'_>,
+    ) -> LlmChainOutput {
+        let reason = violation.to_string();
+        let rejected = |reason: String| LlmChainOutput {
+            text: None,
+            outcome: LlmOutcome::Rejected {
+                provider: llm.name().to_string(),
+                reason,
+            },
+            provider: llm.clone(),
+        };
 
-        if api_key.is_empty() {
-            return Err(PipelineError::Config(format!(
-                "STT provider '{}' requires an API key",
-                provider_id
-            )));
+        if !retry {
+            log::warn!("Pipeline: LLM output rejected ({}), using raw transcript", reason);
+            self.log_warning(format!("LLM output rejected ({}); using raw transcript", reason));
+            return rejected(reason);
         }
 
-        let provider: Arc<dyn SttProvider> = match provider_id.as_str() {
-            "openai" => Arc::new(
-                crate::stt::OpenAiSttProvider::new(
-                    api_key,
-                    model,
-                    self.config.stt_transcription_prompt.clone(),
-                )
-                .with_request_log_store(self.config.request_log_store.clone()),
-            ),
-            "groq" => Arc::new(
-                crate::stt::GroqSttProvider::new(
-                    api_key,
-                    model,
-                    self.config.stt_transcription_prompt.clone(),
-                )
-                .with_request_log_store(self.config.request_log_store.clone()),
-            ),
-            "deepgram" => Arc::new(
-                crate::stt::DeepgramSttProvider::new(api_key, model)
-                    .with_request_log_store(self.config.request_log_store.clone()),
-            ),
-            other => {
-                return Err(PipelineError::Config(format!(
-                    "Unknown STT provider: {}",
-                    other
-                )))
+        log::warn!("Pipeline: LLM output rejected ({}), retrying with strict prompt", reason);
+        self.log_warning(format!("LLM output rejected ({}); retrying with strict prompt", reason));
+
+        let strict = request.prompts.strict();
+        let strict_request = LlmRequest {
+            prompts: &strict,
+            ..*request
+        };
+        match self.attempt(llm, &strict_request).await {
+            Some((Ok(formatted), _)) => LlmChainOutput {
+                text: Some(formatted),
+                outcome: LlmOutcome::SucceededAfterStrictRetry {
+                    provider: llm.name().to_string(),
+                    model: llm.model().to_string(),
+                    attempts,
+                    reason,
+                },
+                provider: llm.clone(),
+            },
+            Some((Err(e), _)) => {
+                let reason = format!("{}; strict retry: {}", reason, e);
+                log::warn!("Pipeline: LLM strict retry failed ({}), using raw transcript", reason);
+                self.log_warning(format!("LLM strict retry failed ({}); using raw transcript", e));
+                rejected(reason)
+            }
+            None => LlmChainOutput {
+                text: None,
+                outcome: self.timed_out(),
+                provider: llm.clone(),
+            },
+        }
+    }
+
+    /// What is left of the time budget for `request`.
+    fn remaining(&self, request: &LlmRequest<'_>) -> Duration {
+        self.budget.saturating_sub(request.started.elapsed())
+    }
+
+    /// One provider's turn (including retries), or `None` once the time budget runs out.
+    ///
+    /// Also returns whether any of the response was streamed.
+    async fn attempt(
+        &self,
+        llm: &Arc<dyn LlmProvider>,
+        request: &LlmRequest<'_>,
+    ) -> Option<(Result<String, LlmError>, bool)> {
+        let LlmRequest {
+            text,
+            prompts,
+            retry_config,
+            deltas,
+            ..
+        } = *request;
+        let remaining = self.remaining(request);
+        let guardrail = &self.guardrail;
+        let request = async {
+            match deltas {
+                Some(deltas) => {
+                    relay_llm_deltas(deltas, |provider_tx| async move {
+                        format_with_llm(llm.as_ref(), text, prompts, guardrail, retry_config, &self.health, Some(&provider_tx))
+                            .await
+                    })
+                    .await
+                }
+                None => (
+                    format_with_llm(llm.as_ref(), text, prompts, guardrail, retry_config, &self.health, None).await,
+                    false,
+                ),
             }
         };
 
-        self.stt_provider_cache.insert(cache_key, provider.clone());
-        Ok(provider)
+        tokio::time::timeout(remaining, request).await.ok()
     }
 
-    fn get_or_create_llm_provider(
-        &mut self,
-        provider_id: &str,
-        model: Option<String>,
-        timeout: Duration,
-        ollama_url: Option<String>,
-    ) -> Result<Arc<dyn LlmProvider>, PipelineError> {
-        let model_key = model.clone().unwrap_or_else(|| "<default>".to_string());
-        let url_key = ollama_url
-            .clone()
-            .unwrap_or_else(|| "<default-url>".to_string());
-        let cache_key = format!(
-            "{}::{}::{}::{}",
-            provider_id,
-            model_key,
-            timeout.as_secs_f64(),
-            url_key
+    fn timed_out(&self) -> LlmOutcome {
+        log::warn!(
+            "Pipeline: LLM formatting timed out after {:?}, using raw transcript",
+            self.budget
         );
+        LlmOutcome::TimedOut
+    }
 
-        if let Some(p) = self.llm_provider_cache.get(&cache_key) {
-            return Ok(p.clone());
+    fn log_warning(&self, message: String) {
+        if let Some(store) = &self.request_log_store {
+            store.with_current(|log| log.warn(message));
         }
+    }
+}
 
-        let api_key = if provider_id == "ollama" {
-            String::new()
-        } else {
-            self.config
-                .llm_api_keys
-                .get(provider_id)
-                .cloned()
-                .unwrap_or_default()
-        };
+/// LLM prompts for one dictation, with the translation section added when the LLM translates.
+///
+/// With rewriting disabled the LLM only translates (no formatting rules).
+fn translation_prompts(
+    prompts: PromptSections,
+    rewrite_enabled: bool,
+    route: TranslationRoute,
+    translation: &TranslationConfig,
+) -> PromptSections {
+    if route != TranslationRoute::Llm {
+        return prompts;
+    }
 
-        if provider_id != "ollama" && api_key.is_empty() {
-            return Err(PipelineError::Config(format!(
-                "LLM provider '{}' requires an API key",
-                provider_id
-            )));
+    let section = translation.prompt_section();
+    if rewrite_enabled {
+        PromptSections {
+            translation: Some(section),
+            ..prompts
         }
-
-        // Preserve global LLM config (including provider-specific knobs) but override the
-        // effective provider/model/timeout for this transcription.
-        let mut cfg = self.config.llm_config.clone();
-        cfg.enabled = true;
-        cfg.provider = provider_id.to_string();
-        cfg.api_key = api_key;
-        cfg.model = model;
-        cfg.ollama_url = ollama_url;
-        cfg.timeout = timeout;
-
-        let provider = create_llm_provider(&cfg, self.config.request_log_store.clone());
-        self.llm_provider_cache.insert(cache_key, provider.clone());
-        Ok(provider)
+    } else {
+        PromptSections::translation_only(section)
     }
+}
 
-    fn initialize_providers(&mut self, config: &PipelineConfig) {
-        // Clear caches on any config update.
-        self.stt_provider_cache.clear();
-        self.llm_provider_cache.clear();
+impl SttFallbackChain {
+    fn primary(&self) -> &SttCandidate {
+        &self.candidates[0]
+    }
 
-        // Initialize STT providers
-        self.stt_registry = SttRegistry::new();
-        let canonical = canonicalize_stt_provider_id(&config.stt_provider);
-        match self.get_or_create_stt_provider(&canonical, config.stt_model.clone()) {
-            Ok(provider) => {
-                self.stt_registry.register(&canonical, provider);
-                let _ = self.stt_registry.set_current(&canonical);
-            }
-            Err(e) => {
-                log::warn!(
-                    "Pipeline: Default STT provider '{}' not initialized: {}",
-                    canonical,
-                    e
-                );
+    /// Race the primary against a hedge request sent after `hedge.delay`.
+    ///
+    /// The first successful transcript wins and the other request is cancelled through its
+    /// child token. A failed request lets the other one finish; if the primary fails before the
+    /// hedge was sent, the error is returned right away (the fallback chain takes over).
+    ///
+    /// Each request logs into its own scratch log; only the winner's STT payloads end up in the
+    /// request log.
+    async fn transcribe_hedged<'a>(
+        &'a self,
+        primary: &'a SttCandidate,
+        hedge: &'a SttHedge,
+        upload: &SttUpload,
+        retry_config: &RetryConfig,
+        cancel_token: &CancellationToken,
+    ) -> Result<(Transcript, &'a SttCandidate), SttError> {
+        let started = std::time::Instant::now();
+        let primary_token = cancel_token.child_token();
+        let hedge_token = cancel_token.child_token();
+        let primary_log = RequestLogScratch::default();
+        let hedge_log = RequestLogScratch::default();
+
+        let primary_future = primary_log.scope(cancellable(
+            &primary_token,
+            transcribe_with_retry(primary, upload, retry_config, &self.health, self.translate),
+        ));
+        let hedge_future = async {
+            tokio::time::sleep(hedge.delay).await;
+            log::info!(
+                "Pipeline: STT primary slower than {:?}, sending hedge request to '{}'",
+                hedge.delay,
+                hedge.candidate.provider.name()
+            );
+            hedge_log
+                .scope(cancellable(
+                    &hedge_token,
+                    transcribe_with_retry(&hedge.candidate, upload, retry_config, &self.health, self.translate),
+                ))
+                .await
+        };
+        tokio::pin!(primary_future, hedge_future);
+
+        // Set when the primary failed after the hedge was sent.
+        let mut primary_error: Option<SttError> = None;
+        let mut hedge_failed = false;
+
+        let (result, winner) = loop {
+            tokio::select! {
+                result = &mut primary_future, if primary_error.is_none() => match result {
+                    Ok(transcript) => {
+                        hedge_token.cancel();
+                        let latency = started.elapsed();
+                        self.record_hedge(SttHedgeLog {
+                            winner: SttHedgeWinner::Primary,
+                            provider: primary.provider.name().to_string(),
+                            model: primary.model.clone(),
+                            hedge_delay_ms: hedge.delay.as_millis() as u64,
+                            hedge_sent: latency >= hedge.delay,
+                            latency_ms: latency.as_millis() as u64,
+                        });
+                        break (Ok((transcript, primary)), SttHedgeWinner::Primary);
+                    }
+                    Err(e) => {
+                        if hedge_failed || started.elapsed() < hedge.delay {
+                            hedge_token.cancel();
+                            break (Err(e), SttHedgeWinner::Primary);
+                        }
+                        primary_error = Some(e);
+                    }
+                },
+
+                result = &mut hedge_future, if !hedge_failed => match result {
+                    Ok(transcript) => {
+                        primary_token.cancel();
+                        self.record_hedge(SttHedgeLog {
+                            winner: SttHedgeWinner::Hedge,
+                            provider: hedge.candidate.provider.name().to_string(),
+                            model: hedge.candidate.model.clone(),
+                            hedge_delay_ms: hedge.delay.as_millis() as u64,
+                            hedge_sent: true,
+                            latency_ms: started.elapsed().as_millis() as u64,
+                        });
+                        break (Ok((transcript, &hedge.candidate)), SttHedgeWinner::Hedge);
+                    }
+                    Err(e) => {
+                        log::warn!("Pipeline: STT hedge request failed ({})", e);
+                        if let Some(primary_error) = primary_error.take() {
+                            break (Err(primary_error), SttHedgeWinner::Primary);
+                        }
+                        hedge_failed = true;
+                    }
+                },
             }
-        }
-
-        // Note: LLM providers are created on-demand per transcription based on the active profile.
-    }
+        };
 
-    /// Reset to idle state, clearing any error condition
-    fn reset_to_idle(&mut self) {
-        self.state = PipelineState::Idle;
-        self.cancel_token = None;
+        if let Some(store) = &self.request_log_store {
+            store.merge_scratch(&primary_log, winner == SttHedgeWinner::Primary);
+            store.merge_scratch(&hedge_log, winner == SttHedgeWinner::Hedge);
+        }
+        result
     }
 
-    /// Transition to error state
-    fn set_error(&mut self, msg: &str) {
-        log::error!("Pipeline error: {}", msg);
-        self.state = PipelineState::Error;
-        self.cancel_token = None;
-    }
-}
+    /// Batch-transcribe a whole recording, splitting it at pauses first when it's long.
+    ///
+    /// Chunks are transcribed concurrently (each with retries, hedging and fallback) and
+    /// stitched back together in order. The first chunk's provider is reported as the one used.
+    /// With an upload encoding configured, each chunk is also compressed before it is sent.
+    async fn transcribe_recording(
+        &self,
+        wav_bytes: Vec<u8>,
+        retry_config: &RetryConfig,
+        timeout: Duration,
+        cancel_token: &CancellationToken,
+    ) -> Result<(Transcript, &SttCandidate), PipelineError> {
+        let duration_secs = wav_duration_secs(&wav_bytes).unwrap_or(0.0);
+        let raw_size = wav_bytes.len();
+        let split = self.chunking.should_split(duration_secs);
+        let upload_encoding = self.upload_encoding;
+
+        if !split && matches!(upload_encoding, AudioEncoding::Wav) {
+            self.record_uploads(raw_size, &[]);
+            return self
+                .transcribe(&SttUpload::wav(wav_bytes), retry_config, timeout, cancel_token)
+                .await;
+        }
 
-/// Create an LLM provider based on configuration
-fn create_llm_provider(
-    config: &LlmConfig,
-    request_log_store: Option<RequestLogStore>,
-) -> Arc<dyn LlmProvider> {
-    match config.provider.as_str() {
-        "anthropic" => {
-            let provider = if let Some(model) = &config.model {
-                AnthropicLlmProvider::with_model(config.api_key.clone(), model.clone())
+        // Splitting and compressing are CPU-bound; keep them off the async runtime.
+        let max_chunk_secs = self.chunking.max_chunk_secs;
+        let prepared = tokio::task::spawn_blocking(move || {
+            let chunks = if split {
+                AudioBuffer::from_wav_bytes(&wav_bytes)?.split_at_pauses(max_chunk_secs)?
             } else {
-                AnthropicLlmProvider::new(config.api_key.clone())
+                vec![WavChunk {
+                    wav_bytes,
+                    start_secs: 0.0,
+                }]
             };
-            Arc::new(
-                provider
-                    .with_timeout(config.timeout)
-                    .with_request_log_store(request_log_store.clone())
-                    .with_thinking_budget(config.anthropic_thinking_budget),
+            Ok::<_, AudioCaptureError>(
+                chunks
+                    .into_iter()
+                    .map(|chunk| {
+                        let compressed = (!matches!(upload_encoding, AudioEncoding::Wav))
+                            .then(|| encode_wav_as(&chunk.wav_bytes, upload_encoding));
+                        (chunk, compressed)
+                    })
+                    .collect::<Vec<_>>(),
             )
+        })
+        .await
+        .map_err(|e| PipelineError::Config(format!("Audio chunking task failed: {}", e)))??;
+
+        let mut chunks = Vec::with_capacity(prepared.len());
+        for (chunk, compressed) in prepared {
+            let mut upload = SttUpload::wav(chunk.wav_bytes);
+            match compressed {
+                Some(Ok(encoded)) => {
+                    upload.compressed = Some((Arc::new(encoded.bytes), encoded.format));
+                }
+                Some(Err(e)) => {
+                    log::warn!("Pipeline: {:?} encoding failed, uploading WAV ({})", upload_encoding, e);
+                    if let Some(store) = &self.request_log_store {
+                        let message = format!("Audio compression failed ({}); uploading WAV", e);
+                        store.with_current(|log| log.warn(message));
+                    }
+                }
+                None => {}
+            }
+            chunks.push((chunk.start_secs, upload));
         }
-        "groq" => {
-            let provider = if let Some(model) = &config.model {
-                GroqLlmProvider::with_model(config.api_key.clone(), model.clone())
-            } else {
-                GroqLlmProvider::new(config.api_key.clone())
-            };
-            Arc::new(
-                provider
-                    .with_timeout(config.timeout)
-                    .with_request_log_store(request_log_store.clone()),
-            )
+        self.record_uploads(raw_size, &chunks);
+
+        if !split {
+            let (_, upload) = &chunks[0];
+            return self.transcribe(upload, retry_config, timeout, cancel_token).await;
         }
-        "gemini" => {
-            let provider = if let Some(model) = &config.model {
-                GeminiLlmProvider::with_model(config.api_key.clone(), model.clone())
-            } else {
-                GeminiLlmProvider::new(config.api_key.clone())
-            };
 
-            Arc::new(
-                provider
-                    .with_timeout(config.timeout)
-                    .with_request_log_store(request_log_store.clone())
-                    .with_thinking_budget(config.gemini_thinking_budget)
-                    .with_thinking_level(config.gemini_thinking_level.clone()),
-            )
+        // The limit applies to what is sent: the compressed copy when the primary accepts it.
+        if self.max_chunk_bytes > 0 {
+            let primary = self.primary().provider.as_ref();
+            if let Some(size) = chunks
+                .iter()
+                .map(|(_, upload)| upload.audio_for(primary).0.len())
+                .find(|size| *size > self.max_chunk_bytes)
+            {
+                return Err(PipelineError::RecordingTooLarge(size, self.max_chunk_bytes));
+            }
         }
-        "ollama" => {
-            let provider = OllamaLlmProvider::with_url(
-                config
-                    .ollama_url
-                    .clone()
-                    .unwrap_or_else(|| "http://localhost:11434".to_string()),
-                config.model.clone(),
+
+        log::info!(
+            "Pipeline: Transcribing {:.1}s recording in {} chunks",
+            duration_secs,
+            chunks.len()
+        );
+        if let Some(store) = &self.request_log_store {
+            let message = format!(
+                "Long recording ({:.1}s) split into {} chunks at pauses",
+                duration_secs,
+                chunks.len()
             );
-            Arc::new(
-                provider
-                    .with_timeout(config.timeout)
-                    .with_request_log_store(request_log_store.clone()),
-            )
+            store.with_current(|log| log.info(message));
         }
-        _ => {
-            // Default to OpenAI
-            let provider = if let Some(model) = &config.model {
-                OpenAiLlmProvider::with_model(config.api_key.clone(), model.clone())
-            } else {
-                OpenAiLlmProvider::new(config.api_key.clone())
-            };
-            Arc::new(
-                provider
-                    .with_timeout(config.timeout)
-                    .with_request_log_store(request_log_store.clone())
-                    .with_reasoning_effort(config.openai_reasoning_effort.clone()),
-            )
+
+        // Each chunk logs into its own scratch log so concurrent chunks don't overwrite each
+        // other's payloads; the logs are merged in chunk order as the chunks finish.
+        let mut results = futures_util::stream::iter(chunks)
+            .map(|(start_secs, upload)| async move {
+                let chunk_log = RequestLogScratch::default();
+                let result = chunk_log
+                    .scope(self.transcribe(&upload, retry_config, timeout, cancel_token))
+                    .await
+                    .map(|(transcript, used)| (start_secs, transcript, used));
+                (chunk_log, result)
+            })
+            .buffered(self.chunking.max_concurrency.max(1))
+            .enumerate();
+
+        // Returning early on an error drops the stream, which cancels the in-flight chunks.
+        let mut parts = Vec::new();
+        let mut used: Option<&SttCandidate> = None;
+        while let Some((index, (chunk_log, result))) = results.next().await {
+            if let Some(store) = &self.request_log_store {
+                // The first chunk's payloads stand for the request, as its provider does.
+                store.merge_scratch(&chunk_log, index == 0);
+            }
+            let (start_secs, transcript, chunk_used) = result?;
+            match used {
+                None => used = Some(chunk_used),
+                Some(first) if first.provider.name() != chunk_used.provider.name() => {
+                    log::warn!(
+                        "Pipeline: Chunk at {:.1}s transcribed by '{}' (first chunk: '{}')",
+                        start_secs,
+                        chunk_used.provider.name(),
+                        first.provider.name()
+                    );
+                }
+                Some(_) => {}
+            }
+            parts.push((start_secs, transcript));
         }
+
+        let used = used.ok_or(PipelineError::NoProvider)?;
+        Ok((Transcript::stitch(parts), used))
     }
-}
 
-/// Thread-safe wrapper for the recording pipeline
-///
-/// Uses standard Mutex to be Send + Sync for Tauri state management.
-/// Provides robust error handling and cancellation support.
-pub struct SharedPipeline {
-    inner: Arc<Mutex<PipelineInner>>,
-    level_meter: crate::audio_capture::SharedAudioLevelMeter,
-    waveform_meter: crate::audio_capture::SharedAudioWaveformMeter,
-}
+    /// Drop likely hallucinations from a finished transcript, logging each drop.
+    fn filter_hallucinations(&self, transcript: Transcript) -> Transcript {
+        let (transcript, drops) = filter_hallucinations(transcript, &self.hallucination_filter);
+        if drops.is_empty() {
+            return transcript;
+        }
 
-impl SharedPipeline {
-    /// Create a new shared pipeline
-    pub fn new(config: PipelineConfig) -> Self {
-        let inner = PipelineInner::new(config);
-        let level_meter = inner.audio_capture.shared_level_meter();
-        let waveform_meter = inner.audio_capture.shared_waveform_meter();
-        Self {
-            inner: Arc::new(Mutex::new(inner)),
-            level_meter,
-            waveform_meter,
+        for drop in &drops {
+            log::info!("Pipeline: Dropped hallucinated STT text {:?} ({:?})", drop.text, drop.reason);
+        }
+        if let Some(store) = &self.request_log_store {
+            store.with_current(|log| {
+                for drop in &drops {
+                    log.warn(format!("Dropped likely hallucination: \"{}\"", drop.text));
+                }
+                log.hallucination_drops.extend(drops);
+            });
         }
+        transcript
     }
 
-    /// Try to read the current state without blocking.
-    ///
-    /// This is useful for UI publishers that should not stall the runtime when
-    /// the pipeline mutex is briefly held (e.g., during start-up).
-    pub fn try_state(&self) -> Option<PipelineState> {
-        self.inner.try_lock().ok().map(|inner| inner.state)
+    /// Record the raw recording size and, when compressing, the total uploaded size.
+    fn record_uploads(&self, raw_size: usize, uploads: &[(f32, SttUpload)]) {
+        let compressed = uploads.iter().any(|(_, upload)| upload.compressed.is_some());
+        let upload_size: usize = uploads.iter().map(|(_, upload)| upload.upload_size()).sum();
+        let encoding = self.upload_encoding;
+
+        if compressed {
+            log::info!(
+                "Pipeline: Compressed {} bytes of WAV to {} bytes ({:?})",
+                raw_size,
+                upload_size,
+                encoding
+            );
+        }
+        if let Some(store) = &self.request_log_store {
+            store.with_current(|log| {
+                log.audio_size_bytes = Some(raw_size);
+                if compressed {
+                    log.audio_upload_encoding = Some(encoding);
+                    log.audio_upload_size_bytes = Some(upload_size);
+                }
+            });
+        }
     }
 
-    /// Get the most recent realtime audio input level snapshot without locking
-    /// the pipeline mutex.
-    #[cfg_attr(not(test), allow(dead_code))]
-    pub fn audio_level_snapshot_fast(&self) -> AudioLevelSnapshot {
-        self.level_meter.snapshot()
+    fn record_hedge(&self, outcome: SttHedgeLog) {
+        log::info!(
+            "Pipeline: STT hedge race won by {:?} ({}) in {}ms",
+            outcome.winner,
+            outcome.provider,
+            outcome.latency_ms
+        );
+        if let Some(store) = &self.request_log_store {
+            store.with_current(|log| {
+                if outcome.winner == SttHedgeWinner::Hedge {
+                    log.info(format!(
+                        "STT hedge request ({}) answered first after {}ms",
+                        outcome.provider, outcome.latency_ms
+                    ));
+                }
+                log.stt_hedge = Some(outcome);
+            });
+        }
     }
 
-    /// Get the most recent realtime waveform min/max buckets without locking the
-    /// pipeline mutex.
-    #[cfg_attr(not(test), allow(dead_code))]
-    pub fn audio_waveform_snapshot_fast(&self) -> crate::audio_capture::AudioWaveformSnapshot {
-        self.waveform_meter.snapshot()
+    fn is_available(&self, candidate: &SttCandidate) -> bool {
+        self.health
+            .is_available(ProviderKind::Stt, candidate.provider.name(), candidate.model.as_deref())
     }
 
-    /// Start recording
+    /// Decide where this dictation is translated, based on the primary provider.
     ///
-    /// Creates a new cancellation token for this recording session.
-    pub fn start_recording(&self) -> Result<(), PipelineError> {
-        let mut inner = self.inner.lock().map_err(|e| PipelineError::Lock(e.to_string()))?;
-
-        // State guard: only allow starting from Idle or Error states
-        if !inner.state.can_start_recording() {
-            return Err(PipelineError::AlreadyRecording);
-        }
-
-        // Create a new cancellation token for this session
-        let cancel_token = CancellationToken::new();
-        inner.cancel_token = Some(cancel_token);
-
-        let max_duration = inner.config.max_duration_secs;
-        // Clone out of the config to avoid borrowing `inner` immutably while calling into
-        // `audio_capture` mutably.
-        let input_device_name = inner.config.input_device_name.clone();
-        match inner
-            .audio_capture
-            .start_with_device_name(max_duration, input_device_name.as_deref())
-        {
-            Ok(()) => {
-                inner.state = PipelineState::Recording;
-                log::info!("Pipeline: Recording started");
-                Ok(())
-            }
-            Err(e) => {
-                inner.set_error(&format!("Failed to start recording: {}", e));
-                Err(PipelineError::AudioCapture(e))
+    /// For `TranslationRoute::Stt` the chain switches to the translate task and drops fallback
+    /// and hedge providers that can't translate, so every result is in the same language.
+    fn route_translation(&mut self, translation: &TranslationConfig) -> TranslationRoute {
+        let route = translation.route(self.primary().provider.supports_translation());
+        if route == TranslationRoute::Stt {
+            self.translate = true;
+            self.candidates.retain(|c| c.provider.supports_translation());
+            if self
+                .hedge
+                .as_ref()
+                .is_some_and(|h| !h.candidate.provider.supports_translation())
+            {
+                self.hedge = None;
             }
         }
+        route
     }
 
-    /// Stop recording and return the raw WAV audio
-    #[cfg_attr(not(test), allow(dead_code))]
-    pub fn stop_recording(&self) -> Result<Vec<u8>, PipelineError> {
-        let mut inner = self.inner.lock().map_err(|e| PipelineError::Lock(e.to_string()))?;
-
-        if !inner.state.can_stop_recording() {
-            return Err(PipelineError::NotRecording);
+    /// Transcribe with each provider in turn until one succeeds.
+    ///
+    /// Every provider gets the full retry policy and its own timeout (shared with the hedge
+    /// request, if any). Cancellation stops the chain immediately; any other failure moves on to
+    /// the next provider. Returns the transcript and the provider that produced it.
+    ///
+    /// Providers whose circuit is open are tried last, with a single attempt and no hedge.
+    async fn transcribe(
+        &self,
+        upload: &SttUpload,
+        retry_config: &RetryConfig,
+        timeout: Duration,
+        cancel_token: &CancellationToken,
+    ) -> Result<(Transcript, &SttCandidate), PipelineError> {
+        let mut last_error = PipelineError::NoProvider;
+
+        let (healthy, unhealthy): (Vec<_>, Vec<_>) =
+            self.candidates.iter().partition(|c| self.is_available(c));
+        for candidate in &unhealthy {
+            log::warn!(
+                "Pipeline: STT provider '{}' circuit open, trying it last",
+                candidate.provider.name()
+            );
         }
-
-        let cfg = AudioEncodeConfig {
-            noise_gate_threshold_dbfs: inner.config.noise_gate_threshold_dbfs,
-            downmix_to_mono: inner.config.audio_downmix_to_mono,
-            resample_to_16khz: inner.config.audio_resample_to_16khz,
-            highpass_enabled: inner.config.audio_highpass_enabled,
-            agc_enabled: inner.config.audio_agc_enabled,
-            noise_suppression_enabled: inner.config.audio_noise_suppression_enabled,
-            detect_speech_presence: inner.config.quiet_audio_require_speech,
+        let single_attempt = RetryConfig {
+            max_retries: 0,
+            ..retry_config.clone()
         };
 
-        match inner.audio_capture.stop_and_get_wav_with_diagnostics(cfg)
-        {
-            Ok((wav_bytes, diagnostics)) => {
-                // Keep a copy for STT testing/debugging UI.
-                inner.last_wav_bytes = Some(wav_bytes.clone());
-                inner.last_recording_diagnostics = Some(diagnostics);
-
-                // Check size limit
-                let max_bytes = inner.config.max_recording_bytes;
-                if max_bytes > 0 && wav_bytes.len() > max_bytes {
-                    inner.set_error(&format!(
-                        "Recording too large: {} bytes",
-                        wav_bytes.len()
-                    ));
-                    return Err(PipelineError::RecordingTooLarge(wav_bytes.len(), max_bytes));
-                }
+        for (index, candidate) in healthy.iter().chain(&unhealthy).copied().enumerate() {
+            let available = index < healthy.len();
+            let retry_config = if available { retry_config } else { &single_attempt };
 
-                inner.reset_to_idle();
-                log::info!(
-                    "Pipeline: Recording stopped, {} bytes captured",
-                    wav_bytes.len()
+            if index > 0 {
+                log::warn!(
+                    "Pipeline: Falling back to STT provider '{}' ({})",
+                    candidate.provider.name(),
+                    last_error
                 );
-                Ok(wav_bytes)
-            }
-            Err(e) => {
-                inner.set_error(&format!("Failed to stop recording: {}", e));
-                Err(PipelineError::AudioCapture(e))
+                if let Some(store) = &self.request_log_store {
+                    let message = format!(
+                        "STT failed ({}); falling back to {}",
+                        last_error,
+                        candidate.provider.name()
+                    );
+                    store.with_current(|log| log.warn(message));
+                }
             }
-        }
-    }
 
-    /// Stop recording and return a before/after pair of WAV bytes.
-    ///
-    /// - before: raw capture with no preprocessing/gates
-    /// - after: capture encoded with the current audio settings
-    ///
-    /// Intended for settings UI A/B testing.
-    #[cfg_attr(not(test), allow(dead_code))]
-    pub fn stop_recording_before_after(&self) -> Result<(Vec<u8>, Vec<u8>), PipelineError> {
-        let mut inner = self.inner.lock().map_err(|e| PipelineError::Lock(e.to_string()))?;
+            let transcription_future = async {
+                match &self.hedge {
+                    Some(hedge)
+                        if std::ptr::eq(candidate, self.primary())
+                            && available
+                            && self.is_available(&hedge.candidate) =>
+                    {
+                        self.transcribe_hedged(candidate, hedge, upload, retry_config, cancel_token)
+                            .await
+                    }
+                    _ => transcribe_with_retry(candidate, upload, retry_config, &self.health, self.translate)
+                        .await
+                        .map(|transcript| (transcript, candidate)),
+                }
+            };
 
-        if !inner.state.can_stop_recording() {
-            return Err(PipelineError::NotRecording);
-        }
+            // Race between transcription, timeout, and cancellation
+            let result = tokio::select! {
+                biased;
 
-        let after_cfg = AudioEncodeConfig {
-            noise_gate_threshold_dbfs: inner.config.noise_gate_threshold_dbfs,
-            downmix_to_mono: inner.config.audio_downmix_to_mono,
-            resample_to_16khz: inner.config.audio_resample_to_16khz,
-            highpass_enabled: inner.config.audio_highpass_enabled,
-            agc_enabled: inner.config.audio_agc_enabled,
-            noise_suppression_enabled: inner.config.audio_noise_suppression_enabled,
-            detect_speech_presence: inner.config.quiet_audio_require_speech,
-        };
+                // Cancellation takes priority
+                _ = cancel_token.cancelled() => {
+                    log::info!("Pipeline: Transcription cancelled");
+                    return Err(PipelineError::Cancelled);
+                }
 
-        match inner.audio_capture.stop_and_get_wav_before_after(after_cfg) {
-            Ok((before_wav, after_wav, diagnostics)) => {
-                // Keep a copy of the processed output for STT test + debugging.
-                inner.last_wav_bytes = Some(after_wav.clone());
-                inner.last_recording_diagnostics = Some(diagnostics);
+                // Timeout
+                _ = tokio::time::sleep(timeout) => {
+                    log::warn!(
+                        "Pipeline: Transcription ({}) timed out after {:?}",
+                        candidate.provider.name(),
+                        timeout
+                    );
+                    self.health.record_failure(
+                        ProviderKind::Stt,
+                        candidate.provider.name(),
+                        candidate.model.as_deref(),
+                        timeout,
+                        format!("Timed out after {:?}", timeout),
+                    );
+                    Err(PipelineError::Timeout(timeout))
+                }
 
-                // Check size limit (both, to avoid surprising huge payloads)
-                let max_bytes = inner.config.max_recording_bytes;
-                if max_bytes > 0 {
-                    if before_wav.len() > max_bytes {
-                        inner.set_error(&format!(
-                            "Recording too large: {} bytes",
-                            before_wav.len()
-                        ));
-                        return Err(PipelineError::RecordingTooLarge(before_wav.len(), max_bytes));
-                    }
-                    if after_wav.len() > max_bytes {
-                        inner.set_error(&format!(
-                            "Recording too large: {} bytes",
-                            after_wav.len()
-                        ));
-                        return Err(PipelineError::RecordingTooLarge(after_wav.len(), max_bytes));
-                    }
+                // Actual transcription
+                result = transcription_future => {
+                    result.map_err(PipelineError::from)
                 }
+            };
 
-                inner.reset_to_idle();
-                Ok((before_wav, after_wav))
-            }
-            Err(e) => {
-                inner.set_error(&format!("Failed to stop recording: {}", e));
-                Err(PipelineError::AudioCapture(e))
+            match result {
+                Ok(answer) => return Ok(answer),
+                Err(PipelineError::Cancelled) => return Err(PipelineError::Cancelled),
+                Err(e) => last_error = e,
             }
         }
+
+        Err(last_error)
     }
+}
 
-    /// Transcribe the last captured audio (WAV bytes) using the current effective STT settings.
+/// Configuration for the recording pipeline
+#[derive(Debug, Clone)]
+pub struct PipelineConfig {
+    /// Optional backend input device name (CPAL device name).
     ///
-    /// This is intended for settings UI testing and debugging.
-    pub async fn transcribe_last_audio_for_profile(
-        &self,
-        profile_id: Option<&str>,
-    ) -> Result<String, PipelineError> {
-        let (wav_bytes, stt_provider, retry_config, cancel_token) = {
-            let mut inner = self
-                .inner
-                .lock()
-                .map_err(|e| PipelineError::Lock(e.to_string()))?;
-
-            let wav_bytes = inner
-                .last_wav_bytes
-                .clone()
-                .ok_or_else(|| {
-                    PipelineError::Config(
-                        "No audio captured yet. Record once to create test audio.".to_string(),
-                    )
-                })?;
+    /// When set, recording will attempt to use the first input device whose name
+    /// matches exactly, falling back to the system default if not found.
+    pub input_device_name: Option<String>,
+    /// Maximum recording duration in seconds
+    pub max_duration_secs: f32,
+    /// STT provider to use
+    pub stt_provider: String,
+    /// API key for the STT provider
+    pub stt_api_key: String,
+    /// API keys for all configured STT providers (provider id -> key)
+    pub stt_api_keys: HashMap<String, String>,
+    /// Optional model override for STT
+    pub stt_model: Option<String>,
 
-            let config = inner.config.clone();
+    /// Optional global transcription prompt.
+    ///
+    /// Applied by STT providers that support prompting (currently OpenAI transcription endpoint models).
+    pub stt_transcription_prompt: Option<String>,
 
-            // Resolve per-profile overrides. Note: program prompt profiles live under llm_config.
-            let profile = profile_id
-                .and_then(|id| {
-                    if id == "default" {
-                        None
-                    } else {
-                        Some(id)
-                    }
-                })
-                .and_then(|id| {
-                    config
-                        .llm_config
-                        .program_prompt_profiles
-                        .iter()
-                        .find(|p| p.id == id)
-                        .cloned()
-                });
+    /// Endpoint settings for the "openai-compatible" STT provider (self-hosted servers).
+    pub stt_openai_compatible: OpenAiCompatibleSttConfig,
 
-            let desired_stt_provider = canonicalize_stt_provider_id(
-                profile
-                    .as_ref()
-                    .and_then(|p| p.stt_provider.as_deref())
-                    .unwrap_or(config.stt_provider.as_str()),
-            );
-            let desired_stt_model = profile
-                .as_ref()
-                .and_then(|p| p.stt_model.clone())
-                .or_else(|| config.stt_model.clone());
+    /// Providers tried in order when the effective STT provider fails (after retries) or
+    /// times out, e.g. groq -> openai -> local-whisper. Unavailable entries are skipped.
+    pub stt_fallback_chain: Vec<SttFallbackEntry>,
 
-            let stt_provider = match inner.get_or_create_stt_provider(
-                &desired_stt_provider,
-                desired_stt_model.clone(),
-            ) {
-                Ok(p) => p,
-                Err(e) => {
-                    // If the profile specified an override provider, fall back to global provider.
-                    let global_provider = canonicalize_stt_provider_id(&config.stt_provider);
-                    if global_provider != desired_stt_provider {
-                        log::warn!(
-                            "Pipeline: Profile STT provider '{}' unavailable ({}), falling back to '{}'",
-                            desired_stt_provider,
-                            e,
-                            global_provider
-                        );
+    /// Race a delayed second STT request against slow primary requests (tail latency).
+    pub stt_hedge: SttHedgeConfig,
 
-                        let global_model = config.stt_model.clone();
-                        inner
-                            .get_or_create_stt_provider(&global_provider, global_model)
-                            .map_err(|err| {
-                                inner.set_error(&format!(
-                                    "No STT provider configured: {}",
-                                    err
-                                ));
-                                PipelineError::NoProvider
-                            })?
-                    } else {
-                        inner.set_error(&format!("No STT provider configured: {}", e));
-                        return Err(PipelineError::NoProvider);
-                    }
-                }
-            };
+    /// Split long recordings at pauses and transcribe the chunks concurrently.
+    pub stt_chunking: SttChunkingConfig,
 
-            let cancel_token = inner
-                .cancel_token
-                .clone()
-                .unwrap_or_else(CancellationToken::new);
+    /// Stream audio to the STT provider while recording.
+    ///
+    /// Providers without a live API are driven through a batch adapter. If the streaming
+    /// session fails, the stop-time batch path is used instead.
+    pub stt_streaming_enabled: bool,
+    /// Retry configuration for STT requests
+    pub retry_config: RetryConfig,
+    /// VAD auto-stop configuration
+    pub vad_config: VadAutoStopConfig,
+    /// Timeout for transcription requests
+    pub transcription_timeout: Duration,
+    /// Maximum recording size in bytes (0 = no limit beyond default)
+    pub max_recording_bytes: usize,
 
-            (
-                wav_bytes,
-                stt_provider,
-                config.retry_config.clone(),
-                cancel_token,
-            )
-        };
+    /// Enable a quiet-audio gate to avoid silent-audio hallucinations.
+    pub quiet_audio_gate_enabled: bool,
+    /// Treat recordings shorter than this as effectively quiet.
+    pub quiet_audio_min_duration_secs: f32,
+    /// RMS threshold (in dBFS) below which the audio is considered quiet.
+    pub quiet_audio_rms_dbfs_threshold: f32,
+    /// Peak threshold (in dBFS) below which the audio is considered quiet.
+    pub quiet_audio_peak_dbfs_threshold: f32,
 
-        let wav = Arc::new(wav_bytes);
-        let format = AudioFormat::default();
+    /// Optional noise gate threshold (dBFS), applied at stop-time before WAV encoding.
+    ///
+    /// Recommended range: -75..-30. `None` disables the noise gate.
+    pub noise_gate_threshold_dbfs: Option<f32>,
 
-        let transcription_future = async {
-            with_retry(&retry_config, || {
-                let provider = stt_provider.clone();
-                let wav = wav.clone();
-                let format = format.clone();
+    // ------------------------------------------------------------------------
+    // Voice pickup (preprocessing) options
+    // ------------------------------------------------------------------------
+    /// Convert captured audio to mono before WAV encoding.
+    pub audio_downmix_to_mono: bool,
+    /// Resample to 16kHz before WAV encoding.
+    pub audio_resample_to_16khz: bool,
+    /// Apply a lightweight high-pass (DC/rumble) filter.
+    pub audio_highpass_enabled: bool,
+    /// Apply a lightweight auto-gain/normalization.
+    pub audio_agc_enabled: bool,
+    /// Apply a lightweight noise suppression.
+    pub audio_noise_suppression_enabled: bool,
+    /// Compress uploads to batch STT providers that accept it (FLAC/Ogg-Opus). The recording
+    /// itself is always kept as WAV.
+    pub audio_upload_encoding: AudioEncoding,
+    /// Drop STT segments that look like hallucinations (non-speech, repeats, blocklist).
+    pub hallucination_filter: HallucinationFilterConfig,
+    /// Circuit breaker settings for unhealthy STT/LLM providers.
+    pub provider_health: ProviderHealthConfig,
+    /// Speak in one language, output text in another (profiles may override).
+    pub translation: TranslationConfig,
+    /// Spoken language passed to the STT provider (fixed, auto, or a set of candidates;
+    /// profiles may override).
+    pub stt_language: SttLanguage,
+    /// Stream the LLM rewrite; the text deltas of each recording are available through
+    /// `take_llm_deltas`.
+    pub llm_streaming_enabled: bool,
 
-                async move {
-                    provider.transcribe(wav.as_slice(), &format).await
-                }
-            })
-            .await
-        };
+    // ------------------------------------------------------------------------
+    // Extra hallucination protection
+    // ------------------------------------------------------------------------
+    /// If enabled, run an offline VAD scan at stop-time and skip STT when no speech is detected.
+    pub quiet_audio_require_speech: bool,
+    /// LLM formatting configuration
+    pub llm_config: LlmConfig,
+    /// API keys for all configured LLM providers (provider id -> key)
+    pub llm_api_keys: HashMap<String, String>,
 
-        // Cancellation protection (test endpoint intentionally does NOT enforce timeout)
-        tokio::select! {
-            biased;
+    /// Optional request log store for capturing provider request/response payloads.
+    pub request_log_store: Option<RequestLogStore>,
+    /// Personal dictionary: replacements applied to the STT text, also written into the LLM
+    /// dictionary section and the STT vocabulary.
+    pub dictionary: Vec<DictionaryEntry>,
+    /// Voice snippets expanded after STT (None disables snippets).
+    pub snippet_store: Option<SnippetStore>,
+    /// Pending dictionary suggestions, fed by re-dictations (None disables learning).
+    pub dictionary_suggestions: Option<DictionarySuggestionStore>,
+    /// Path to local Whisper model (for local-whisper feature)
+    #[cfg(feature = "local-whisper")]
+    pub whisper_model_path: Option<std::path::PathBuf>,
+}
 
-            _ = cancel_token.cancelled() => {
-                Err(PipelineError::Cancelled)
-            }
+impl Default for PipelineConfig {
+    fn default() -> Self {
+        Self {
+            input_device_name: None,
+            max_duration_secs: 300.0, // 5 minutes max
+            stt_provider: "groq".to_string(),
+            stt_api_key: String::new(),
+            stt_api_keys: HashMap::new(),
+            stt_model: None,
+            stt_transcription_prompt: None,
+            stt_openai_compatible: OpenAiCompatibleSttConfig::default(),
+            stt_fallback_chain: Vec::new(),
+            stt_hedge: SttHedgeConfig::default(),
+            stt_chunking: SttChunkingConfig::default(),
+            stt_streaming_enabled: false,
+            retry_config: RetryConfig::default(),
+            vad_config: VadAutoStopConfig::default(),
+            transcription_timeout: DEFAULT_TRANSCRIPTION_TIMEOUT,
+            max_recording_bytes: MAX_WAV_SIZE_BYTES,
 
-            result = transcription_future => {
-                result
-                    .map(normalize_stt_text)
-                    .map_err(PipelineError::from)
-            }
-        }
-    }
+            quiet_audio_gate_enabled: true,
+            quiet_audio_min_duration_secs: DEFAULT_QUIET_AUDIO_MIN_DURATION_SECS,
+            quiet_audio_rms_dbfs_threshold: DEFAULT_QUIET_AUDIO_RMS_DBFS_THRESHOLD,
+            quiet_audio_peak_dbfs_threshold: DEFAULT_QUIET_AUDIO_PEAK_DBFS_THRESHOLD,
 
-    /// Stop recording and transcribe the audio, returning a detailed result.
-    ///
-    /// This is the main end-to-end function for voice dictation.
-    /// Includes:
-    /// - Automatic retry with exponential backoff on transient failures
-    /// - Timeout protection
-    /// - Cancellation support
-    /// - Proper error recovery
-    /// - Optional LLM formatting
-    pub async fn stop_and_transcribe_detailed(
-        &self,
-    ) -> Result<TranscriptionResult, PipelineError> {
-        // Phase 1: Stop recording and prepare for transcription (synchronous, holds lock briefly)
-        let (wav_bytes, stt_provider, llm_provider, llm_prompts, llm_timeout, retry_config, timeout, cancel_token) = {
-            let mut inner = self.inner.lock().map_err(|e| PipelineError::Lock(e.to_string()))?;
+            noise_gate_threshold_dbfs: None,
 
-            if !inner.state.can_stop_recording() {
-                return Err(PipelineError::NotRecording);
-            }
+            audio_downmix_to_mono: true,
+            audio_resample_to_16khz: false,
+            audio_highpass_enabled: true,
+            audio_agc_enabled: false,
+            audio_noise_suppression_enabled: false,
+            audio_upload_encoding: AudioEncoding::Wav,
+            hallucination_filter: HallucinationFilterConfig::default(),
+            provider_health: ProviderHealthConfig::default(),
+            translation: TranslationConfig::default(),
+            stt_language: SttLanguage::Auto,
+            llm_streaming_enabled: false,
 
-            let encode_cfg = AudioEncodeConfig {
-                noise_gate_threshold_dbfs: inner.config.noise_gate_threshold_dbfs,
-                downmix_to_mono: inner.config.audio_downmix_to_mono,
-                resample_to_16khz: inner.config.audio_resample_to_16khz,
-                highpass_enabled: inner.config.audio_highpass_enabled,
-                agc_enabled: inner.config.audio_agc_enabled,
-                noise_suppression_enabled: inner.config.audio_noise_suppression_enabled,
-                detect_speech_presence: inner.config.quiet_audio_require_speech,
-            };
+            quiet_audio_require_speech: false,
 
-            let (wav_bytes, diagnostics) = match inner
-                .audio_capture
-                .stop_and_get_wav_with_diagnostics(encode_cfg)
-            {
-                Ok(out) => out,
-                Err(e) => {
-                    inner.set_error(&format!("Failed to stop recording: {}", e));
-                    return Err(PipelineError::AudioCapture(e));
-                }
-            };
+            llm_config: LlmConfig::default(),
+            llm_api_keys: HashMap::new(),
+            request_log_store: None,
+            dictionary: Vec::new(),
+            snippet_store: None,
+            dictionary_suggestions: None,
+            #[cfg(feature = "local-whisper")]
+            whisper_model_path: None,
+        }
+    }
+}
 
-            let stats = diagnostics.stats;
+/// Internal state for the recording pipeline
+struct PipelineInner {
+    audio_capture: AudioCapture,
+    stt_registry: SttRegistry,
+    stt_provider_cache: HashMap<String, Arc<dyn SttProvider>>,
+    llm_provider_cache: HashMap<String, Arc<dyn LlmProvider>>,
+    /// Health of the cached providers; survives config updates and cache clears.
+    provider_health: ProviderHealthTracker,
+    state: PipelineState,
+    config: PipelineConfig,
+    /// Cancellation token for the current operation
+    cancel_token: Option<CancellationToken>,
 
-            // Persist diagnostics for UI readout.
-            inner.last_recording_diagnostics = Some(diagnostics);
+    /// Last captured audio (WAV bytes). Used for debugging/testing.
+    last_wav_bytes: Option<Vec<u8>>,
 
-            // Keep a copy for STT testing/debugging UI.
-            inner.last_wav_bytes = Some(wav_bytes.clone());
+    /// Program profile the last dictation ran under (None = Default), so a failed dictation
+    /// can be retried under the same profile.
+    last_profile_id: Option<String>,
 
-            // Optional extra hallucination protection: if VAD says "no speech", skip STT.
-            if inner.config.quiet_audio_gate_enabled
-                && inner.config.quiet_audio_require_speech
-                && inner
-                    .last_recording_diagnostics
-                    .and_then(|d| d.speech_detected)
-                    == Some(false)
-            {
-                log::info!(
-                    "Pipeline: Skipping STT because no speech was detected by offline VAD (duration {:.2}s, rms {:.1} dBFS, peak {:.1} dBFS)",
-                    stats.duration_secs,
-                    amp_to_dbfs(stats.rms),
-                    amp_to_dbfs(stats.peak)
-                );
+    /// Last recording diagnostics (raw stats + optional speech detection).
+    last_recording_diagnostics: Option<AudioCaptureDiagnostics>,
 
-                inner.reset_to_idle();
-                return Ok(TranscriptionResult {
-                    stt_text: String::new(),
-                    final_text: String::new(),
-                    stt_duration_ms: 0,
-                    llm_duration_ms: None,
-                    llm_provider_used: None,
-                    llm_model_used: None,
-                    llm_outcome: LlmOutcome::NotAttempted,
-                });
-            }
+    /// Live STT session fed by the current recording (when streaming is enabled).
+    stt_stream: Option<SttStream>,
 
-            if inner.config.quiet_audio_gate_enabled
-                && is_effectively_quiet(
-                    stats,
-                    inner.config.quiet_audio_min_duration_secs,
-                    inner.config.quiet_audio_rms_dbfs_threshold,
-                    inner.config.quiet_audio_peak_dbfs_threshold,
-                )
-            {
-                log::info!(
-                    "Pipeline: Skipping STT because recording is quiet (duration {:.2}s, rms {:.1} dBFS, peak {:.1} dBFS)",
-                    stats.duration_secs,
-                    amp_to_dbfs(stats.rms),
-                    amp_to_dbfs(stats.peak)
-                );
+    /// LLM delta channel for the current recording (when LLM streaming is enabled). The
+    /// receiver is handed out by `take_llm_deltas`; the sender is used by the rewrite step.
+    llm_deltas: Option<LlmDeltaSender>,
+    llm_delta_events: Option<LlmDeltaReceiver>,
 
-                inner.reset_to_idle();
-                return Ok(TranscriptionResult {
-                    stt_text: String::new(),
-                    final_text: String::new(),
-                    stt_duration_ms: 0,
-                    llm_duration_ms: None,
-                    llm_provider_used: None,
-                    llm_model_used: None,
-                    llm_outcome: LlmOutcome::NotAttempted,
-                });
-            }
+    /// Final text of the last dictation (the `{{previous_output}}` prompt variable).
+    previous_output: Option<String>,
 
-            // Check size limit
-            let max_bytes = inner.config.max_recording_bytes;
-            if max_bytes > 0 && wav_bytes.len() > max_bytes {
-                inner.set_error(&format!("Recording too large: {} bytes", wav_bytes.len()));
-                return Err(PipelineError::RecordingTooLarge(wav_bytes.len(), max_bytes));
-            }
+    /// Raw STT text of the last dictation, to spot it being re-dictated with corrections.
+    previous_dictation: Option<PreviousDictation>,
+}
 
-            inner.state = PipelineState::Transcribing;
+/// A finished dictation, kept until the next one.
+struct PreviousDictation {
+    stt_text: String,
+    profile_id: Option<String>,
+    finished_at: std::time::Instant,
+}
 
-            let llm_config = inner.config.llm_config.clone();
-            let active_profile = select_profile_for_foreground_app(&llm_config);
-            let llm_prompts = active_profile
-                .as_ref()
-                .map(|p| p.prompts.clone())
-                .unwrap_or_else(|| llm_config.prompts.clone());
+/// The post-STT stage of a dictation (dictionary, snippets, LLM rewrite), resolved in phase 1
+/// together with the STT providers (see `SharedPipeline::rewrite_dictation`).
+struct DictationRewrite {
+    dictionary: Vec<DictionaryEntry>,
+    snippets: DictationSnippets,
+    llm_chain: Option<LlmFallbackChain>,
+    llm_prompts: PromptSections,
+    /// Receives the rewrite as it streams in (live dictations only).
+    llm_deltas: Option<LlmDeltaSender>,
+    /// The selection a transform instruction rewrites (the STT text is the instruction).
+    transform: Option<String>,
+}
 
-            // Resolve effective STT settings (profile overrides -> global defaults, with safe fallback)
-            let desired_stt_provider = canonicalize_stt_provider_id(
-                active_profile
-                    .as_ref()
-                    .and_then(|p| p.stt_provider.as_deref())
-                    .unwrap_or(inner.config.stt_provider.as_str()),
-            );
-            let desired_stt_model = active_profile
-                .as_ref()
-                .and_then(|p| p.stt_model.clone())
-                .or_else(|| inner.config.stt_model.clone());
-            let desired_timeout = active_profile
-                .as_ref()
-                .and_then(|p| p.stt_timeout_seconds)
-                .map(|s| seconds_to_duration_or(s, inner.config.transcription_timeout))
-                .unwrap_or(inner.config.transcription_timeout);
+/// The text a dictation inserts, with how its LLM rewrite went.
+struct RewriteOutput {
+    final_text: String,
+    llm_duration_ms: Option<u64>,
+    llm_provider_used: Option<String>,
+    llm_model_used: Option<String>,
+    llm_outcome: LlmOutcome,
+}
 
-            let stt_provider = match inner.get_or_create_stt_provider(&desired_stt_provider, desired_stt_model.clone()) {
-                Ok(p) => p,
-                Err(e) => {
-                    // If the profile specified an override provider, fall back to global provider.
-                    let global_provider = canonicalize_stt_provider_id(&inner.config.stt_provider);
-                    if global_provider != desired_stt_provider {
-                        log::warn!(
-                            "Pipeline: Profile STT provider '{}' unavailable ({}), falling back to '{}'",
-                            desired_stt_provider,
-                            e,
-                            global_provider
-                        );
-                        let global_model = inner.config.stt_model.clone();
-                        inner.get_or_create_stt_provider(&global_provider, global_model)
-                            .map_err(|err| {
-                                inner.set_error(&format!("No STT provider configured: {}", err));
-                                PipelineError::NoProvider
-                            })?
-                    } else {
-                        inner.set_error(&format!("No STT provider configured: {}", e));
-                        return Err(PipelineError::NoProvider);
-                    }
-                }
-            };
+/// The voice snippets for one dictation, with the profile their bodies are rendered for.
+#[derive(Default)]
+struct DictationSnippets {
+    snippets: Vec<Snippet>,
+    profile: Option<crate::llm::ProgramPromptProfile>,
+    /// Whether the live template variables are filled (see `LIVE_TEMPLATE_VARIABLES`).
+    live: bool,
+}
 
-            // Resolve effective LLM provider/model (profile overrides -> global defaults), gated by
-            // the active profile's enable flag (falls back to the global enable).
-            let llm_timeout = llm_config.timeout;
-            let effective_llm_enabled = active_profile
-                .as_ref()
-                .and_then(|p| p.rewrite_llm_enabled)
-                .unwrap_or(inner.config.llm_config.enabled);
+impl PipelineInner {
+    fn new(config: PipelineConfig) -> Self {
+        let audio_capture = AudioCapture::with_vad_config(config.vad_config.clone());
+        let mut inner = Self {
+            audio_capture,
+            stt_registry: SttRegistry::new(),
+            stt_provider_cache: HashMap::new(),
+            llm_provider_cache: HashMap::new(),
+            provider_health: ProviderHealthTracker::new(config.provider_health.clone()),
+            state: PipelineState::Idle,
+            config: config.clone(),
+            cancel_token: None,
+            last_wav_bytes: None,
+            last_profile_id: None,
+            last_recording_diagnostics: None,
+            stt_stream: None,
+            llm_deltas: None,
+            llm_delta_events: None,
+            previous_output: None,
+            previous_dictation: None,
+        };
+        inner.initialize_providers(&config);
+        inner
+    }
 
-            let llm_provider = if effective_llm_enabled {
-                let desired_llm_provider = active_profile
-                    .as_ref()
-                    .and_then(|p| p.llm_provider.clone())
-                    .unwrap_or_else(|| llm_config.provider.clone());
-                let desired_llm_model = active_profile
-                    .as_ref()
-                    .and_then(|p| p.llm_model.clone())
-                    .or_else(|| llm_config.model.clone());
-
-                match inner.get_or_create_llm_provider(
-                    desired_llm_provider.as_str(),
-                    desired_llm_model.clone(),
-                    llm_timeout,
-                    llm_config.ollama_url.clone(),
-                ) {
-                    Ok(p) => Some(p),
-                    Err(e) => {
-                        // Fallback to global provider if profile requested a different one.
-                        if active_profile
-                            .as_ref()
-                            .and_then(|p| p.llm_provider.as_ref())
-                            .is_some()
-                            && desired_llm_provider != llm_config.provider
-                        {
-                            log::warn!(
-                                "Pipeline: Profile LLM provider '{}' unavailable ({}), falling back to '{}'",
-                                desired_llm_provider,
-                                e,
-                                llm_config.provider
-                            );
-                            inner
-                                .get_or_create_llm_provider(
-                                    llm_config.provider.as_str(),
-                                    llm_config.model.clone(),
-                                    llm_timeout,
-                                    llm_config.ollama_url.clone(),
-                                )
-                                .ok()
-                        } else {
-                            log::warn!(
-                                "Pipeline: LLM disabled for this transcription ({}).",
-                                e
-                            );
-                            None
-                        }
-                    }
-                }
-            } else {
-                None
-            };
+    /// Create (or reuse) an STT provider biased towards `vocabulary`.
+    fn get_or_create_stt_provider(
+        &mut self,
+        provider_id: &str,
+        model: Option<String>,
+        language: &SttLanguage,
+        vocabulary: &Vocabulary,
+    ) -> Result<Arc<dyn SttProvider>, PipelineError> {
+        let provider_id = canonicalize_stt_provider_id(provider_id);
+        let model_key = model.clone().unwrap_or_else(|| "<default>".to_string());
+        let cache_key = format!(
+            "{}::{}::{}::{}",
+            provider_id,
+            model_key,
+            language.cache_key(),
+            vocabulary.cache_key()
+        );
 
-            let retry_config = inner.config.retry_config.clone();
-            let cancel_token = inner.cancel_token.clone().unwrap_or_else(CancellationToken::new);
+        if let Some(p) = self.stt_provider_cache.get(&cache_key) {
+            return Ok(p.clone());
+        }
+        let vocabulary = vocabulary.clone();
 
-            (
-                wav_bytes,
-                stt_provider,
-                llm_provider,
-                llm_prompts,
-                llm_timeout,
-                retry_config,
-                desired_timeout,
-                cancel_token,
+        #[cfg(feature = "local-whisper")]
+        if provider_id == "local-whisper" {
+            if let Some(model_path) = &self.config.whisper_model_path {
+                // Providers for the same model share one loaded copy of it.
+                let provider = crate::stt::LocalWhisperProvider::new(model_path.clone())
+                    .map_err(|e| PipelineError::Config(format!("Local Whisper init failed: {}", e)))?
+                    .with_language(language.clone())
+                    .with_vocabulary(vocabulary);
+                let provider = Arc::new(provider);
+                self.stt_provider_cache.insert(cache_key, provider.clone());
+                return Ok(provider);
+            }
+
+            return Err(PipelineError::Config(
+                "Local Whisper selected but no model path configured".to_string(),
+            ));
+        }
+
+        // Self-hosted servers usually run without auth, so the key is optional here.
+        if provider_id == "openai-compatible" {
+            let provider = crate::stt::OpenAiCompatibleSttProvider::new(
+                &self.config.stt_openai_compatible,
+                model,
+                self.config.stt_transcription_prompt.clone(),
             )
+            .map_err(|e| PipelineError::Config(e.to_string()))?
+            .with_vocabulary(vocabulary)
+            .with_request_log_store(self.config.request_log_store.clone());
+            let provider: Arc<dyn SttProvider> = Arc::new(provider);
+            self.stt_provider_cache.insert(cache_key, provider.clone());
+            return Ok(provider);
+        }
+
+        let api_key = self
+            .config
+            .stt_api_keys
+            .get(&provider_id)
+            .cloned()
+            .unwrap_or_default();
+
+        if api_key.is_empty() {
+            return Err(PipelineError::Config(format!(
+                "STT provider '{}' requires an API key",
+                provider_id
+            )));
+        }
+
+        let provider: Arc<dyn SttProvider> = match provider_id.as_str() {
+            "openai" => Arc::new(
+                crate::stt::OpenAiSttProvider::new(
+                    api_key,
+                    model,
+                    self.config.stt_transcription_prompt.clone(),
+                )
+                .with_language(language.clone())
+                .with_vocabulary(vocabulary)
+                .with_request_log_store(self.config.request_log_store.clone()),
+            ),
+            "groq" => Arc::new(
+                crate::stt::GroqSttProvider::new(
+                    api_key,
+                    model,
+                    self.config.stt_transcription_prompt.clone(),
+                )
+                .with_language(language.clone())
+                .with_vocabulary(vocabulary)
+                .with_request_log_store(self.config.request_log_store.clone()),
+            ),
+            "deepgram" => Arc::new(
+                crate::stt::DeepgramSttProvider::new(api_key, model)
+                    .with_language(language.clone())
+                    .with_vocabulary(vocabulary)
+                    .with_request_log_store(self.config.request_log_store.clone()),
+            ),
+            "assemblyai" => Arc::new(
+                crate::stt::AssemblyAiSttProvider::new(api_key, model)
+                    .with_vocabulary(vocabulary)
+                    .with_language(language.clone())
+                    .with_request_log_store(self.config.request_log_store.clone()),
+            ),
+            other => {
+                return Err(PipelineError::Config(format!(
+                    "Unknown STT provider: {}",
+                    other
+                )))
+            }
         };
 
-        log::info!(
-            "Pipeline: Starting transcription ({} bytes, timeout {:?})",
-            wav_bytes.len(),
-            timeout
+        self.stt_provider_cache.insert(cache_key, provider.clone());
+        Ok(provider)
+    }
+
+    /// Personal dictionary terms for `profile`, passed to each STT provider's biasing mechanism:
+    /// the profile's dictionary prompt section plus the entries that apply to the profile.
+    fn stt_vocabulary(&self, profile: Option<&crate::llm::ProgramPromptProfile>) -> Vocabulary {
+        let prompts = profile
+            .map(|p| &p.prompts)
+            .unwrap_or(&self.config.llm_config.prompts);
+        with_dictionary(prompts, &self.dictation_dictionary(profile)).vocabulary()
+    }
+
+    /// Resolve the effective STT provider (profile overrides -> global defaults).
+    ///
+    /// If the profile's override provider is unavailable, falls back to the global provider.
+    fn resolve_stt_provider(
+        &mut self,
+        profile: Option<&crate::llm::ProgramPromptProfile>,
+    ) -> Result<SttCandidate, PipelineError> {
+        let desired_stt_provider = canonicalize_stt_provider_id(
+            profile
+                .and_then(|p| p.stt_provider.as_deref())
+                .unwrap_or(self.config.stt_provider.as_str()),
         );
+        let desired_stt_model = profile
+            .and_then(|p| p.stt_model.clone())
+            .or_else(|| self.config.stt_model.clone());
+        let language = self.effective_stt_language(profile);
+        let vocabulary = self.stt_vocabulary(profile);
+
+        match self.get_or_create_stt_provider(
+            &desired_stt_provider,
+            desired_stt_model.clone(),
+            &language,
+            &vocabulary,
+        ) {
+            Ok(provider) => Ok(SttCandidate {
+                provider,
+                model: desired_stt_model,
+            }),
+            Err(e) => {
+                let global_provider = canonicalize_stt_provider_id(&self.config.stt_provider);
+                if global_provider == desired_stt_provider {
+                    return Err(e);
+                }
 
-        // Phase 2: Transcribe with retry logic (async, outside the lock)
-        let format = AudioFormat::default();
-        let wav_bytes_for_retry = wav_bytes.clone();
+                log::warn!(
+                    "Pipeline: Profile STT provider '{}' unavailable ({}), falling back to '{}'",
+                    desired_stt_provider,
+                    e,
+                    global_provider
+                );
+                let global_model = self.config.stt_model.clone();
+                let provider = self.get_or_create_stt_provider(
+                    &global_provider,
+                    global_model.clone(),
+                    &language,
+                    &vocabulary,
+                )?;
+                Ok(SttCandidate {
+                    provider,
+                    model: global_model,
+                })
+            }
+        }
+    }
 
-        // Wrap the transcription in a timeout and cancellation
-        let transcription_future = async {
-            with_retry(&retry_config, || {
-                let provider = stt_provider.clone();
-                let wav_bytes = wav_bytes_for_retry.clone();
-                let format = format.clone();
-                async move { provider.transcribe(&wav_bytes, &format).await }
-            })
-            .await
+    /// Resolve the effective STT provider followed by the configured fallback providers.
+    ///
+    /// Fallback entries that can't be created (missing API key, no model path, ...) or that
+    /// duplicate an earlier entry are skipped.
+    fn resolve_stt_chain(
+        &mut self,
+        profile: Option<&crate::llm::ProgramPromptProfile>,
+    ) -> Result<SttFallbackChain, PipelineError> {
+        let mut candidates = vec![self.resolve_stt_provider(profile)?];
+        let language = self.effective_stt_language(profile);
+        let vocabulary = self.stt_vocabulary(profile);
+
+        for entry in self.config.stt_fallback_chain.clone() {
+            let provider_id = canonicalize_stt_provider_id(&entry.provider);
+            let model = entry.model.filter(|m| !m.trim().is_empty());
+            if candidates
+                .iter()
+                .any(|c| c.provider.name() == provider_id && c.model == model)
+            {
+                continue;
+            }
+
+            match self.get_or_create_stt_provider(&provider_id, model.clone(), &language, &vocabulary)
+            {
+                Ok(provider) => candidates.push(SttCandidate { provider, model }),
+                Err(e) => {
+                    log::warn!(
+                        "Pipeline: Skipping STT fallback provider '{}' ({})",
+                        provider_id,
+                        e
+                    );
+                }
+            }
+        }
+
+        let hedge = if self.config.stt_hedge.enabled {
+            self.resolve_stt_hedge(&candidates[0], &language, &vocabulary)
+        } else {
+            None
         };
 
-        // Race between transcription, timeout, and cancellation
-        let stt_start = std::time::Instant::now();
-        let stt_result = tokio::select! {
-            biased;
+        Ok(SttFallbackChain {
+            candidates,
+            hedge,
+            chunking: self.config.stt_chunking.clone(),
+            max_chunk_bytes: self.config.max_recording_bytes,
+            upload_encoding: self.config.audio_upload_encoding,
+            hallucination_filter: self.config.hallucination_filter.clone(),
+            health: self.provider_health.clone(),
+            translate: false,
+            request_log_store: self.config.request_log_store.clone(),
+        })
+    }
 
-            // Cancellation takes priority
-            _ = cancel_token.cancelled() => {
-                log::info!("Pipeline: Transcription cancelled");
-                Err(PipelineError::Cancelled)
+    /// Resolve the hedge request for `primary` (None if its provider is unavailable).
+    fn resolve_stt_hedge(
+        &mut self,
+        primary: &SttCandidate,
+        language: &SttLanguage,
+        vocabulary: &Vocabulary,
+    ) -> Option<SttHedge> {
+        let hedge_config = self.config.stt_hedge.clone();
+        let model = hedge_config.model.filter(|m| !m.trim().is_empty());
+        let provider_id = hedge_config.provider.filter(|p| !p.trim().is_empty());
+        let candidate = match (provider_id, model) {
+            (None, None) => SttCandidate {
+                provider: primary.provider.clone(),
+                model: primary.model.clone(),
+            },
+            (provider_id, model) => {
+                let provider_id = canonicalize_stt_provider_id(
+                    provider_id.as_deref().unwrap_or(primary.provider.name()),
+                );
+                match self.get_or_create_stt_provider(&provider_id, model.clone(), language, vocabulary)
+                {
+                    Ok(provider) => SttCandidate { provider, model },
+                    Err(e) => {
+                        log::warn!(
+                            "Pipeline: STT hedging disabled, provider '{}' unavailable ({})",
+                            provider_id,
+                            e
+                        );
+                        return None;
+                    }
+                }
+            }
+        };
+
+        Some(SttHedge {
+            candidate,
+            delay: Duration::from_millis(hedge_config.delay_ms),
+        })
+    }
+
+    /// Open a streaming STT session fed by the recording that just started.
+    ///
+    /// Failures are logged and leave `stt_stream` empty; the batch path still runs at stop time.
+    fn start_stt_stream(&mut self, audio_rx: AudioChunkReceiver) {
+        let active_profile = select_profile_for_foreground_app(&self.config.llm_config);
+        let provider = match self.resolve_stt_provider(active_profile.as_ref()) {
+            Ok(candidate) => candidate.provider,
+            Err(e) => {
+                log::warn!("Pipeline: Streaming STT not started ({})", e);
+                return;
+            }
+        };
+
+        // The batch translate task will be used at stop time; a live transcript would be discarded.
+        let translation = self.effective_translation(active_profile.as_ref());
+        if translation.route(provider.supports_translation()) == TranslationRoute::Stt {
+            log::info!("Pipeline: Streaming STT skipped (translation runs in batch STT)");
+            return;
+        }
+
+        let format = AudioFormat {
+            sample_rate: self.audio_capture.sample_rate(),
+            channels: 1,
+            encoding: AudioEncoding::Pcm16,
+        };
+
+        match start_streaming_session(provider, format, audio_rx) {
+            Ok(stream) => {
+                log::info!(
+                    "Pipeline: Streaming STT session started ({})",
+                    stream.provider_name()
+                );
+                self.stt_stream = Some(stream);
+            }
+            Err(e) => {
+                log::warn!("Pipeline: Streaming STT not started ({})", e);
+            }
+        }
+    }
+
+    fn get_or_create_llm_provider(
+        &mut self,
+        provider_id: &str,
+        model: Option<String>,
+        timeout: Duration,
+        ollama_url: Option<String>,
+    ) -> Result<Arc<dyn LlmProvider>, PipelineError> {
+        let model_key = model.clone().unwrap_or_else(|| "<default>".to_string());
+        let url_key = ollama_url
+            .clone()
+            .unwrap_or_else(|| "<default-url>".to_string());
+        let cache_key = format!(
+            "{}::{}::{}::{}",
+            provider_id,
+            model_key,
+            timeout.as_secs_f64(),
+            url_key
+        );
+
+        if let Some(p) = self.llm_provider_cache.get(&cache_key) {
+            return Ok(p.clone());
+        }
+
+        // Ollama needs no key; OpenAI-compatible servers carry an optional key in their own settings.
+        let api_key = if provider_id == "ollama" || provider_id == "openai-compatible" {
+            String::new()
+        } else {
+            self.config
+                .llm_api_keys
+                .get(provider_id)
+                .cloned()
+                .unwrap_or_default()
+        };
+
+        if provider_id == "openai-compatible" {
+            if !self.config.llm_config.openai_compatible.is_configured() {
+                return Err(PipelineError::Config(
+                    "LLM provider 'openai-compatible' requires a base URL".to_string(),
+                ));
+            }
+        } else if provider_id != "ollama" && api_key.is_empty() {
+            return Err(PipelineError::Config(format!(
+                "LLM provider '{}' requires an API key",
+                provider_id
+            )));
+        }
+
+        // Preserve global LLM config (including provider-specific knobs) but override the
+        // effective provider/model/timeout for this transcription.
+        let mut cfg = self.config.llm_config.clone();
+        cfg.enabled = true;
+        cfg.provider = provider_id.to_string();
+        cfg.api_key = api_key;
+        cfg.model = model;
+        cfg.ollama_url = ollama_url;
+        cfg.timeout = timeout;
+
+        let provider = create_llm_provider(&cfg, self.config.request_log_store.clone());
+        self.llm_provider_cache.insert(cache_key, provider.clone());
+        Ok(provider)
+    }
+
+    /// Translation settings for this dictation (profile override -> global setting).
+    fn effective_translation(
+        &self,
+        profile: Option<&crate::llm::ProgramPromptProfile>,
+    ) -> TranslationConfig {
+        profile
+            .and_then(|p| p.translation.clone())
+            .unwrap_or_else(|| self.config.translation.clone())
+    }
+
+    fn effective_stt_language(
+        &self,
+        profile: Option<&crate::llm::ProgramPromptProfile>,
+    ) -> SttLanguage {
+        profile
+            .and_then(|p| p.stt_language.clone())
+            .unwrap_or_else(|| self.config.stt_language.clone())
+    }
+
+    /// Whether `provider` may be used; while its circuit is open it is skipped (the next
+    /// fallback, or the raw transcript, is used instead).
+    fn llm_available(&self, provider: &Arc<dyn LlmProvider>) -> bool {
+        let available = self
+            .provider_health
+            .is_available(ProviderKind::Llm, provider.name(), Some(provider.model()));
+        if !available {
+            log::warn!("Pipeline: LLM provider '{}' circuit open, skipping it", provider.name());
+            if let Some(store) = &self.config.request_log_store {
+                let message = format!("LLM provider {} is unhealthy; skipped", provider.name());
+                store.with_current(|log| log.warn(message));
+            }
+        }
+        available
+    }
+
+    /// Values for the prompt variables that `prompts` references.
+    fn prompt_context(
+        &self,
+        profile: Option<&crate::llm::ProgramPromptProfile>,
+        prompts: &PromptSections,
+        live: bool,
+    ) -> PromptContext {
+        self.template_context(profile, &prompt_variables(prompts), live)
+    }
+
+    /// Values for the template variables in `used`.
+    ///
+    /// Only used values are looked up, so the clipboard is never read for templates that
+    /// don't use `{{clipboard}}`. Without `live`, the live variables stay empty.
+    fn template_context(
+        &self,
+        profile: Option<&crate::llm::ProgramPromptProfile>,
+        used: &BTreeSet<String>,
+        live: bool,
+    ) -> PromptContext {
+        let uses =
+            |name: &str| used.contains(name) && (live || !LIVE_TEMPLATE_VARIABLES.contains(&name));
+        let now = chrono::Local::now();
+
+        PromptContext {
+            app_name: uses("app_name")
+                .then(crate::windows_apps::get_foreground_process_path)
+                .flatten()
+                .and_then(|path| app_name_from_path(&path)),
+            window_title: uses("window_title")
+                .then(crate::windows_apps::get_foreground_window_title)
+                .flatten(),
+            date: Some(now.format("%A, %B %-d, %Y").to_string()),
+            time: Some(now.format("%H:%M").to_string()),
+            profile: Some(profile.map_or_else(|| "Default".to_string(), |p| p.name.clone())),
+            user_name: self
+                .config
+                .llm_config
+                .user_name
+                .clone()
+                .filter(|name| !name.trim().is_empty()),
+            previous_output: uses("previous_output")
+                .then(|| self.previous_output.clone())
+                .flatten()
+                .map(truncate_context_value),
+            clipboard: uses("clipboard")
+                .then(read_clipboard_text)
+                .flatten()
+                .map(truncate_context_value),
+        }
+    }
+
+    /// Fill the template variables in `prompts` for the rewrite about to run.
+    fn render_prompts(
+        &self,
+        prompts: PromptSections,
+        profile: Option<&crate::llm::ProgramPromptProfile>,
+        live: bool,
+    ) -> PromptSections {
+        let unknown = unknown_prompt_variables(&prompts);
+        if !unknown.is_empty() {
+            log::warn!(
+                "Pipeline: Prompt references unknown variable(s) {}, left as is",
+                unknown.join(", ")
+            );
+        }
+        if prompt_variables(&prompts).is_empty() {
+            return prompts;
+        }
+
+        let context = self.prompt_context(profile, &prompts, live);
+        render_prompt_sections(&prompts, &context)
+    }
+
+    /// Propose dictionary entries when a dictation repeats the previous one with a few words
+    /// changed (the user said it again because it was misheard).
+    fn learn_from_redictation(&mut self, stt_text: &str, profile_id: Option<&str>) {
+        if stt_text.trim().is_empty() {
+            return;
+        }
+        let previous = self.previous_dictation.replace(PreviousDictation {
+            stt_text: stt_text.to_string(),
+            profile_id: profile_id.map(str::to_string),
+            finished_at: std::time::Instant::now(),
+        });
+
+        let Some(store) = &self.config.dictionary_suggestions else {
+            return;
+        };
+        let Some(previous) = previous.filter(|p| {
+            p.finished_at.elapsed() <= REDICTATION_WINDOW && p.profile_id.as_deref() == profile_id
+        }) else {
+            return;
+        };
+        if !is_redictation(&previous.stt_text, stt_text) {
+            return;
+        }
+
+        for correction in propose_corrections(&previous.stt_text, stt_text) {
+            match store.propose(correction, profile_id, CorrectionSource::Redictation) {
+                Ok(Some(suggestion)) => log::info!(
+                    "Pipeline: Re-dictation suggests dictionary entry \"{}\"",
+                    suggestion.correction.dictionary_entry()
+                ),
+                Ok(None) => {}
+                Err(e) => log::warn!("Pipeline: Failed to record dictionary suggestion: {}", e),
             }
+        }
+    }
+
+    /// The dictionary entries that apply to the profile.
+    fn dictation_dictionary(
+        &self,
+        profile: Option<&crate::llm::ProgramPromptProfile>,
+    ) -> Vec<DictionaryEntry> {
+        entries_for_profile(&self.config.dictionary, profile.map(|p| p.id.as_str()))
+    }
+
+    /// The enabled voice snippets, with their bodies still unrendered (see `render_snippets`).
+    fn dictation_snippets(
+        &self,
+        profile: Option<&crate::llm::ProgramPromptProfile>,
+        live: bool,
+    ) -> DictationSnippets {
+        DictationSnippets {
+            snippets: self
+                .config
+                .snippet_store
+                .as_ref()
+                .map(SnippetStore::enabled)
+                .unwrap_or_default(),
+            profile: profile.cloned(),
+            live,
+        }
+    }
+
+    /// Fill the template variables in the bodies of `snippets`.
+    fn render_snippets(
+        &self,
+        mut snippets: Vec<Snippet>,
+        profile: Option<&crate::llm::ProgramPromptProfile>,
+        live: bool,
+    ) -> Vec<Snippet> {
+        let used: BTreeSet<String> = snippets
+            .iter()
+            .flat_map(|snippet| template_variables(&snippet.body))
+            .collect();
+        if !used.is_empty() {
+            let context = self.template_context(profile, &used, live);
+            for snippet in &mut snippets {
+                snippet.body = render_template(&snippet.body, &context);
+            }
+        }
+        snippets
+    }
+
+    /// Resolve the effective LLM provider followed by the configured fallback providers.
+    ///
+    /// A profile's own fallback chain replaces the global one. Entries that can't be created
+    /// (missing API key, ...), duplicate an earlier entry or have an open circuit are skipped.
+    /// Returns None when no provider is usable.
+    fn resolve_llm_chain(
+        &mut self,
+        profile: Option<&crate::llm::ProgramPromptProfile>,
+    ) -> Option<LlmFallbackChain> {
+        let llm_config = self.config.llm_config.clone();
+        let timeout = llm_config.timeout;
+
+        let desired_llm_provider = profile
+            .and_then(|p| p.llm_provider.clone())
+            .unwrap_or_else(|| llm_config.provider.clone());
+        let desired_llm_model = profile
+            .and_then(|p| p.llm_model.clone())
+            .or_else(|| llm_config.model.clone());
+
+        let primary = match self.get_or_create_llm_provider(
+            desired_llm_provider.as_str(),
+            desired_llm_model,
+            timeout,
+            llm_config.ollama_url.clone(),
+        ) {
+            Ok(p) => Some(p),
+            Err(e) => {
+                // Fallback to global provider if profile requested a different one.
+                if profile.and_then(|p| p.llm_provider.as_ref()).is_some()
+                    && desired_llm_provider != llm_config.provider
+                {
+                    log::warn!(
+                        "Pipeline: Profile LLM provider '{}' unavailable ({}), falling back to '{}'",
+                        desired_llm_provider,
+                        e,
+                        llm_config.provider
+                    );
+                    self.get_or_create_llm_provider(
+                        llm_config.provider.as_str(),
+                        llm_config.model.clone(),
+                        timeout,
+                        llm_config.ollama_url.clone(),
+                    )
+                    .ok()
+                } else {
+                    log::warn!("Pipeline: LLM provider '{}' unavailable ({})", desired_llm_provider, e);
+                    None
+                }
+            }
+        };
+
+        let mut providers: Vec<Arc<dyn LlmProvider>> = primary.into_iter().collect();
+        let fallback_chain = profile
+            .and_then(|p| p.llm_fallback_chain.clone())
+            .unwrap_or(llm_config.fallback_chain);
+
+        for entry in fallback_chain {
+            let provider_id = entry.provider.trim();
+            let model = entry.model.filter(|m| !m.trim().is_empty());
+            match self.get_or_create_llm_provider(
+                provider_id,
+                model,
+                timeout,
+                llm_config.ollama_url.clone(),
+            ) {
+                Ok(provider) => {
+                    if !providers
+                        .iter()
+                        .any(|p| p.name() == provider.name() && p.model() == provider.model())
+                    {
+                        providers.push(provider);
+                    }
+                }
+                Err(e) => {
+                    log::warn!(
+                        "Pipeline: Skipping LLM fallback provider '{}' ({})",
+                        provider_id,
+                        e
+                    );
+                }
+            }
+        }
+
+        providers.retain(|p| self.llm_available(p));
+        if providers.is_empty() {
+            log::warn!("Pipeline: LLM disabled for this transcription (no usable provider)");
+            return None;
+        }
+
+        Some(LlmFallbackChain {
+            providers,
+            budget: timeout,
+            guardrail: llm_config.guardrail.clone(),
+            health: self.provider_health.clone(),
+            request_log_store: self.config.request_log_store.clone(),
+        })
+    }
+
+    fn initialize_providers(&mut self, config: &PipelineConfig) {
+        // Clear caches on any config update.
+        self.stt_provider_cache.clear();
+        self.llm_provider_cache.clear();
+
+        // Initialize STT providers
+        self.stt_registry = SttRegistry::new();
+        let canonical = canonicalize_stt_provider_id(&config.stt_provider);
+        let vocabulary = self.stt_vocabulary(None);
+        match self.get_or_create_stt_provider(
+            &canonical,
+            config.stt_model.clone(),
+            &config.stt_language,
+            &vocabulary,
+        ) {
+            Ok(provider) => {
+                self.stt_registry.register(&canonical, provider);
+                let _ = self.stt_registry.set_current(&canonical);
+            }
+            Err(e) => {
+                log::warn!(
+                    "Pipeline: Default STT provider '{}' not initialized: {}",
+                    canonical,
+                    e
+                );
+            }
+        }
+
+        // Note: LLM providers are created on-demand per transcription based on the active profile.
+    }
+
+    /// Reset to idle state, clearing any error condition
+    fn reset_to_idle(&mut self) {
+        self.state = PipelineState::Idle;
+        self.cancel_token = None;
+        self.stt_stream = None;
+        self.llm_deltas = None;
+        self.llm_delta_events = None;
+    }
+
+    /// Transition to error state
+    fn set_error(&mut self, msg: &str) {
+        log::error!("Pipeline error: {}", msg);
+        self.state = PipelineState::Error;
+        self.cancel_token = None;
+        self.stt_stream = None;
+        self.llm_deltas = None;
+        self.llm_delta_events = None;
+    }
+}
+
+/// Create an LLM provider based on configuration
+fn create_llm_provider(
+    config: &LlmConfig,
+    request_log_store: Option<RequestLogStore>,
+) -> Arc<dyn LlmProvider> {
+    match config.provider.as_str() {
+        "anthropic" => {
+            let provider = if let Some(model) = &config.model {
+                AnthropicLlmProvider::with_model(config.api_key.clone(), model.clone())
+            } else {
+                AnthropicLlmProvider::new(config.api_key.clone())
+            };
+            Arc::new(
+                provider
+                    .with_timeout(config.timeout)
+                    .with_request_log_store(request_log_store.clone())
+                    .with_thinking_budget(config.anthropic_thinking_budget),
+            )
+        }
+        "groq" => {
+            let provider = if let Some(model) = &config.model {
+                GroqLlmProvider::with_model(config.api_key.clone(), model.clone())
+            } else {
+                GroqLlmProvider::new(config.api_key.clone())
+            };
+            Arc::new(
+                provider
+                    .with_timeout(config.timeout)
+                    .with_request_log_store(request_log_store.clone()),
+            )
+        }
+        "gemini" => {
+            let provider = if let Some(model) = &config.model {
+                GeminiLlmProvider::with_model(config.api_key.clone(), model.clone())
+            } else {
+                GeminiLlmProvider::new(config.api_key.clone())
+            };
+
+            Arc::new(
+                provider
+                    .with_timeout(config.timeout)
+                    .with_request_log_store(request_log_store.clone())
+                    .with_thinking_budget(config.gemini_thinking_budget)
+                    .with_thinking_level(config.gemini_thinking_level.clone()),
+            )
+        }
+        "ollama" => {
+            let provider = OllamaLlmProvider::with_url(
+                config
+                    .ollama_url
+                    .clone()
+                    .unwrap_or_else(|| "http://localhost:11434".to_string()),
+                config.model.clone(),
+            );
+            Arc::new(
+                provider
+                    .with_timeout(config.timeout)
+                    .with_request_log_store(request_log_store.clone()),
+            )
+        }
+        "openai-compatible" => {
+            let provider =
+                OpenAiCompatibleLlmProvider::new(&config.openai_compatible, config.model.clone());
+            Arc::new(
+                provider
+                    .with_timeout(config.timeout)
+                    .with_request_log_store(request_log_store.clone()),
+            )
+        }
+        _ => {
+            // Default to OpenAI
+            let provider = if let Some(model) = &config.model {
+                OpenAiLlmProvider::with_model(config.api_key.clone(), model.clone())
+            } else {
+                OpenAiLlmProvider::new(config.api_key.clone())
+            };
+            Arc::new(
+                provider
+                    .with_timeout(config.timeout)
+                    .with_request_log_store(request_log_store.clone())
+                    .with_reasoning_effort(config.openai_reasoning_effort.clone()),
+            )
+        }
+    }
+}
+
+/// Thread-safe wrapper for the recording pipeline
+///
+/// Uses standard Mutex to be Send + Sync for Tauri state management.
+/// Provides robust error handling and cancellation support.
+pub struct SharedPipeline {
+    inner: Arc<Mutex<PipelineInner>>,
+    level_meter: crate::audio_capture::SharedAudioLevelMeter,
+    waveform_meter: crate::audio_capture::SharedAudioWaveformMeter,
+}
+
+impl SharedPipeline {
+    /// Create a new shared pipeline
+    pub fn new(config: PipelineConfig) -> Self {
+        let inner = PipelineInner::new(config);
+        let level_meter = inner.audio_capture.shared_level_meter();
+        let waveform_meter = inner.audio_capture.shared_waveform_meter();
+        Self {
+            inner: Arc::new(Mutex::new(inner)),
+            level_meter,
+            waveform_meter,
+        }
+    }
+
+    /// Try to read the current state without blocking.
+    ///
+    /// This is useful for UI publishers that should not stall the runtime when
+    /// the pipeline mutex is briefly held (e.g., during start-up).
+    pub fn try_state(&self) -> Option<PipelineState> {
+        self.inner.try_lock().ok().map(|inner| inner.state)
+    }
+
+    /// Get the most recent realtime audio input level snapshot without locking
+    /// the pipeline mutex.
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn audio_level_snapshot_fast(&self) -> AudioLevelSnapshot {
+        self.level_meter.snapshot()
+    }
+
+    /// Get the most recent realtime waveform min/max buckets without locking the
+    /// pipeline mutex.
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn audio_waveform_snapshot_fast(&self) -> crate::audio_capture::AudioWaveformSnapshot {
+        self.waveform_meter.snapshot()
+    }
+
+    /// Start recording
+    ///
+    /// Creates a new cancellation token for this recording session.
+    pub fn start_recording(&self) -> Result<(), PipelineError> {
+        let mut inner = self.inner.lock().map_err(|e| PipelineError::Lock(e.to_string()))?;
+
+        // State guard: only allow starting from Idle or Error states
+        if !inner.state.can_start_recording() {
+            return Err(PipelineError::AlreadyRecording);
+        }
+
+        // Create a new cancellation token for this session
+        let cancel_token = CancellationToken::new();
+        inner.cancel_token = Some(cancel_token);
+
+        // Optionally tee capture audio into a live STT session.
+        let stream_audio_rx = if inner.config.stt_streaming_enabled {
+            let (audio_tx, audio_rx) = audio_chunk_channel();
+            inner.audio_capture.set_chunk_sink(Some(audio_tx));
+            Some(audio_rx)
+        } else {
+            inner.audio_capture.set_chunk_sink(None);
+            None
+        };
+
+        let max_duration = inner.config.max_duration_secs;
+        // Clone out of the config to avoid borrowing `inner` immutably while calling into
+        // `audio_capture` mutably.
+        let input_device_name = inner.config.input_device_name.clone();
+        match inner
+            .audio_capture
+            .start_with_device_name(max_duration, input_device_name.as_deref())
+        {
+            Ok(()) => {
+                inner.state = PipelineState::Recording;
+                if let Some(audio_rx) = stream_audio_rx {
+                    inner.start_stt_stream(audio_rx);
+                }
+                if inner.config.llm_streaming_enabled {
+                    let (deltas_tx, deltas_rx) = llm_delta_channel();
+                    inner.llm_deltas = Some(deltas_tx);
+                    inner.llm_delta_events = Some(deltas_rx);
+                }
+                log::info!("Pipeline: Recording started");
+                Ok(())
+            }
+            Err(e) => {
+                inner.set_error(&format!("Failed to start recording: {}", e));
+                Err(PipelineError::AudioCapture(e))
+            }
+        }
+    }
+
+    /// Stop recording and return the raw WAV audio
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn stop_recording(&self) -> Result<Vec<u8>, PipelineError> {
+        let mut inner = self.inner.lock().map_err(|e| PipelineError::Lock(e.to_string()))?;
+
+        if !inner.state.can_stop_recording() {
+            return Err(PipelineError::NotRecording);
+        }
+
+        let cfg = AudioEncodeConfig {
+            noise_gate_threshold_dbfs: inner.config.noise_gate_threshold_dbfs,
+            downmix_to_mono: inner.config.audio_downmix_to_mono,
+            resample_to_16khz: inner.config.audio_resample_to_16khz,
+            highpass_enabled: inner.config.audio_highpass_enabled,
+            agc_enabled: inner.config.audio_agc_enabled,
+            noise_suppression_enabled: inner.config.audio_noise_suppression_enabled,
+            detect_speech_presence: inner.config.quiet_audio_require_speech,
+            encoding: AudioEncoding::Wav,
+        };
+
+        match inner.audio_capture.stop_and_get_wav_with_diagnostics(cfg)
+        {
+            Ok((wav_bytes, diagnostics)) => {
+                // Keep a copy for STT testing/debugging UI.
+                inner.last_wav_bytes = Some(wav_bytes.clone());
+                inner.last_recording_diagnostics = Some(diagnostics);
+
+                // Check size limit
+                let max_bytes = inner.config.max_recording_bytes;
+                if max_bytes > 0 && wav_bytes.len() > max_bytes {
+                    inner.set_error(&format!(
+                        "Recording too large: {} bytes",
+                        wav_bytes.len()
+                    ));
+                    return Err(PipelineError::RecordingTooLarge(wav_bytes.len(), max_bytes));
+                }
+
+                inner.reset_to_idle();
+                log::info!(
+                    "Pipeline: Recording stopped, {} bytes captured",
+                    wav_bytes.len()
+                );
+                Ok(wav_bytes)
+            }
+            Err(e) => {
+                inner.set_error(&format!("Failed to stop recording: {}", e));
+                Err(PipelineError::AudioCapture(e))
+            }
+        }
+    }
+
+    /// Stop recording and return a before/after pair of WAV bytes.
+    ///
+    /// - before: raw capture with no preprocessing/gates
+    /// - after: capture encoded with the current audio settings
+    ///
+    /// Intended for settings UI A/B testing.
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn stop_recording_before_after(&self) -> Result<(Vec<u8>, Vec<u8>), PipelineError> {
+        let mut inner = self.inner.lock().map_err(|e| PipelineError::Lock(e.to_string()))?;
+
+        if !inner.state.can_stop_recording() {
+            return Err(PipelineError::NotRecording);
+        }
+
+        let after_cfg = AudioEncodeConfig {
+            noise_gate_threshold_dbfs: inner.config.noise_gate_threshold_dbfs,
+            downmix_to_mono: inner.config.audio_downmix_to_mono,
+            resample_to_16khz: inner.config.audio_resample_to_16khz,
+            highpass_enabled: inner.config.audio_highpass_enabled,
+            agc_enabled: inner.config.audio_agc_enabled,
+            noise_suppression_enabled: inner.config.audio_noise_suppression_enabled,
+            detect_speech_presence: inner.config.quiet_audio_require_speech,
+            encoding: AudioEncoding::Wav,
+        };
+
+        match inner.audio_capture.stop_and_get_wav_before_after(after_cfg) {
+            Ok((before_wav, after_wav, diagnostics)) => {
+                // Keep a copy of the processed output for STT test + debugging.
+                inner.last_wav_bytes = Some(after_wav.clone());
+                inner.last_recording_diagnostics = Some(diagnostics);
+
+                // Check size limit (both, to avoid surprising huge payloads)
+                let max_bytes = inner.config.max_recording_bytes;
+                if max_bytes > 0 {
+                    if before_wav.len() > max_bytes {
+                        inner.set_error(&format!(
+                            "Recording too large: {} bytes",
+                            before_wav.len()
+                        ));
+                        return Err(PipelineError::RecordingTooLarge(before_wav.len(), max_bytes));
+                    }
+                    if after_wav.len() > max_bytes {
+                        inner.set_error(&format!(
+                            "Recording too large: {} bytes",
+                            after_wav.len()
+                        ));
+                        return Err(PipelineError::RecordingTooLarge(after_wav.len(), max_bytes));
+                    }
+                }
+
+                inner.reset_to_idle();
+                Ok((before_wav, after_wav))
+            }
+            Err(e) => {
+                inner.set_error(&format!("Failed to stop recording: {}", e));
+                Err(PipelineError::AudioCapture(e))
+            }
+        }
+    }
+
+    /// Transcribe the last captured audio (WAV bytes) using the current effective STT settings.
+    ///
+    /// This is intended for settings UI testing and debugging.
+    pub async fn transcribe_last_audio_for_profile(
+        &self,
+        profile_id: Option<&str>,
+    ) -> Result<String, PipelineError> {
+        let (wav_bytes, stt_provider, retry_config, cancel_token) = {
+            let mut inner = self
+                .inner
+                .lock()
+                .map_err(|e| PipelineError::Lock(e.to_string()))?;
+
+            let wav_bytes = inner
+                .last_wav_bytes
+                .clone()
+                .ok_or_else(|| {
+                    PipelineError::Config(
+                        "No audio captured yet. Record once to create test audio.".to_string(),
+                    )
+                })?;
+
+            let config = inner.config.clone();
+
+            // Resolve per-profile overrides. Note: program prompt profiles live under llm_config.
+            let profile = profile_id
+                .and_then(|id| {
+                    if id == "default" {
+                        None
+                    } else {
+                        Some(id)
+                    }
+                })
+                .and_then(|id| {
+                    config
+                        .llm_config
+                        .program_prompt_profiles
+                        .iter()
+                        .find(|p| p.id == id)
+                        .cloned()
+                });
+
+            let stt_provider = match inner.resolve_stt_provider(profile.as_ref()) {
+                Ok(candidate) => candidate.provider,
+                Err(e) => {
+                    inner.set_error(&format!("No STT provider configured: {}", e));
+                    return Err(PipelineError::NoProvider);
+                }
+            };
+
+            let cancel_token = inner
+                .cancel_token
+                .clone()
+                .unwrap_or_else(CancellationToken::new);
+
+            (
+                wav_bytes,
+                stt_provider,
+                config.retry_config.clone(),
+                cancel_token,
+            )
+        };
+
+        let wav = Arc::new(wav_bytes);
+        let format = AudioFormat::default();
+
+        let transcription_future = async {
+            with_retry(&retry_config, || {
+                let provider = stt_provider.clone();
+                let wav = wav.clone();
+                let format = format.clone();
+
+                async move {
+                    provider.transcribe(wav.as_slice(), &format).await
+                }
+            })
+            .await
+        };
+
+        // Cancellation protection (test endpoint intentionally does NOT enforce timeout)
+        tokio::select! {
+            biased;
+
+            _ = cancel_token.cancelled() => {
+                Err(PipelineError::Cancelled)
+            }
+
+            result = transcription_future => {
+                result
+                    .map(normalize_stt_text)
+                    .map_err(PipelineError::from)
+            }
+        }
+    }
+
+    /// Stop recording and transcribe the audio, returning a detailed result.
+    ///
+    /// This is the main end-to-end function for voice dictation.
+    /// Includes:
+    /// - Automatic retry with exponential backoff on transient failures
+    /// - Timeout protection
+    /// - Cancellation support
+    /// - Proper error recovery
+    /// - Optional LLM formatting
+    pub async fn stop_and_transcribe_detailed(
+        &self,
+    ) -> Result<TranscriptionResult, PipelineError> {
+        self.stop_and_process(None).await
+    }
+
+    /// Stop recording and rewrite `selection` according to the recorded instruction.
+    ///
+    /// The transcript is the instruction; `final_text` is the transformed selection. Unlike
+    /// dictation, there is no raw-transcript fallback: a failed rewrite is an error.
+    pub async fn stop_and_transform_selection(
+        &self,
+        selection: String,
+    ) -> Result<TranscriptionResult, PipelineError> {
+        self.stop_and_process(Some(selection)).await
+    }
+
+    /// Apply the personal dictionary replacements to `stt_text` and record them in the request
+    /// log.
+    fn apply_dictionary(&self, stt_text: &str, dictionary: &[DictionaryEntry]) -> String {
+        let (text, replacements) = apply_dictionary(stt_text, dictionary);
+        if replacements.is_empty() {
+            return text;
+        }
+
+        log::info!("Pipeline: Applied {} dictionary replacement(s)", replacements.len());
+        if let Some(store) = self.request_log_store() {
+            store.with_current(|log| {
+                for replacement in &replacements {
+                    log.info(format!(
+                        "Dictionary: \"{}\" -> \"{}\"",
+                        replacement.spoken, replacement.term
+                    ));
+                }
+                log.dictionary_replacements.extend(replacements);
+            });
+        }
+        text
+    }
+
+    /// Expand the voice snippets triggered in `stt_text` and record them in the request log.
+    ///
+    /// Only the triggered snippets are rendered: filling a body can read the clipboard and the
+    /// foreground window.
+    fn expand_snippets(
+        &self,
+        stt_text: &str,
+        snippets: &DictationSnippets,
+    ) -> Result<SnippetExpansion, PipelineError> {
+        let triggered = expand_snippets(stt_text, &snippets.snippets);
+        if triggered.is_empty() {
+            return Ok(triggered);
+        }
+
+        let matched: Vec<Snippet> = snippets
+            .snippets
+            .iter()
+            .filter(|snippet| triggered.expanded.iter().any(|e| e.snippet_id == snippet.id))
+            .cloned()
+            .collect();
+        let rendered = {
+            let inner = self.inner.lock().map_err(|e| PipelineError::Lock(e.to_string()))?;
+            inner.render_snippets(matched, snippets.profile.as_ref(), snippets.live)
+        };
+        // Match again so the body-ending punctuation check sees the rendered bodies.
+        let expansion = expand_snippets(stt_text, &rendered);
+
+        log::info!("Pipeline: Expanded {} snippet(s)", expansion.expanded.len());
+        if let Some(store) = self.request_log_store() {
+            store.with_current(|log| {
+                for expanded in &expansion.expanded {
+                    log.info(format!("Expanded snippet \"{}\"", expanded.trigger));
+                }
+                log.snippet_expansions.extend(expansion.expanded.iter().cloned());
+            });
+        }
+        Ok(expansion)
+    }
+
+    /// Put the snippet bodies back into the LLM rewrite of `expansion.marked_text()`.
+    ///
+    /// A rewrite that lost or duplicated a marker is discarded for the unrewritten text.
+    fn restore_snippet_markers(&self, expansion: &SnippetExpansion, rewritten: String) -> String {
+        if expansion.is_empty() {
+            return rewritten;
+        }
+
+        match expansion.restore_markers(&rewritten) {
+            Some(text) => text,
+            None => {
+                log::warn!("Pipeline: LLM rewrite mangled the snippet markers, using the transcript");
+                if let Some(store) = self.request_log_store() {
+                    store.with_current(|log| {
+                        log.warn("LLM rewrite mangled the snippet markers; rewrite discarded")
+                    });
+                }
+                expansion.text()
+            }
+        }
+    }
+
+    fn request_log_store(&self) -> Option<RequestLogStore> {
+        self.inner
+            .lock()
+            .ok()
+            .and_then(|inner| inner.config.request_log_store.clone())
+    }
+
+    async fn stop_and_process(
+        &self,
+        transform: Option<String>,
+    ) -> Result<TranscriptionResult, PipelineError> {
+        // Phase 1: Stop recording and prepare for transcription (synchronous, holds lock briefly)
+        let learns = transform.is_none();
+        let (wav_bytes, stt_stream, stt_chain, rewrite, profile_id, retry_config, timeout, cancel_token) = {
+            let mut inner = self.inner.lock().map_err(|e| PipelineError::Lock(e.to_string()))?;
+
+            if !inner.state.can_stop_recording() {
+                return Err(PipelineError::NotRecording);
+            }
+
+            let encode_cfg = AudioEncodeConfig {
+                noise_gate_threshold_dbfs: inner.config.noise_gate_threshold_dbfs,
+                downmix_to_mono: inner.config.audio_downmix_to_mono,
+                resample_to_16khz: inner.config.audio_resample_to_16khz,
+                highpass_enabled: inner.config.audio_highpass_enabled,
+                agc_enabled: inner.config.audio_agc_enabled,
+                noise_suppression_enabled: inner.config.audio_noise_suppression_enabled,
+                detect_speech_presence: inner.config.quiet_audio_require_speech,
+                encoding: AudioEncoding::Wav,
+            };
+
+            let (wav_bytes, diagnostics) = match inner
+                .audio_capture
+                .stop_and_get_wav_with_diagnostics(encode_cfg)
+            {
+                Ok(out) => out,
+                Err(e) => {
+                    inner.set_error(&format!("Failed to stop recording: {}", e));
+                    return Err(PipelineError::AudioCapture(e));
+                }
+            };
+
+            let stats = diagnostics.stats;
+
+            // Capture has stopped, so the streaming session (if any) has received all audio.
+            // Dropping it on the early returns below cancels it.
+            let stt_stream = inner.stt_stream.take();
+            // Likewise, dropping the delta sender ends the LLM delta stream.
+            let llm_deltas = inner.llm_deltas.take();
+            inner.llm_delta_events = None;
+
+            // Persist diagnostics for UI readout.
+            inner.last_recording_diagnostics = Some(diagnostics);
+
+            // Keep a copy for STT testing/debugging UI.
+            inner.last_wav_bytes = Some(wav_bytes.clone());
+
+            // Optional extra hallucination protection: if VAD says "no speech", skip STT.
+            if inner.config.quiet_audio_gate_enabled
+                && inner.config.quiet_audio_require_speech
+                && inner
+                    .last_recording_diagnostics
+                    .and_then(|d| d.speech_detected)
+                    == Some(false)
+            {
+                log::info!(
+                    "Pipeline: Skipping STT because no speech was detected by offline VAD (duration {:.2}s, rms {:.1} dBFS, peak {:.1} dBFS)",
+                    stats.duration_secs,
+                    amp_to_dbfs(stats.rms),
+                    amp_to_dbfs(stats.peak)
+                );
+
+                inner.reset_to_idle();
+                return Ok(TranscriptionResult {
+                    stt_text: String::new(),
+                    stt_transcript: Transcript::default(),
+                    final_text: String::new(),
+                    stt_duration_ms: 0,
+                    stt_provider_used: None,
+                    stt_model_used: None,
+                    llm_duration_ms: None,
+                    llm_provider_used: None,
+                    llm_model_used: None,
+                    llm_outcome: LlmOutcome::NotAttempted,
+                    profile_id: None,
+                });
+            }
+
+            if inner.config.quiet_audio_gate_enabled
+                && is_effectively_quiet(
+                    stats,
+                    inner.config.quiet_audio_min_duration_secs,
+                    inner.config.quiet_audio_rms_dbfs_threshold,
+                    inner.config.quiet_audio_peak_dbfs_threshold,
+                )
+            {
+                log::info!(
+                    "Pipeline: Skipping STT because recording is quiet (duration {:.2}s, rms {:.1} dBFS, peak {:.1} dBFS)",
+                    stats.duration_secs,
+                    amp_to_dbfs(stats.rms),
+                    amp_to_dbfs(stats.peak)
+                );
+
+                inner.reset_to_idle();
+                return Ok(TranscriptionResult {
+                    stt_text: String::new(),
+                    stt_transcript: Transcript::default(),
+                    final_text: String::new(),
+                    stt_duration_ms: 0,
+                    stt_provider_used: None,
+                    stt_model_used: None,
+                    llm_duration_ms: None,
+                    llm_provider_used: None,
+                    llm_model_used: None,
+                    llm_outcome: LlmOutcome::NotAttempted,
+                    profile_id: None,
+                });
+            }
+
+            // Check size limit (long recordings are split, and the limit applies per chunk)
+            let max_bytes = inner.config.max_recording_bytes;
+            if max_bytes > 0
+                && wav_bytes.len() > max_bytes
+                && !inner.config.stt_chunking.should_split(stats.duration_secs)
+            {
+                inner.set_error(&format!("Recording too large: {} bytes", wav_bytes.len()));
+                return Err(PipelineError::RecordingTooLarge(wav_bytes.len(), max_bytes));
+            }
+
+            inner.state = PipelineState::Transcribing;
+
+            let llm_config = inner.config.llm_config.clone();
+            let active_profile = select_profile_for_foreground_app(&llm_config);
+            inner.last_profile_id = active_profile.as_ref().map(|p| p.id.clone());
+            let llm_prompts = active_profile
+                .as_ref()
+                .map(|p| p.prompts.clone())
+                .unwrap_or_else(|| llm_config.prompts.clone());
+
+            // Resolve effective STT settings (profile overrides -> global defaults, with safe fallback)
+            let desired_timeout = active_profile
+                .as_ref()
+                .and_then(|p| p.stt_timeout_seconds)
+                .map(|s| seconds_to_duration_or(s, inner.config.transcription_timeout))
+                .unwrap_or(inner.config.transcription_timeout);
+
+            let mut stt_chain = match inner.resolve_stt_chain(active_profile.as_ref()) {
+                Ok(chain) => chain,
+                Err(e) => {
+                    inner.set_error(&format!("No STT provider configured: {}", e));
+                    return Err(PipelineError::NoProvider);
+                }
+            };
+
+            // Translation mode: the STT translate task when possible, otherwise the LLM stage.
+            // A transform instruction is never output, so it is transcribed as spoken.
+            let translation = match transform {
+                Some(_) => TranslationConfig::default(),
+                None => inner.effective_translation(active_profile.as_ref()),
+            };
+            let translation_route = stt_chain.route_translation(&translation);
+
+            // Resolve the effective LLM provider/model (profile overrides -> global defaults) and its
+            // fallbacks, gated by the active profile's enable flag (falls back to the global enable).
+            let effective_llm_enabled = active_profile
+                .as_ref()
+                .and_then(|p| p.rewrite_llm_enabled)
+                .unwrap_or(inner.config.llm_config.enabled);
+
+            let mut llm_chain = if transform.is_some()
+                || effective_llm_enabled
+                || translation_route == TranslationRoute::Llm
+            {
+                inner.resolve_llm_chain(active_profile.as_ref())
+            } else {
+                None
+            };
+            // The dictionary corrects the dictated text; a transform instruction is sent as spoken.
+            let dictionary = match transform {
+                Some(_) => Vec::new(),
+                None => inner.dictation_dictionary(active_profile.as_ref()),
+            };
+            let llm_prompts = match transform {
+                Some(_) => PromptSections::transform(llm_config.transform_prompt.clone()),
+                None => translation_prompts(
+                    with_dictionary(&llm_prompts, &dictionary),
+                    effective_llm_enabled,
+                    translation_route,
+                    &translation,
+                ),
+            };
+            if transform.is_some() {
+                match llm_chain.as_mut() {
+                    // The rewrite is meant to differ from the spoken instruction.
+                    Some(chain) => chain.guardrail.enabled = false,
+                    None => {
+                        let message = "Transform selection needs an LLM provider".to_string();
+                        inner.set_error(&message);
+                        return Err(PipelineError::Config(message));
+                    }
+                }
+            }
+            // Fill the prompt template variables ({{app_name}}, {{clipboard}}, ...) now, while
+            // the target app is still in the foreground.
+            let llm_prompts = match &llm_chain {
+                Some(_) => inner.render_prompts(llm_prompts, active_profile.as_ref(), true),
+                None => llm_prompts,
+            };
+
+            // A transform instruction is never inserted, so snippets don't apply to it.
+            let snippets = match transform {
+                Some(_) => DictationSnippets::default(),
+                None => inner.dictation_snippets(active_profile.as_ref(), true),
+            };
+
+            // A streaming session transcribes in the spoken language; it can't serve STT translation.
+            let stt_stream = stt_stream.filter(|_| translation_route != TranslationRoute::Stt);
+
+            let retry_config = inner.config.retry_config.clone();
+            let cancel_token = inner.cancel_token.clone().unwrap_or_else(CancellationToken::new);
+
+            let rewrite = DictationRewrite {
+                dictionary,
+                snippets,
+                llm_chain,
+                llm_prompts,
+                // The transformed selection is pasted in one go.
+                llm_deltas: llm_deltas.filter(|_| transform.is_none()),
+                transform,
+            };
+
+            (
+                wav_bytes,
+                stt_stream,
+                stt_chain,
+                rewrite,
+                active_profile.map(|p| p.id),
+                retry_config,
+                desired_timeout,
+                cancel_token,
+            )
+        };
+
+        log::info!(
+            "Pipeline: Starting transcription ({} bytes, timeout {:?})",
+            wav_bytes.len(),
+            timeout
+        );
+
+        let stt_start = std::time::Instant::now();
+
+        // Phase 2a: Finish the streaming session, if one ran during recording. Most of the
+        // audio has usually been transcribed by now; on failure fall through to batch STT.
+        let streamed_result = match stt_stream {
+            Some(stream) => {
+                let provider_name = stream.provider_name();
+                let result = tokio::select! {
+                    biased;
+
+                    _ = cancel_token.cancelled() => {
+                        log::info!("Pipeline: Streaming transcription cancelled");
+                        Err(PipelineError::Cancelled)
+                    }
+
+                    _ = tokio::time::sleep(timeout) => {
+                        Err(PipelineError::Timeout(timeout))
+                    }
+
+                    result = stream.finish() => {
+                        result.map_err(PipelineError::from)
+                    }
+                };
+
+                match result {
+                    Err(e) if !matches!(e, PipelineError::Cancelled) => {
+                        log::warn!(
+                            "Pipeline: Streaming STT ({}) failed ({}), falling back to batch transcription",
+                            provider_name,
+                            e
+                        );
+                        None
+                    }
+                    other => {
+                        // The stream was opened from the same profile resolution as the primary.
+                        let primary = stt_chain.primary();
+                        let model = if primary.provider.name() == provider_name {
+                            primary.model.clone()
+                        } else {
+                            None
+                        };
+                        Some(other.map(|transcript| (transcript, provider_name.to_string(), model)))
+                    }
+                }
+            }
+            None => None,
+        };
+
+        let stt_result = match streamed_result {
+            Some(result) => result,
+            None => {
+                // Phase 2b: Transcribe with retry logic and provider fallback (async, outside the lock).
+                // After a failed streaming session only the rest of the original deadline is left.
+                let remaining = timeout.saturating_sub(stt_start.elapsed());
+                if remaining.is_zero() {
+                    Err(PipelineError::Timeout(timeout))
+                } else {
+                    stt_chain
+                        .transcribe_recording(wav_bytes, &retry_config, remaining, &cancel_token)
+                        .await
+                        .map(|(transcript, used)| {
+                            (transcript, used.provider.name().to_string(), used.model.clone())
+                        })
+                }
+            }
+        };
+
+        let (stt_text, stt_transcript, stt_provider_used, stt_model_used) = match stt_result {
+            Ok((transcript, provider, model)) => {
+                let (text, transcript) = split_stt_transcript(stt_chain.filter_hallucinations(transcript));
+                (text, transcript, provider, model)
+            }
+            Err(e) => {
+                let mut inner = self
+                    .inner
+                    .lock()
+                    .map_err(|err| PipelineError::Lock(err.to_string()))?;
+                if matches!(e, PipelineError::Cancelled) {
+                    inner.reset_to_idle();
+                } else {
+                    inner.set_error(&e.to_string());
+                }
+                return Err(e);
+            }
+        };
+        let stt_duration_ms = stt_start.elapsed().as_millis() as u64;
+        log::info!("Pipeline: STT complete, {} chars", stt_text.len());
+
+        // Phase 3: Dictionary, snippets and optional LLM formatting
+        let rewritten = self
+            .rewrite_dictation(&stt_text, rewrite, &retry_config, &cancel_token, true)
+            .await?;
+        let final_text = rewritten.final_text;
+
+        // Phase 4: Update state to idle
+        {
+            let mut inner = self.inner.lock().map_err(|e| PipelineError::Lock(e.to_string()))?;
+            inner.reset_to_idle();
+            if !final_text.trim().is_empty() {
+                inner.previous_output = Some(final_text.clone());
+            }
+            if learns {
+                inner.learn_from_redictation(&stt_text, profile_id.as_deref());
+            }
+            log::info!("Pipeline: Complete, {} chars output", final_text.len());
+        }
+
+        Ok(TranscriptionResult {
+            stt_text,
+            stt_transcript,
+            final_text,
+            stt_duration_ms,
+            stt_provider_used: Some(stt_provider_used),
+            stt_model_used,
+            llm_duration_ms: rewritten.llm_duration_ms,
+            llm_provider_used: rewritten.llm_provider_used,
+            llm_model_used: rewritten.llm_model_used,
+            llm_outcome: rewritten.llm_outcome,
+            profile_id,
+        })
+    }
+
+    /// Phase 3 of a dictation: dictionary replacements, voice snippets and the optional LLM
+    /// rewrite of `stt_text`. A foreground run shows the rewrite as pipeline state.
+    async fn rewrite_dictation(
+        &self,
+        stt_text: &str,
+        rewrite: DictationRewrite,
+        retry_config: &RetryConfig,
+        cancel_token: &CancellationToken,
+        foreground: bool,
+    ) -> Result<RewriteOutput, PipelineError> {
+        let DictationRewrite {
+            dictionary,
+            snippets,
+            llm_chain,
+            llm_prompts,
+            llm_deltas,
+            transform,
+        } = rewrite;
+
+        // Personal dictionary replacements, then voice snippets: a dictation that is just a
+        // trigger is inserted as is, without a rewrite.
+        let corrected = self.apply_dictionary(stt_text, &dictionary);
+        let expansion = self.expand_snippets(&corrected, &snippets)?;
+
+        // Nothing to format when STT (or the hallucination filter) left no text.
+        let Some(chain) =
+            llm_chain.filter(|_| !stt_text.trim().is_empty() && !expansion.full_match)
+        else {
+            return Ok(RewriteOutput {
+                final_text: expansion.text(),
+                llm_duration_ms: None,
+                llm_provider_used: None,
+                llm_model_used: None,
+                llm_outcome: LlmOutcome::NotAttempted,
+            });
+        };
+
+        // Expose the optional LLM step as a distinct phase for UI.
+        if foreground {
+            let mut inner = self
+                .inner
+                .lock()
+                .map_err(|e| PipelineError::Lock(e.to_string()))?;
+            if inner.state == PipelineState::Transcribing {
+                inner.state = PipelineState::Rewriting;
+            }
+        }
+
+        log::info!("Pipeline: Applying LLM formatting");
+        let llm_start = std::time::Instant::now();
+
+        // A transform sends the selection along with the spoken instruction. Inline snippets
+        // are sent as markers so the rewrite can't alter them (and aren't streamed).
+        let llm_input = match &transform {
+            Some(selection) => transform_user_message(selection, stt_text),
+            None if expansion.is_empty() => corrected,
+            None => expansion.marked_text(),
+        };
+        let (llm_prompts, llm_deltas) = if expansion.is_empty() {
+            (llm_prompts, llm_deltas)
+        } else {
+            (llm_prompts.with_snippet_markers(), None)
+        };
+
+        // The chain falls back to the raw transcript on timeout/failure instead of failing.
+        let llm_result = tokio::select! {
+            biased;
+
+            _ = cancel_token.cancelled() => {
+                log::info!("Pipeline: LLM formatting cancelled");
+                Err(PipelineError::Cancelled)
+            }
+
+            output = chain.format(&llm_input, &llm_prompts, retry_config, llm_deltas.as_ref()) => Ok(output),
+        };
+
+        let llm_duration_ms = Some(llm_start.elapsed().as_millis() as u64);
+
+        let output = match llm_result {
+            Ok(output) => output,
+            Err(e) => {
+                if foreground {
+                    let mut inner = self.inner.lock().map_err(|e| PipelineError::Lock(e.to_string()))?;
+                    inner.reset_to_idle();
+                }
+                return Err(e);
+            }
+        };
+
+        let final_text = match output.text {
+            Some(formatted) => {
+                log::info!("Pipeline: LLM formatted {} -> {} chars", stt_text.len(), formatted.len());
+                self.restore_snippet_markers(&expansion, formatted)
+            }
+            // Pasting the instruction over the selection would lose the selection.
+            None if transform.is_some() => {
+                let reason = match &output.outcome {
+                    LlmOutcome::TimedOut => "LLM timed out".to_string(),
+                    LlmOutcome::Failed(err) => err.clone(),
+                    _ => "no LLM output".to_string(),
+                };
+                let mut inner = self.inner.lock().map_err(|e| PipelineError::Lock(e.to_string()))?;
+                inner.set_error(&reason);
+                return Err(PipelineError::Transform(reason));
+            }
+            None => expansion.text(),
+        };
+
+        Ok(RewriteOutput {
+            final_text,
+            llm_duration_ms,
+            llm_provider_used: Some(output.provider.name().to_string()),
+            llm_model_used: Some(output.provider.model().to_string()),
+            llm_outcome: output.outcome,
+        })
+    }
+
+    /// Transcribe provided WAV bytes using the same STT + optional LLM logic as the main pipeline.
+    ///
+    /// This is used for retrying failed requests from persisted audio.
+    pub async fn transcribe_wav_bytes_detailed(
+        &self,
+        wav_bytes: Vec<u8>,
+    ) -> Result<TranscriptionResult, PipelineError> {
+        self.transcribe_saved_wav(wav_bytes, SavedWavRun::Foreground).await
+    }
+
+    /// Re-transcribe a queued dictation under the profile it was recorded with (None = Default).
+    ///
+    /// Unlike `transcribe_wav_bytes_detailed`, this leaves the pipeline state and the audio kept
+    /// for STT testing alone, so the user can start a new dictation while it runs. The retry is
+    /// logged as a request of its own, linked to `request_id`, rather than into the current log.
+    pub async fn retranscribe_in_background(
+        &self,
+        request_id: &str,
+        wav_bytes: Vec<u8>,
+        profile_id: Option<&str>,
+    ) -> Result<TranscriptionResult, PipelineError> {
+        let run = SavedWavRun::Background { profile_id };
+        let Some(store) = self.request_log_store() else {
+            return self.transcribe_saved_wav(wav_bytes, run).await;
+        };
+
+        let config = self.config();
+        let scratch = RequestLogScratch::for_request(config.stt_provider, config.stt_model);
+        let audio_size_bytes = wav_bytes.len();
+        let result = scratch.scope(self.transcribe_saved_wav(wav_bytes, run)).await;
+
+        let mut log = scratch.snapshot();
+        log.retry_of = Some(request_id.to_string());
+        log.audio_size_bytes = Some(audio_size_bytes);
+        match &result {
+            Ok(result) => {
+                log.raw_transcript = Some(result.stt_text.clone());
+                log.stt_transcript =
+                    Some(result.stt_transcript.clone()).filter(|t| t.has_details());
+                log.formatted_transcript = Some(result.final_text.clone());
+                log.stt_duration_ms = Some(result.stt_duration_ms);
+                if let Some(provider) = &result.stt_provider_used {
+                    log.stt_provider = provider.clone();
+                    log.stt_model = result.stt_model_used.clone();
+                }
+                log.llm_duration_ms = result.llm_duration_ms;
+                if result.llm_attempted() {
+                    log.llm_provider = result.llm_provider_used.clone();
+                    log.llm_model = result.llm_model_used.clone();
+                }
+                log.info(format!(
+                    "Background retry of {} completed ({} chars)",
+                    request_id,
+                    result.final_text.len()
+                ));
+                log.complete_success();
+            }
+            Err(PipelineError::Cancelled) => log.complete_cancelled(),
+            Err(e) => log.complete_error(e.to_string()),
+        }
+        store.add_completed(log);
+
+        result
+    }
+
+    async fn transcribe_saved_wav(
+        &self,
+        wav_bytes: Vec<u8>,
+        run: SavedWavRun<'_>,
+    ) -> Result<TranscriptionResult, PipelineError> {
+        let foreground = matches!(run, SavedWavRun::Foreground);
+
+        // Phase 1: Resolve providers/config under lock.
+        let (stt_chain, rewrite, profile_id, retry_config, timeout, cancel_token) = {
+            let mut inner = self.inner.lock().map_err(|e| PipelineError::Lock(e.to_string()))?;
+
+            if foreground {
+                // Guard: don't run a retry while actively recording.
+                if inner.state == PipelineState::Recording {
+                    return Err(PipelineError::AlreadyRecording);
+                }
+                if matches!(inner.state, PipelineState::Transcribing | PipelineState::Rewriting) {
+                    return Err(PipelineError::Lock("Pipeline already transcribing".to_string()));
+                }
+
+                // Keep a copy for STT testing/debugging UI.
+                inner.last_wav_bytes = Some(wav_bytes.clone());
+            }
+
+            // Check size limit (long recordings are split, and the limit applies per chunk)
+            let max_bytes = inner.config.max_recording_bytes;
+            let duration_secs = wav_duration_secs(&wav_bytes).unwrap_or(0.0);
+            if max_bytes > 0
+                && wav_bytes.len() > max_bytes
+                && !inner.config.stt_chunking.should_split(duration_secs)
+            {
+                if foreground {
+                    inner.set_error(&format!("Recording too large: {} bytes", wav_bytes.len()));
+                }
+                return Err(PipelineError::RecordingTooLarge(wav_bytes.len(), max_bytes));
+            }
+
+            // Ensure we have a cancellation token for this attempt. A background run is not
+            // the current operation, so the user's cancel doesn't reach it.
+            let cancel_token = CancellationToken::new();
+            if foreground {
+                inner.state = PipelineState::Transcribing;
+                inner.cancel_token = Some(cancel_token.clone());
+            }
+
+            let llm_config = inner.config.llm_config.clone();
+            let active_profile = match run {
+                SavedWavRun::Foreground => select_profile_for_foreground_app(&llm_config),
+                SavedWavRun::Background { profile_id } => profile_id.and_then(|id| {
+                    llm_config
+                        .program_prompt_profiles
+                        .iter()
+                        .find(|p| p.id == id)
+                        .cloned()
+                }),
+            };
+            if foreground {
+                inner.last_profile_id = active_profile.as_ref().map(|p| p.id.clone());
+            }
+            let llm_prompts = active_profile
+                .as_ref()
+                .map(|p| p.prompts.clone())
+                .unwrap_or_else(|| llm_config.prompts.clone());
+
+            // Resolve effective STT settings (profile overrides -> global defaults, with safe fallback)
+            let desired_timeout = active_profile
+                .as_ref()
+                .and_then(|p| p.stt_timeout_seconds)
+                .map(|s| seconds_to_duration_or(s, inner.config.transcription_timeout))
+                .unwrap_or(inner.config.transcription_timeout);
+
+            let mut stt_chain = match inner.resolve_stt_chain(active_profile.as_ref()) {
+                Ok(chain) => chain,
+                Err(e) => {
+                    if foreground {
+                        inner.set_error(&format!("No STT provider configured: {}", e));
+                    } else {
+                        log::warn!("Pipeline: No STT provider configured for retry: {}", e);
+                    }
+                    return Err(PipelineError::NoProvider);
+                }
+            };
+
+            // Translation mode: the STT translate task when possible, otherwise the LLM stage.
+            let translation = inner.effective_translation(active_profile.as_ref());
+            let translation_route = stt_chain.route_translation(&translation);
+
+            // Resolve the effective LLM provider/model (profile overrides -> global defaults) and its
+            // fallbacks, gated by the active profile's enable flag (falls back to the global enable).
+            let effective_llm_enabled = active_profile
+                .as_ref()
+                .and_then(|p| p.rewrite_llm_enabled)
+                .unwrap_or(inner.config.llm_config.enabled);
+
+            let llm_chain = if effective_llm_enabled || translation_route == TranslationRoute::Llm {
+                inner.resolve_llm_chain(active_profile.as_ref())
+            } else {
+                None
+            };
+            let dictionary = inner.dictation_dictionary(active_profile.as_ref());
+            let llm_prompts = translation_prompts(
+                with_dictionary(&llm_prompts, &dictionary),
+                effective_llm_enabled,
+                translation_route,
+                &translation,
+            );
+            // Fill the prompt template variables ({{app_name}}, {{clipboard}}, ...) now. A
+            // background retry runs long after the dictation, so it leaves the live ones empty.
+            let llm_prompts = match &llm_chain {
+                Some(_) => inner.render_prompts(llm_prompts, active_profile.as_ref(), foreground),
+                None => llm_prompts,
+            };
+
+            let snippets = inner.dictation_snippets(active_profile.as_ref(), foreground);
+            let retry_config = inner.config.retry_config.clone();
+
+            let rewrite = DictationRewrite {
+                dictionary,
+                snippets,
+                llm_chain,
+                llm_prompts,
+                llm_deltas: None,
+                transform: None,
+            };
+
+            (
+                stt_chain,
+                rewrite,
+                active_profile.map(|p| p.id),
+                retry_config,
+                desired_timeout,
+                cancel_token,
+            )
+        };
+
+        log::info!(
+            "Pipeline: Starting retry transcription ({} bytes, timeout {:?})",
+            wav_bytes.len(),
+            timeout
+        );
+
+        // Phase 2: STT transcription (with provider fallback)
+        let stt_start = std::time::Instant::now();
+        let stt_result = stt_chain
+            .transcribe_recording(wav_bytes, &retry_config, timeout, &cancel_token)
+            .await
+            .map(|(transcript, used)| (transcript, used.provider.name().to_string(), used.model.clone()));
+
+        let (stt_text, stt_transcript, stt_provider_used, stt_model_used) = match stt_result {
+            Ok((transcript, provider, model)) => {
+                let (text, transcript) = split_stt_transcript(stt_chain.filter_hallucinations(transcript));
+                (text, transcript, provider, model)
+            }
+            Err(e) => {
+                if foreground {
+                    let mut inner = self
+                        .inner
+                        .lock()
+                        .map_err(|err| PipelineError::Lock(err.to_string()))?;
+                    if matches!(e, PipelineError::Cancelled) {
+                        inner.reset_to_idle();
+                    } else {
+                        inner.set_error(&e.to_string());
+                    }
+                }
+                return Err(e);
+            }
+        };
+
+        let stt_duration_ms = stt_start.elapsed().as_millis() as u64;
+        log::info!("Pipeline: Retry STT complete, {} chars", stt_text.len());
+
+        // Phase 3: Dictionary, snippets and optional LLM formatting
+        let rewritten = self
+            .rewrite_dictation(&stt_text, rewrite, &retry_config, &cancel_token, foreground)
+            .await?;
+        let final_text = rewritten.final_text;
+
+        // Phase 4: Reset to idle
+        if foreground {
+            let mut inner = self.inner.lock().map_err(|e| PipelineError::Lock(e.to_string()))?;
+            inner.reset_to_idle();
+            if !final_text.trim().is_empty() {
+                inner.previous_output = Some(final_text.clone());
+            }
+            log::info!("Pipeline: Retry complete, {} chars output", final_text.len());
+        }
+
+        Ok(TranscriptionResult {
+            stt_text,
+            stt_transcript,
+            final_text,
+            stt_duration_ms,
+            stt_provider_used: Some(stt_provider_used),
+            stt_model_used,
+            llm_duration_ms: rewritten.llm_duration_ms,
+            llm_provider_used: rewritten.llm_provider_used,
+            llm_model_used: rewritten.llm_model_used,
+            llm_outcome: rewritten.llm_outcome,
+            profile_id,
+        })
+    }
+
+    /// Program profile the last dictation ran under (None = Default).
+    pub fn last_profile_id(&self) -> Option<String> {
+        self.inner.lock().ok().and_then(|inner| inner.last_profile_id.clone())
+    }
+
+    /// The STT providers a retry under `profile_id` (None = Default) would try, in order.
+    pub fn stt_providers_for_profile(
+        &self,
+        profile_id: Option<&str>,
+    ) -> Result<Vec<String>, PipelineError> {
+        let mut inner = self.inner.lock().map_err(|e| PipelineError::Lock(e.to_string()))?;
+        let profile = profile_id.and_then(|id| {
+            inner
+                .config
+                .llm_config
+                .program_prompt_profiles
+                .iter()
+                .find(|p| p.id == id)
+                .cloned()
+        });
+        let chain = inner.resolve_stt_chain(profile.as_ref())?;
+        Ok(chain
+            .candidates
+            .iter()
+            .map(|c| c.provider.name().to_string())
+            .collect())
+    }
+
+    /// Stop recording and transcribe the audio.
+    ///
+    /// Kept for backwards compatibility. Prefer `stop_and_transcribe_detailed`.
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub async fn stop_and_transcribe(&self) -> Result<String, PipelineError> {
+        self.stop_and_transcribe_detailed()
+            .await
+            .map(|r| r.final_text)
+    }
+
+    /// Update configuration
+    ///
+    /// Note: This will not affect an in-progress recording.
+    pub fn update_config(&self, config: PipelineConfig) -> Result<(), PipelineError> {
+        let mut inner = self.inner.lock().map_err(|e| PipelineError::Lock(e.to_string()))?;
+
+        // Don't update config while recording - could cause issues
+        if inner.state == PipelineState::Recording {
+            log::warn!("Pipeline: Config update requested while recording, will take effect after current session");
+        }
+
+        inner.config = config.clone();
+        inner.provider_health.set_config(config.provider_health.clone());
+        inner.stt_registry = SttRegistry::new();
+        inner.initialize_providers(&config);
+        // Update VAD config on audio capture
+        inner.audio_capture.set_vad_config(config.vad_config);
+        log::info!("Pipeline configuration updated");
+        Ok(())
+    }
+
+    /// Check if recording
+    pub fn is_recording(&self) -> bool {
+        self.inner
+            .lock()
+            .map(|inner| inner.state == PipelineState::Recording)
+            .unwrap_or(false)
+    }
+
+    /// Take the transcript event receiver of the live streaming session, if one is running.
+    ///
+    /// Events end when the session finishes. Can only be taken once per recording.
+    pub fn take_transcript_events(
+        &self,
+    ) -> Option<tokio::sync::mpsc::UnboundedReceiver<TranscriptEvent>> {
+        self.inner
+            .lock()
+            .ok()
+            .and_then(|mut inner| inner.stt_stream.as_mut().and_then(|s| s.take_events()))
+    }
+
+    /// Take the LLM delta receiver of the current recording, if LLM streaming is enabled.
+    ///
+    /// Deltas arrive while the recording is rewritten; the channel closes once the rewrite
+    /// step is over (or skipped). Can only be taken once per recording.
+    pub fn take_llm_deltas(&self) -> Option<LlmDeltaReceiver> {
+        self.inner
+            .lock()
+            .ok()
+            .and_then(|mut inner| inner.llm_delta_events.take())
+    }
+
+    /// Health of every STT/LLM provider the pipeline has used.
+    pub fn provider_health(&self) -> Vec<ProviderHealthSnapshot> {
+        self.inner
+            .lock()
+            .map(|inner| inner.provider_health.snapshot())
+            .unwrap_or_default()
+    }
+
+    /// Forget recorded provider health, closing every circuit.
+    pub fn reset_provider_health(&self) {
+        if let Ok(inner) = self.inner.lock() {
+            inner.provider_health.reset();
+        }
+    }
+
+    /// Get a clone of the last captured WAV bytes, if present.
+    pub fn clone_last_wav_bytes(&self) -> Option<Vec<u8>> {
+        self.inner.lock().ok().and_then(|inner| inner.last_wav_bytes.clone())
+    }
+
+    /// Get a copy of the last recording diagnostics (raw stats + optional speech detection).
+    pub fn last_recording_diagnostics(&self) -> Option<AudioCaptureDiagnostics> {
+        self.inner
+            .lock()
+            .ok()
+            .and_then(|inner| inner.last_recording_diagnostics)
+    }
+
+    /// Poll for VAD events (non-blocking)
+    ///
+    /// Returns the next VAD event if one is available, or None if no events are pending.
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn poll_vad_event(&self) -> Option<AudioCaptureEvent> {
+        self.inner
+            .lock()
+            .ok()
+            .and_then(|inner| inner.audio_capture.poll_vad_event())
+    }
+
+    /// Check if VAD auto-stop is enabled
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn is_vad_auto_stop_enabled(&self) -> bool {
+        self.inner
+            .lock()
+            .map(|inner| inner.audio_capture.is_vad_auto_stop_enabled())
+            .unwrap_or(false)
+    }
+
+    /// Cancel current operation
+    ///
+    /// This will:
+    /// - Stop any ongoing recording
+    /// - Signal cancellation to any in-flight transcription
+    /// - Reset the pipeline to Idle state
+    pub fn cancel(&self) {
+        if let Ok(mut inner) = self.inner.lock() {
+            if !inner.state.can_cancel() {
+                log::debug!("Pipeline: Cancel requested but nothing to cancel (state: {:?})", inner.state);
+                return;
+            }
+
+            // Signal cancellation to any async tasks
+            if let Some(token) = inner.cancel_token.take() {
+                token.cancel();
+            }
+
+            // Stop audio capture if recording
+            if inner.state == PipelineState::Recording {
+                inner.audio_capture.stop();
+            }
+
+            inner.reset_to_idle();
+            log::info!("Pipeline: Cancelled and reset to idle");
+        }
+    }
+
+    /// Force reset the pipeline to idle state
+    ///
+    /// Use this to recover from stuck states. Cancels any in-progress operations.
+    pub fn force_reset(&self) {
+        if let Ok(mut inner) = self.inner.lock() {
+            // Cancel any async tasks
+            if let Some(token) = inner.cancel_token.take() {
+                token.cancel();
+            }
+
+            // Force stop audio capture
+            inner.audio_capture.stop();
+
+            // Reset state
+            inner.reset_to_idle();
+            log::warn!("Pipeline: Force reset to idle");
+        }
+    }
+
+    /// Get current state
+    pub fn state(&self) -> PipelineState {
+        self.inner
+            .lock()
+            .map(|inner| inner.state)
+            .unwrap_or(PipelineState::Error)
+    }
+
+    /// Get the most recent realtime audio input level snapshot.
+    ///
+    /// This is cheap and intended for UI metering (e.g., overlay waveform). The snapshot is
+    /// updated from the CPAL input callback while recording.
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn audio_level_snapshot(&self) -> AudioLevelSnapshot {
+        self.inner
+            .lock()
+            .map(|inner| inner.audio_capture.level_snapshot())
+            .unwrap_or(AudioLevelSnapshot {
+                seq: 0,
+                rms: 0.0,
+                peak: 0.0,
+            })
+    }
+
+    /// Get the name of the current STT provider
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn current_provider_name(&self) -> String {
+        self.inner
+            .lock()
+            .map(|inner| inner.stt_registry.current_name().to_string())
+            .unwrap_or_default()
+    }
+
+    /// Get a clone of the current pipeline configuration
+    pub fn config(&self) -> PipelineConfig {
+        self.inner
+            .lock()
+            .map(|inner| inner.config.clone())
+            .unwrap_or_default()
+    }
+
+    /// Current values for the prompt variables that `prompts` references (for previews).
+    pub fn prompt_context(
+        &self,
+        profile: Option<&crate::llm::ProgramPromptProfile>,
+        prompts: &PromptSections,
+    ) -> PromptContext {
+        self.inner
+            .lock()
+            .map(|inner| inner.prompt_context(profile, prompts, true))
+            .unwrap_or_default()
+    }
+
+    /// Check if the pipeline is in an error state
+    pub fn is_error(&self) -> bool {
+        self.inner
+            .lock()
+            .map(|inner| inner.state == PipelineState::Error)
+            .unwrap_or(true)
+    }
+
+    /// Whether there is a previously captured audio buffer available for testing.
+    pub fn has_last_audio(&self) -> bool {
+        self.inner
+            .lock()
+            .ok()
+            .and_then(|inner| inner.last_wav_bytes.as_ref().map(|b| !b.is_empty()))
+            .unwrap_or(false)
+    }
+
+    /// Get the cancellation token for external use (e.g., for coordinating with other async tasks)
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn get_cancel_token(&self) -> Option<CancellationToken> {
+        self.inner
+            .lock()
+            .ok()
+            .and_then(|inner| inner.cancel_token.clone())
+    }
+}
+
+impl Default for SharedPipeline {
+    fn default() -> Self {
+        Self::new(PipelineConfig::default())
+    }
+}
+
+impl Clone for SharedPipeline {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            level_meter: self.level_meter.clone(),
+            waveform_meter: self.waveform_meter.clone(),
+        }
+    }
+}
+
+// Ensure SharedPipeline is Send + Sync for Tauri state
+unsafe impl Send for SharedPipeline {}
+unsafe impl Sync for SharedPipeline {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api_error::ApiError;
+    use crate::llm::{
+        streamed_remainder, LlmFallbackEntry, SentenceChunker, StreamedRemainder,
+        DEFAULT_LLM_TIMEOUT,
+    };
+
+    #[test]
+    fn test_pipeline_config_default() {
+        let config = PipelineConfig::default();
+        assert_eq!(config.max_duration_secs, 300.0);
+        assert_eq!(config.stt_provider, "groq");
+        assert_eq!(config.transcription_timeout, DEFAULT_TRANSCRIPTION_TIMEOUT);
+        assert_eq!(config.max_recording_bytes, MAX_WAV_SIZE_BYTES);
+    }
+
+    #[test]
+    fn test_shared_pipeline_creation() {
+        let config = PipelineConfig {
+            stt_api_key: "test-key".to_string(),
+            ..Default::default()
+        };
+        let pipeline = SharedPipeline::new(config);
+        assert_eq!(pipeline.state(), PipelineState::Idle);
+        assert!(!pipeline.is_error());
+    }
+
+    #[test]
+    fn test_state_guards() {
+        assert!(PipelineState::Idle.can_start_recording());
+        assert!(PipelineState::Error.can_start_recording());
+        assert!(!PipelineState::Recording.can_start_recording());
+        assert!(!PipelineState::Transcribing.can_start_recording());
+
+        assert!(PipelineState::Recording.can_stop_recording());
+        assert!(!PipelineState::Idle.can_stop_recording());
+
+        assert!(PipelineState::Recording.can_cancel());
+        assert!(PipelineState::Transcribing.can_cancel());
+        assert!(!PipelineState::Idle.can_cancel());
+    }
+
+    #[test]
+    fn test_force_reset() {
+        let config = PipelineConfig {
+            stt_api_key: "test-key".to_string(),
+            ..Default::default()
+        };
+        let pipeline = SharedPipeline::new(config);
+
+        // Force reset should always work
+        pipeline.force_reset();
+        assert_eq!(pipeline.state(), PipelineState::Idle);
+    }
+
+    #[tokio::test]
+    async fn test_background_retry_leaves_pipeline_state_alone() {
+        let pipeline = SharedPipeline::new(PipelineConfig {
+            max_recording_bytes: 4,
+            ..Default::default()
+        });
+
+        let result = pipeline.retranscribe_in_background("req-1", vec![0; 16], Some("slack")).await;
+        assert!(matches!(result, Err(PipelineError::RecordingTooLarge(16, 4))));
+        assert_eq!(pipeline.state(), PipelineState::Idle);
+        assert!(pipeline.clone_last_wav_bytes().is_none());
+
+        let result = pipeline.transcribe_wav_bytes_detailed(vec![0; 16]).await;
+        assert!(matches!(result, Err(PipelineError::RecordingTooLarge(16, 4))));
+        assert_eq!(pipeline.state(), PipelineState::Error);
+        assert!(pipeline.clone_last_wav_bytes().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_background_retry_is_logged_as_its_own_request() {
+        let store = RequestLogStore::new();
+        let pipeline = SharedPipeline::new(PipelineConfig {
+            max_recording_bytes: 4,
+            request_log_store: Some(store.clone()),
+            ..Default::default()
+        });
+        let current = store.start_request("openai".to_string(), None);
+
+        let result = pipeline.retranscribe_in_background("req-1", vec![0; 16], None).await;
+        assert!(result.is_err());
+
+        let current_log = store.with_current(|log| log.clone()).unwrap();
+        assert_eq!(current_log.id, current);
+        assert!(current_log.entries.is_empty());
+        let logs = store.get_logs(None);
+        assert_eq!(logs.len(), 1);
+        assert_eq!(logs[0].retry_of.as_deref(), Some("req-1"));
+        assert_eq!(logs[0].status, crate::request_log::RequestStatus::Error);
+    }
 
-            // Timeout
-            _ = tokio::time::sleep(timeout) => {
-                log::warn!("Pipeline: Transcription timed out after {:?}", timeout);
-                Err(PipelineError::Timeout(timeout))
-            }
+    struct FixedProvider {
+        name: &'static str,
+        result: Result<&'static str, ()>,
+        delay: Duration,
+        translation: Option<&'static str>,
+        accepts_flac: bool,
+    }
 
-            // Actual transcription
-            result = transcription_future => {
-                result.map_err(PipelineError::from)
+    impl FixedProvider {
+        fn new(name: &'static str, result: Result<&'static str, ()>) -> Self {
+            Self {
+                name,
+                result,
+                delay: Duration::ZERO,
+                translation: None,
+                accepts_flac: false,
             }
-        };
+        }
 
-        let stt_text = match stt_result {
-            Ok(t) => normalize_stt_text(t),
-            Err(e) => {
-                let mut inner = self
-                    .inner
-                    .lock()
-                    .map_err(|err| PipelineError::Lock(err.to_string()))?;
-                if matches!(e, PipelineError::Cancelled) {
-                    inner.reset_to_idle();
-                } else {
-                    inner.set_error(&e.to_string());
-                }
-                return Err(e);
-            }
-        };
-        let stt_duration_ms = stt_start.elapsed().as_millis() as u64;
-        log::info!("Pipeline: STT complete, {} chars", stt_text.len());
+        fn accepting_flac(mut self) -> Self {
+            self.accepts_flac = true;
+            self
+        }
 
-        // Phase 3: Optional LLM formatting
-        let mut llm_duration_ms: Option<u64> = None;
-        let mut llm_outcome: LlmOutcome = LlmOutcome::NotAttempted;
+        fn slow(mut self, delay: Duration) -> Self {
+            self.delay = delay;
+            self
+        }
 
-        // Capture the *actual* provider/model that will be used (including provider defaults)
-        // before we move `llm_provider` into the formatting block.
-        let llm_provider_used: Option<String> = llm_provider.as_ref().map(|p| p.name().to_string());
-        let llm_model_used: Option<String> = llm_provider.as_ref().map(|p| p.model().to_string());
+        fn translating(mut self, text: &'static str) -> Self {
+            self.translation = Some(text);
+            self
+        }
+    }
 
-        let final_text = if let Some(llm) = llm_provider {
-            // Expose the optional LLM step as a distinct phase for UI.
-            {
-                let mut inner = self
-                    .inner
-                    .lock()
-                    .map_err(|e| PipelineError::Lock(e.to_string()))?;
-                if inner.state == PipelineState::Transcribing {
-                    inner.state = PipelineState::Rewriting;
-                }
+    #[async_trait::async_trait]
+    impl SttProvider for FixedProvider {
+        async fn transcribe(&self, _audio: &[u8], _format: &AudioFormat) -> Result<String, SttError> {
+            tokio::time::sleep(self.delay).await;
+            match self.result {
+                Ok(text) => Ok(text.to_string()),
+                Err(()) => Err(SttError::Api(ApiError::new("Internal Server Error").with_status(500))),
             }
+        }
 
-            log::info!("Pipeline: Applying LLM formatting");
+        fn supports_translation(&self) -> bool {
+            self.translation.is_some()
+        }
 
-            llm_outcome = LlmOutcome::Succeeded; // may be overwritten by fallback paths
-            let llm_start = std::time::Instant::now();
+        fn accepts_encoding(&self, encoding: AudioEncoding) -> bool {
+            matches!(encoding, AudioEncoding::Wav) || (self.accepts_flac && encoding == AudioEncoding::Flac)
+        }
 
-            // Apply LLM formatting with timeout
-            let llm_result = tokio::select! {
-                biased;
+        async fn translate_detailed(
+            &self,
+            _audio: &[u8],
+            _format: &AudioFormat,
+        ) -> Result<Transcript, SttError> {
+            Ok(Transcript::from_text(self.translation.unwrap_or_default().to_string()))
+        }
 
-                _ = cancel_token.cancelled() => {
-                    log::info!("Pipeline: LLM formatting cancelled");
-                    Err(PipelineError::Cancelled)
-                }
+        fn name(&self) -> &'static str {
+            self.name
+        }
+    }
 
-                _ = tokio::time::sleep(llm_timeout) => {
-                    log::warn!("Pipeline: LLM formatting timed out, using raw transcript");
-                    // On timeout, fall back to raw transcript instead of failing
-                    llm_outcome = LlmOutcome::TimedOut;
-                    Ok(stt_text.clone())
-                }
+    fn chain(providers: Vec<FixedProvider>) -> SttFallbackChain {
+        SttFallbackChain {
+            candidates: providers
+                .into_iter()
+                .map(|p| SttCandidate {
+                    provider: Arc::new(p),
+                    model: None,
+                })
+                .collect(),
+            hedge: None,
+            chunking: SttChunkingConfig::default(),
+            max_chunk_bytes: 0,
+            upload_encoding: AudioEncoding::Wav,
+            hallucination_filter: HallucinationFilterConfig::default(),
+            health: ProviderHealthTracker::default(),
+            translate: false,
+            request_log_store: None,
+        }
+    }
 
-                result = format_text(llm.as_ref(), &stt_text, &llm_prompts) => {
-                    match result {
-                        Ok(formatted) => {
-                            log::info!("Pipeline: LLM formatted {} -> {} chars", stt_text.len(), formatted.len());
-                            Ok(formatted)
-                        }
-                        Err(e) => {
-                            log::warn!("Pipeline: LLM formatting failed ({}), using raw transcript", e);
-                            // On error, fall back to raw transcript instead of failing
-                            llm_outcome = LlmOutcome::Failed(e.to_string());
-                            Ok(stt_text.clone())
-                        }
-                    }
-                }
-            };
+    fn no_retries() -> RetryConfig {
+        RetryConfig {
+            max_retries: 0,
+            ..Default::default()
+        }
+    }
 
-            llm_duration_ms = Some(llm_start.elapsed().as_millis() as u64);
+    #[tokio::test]
+    async fn test_stt_chain_falls_back_to_next_provider() {
+        let chain = chain(vec![
+            FixedProvider::new("groq", Err(())),
+            FixedProvider::new("openai", Ok("hello")),
+        ]);
+
+        let (transcript, used) = chain
+            .transcribe(
+                &SttUpload::wav(Vec::new()),
+                &no_retries(),
+                Duration::from_secs(5),
+                &CancellationToken::new(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(transcript.text, "hello");
+        assert_eq!(used.provider.name(), "openai");
+    }
 
-            match llm_result {
-                Ok(text) => text,
-                Err(PipelineError::Cancelled) => {
-                    let mut inner = self.inner.lock().map_err(|e| PipelineError::Lock(e.to_string()))?;
-                    inner.reset_to_idle();
-                    return Err(PipelineError::Cancelled);
-                }
-                Err(_) => stt_text.clone(), // Fallback on other errors
-            }
-        } else {
-            stt_text.clone()
+    #[tokio::test]
+    async fn test_stt_translation_uses_translate_task_and_drops_other_providers() {
+        let mut chain = chain(vec![
+            FixedProvider::new("groq", Ok("hallo welt")).translating("hello world"),
+            FixedProvider::new("openai", Ok("hallo welt")),
+        ]);
+        let translation = TranslationConfig {
+            enabled: true,
+            source_language: Some("de".to_string()),
+            target_language: "en".to_string(),
         };
 
-        // Phase 4: Update state to idle
-        {
-            let mut inner = self.inner.lock().map_err(|e| PipelineError::Lock(e.to_string()))?;
-            inner.reset_to_idle();
-            log::info!("Pipeline: Complete, {} chars output", final_text.len());
-        }
+        assert_eq!(chain.route_translation(&translation), TranslationRoute::Stt);
+        assert_eq!(chain.candidates.len(), 1);
 
-        Ok(TranscriptionResult {
-            stt_text,
-            final_text,
-            stt_duration_ms,
-            llm_duration_ms,
-            llm_provider_used,
-            llm_model_used,
-            llm_outcome,
-        })
+        let (transcript, _) = chain
+            .transcribe(
+                &SttUpload::wav(Vec::new()),
+                &no_retries(),
+                Duration::from_secs(5),
+                &CancellationToken::new(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(transcript.text, "hello world");
     }
 
-    /// Transcribe provided WAV bytes using the same STT + optional LLM logic as the main pipeline.
-    ///
-    /// This is used for retrying failed requests from persisted audio.
-    pub async fn transcribe_wav_bytes_detailed(
-        &self,
-        wav_bytes: Vec<u8>,
-    ) -> Result<TranscriptionResult, PipelineError> {
-        // Phase 1: Resolve providers/config under lock.
-        let (stt_provider, llm_provider, llm_prompts, llm_timeout, retry_config, timeout, cancel_token) = {
-            let mut inner = self.inner.lock().map_err(|e| PipelineError::Lock(e.to_string()))?;
+    #[test]
+    fn test_translation_falls_back_to_llm_prompt() {
+        let mut chain = chain(vec![FixedProvider::new("deepgram", Ok("hallo"))]);
+        let translation = TranslationConfig {
+            enabled: true,
+            source_language: None,
+            target_language: "en".to_string(),
+        };
 
-            // Guard: don't run a retry while actively recording.
-            if inner.state == PipelineState::Recording {
-                return Err(PipelineError::AlreadyRecording);
-            }
-            if matches!(inner.state, PipelineState::Transcribing | PipelineState::Rewriting) {
-                return Err(PipelineError::Lock("Pipeline already transcribing".to_string()));
-            }
+        let route = chain.route_translation(&translation);
+        assert_eq!(route, TranslationRoute::Llm);
+        assert!(!chain.translate);
 
-            // Keep a copy for STT testing/debugging UI.
-            inner.last_wav_bytes = Some(wav_bytes.clone());
+        let rewrite = translation_prompts(PromptSections::default(), true, route, &translation);
+        assert!(rewrite.main_custom.is_none());
+        assert!(rewrite.translation.is_some());
 
-            // Check size limit
-            let max_bytes = inner.config.max_recording_bytes;
-            if max_bytes > 0 && wav_bytes.len() > max_bytes {
-                inner.set_error(&format!("Recording too large: {} bytes", wav_bytes.len()));
-                return Err(PipelineError::RecordingTooLarge(wav_bytes.len(), max_bytes));
-            }
+        let only = translation_prompts(PromptSections::default(), false, route, &translation);
+        assert!(only.main_custom.is_some());
+        assert!(only.translation.is_some());
+    }
 
-            inner.state = PipelineState::Transcribing;
+    #[tokio::test]
+    async fn test_stt_chain_tries_open_circuit_provider_last() {
+        let chain = chain(vec![
+            FixedProvider::new("groq", Ok("from groq")),
+            FixedProvider::new("openai", Ok("from openai")),
+        ]);
+        for _ in 0..3 {
+            chain.health.record_failure(
+                ProviderKind::Stt,
+                "groq",
+                None,
+                Duration::from_millis(5),
+                "Internal Server Error".to_string(),
+            );
+        }
 
-            // Ensure we have a cancellation token for this attempt.
-            let cancel_token = CancellationToken::new();
-            inner.cancel_token = Some(cancel_token.clone());
+        let (transcript, used) = chain
+            .transcribe(
+                &SttUpload::wav(Vec::new()),
+                &no_retries(),
+                Duration::from_secs(5),
+                &CancellationToken::new(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(transcript.text, "from openai");
+        assert_eq!(used.provider.name(), "openai");
+    }
 
-            let llm_config = inner.config.llm_config.clone();
-            let active_profile = select_profile_for_foreground_app(&llm_config);
-            let llm_prompts = active_profile
-                .as_ref()
-                .map(|p| p.prompts.clone())
-                .unwrap_or_else(|| llm_config.prompts.clone());
+    #[tokio::test]
+    async fn test_stt_chain_records_provider_failures() {
+        let chain = chain(vec![
+            FixedProvider::new("groq", Err(())),
+            FixedProvider::new("openai", Ok("hello")),
+        ]);
+
+        chain
+            .transcribe(
+                &SttUpload::wav(Vec::new()),
+                &no_retries(),
+                Duration::from_secs(5),
+                &CancellationToken::new(),
+            )
+            .await
+            .unwrap();
 
-            // Resolve effective STT settings (profile overrides -> global defaults, with safe fallback)
-            let desired_stt_provider = canonicalize_stt_provider_id(
-                active_profile
-                    .as_ref()
-                    .and_then(|p| p.stt_provider.as_deref())
-                    .unwrap_or(inner.config.stt_provider.as_str()),
-            );
-            let desired_stt_model = active_profile
-                .as_ref()
-                .and_then(|p| p.stt_model.clone())
-                .or_else(|| inner.config.stt_model.clone());
-            let desired_timeout = active_profile
-                .as_ref()
-                .and_then(|p| p.stt_timeout_seconds)
-                .map(|s| seconds_to_duration_or(s, inner.config.transcription_timeout))
-                .unwrap_or(inner.config.transcription_timeout);
+        let health = chain.health.snapshot();
+        assert_eq!(health.len(), 2);
+        assert_eq!((health[0].provider.as_str(), health[0].consecutive_failures), ("groq", 1));
+        assert_eq!((health[1].provider.as_str(), health[1].success_rate), ("openai", 1.0));
+    }
 
-            let stt_provider = match inner.get_or_create_stt_provider(&desired_stt_provider, desired_stt_model.clone()) {
-                Ok(p) => p,
-                Err(e) => {
-                    // If the profile specified an override provider, fall back to global provider.
-                    let global_provider = canonicalize_stt_provider_id(&inner.config.stt_provider);
-                    if global_provider != desired_stt_provider {
-                        log::warn!(
-                            "Pipeline: Profile STT provider '{}' unavailable ({}), falling back to '{}'",
-                            desired_stt_provider,
-                            e,
-                            global_provider
-                        );
-                        let global_model = inner.config.stt_model.clone();
-                        inner.get_or_create_stt_provider(&global_provider, global_model)
-                            .map_err(|err| {
-                                inner.set_error(&format!("No STT provider configured: {}", err));
-                                PipelineError::NoProvider
-                            })?
-                    } else {
-                        inner.set_error(&format!("No STT provider configured: {}", e));
-                        return Err(PipelineError::NoProvider);
-                    }
-                }
-            };
+    #[tokio::test]
+    async fn test_stt_retries_count_as_one_request() {
+        let chain = chain(vec![
+            FixedProvider::new("groq", Err(())),
+            FixedProvider::new("openai", Ok("hello")),
+        ]);
+        let retry_config = RetryConfig {
+            max_retries: 2,
+            initial_delay: Duration::ZERO,
+            ..Default::default()
+        };
 
-            // Resolve effective LLM provider/model (profile overrides -> global defaults)
-            let llm_timeout = llm_config.timeout;
-            let effective_llm_enabled = active_profile
-                .as_ref()
-                .and_then(|p| p.rewrite_llm_enabled)
-                .unwrap_or(inner.config.llm_config.enabled);
+        chain
+            .transcribe(
+                &SttUpload::wav(Vec::new()),
+                &retry_config,
+                Duration::from_secs(5),
+                &CancellationToken::new(),
+            )
+            .await
+            .unwrap();
 
-            let llm_provider = if effective_llm_enabled {
-                let desired_llm_provider = active_profile
-                    .as_ref()
-                    .and_then(|p| p.llm_provider.clone())
-                    .unwrap_or_else(|| llm_config.provider.clone());
-                let desired_llm_model = active_profile
-                    .as_ref()
-                    .and_then(|p| p.llm_model.clone())
-                    .or_else(|| llm_config.model.clone());
-
-                match inner.get_or_create_llm_provider(
-                    desired_llm_provider.as_str(),
-                    desired_llm_model.clone(),
-                    llm_timeout,
-                    llm_config.ollama_url.clone(),
-                ) {
-                    Ok(p) => Some(p),
-                    Err(e) => {
-                        // Fallback to global provider if profile requested a different one.
-                        if active_profile
-                            .as_ref()
-                            .and_then(|p| p.llm_provider.as_ref())
-                            .is_some()
-                            && desired_llm_provider != llm_config.provider
-                        {
-                            log::warn!(
-                                "Pipeline: Profile LLM provider '{}' unavailable ({}), falling back to '{}'",
-                                desired_llm_provider,
-                                e,
-                                llm_config.provider
-                            );
-                            inner
-                                .get_or_create_llm_provider(
-                                    llm_config.provider.as_str(),
-                                    llm_config.model.clone(),
-                                    llm_timeout,
-                                    llm_config.ollama_url.clone(),
-                                )
-                                .ok()
-                        } else {
-                            log::warn!("Pipeline: LLM disabled for this transcription ({})", e);
-                            None
-                        }
-                    }
-                }
-            } else {
-                None
-            };
+        let groq = &chain.health.snapshot()[0];
+        assert_eq!(groq.provider, "groq");
+        assert_eq!((groq.samples, groq.consecutive_failures), (1, 1));
+    }
 
-            let retry_config = inner.config.retry_config.clone();
+    #[tokio::test]
+    async fn test_stt_chain_returns_last_error_when_all_fail() {
+        let chain = chain(vec![
+            FixedProvider::new("groq", Err(())),
+            FixedProvider::new("openai", Err(())),
+        ]);
+
+        let result = chain
+            .transcribe(
+                &SttUpload::wav(Vec::new()),
+                &no_retries(),
+                Duration::from_secs(5),
+                &CancellationToken::new(),
+            )
+            .await;
+        assert!(matches!(result, Err(PipelineError::Stt(_))));
+    }
 
-            (
-                stt_provider,
-                llm_provider,
-                llm_prompts,
-                llm_timeout,
-                retry_config,
-                desired_timeout,
-                cancel_token,
+    #[tokio::test]
+    async fn test_stt_chain_stops_when_cancelled() {
+        let chain = chain(vec![FixedProvider::new("groq", Ok("hello"))]);
+        let cancel_token = CancellationToken::new();
+        cancel_token.cancel();
+
+        let result = chain
+            .transcribe(
+                &SttUpload::wav(Vec::new()),
+                &no_retries(),
+                Duration::from_secs(5),
+                &cancel_token,
             )
-        };
+            .await;
+        assert!(matches!(result, Err(PipelineError::Cancelled)));
+    }
 
-        log::info!(
-            "Pipeline: Starting retry transcription ({} bytes, timeout {:?})",
-            wav_bytes.len(),
-            timeout
-        );
+    fn hedged_chain(primary: FixedProvider, hedge: FixedProvider, delay: Duration) -> SttFallbackChain {
+        let mut chain = chain(vec![primary]);
+        chain.hedge = Some(SttHedge {
+            candidate: SttCandidate {
+                provider: Arc::new(hedge),
+                model: None,
+            },
+            delay,
+        });
+        chain
+    }
 
-        // Phase 2: STT transcription
-        let format = AudioFormat::default();
-        let wav = Arc::new(wav_bytes);
+    #[tokio::test]
+    async fn test_stt_hedge_wins_when_primary_is_slow() {
+        let chain = hedged_chain(
+            FixedProvider::new("groq", Ok("slow")).slow(Duration::from_secs(5)),
+            FixedProvider::new("openai", Ok("fast")),
+            Duration::from_millis(20),
+        );
 
-        let transcription_future = async {
-            with_retry(&retry_config, || {
-                let provider = stt_provider.clone();
-                let wav = wav.clone();
-                let format = format.clone();
-                async move { provider.transcribe(wav.as_slice(), &format).await }
-            })
+        let (transcript, used) = chain
+            .transcribe(
+                &SttUpload::wav(Vec::new()),
+                &no_retries(),
+                Duration::from_secs(10),
+                &CancellationToken::new(),
+            )
             .await
-        };
+            .unwrap();
+        assert_eq!(transcript.text, "fast");
+        assert_eq!(used.provider.name(), "openai");
+    }
 
-        let stt_start = std::time::Instant::now();
-        let stt_result = tokio::select! {
-            biased;
+    #[tokio::test]
+    async fn test_stt_hedge_not_sent_when_primary_is_fast() {
+        let chain = hedged_chain(
+            FixedProvider::new("groq", Ok("primary")),
+            FixedProvider::new("openai", Ok("hedge")),
+            Duration::from_secs(5),
+        );
 
-            _ = cancel_token.cancelled() => {
-                log::info!("Pipeline: Retry transcription cancelled");
-                Err(PipelineError::Cancelled)
-            }
+        let (transcript, used) = chain
+            .transcribe(
+                &SttUpload::wav(Vec::new()),
+                &no_retries(),
+                Duration::from_secs(10),
+                &CancellationToken::new(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(transcript.text, "primary");
+        assert_eq!(used.provider.name(), "groq");
+    }
 
-            _ = tokio::time::sleep(timeout) => {
-                log::warn!("Pipeline: Retry transcription timed out after {:?}", timeout);
-                Err(PipelineError::Timeout(timeout))
-            }
+    #[tokio::test]
+    async fn test_stt_hedge_covers_primary_failure_after_delay() {
+        let chain = hedged_chain(
+            FixedProvider::new("groq", Err(())).slow(Duration::from_millis(50)),
+            FixedProvider::new("openai", Ok("hedge")).slow(Duration::from_millis(100)),
+            Duration::from_millis(10),
+        );
 
-            result = transcription_future => {
-                result.map_err(PipelineError::from)
-            }
-        };
+        let (transcript, _) = chain
+            .transcribe(
+                &SttUpload::wav(Vec::new()),
+                &no_retries(),
+                Duration::from_secs(10),
+                &CancellationToken::new(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(transcript.text, "hedge");
+    }
 
-        let stt_text = match stt_result {
-            Ok(t) => normalize_stt_text(t),
-            Err(e) => {
-                let mut inner = self
-                    .inner
-                    .lock()
-                    .map_err(|err| PipelineError::Lock(err.to_string()))?;
-                if matches!(e, PipelineError::Cancelled) {
-                    inner.reset_to_idle();
-                } else {
-                    inner.set_error(&e.to_string());
-                }
-                return Err(e);
-            }
+    #[test]
+    fn test_upload_sends_wav_to_providers_without_compression_support() {
+        let upload = SttUpload {
+            wav: Arc::new(vec![0; 44]),
+            compressed: Some((
+                Arc::new(vec![0; 8]),
+                AudioFormat {
+                    encoding: AudioEncoding::Flac,
+                    ..Default::default()
+                },
+            )),
         };
+        assert_eq!(upload.upload_size(), 8);
 
-        let stt_duration_ms = stt_start.elapsed().as_millis() as u64;
-        log::info!("Pipeline: Retry STT complete, {} chars", stt_text.len());
-
-        // Phase 3: Optional LLM formatting
-        let mut llm_duration_ms: Option<u64> = None;
-        let mut llm_outcome: LlmOutcome = LlmOutcome::NotAttempted;
-
-        let llm_provider_used: Option<String> = llm_provider.as_ref().map(|p| p.name().to_string());
-        let llm_model_used: Option<String> = llm_provider.as_ref().map(|p| p.model().to_string());
+        let (audio, format) = upload.audio_for(&FixedProvider::new("local-whisper", Ok("")));
+        assert_eq!(audio.len(), 44);
+        assert_eq!(format.encoding, AudioEncoding::Wav);
+    }
 
-        let final_text = if let Some(llm) = llm_provider {
-            // Expose the optional LLM step as a distinct phase for UI.
-            {
-                let mut inner = self
-                    .inner
-                    .lock()
-                    .map_err(|e| PipelineError::Lock(e.to_string()))?;
-                if inner.state == PipelineState::Transcribing {
-                    inner.state = PipelineState::Rewriting;
-                }
-            }
+    #[tokio::test]
+    async fn test_long_recording_is_chunked_and_stitched() {
+        let mut chain = chain(vec![FixedProvider::new("groq", Ok("hello"))]);
+        chain.chunking.max_chunk_secs = 2.0;
 
-            log::info!("Pipeline: Applying LLM formatting (retry)");
-            llm_outcome = LlmOutcome::Succeeded;
-            let llm_start = std::time::Instant::now();
+        let mut buffer = AudioBuffer::new(16000, 1, 60.0);
+        buffer.append(&vec![0.0; 16000 * 5]);
+        let wav_bytes = buffer.to_wav_bytes().unwrap();
 
-            let llm_result = tokio::select! {
-                biased;
+        let (transcript, used) = chain
+            .transcribe_recording(wav_bytes, &no_retries(), Duration::from_secs(5), &CancellationToken::new())
+            .await
+            .unwrap();
+        assert_eq!(transcript.text, "hello hello hello");
+        assert_eq!(used.provider.name(), "groq");
+    }
 
-                _ = cancel_token.cancelled() => {
-                    log::info!("Pipeline: Retry LLM formatting cancelled");
-                    Err(PipelineError::Cancelled)
-                }
+    #[tokio::test]
+    async fn test_chunk_logs_are_merged_into_the_request_log() {
+        let store = RequestLogStore::new();
+        let mut chain = chain(vec![
+            FixedProvider::new("groq", Err(())),
+            FixedProvider::new("openai", Ok("hello")),
+        ]);
+        chain.chunking.max_chunk_secs = 2.0;
+        chain.request_log_store = Some(store.clone());
+        store.start_request("groq".to_string(), None);
+
+        let mut buffer = AudioBuffer::new(16000, 1, 60.0);
+        buffer.append(&vec![0.0; 16000 * 5]);
+        let wav_bytes = buffer.to_wav_bytes().unwrap();
+
+        let (transcript, _) = chain
+            .transcribe_recording(wav_bytes, &no_retries(), Duration::from_secs(5), &CancellationToken::new())
+            .await
+            .unwrap();
+        assert_eq!(transcript.text, "hello hello hello");
 
-                _ = tokio::time::sleep(llm_timeout) => {
-                    log::warn!("Pipeline: Retry LLM formatting timed out, using raw transcript");
-                    llm_outcome = LlmOutcome::TimedOut;
-                    Ok(stt_text.clone())
-                }
+        let log = store.with_current(|log| log.clone()).unwrap();
+        let fallbacks = log
+            .entries
+            .iter()
+            .filter(|e| e.message.contains("falling back to openai"))
+            .count();
+        assert_eq!(fallbacks, 3);
+    }
 
-                result = format_text(llm.as_ref(), &stt_text, &llm_prompts) => {
-                    match result {
-                        Ok(formatted) => {
-                            log::info!("Pipeline: Retry LLM formatted {} -> {} chars", stt_text.len(), formatted.len());
-                            Ok(formatted)
-                        }
-                        Err(e) => {
-                            log::warn!("Pipeline: Retry LLM formatting failed ({}), using raw transcript", e);
-                            llm_outcome = LlmOutcome::Failed(e.to_string());
-                            Ok(stt_text.clone())
-                        }
-                    }
-                }
-            };
+    #[tokio::test]
+    async fn test_chunk_size_limit_applies_to_uploaded_payload() {
+        let mut buffer = AudioBuffer::new(16000, 1, 60.0);
+        buffer.append(&vec![0.0; 16000 * 5]);
+        let wav_bytes = buffer.to_wav_bytes().unwrap();
+
+        // 2s WAV chunks are ~64KB; silence compresses to far less than the limit.
+        let limited = |provider: FixedProvider| {
+            let mut chain = chain(vec![provider]);
+            chain.chunking.max_chunk_secs = 2.0;
+            chain.upload_encoding = AudioEncoding::Flac;
+            chain.max_chunk_bytes = 16_000;
+            chain
+        };
 
-            llm_duration_ms = Some(llm_start.elapsed().as_millis() as u64);
+        let compressing = limited(FixedProvider::new("groq", Ok("hello")).accepting_flac());
+        let result = compressing
+            .transcribe_recording(wav_bytes.clone(), &no_retries(), Duration::from_secs(5), &CancellationToken::new())
+            .await;
+        assert_eq!(result.unwrap().0.text, "hello hello hello");
+
+        let wav_only = limited(FixedProvider::new("local-whisper", Ok("hello")));
+        let result = wav_only
+            .transcribe_recording(wav_bytes, &no_retries(), Duration::from_secs(5), &CancellationToken::new())
+            .await;
+        assert!(matches!(result, Err(PipelineError::RecordingTooLarge(_, 16_000))));
+    }
 
-            match llm_result {
-                Ok(text) => text,
-                Err(PipelineError::Cancelled) => {
-                    let mut inner = self.inner.lock().map_err(|e| PipelineError::Lock(e.to_string()))?;
-                    inner.reset_to_idle();
-                    return Err(PipelineError::Cancelled);
-                }
-                Err(_) => stt_text.clone(),
-            }
-        } else {
-            stt_text.clone()
+    #[test]
+    fn test_resolve_stt_chain_skips_unavailable_and_duplicates() {
+        let mut stt_api_keys = HashMap::new();
+        stt_api_keys.insert("groq".to_string(), "groq-key".to_string());
+        stt_api_keys.insert("openai".to_string(), "openai-key".to_string());
+        let config = PipelineConfig {
+            stt_provider: "groq".to_string(),
+            stt_api_keys,
+            stt_fallback_chain: vec![
+                SttFallbackEntry { provider: "deepgram".to_string(), model: None },
+                SttFallbackEntry { provider: "groq".to_string(), model: None },
+                SttFallbackEntry { provider: "openai".to_string(), model: Some("whisper-1".to_string()) },
+            ],
+            ..Default::default()
         };
 
-        // Phase 4: Reset to idle
-        {
-            let mut inner = self.inner.lock().map_err(|e| PipelineError::Lock(e.to_string()))?;
-            inner.reset_to_idle();
-            log::info!("Pipeline: Retry complete, {} chars output", final_text.len());
-        }
-
-        Ok(TranscriptionResult {
-            stt_text,
-            final_text,
-            stt_duration_ms,
-            llm_duration_ms,
-            llm_provider_used,
-            llm_model_used,
-            llm_outcome,
-        })
+        let mut inner = PipelineInner::new(config);
+        let chain = inner.resolve_stt_chain(None).unwrap();
+        let names: Vec<&str> = chain.candidates.iter().map(|c| c.provider.name()).collect();
+        assert_eq!(names, vec!["groq", "openai"]);
+        assert_eq!(chain.candidates[1].model.as_deref(), Some("whisper-1"));
+    }
+
+    #[test]
+    fn test_openai_compatible_llm_needs_base_url_not_api_key() {
+        let mut inner = PipelineInner::new(PipelineConfig::default());
+        let timeout = Duration::from_secs(30);
+        assert!(matches!(
+            inner.get_or_create_llm_provider("openai-compatible", None, timeout, None),
+            Err(PipelineError::Config(_))
+        ));
+
+        let mut config = PipelineConfig::default();
+        config.llm_config.openai_compatible.base_url = "http://localhost:1234/v1".to_string();
+        let mut inner = PipelineInner::new(config);
+        let provider = inner
+            .get_or_create_llm_provider(
+                "openai-compatible",
+                Some("local-model".to_string()),
+                timeout,
+                None,
+            )
+            .unwrap();
+        assert_eq!(provider.name(), "openai-compatible");
+        assert_eq!(provider.model(), "local-model");
     }
 
-    /// Stop recording and transcribe the audio.
-    ///
-    /// Kept for backwards compatibility. Prefer `stop_and_transcribe_detailed`.
-    #[cfg_attr(not(test), allow(dead_code))]
-    pub async fn stop_and_transcribe(&self) -> Result<String, PipelineError> {
-        self.stop_and_transcribe_detailed()
-            .await
-            .map(|r| r.final_text)
+    /// LLM that streams `deltas` and then fails with a timeout `failures` times.
+    struct StreamingLlm {
+        deltas: &'static [&'static str],
+        failures: std::sync::atomic::AtomicU32,
+        attempts: std::sync::atomic::AtomicU32,
     }
 
-    /// Update configuration
-    ///
-    /// Note: This will not affect an in-progress recording.
-    pub fn update_config(&self, config: PipelineConfig) -> Result<(), PipelineError> {
-        let mut inner = self.inner.lock().map_err(|e| PipelineError::Lock(e.to_string()))?;
+    #[async_trait::async_trait]
+    impl LlmProvider for StreamingLlm {
+        async fn complete(&self, _system_prompt: &str, _user_message: &str) -> Result<String, LlmError> {
+            unreachable!("streaming rewrites use complete_stream")
+        }
 
-        // Don't update config while recording - could cause issues
-        if inner.state == PipelineState::Recording {
-            log::warn!("Pipeline: Config update requested while recording, will take effect after current session");
+        async fn complete_stream(
+            &self,
+            _system_prompt: &str,
+            _user_message: &str,
+            deltas: &LlmDeltaSender,
+        ) -> Result<String, LlmError> {
+            use std::sync::atomic::Ordering;
+
+            self.attempts.fetch_add(1, Ordering::SeqCst);
+            for delta in self.deltas {
+                let _ = deltas.send(delta.to_string());
+            }
+            if self.failures.load(Ordering::SeqCst) > 0 {
+                self.failures.fetch_sub(1, Ordering::SeqCst);
+                return Err(LlmError::Timeout(Duration::from_secs(1)));
+            }
+            Ok(self.deltas.concat())
         }
 
-        inner.config = config.clone();
-        inner.stt_registry = SttRegistry::new();
-        inner.initialize_providers(&config);
-        // Update VAD config on audio capture
-        inner.audio_capture.set_vad_config(config.vad_config);
-        log::info!("Pipeline configuration updated");
-        Ok(())
-    }
+        fn name(&self) -> &'static str {
+            "streaming"
+        }
 
-    /// Check if recording
-    pub fn is_recording(&self) -> bool {
-        self.inner
-            .lock()
-            .map(|inner| inner.state == PipelineState::Recording)
-            .unwrap_or(false)
+        fn model(&self) -> &str {
+            "test"
+        }
     }
 
-    /// Get a clone of the last captured WAV bytes, if present.
-    pub fn clone_last_wav_bytes(&self) -> Option<Vec<u8>> {
-        self.inner.lock().ok().and_then(|inner| inner.last_wav_bytes.clone())
+    fn streaming_llm(deltas: &'static [&'static str], failures: u32) -> StreamingLlm {
+        StreamingLlm {
+            deltas,
+            failures: failures.into(),
+            attempts: 0.into(),
+        }
     }
 
-    /// Get a copy of the last recording diagnostics (raw stats + optional speech detection).
-    pub fn last_recording_diagnostics(&self) -> Option<AudioCaptureDiagnostics> {
-        self.inner
-            .lock()
-            .ok()
-            .and_then(|inner| inner.last_recording_diagnostics)
+    fn fast_retries() -> RetryConfig {
+        RetryConfig {
+            max_retries: 2,
+            initial_delay: Duration::from_millis(1),
+            ..Default::default()
+        }
     }
 
-    /// Poll for VAD events (non-blocking)
-    ///
-    /// Returns the next VAD event if one is available, or None if no events are pending.
-    #[cfg_attr(not(test), allow(dead_code))]
-    pub fn poll_vad_event(&self) -> Option<AudioCaptureEvent> {
-        self.inner
-            .lock()
-            .ok()
-            .and_then(|inner| inner.audio_capture.poll_vad_event())
+    #[tokio::test]
+    async fn test_streamed_rewrite_forwards_deltas() {
+        let llm = streaming_llm(&[" Hello", " world."], 0);
+        let (deltas_tx, mut deltas_rx) = llm_delta_channel();
+
+        let text = format_with_llm(
+            &llm,
+            "hello world",
+            &PromptSections::default(),
+            &OutputGuardrail::default(),
+            &fast_retries(),
+            &ProviderHealthTracker::default(),
+            Some(&deltas_tx),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(text, "Hello world.");
+        drop(deltas_tx);
+        let mut streamed = Vec::new();
+        while let Some(delta) = deltas_rx.recv().await {
+            streamed.push(delta);
+        }
+        assert_eq!(streamed, vec![" Hello", " world."]);
     }
 
-    /// Check if VAD auto-stop is enabled
-    #[cfg_attr(not(test), allow(dead_code))]
-    pub fn is_vad_auto_stop_enabled(&self) -> bool {
-        self.inner
-            .lock()
-            .map(|inner| inner.audio_capture.is_vad_auto_stop_enabled())
-            .unwrap_or(false)
+    #[tokio::test]
+    async fn test_streamed_rewrite_is_not_retried_after_output() {
+        let (deltas_tx, _deltas_rx) = llm_delta_channel();
+
+        // Failed before any text arrived: retried as usual.
+        let silent = streaming_llm(&[], 1);
+        let result = format_with_llm(
+            &silent,
+            "hello",
+            &PromptSections::default(),
+            &OutputGuardrail::default(),
+            &fast_retries(),
+            &ProviderHealthTracker::default(),
+            Some(&deltas_tx),
+        )
+        .await;
+        assert!(result.is_ok());
+        assert_eq!(silent.attempts.into_inner(), 2);
+
+        // Failed mid-stream: a retry would repeat the text already shown.
+        let interrupted = streaming_llm(&["Hel"], 1);
+        let result = format_with_llm(
+            &interrupted,
+            "hello",
+            &PromptSections::default(),
+            &OutputGuardrail::default(),
+            &fast_retries(),
+            &ProviderHealthTracker::default(),
+            Some(&deltas_tx),
+        )
+        .await;
+        assert!(matches!(result, Err(LlmError::InvalidResponse(_))));
+        assert_eq!(interrupted.attempts.into_inner(), 1);
     }
 
-    /// Cancel current operation
-    ///
-    /// This will:
-    /// - Stop any ongoing recording
-    /// - Signal cancellation to any in-flight transcription
-    /// - Reset the pipeline to Idle state
-    pub fn cancel(&self) {
-        if let Ok(mut inner) = self.inner.lock() {
-            if !inner.state.can_cancel() {
-                log::debug!("Pipeline: Cancel requested but nothing to cancel (state: {:?})", inner.state);
-                return;
-            }
+    /// LLM that answers `reply` (or rejects the request) after `delay`.
+    struct ScriptedLlm {
+        name: &'static str,
+        reply: Option<&'static str>,
+        delay: Duration,
+    }
 
-            // Signal cancellation to any async tasks
-            if let Some(token) = inner.cancel_token.take() {
-                token.cancel();
-            }
+    #[async_trait::async_trait]
+    impl LlmProvider for ScriptedLlm {
+        async fn complete(&self, _system_prompt: &str, _user_message: &str) -> Result<String, LlmError> {
+            tokio::time::sleep(self.delay).await;
+            self.reply
+                .map(|reply| reply.to_string())
+                .ok_or_else(|| LlmError::Api(ApiError::new("bad request").with_status(400)))
+        }
 
-            // Stop audio capture if recording
-            if inner.state == PipelineState::Recording {
-                inner.audio_capture.stop();
-            }
+        fn name(&self) -> &'static str {
+            self.name
+        }
 
-            inner.reset_to_idle();
-            log::info!("Pipeline: Cancelled and reset to idle");
+        fn model(&self) -> &str {
+            "test"
         }
     }
 
-    /// Force reset the pipeline to idle state
-    ///
-    /// Use this to recover from stuck states. Cancels any in-progress operations.
-    pub fn force_reset(&self) {
-        if let Ok(mut inner) = self.inner.lock() {
-            // Cancel any async tasks
-            if let Some(token) = inner.cancel_token.take() {
-                token.cancel();
-            }
-
-            // Force stop audio capture
-            inner.audio_capture.stop();
+    fn scripted_llm(name: &'static str, reply: Option<&'static str>, delay_ms: u64) -> Arc<dyn LlmProvider> {
+        Arc::new(ScriptedLlm {
+            name,
+            reply,
+            delay: Duration::from_millis(delay_ms),
+        })
+    }
 
-            // Reset state
-            inner.reset_to_idle();
-            log::warn!("Pipeline: Force reset to idle");
+    fn llm_chain(providers: Vec<Arc<dyn LlmProvider>>, budget: Duration) -> LlmFallbackChain {
+        LlmFallbackChain {
+            providers,
+            budget,
+            guardrail: OutputGuardrail {
+                enabled: true,
+                ..Default::default()
+            },
+            health: ProviderHealthTracker::default(),
+            request_log_store: None,
         }
     }
 
-    /// Get current state
-    pub fn state(&self) -> PipelineState {
-        self.inner
-            .lock()
-            .map(|inner| inner.state)
-            .unwrap_or(PipelineState::Error)
+    #[tokio::test]
+    async fn test_llm_chain_falls_back_to_next_provider() {
+        let chain = llm_chain(
+            vec![scripted_llm("groq", None, 0), scripted_llm("ollama", Some("Hello."), 0)],
+            Duration::from_secs(5),
+        );
+
+        let output = chain
+            .format("hello", &PromptSections::default(), &fast_retries(), None)
+            .await;
+        assert_eq!(output.text.as_deref(), Some("Hello."));
+        assert_eq!(output.provider.name(), "ollama");
+        assert!(matches!(
+            output.outcome,
+            LlmOutcome::Succeeded { ref provider, attempts: 2, .. } if provider == "ollama"
+        ));
     }
 
-    /// Get the most recent realtime audio input level snapshot.
-    ///
-    /// This is cheap and intended for UI metering (e.g., overlay waveform). The snapshot is
-    /// updated from the CPAL input callback while recording.
-    #[cfg_attr(not(test), allow(dead_code))]
-    pub fn audio_level_snapshot(&self) -> AudioLevelSnapshot {
-        self.inner
-            .lock()
-            .map(|inner| inner.audio_capture.level_snapshot())
-            .unwrap_or(AudioLevelSnapshot {
-                seq: 0,
-                rms: 0.0,
-                peak: 0.0,
-            })
+    #[tokio::test]
+    async fn test_llm_chain_reports_every_failure() {
+        let chain = llm_chain(
+            vec![scripted_llm("groq", None, 0), scripted_llm("ollama", None, 0)],
+            Duration::from_secs(5),
+        );
+
+        let output = chain
+            .format("hello", &PromptSections::default(), &fast_retries(), None)
+            .await;
+        assert!(output.text.is_none());
+        match output.outcome {
+            LlmOutcome::Failed(message) => {
+                assert!(message.starts_with("groq: "));
+                assert!(message.contains("; ollama: "));
+            }
+            other => panic!("unexpected outcome: {:?}", other),
+        }
     }
 
-    /// Get the name of the current STT provider
-    #[cfg_attr(not(test), allow(dead_code))]
-    pub fn current_provider_name(&self) -> String {
-        self.inner
-            .lock()
-            .map(|inner| inner.stt_registry.current_name().to_string())
-            .unwrap_or_default()
+    #[tokio::test]
+    async fn test_llm_chain_is_bounded_by_budget() {
+        let chain = llm_chain(
+            vec![scripted_llm("groq", None, 30), scripted_llm("ollama", Some("Hello."), 5_000)],
+            Duration::from_millis(100),
+        );
+
+        let started = std::time::Instant::now();
+        let output = chain
+            .format("hello", &PromptSections::default(), &fast_retries(), None)
+            .await;
+        assert!(started.elapsed() < Duration::from_secs(2));
+        assert!(output.text.is_none());
+        assert!(matches!(output.outcome, LlmOutcome::TimedOut));
+        assert_eq!(output.provider.name(), "ollama");
     }
 
-    /// Get a clone of the current pipeline configuration
-    pub fn config(&self) -> PipelineConfig {
-        self.inner
-            .lock()
-            .map(|inner| inner.config.clone())
-            .unwrap_or_default()
+    #[tokio::test]
+    async fn test_llm_chain_stops_after_streamed_output() {
+        let chain = llm_chain(
+            vec![
+                Arc::new(streaming_llm(&["Hel"], 1)),
+                scripted_llm("ollama", Some("Hello."), 0),
+            ],
+            Duration::from_secs(5),
+        );
+        let (deltas_tx, _deltas_rx) = llm_delta_channel();
+
+        let output = chain
+            .format("hello", &PromptSections::default(), &fast_retries(), Some(&deltas_tx))
+            .await;
+        assert!(output.text.is_none());
+        assert!(matches!(output.outcome, LlmOutcome::Failed(_)));
+        assert_eq!(output.provider.name(), "streaming");
     }
 
-    /// Check if the pipeline is in an error state
-    pub fn is_error(&self) -> bool {
-        self.inner
-            .lock()
-            .map(|inner| inner.state == PipelineState::Error)
-            .unwrap_or(true)
+    #[tokio::test]
+    async fn test_output_streamed_before_failure_is_replaced() {
+        let chain = llm_chain(
+            vec![Arc::new(streaming_llm(&["It works. ", "And then"], 1))],
+            Duration::from_secs(5),
+        );
+        let (deltas_tx, mut deltas_rx) = llm_delta_channel();
+
+        let output = chain
+            .format(
+                "it works and then",
+                &PromptSections::default(),
+                &fast_retries(),
+                Some(&deltas_tx),
+            )
+            .await;
+        drop(deltas_tx);
+        assert!(output.text.is_none());
+        assert!(matches!(output.outcome, LlmOutcome::Failed(_)));
+
+        // What the output task pasted before the stream broke off.
+        let mut chunker = SentenceChunker::default();
+        let mut pasted = String::new();
+        while let Some(delta) = deltas_rx.recv().await {
+            pasted.extend(chunker.push(&delta));
+        }
+        assert_eq!(pasted, "It works. ");
+
+        // The pipeline falls back to the raw transcript, which doesn't continue the pasted text.
+        assert_eq!(
+            streamed_remainder("it works and then", &pasted),
+            StreamedRemainder::Replace { delete_chars: 10 }
+        );
     }
 
-    /// Whether there is a previously captured audio buffer available for testing.
-    pub fn has_last_audio(&self) -> bool {
-        self.inner
-            .lock()
-            .ok()
-            .and_then(|inner| inner.last_wav_bytes.as_ref().map(|b| !b.is_empty()))
-            .unwrap_or(false)
+    /// LLM that chats unless the strict prompt is in the system prompt.
+    struct ChattyLlm;
+
+    #[async_trait::async_trait]
+    impl LlmProvider for ChattyLlm {
+        async fn complete(&self, system_prompt: &str, user_message: &str) -> Result<String, LlmError> {
+            if system_prompt.contains("## Strict Mode") {
+                Ok(user_message.to_string())
+            } else {
+                Ok(format!("Here is the formatted text: {}", user_message))
+            }
+        }
+
+        fn name(&self) -> &'static str {
+            "chatty"
+        }
+
+        fn model(&self) -> &str {
+            "test"
+        }
     }
 
-    /// Get the cancellation token for external use (e.g., for coordinating with other async tasks)
-    #[cfg_attr(not(test), allow(dead_code))]
-    pub fn get_cancel_token(&self) -> Option<CancellationToken> {
-        self.inner
-            .lock()
-            .ok()
-            .and_then(|inner| inner.cancel_token.clone())
+    #[tokio::test]
+    async fn test_llm_chain_rejected_output_uses_raw_transcript() {
+        let chain = llm_chain(
+            vec![Arc::new(ChattyLlm), scripted_llm("ollama", Some("Hello."), 0)],
+            Duration::from_secs(5),
+        );
+
+        let output = chain
+            .format("hello there", &PromptSections::default(), &fast_retries(), None)
+            .await;
+        assert!(output.text.is_none());
+        assert!(matches!(
+            output.outcome,
+            LlmOutcome::Rejected { ref provider, .. } if provider == "chatty"
+        ));
     }
-}
 
-impl Default for SharedPipeline {
-    fn default() -> Self {
-        Self::new(PipelineConfig::default())
+    #[tokio::test]
+    async fn test_llm_chain_retries_rejected_output_with_strict_prompt() {
+        let mut chain = llm_chain(vec![Arc::new(ChattyLlm)], Duration::from_secs(5));
+        chain.guardrail.action = GuardrailAction::RetryStrict;
+
+        let output = chain
+            .format("hello there", &PromptSections::default(), &fast_retries(), None)
+            .await;
+        assert_eq!(output.text.as_deref(), Some("hello there"));
+        assert!(matches!(
+            output.outcome,
+            LlmOutcome::SucceededAfterStrictRetry { attempts: 1, ref reason, .. }
+                if reason.contains("here is")
+        ));
     }
-}
 
-impl Clone for SharedPipeline {
-    fn clone(&self) -> Self {
-        Self {
-            inner: self.inner.clone(),
-            level_meter: self.level_meter.clone(),
-            waveform_meter: self.waveform_meter.clone(),
-        }
+    #[test]
+    fn test_resolve_llm_chain_skips_unavailable_and_duplicates() {
+        let mut llm_api_keys = HashMap::new();
+        llm_api_keys.insert("groq".to_string(), "groq-key".to_string());
+        let mut config = PipelineConfig {
+            llm_api_keys,
+            ..Default::default()
+        };
+        config.llm_config.provider = "groq".to_string();
+        config.llm_config.fallback_chain = vec![
+            LlmFallbackEntry { provider: "openai".to_string(), model: None },
+            LlmFallbackEntry { provider: "groq".to_string(), model: None },
+            LlmFallbackEntry { provider: "ollama".to_string(), model: Some("llama3.2".to_string()) },
+        ];
+
+        let mut inner = PipelineInner::new(config);
+        let chain = inner.resolve_llm_chain(None).unwrap();
+        let names: Vec<&str> = chain.providers.iter().map(|p| p.name()).collect();
+        assert_eq!(names, vec!["groq", "ollama"]);
+        assert_eq!(chain.budget, DEFAULT_LLM_TIMEOUT);
     }
-}
 
-// Ensure SharedPipeline is Send + Sync for Tauri state
-unsafe impl Send for SharedPipeline {}
-unsafe impl Sync for SharedPipeline {}
+    #[test]
+    fn test_only_triggered_snippets_are_rendered() {
+        let store = SnippetStore::in_memory();
+        let input = |trigger: &str, body: &str, enabled: bool| crate::snippets::SnippetInput {
+            triggers: vec![trigger.to_string()],
+            body: body.to_string(),
+            enabled,
+            inline: true,
+        };
+        store.add(input("my profile", "Profile: {{profile}}", true)).unwrap();
+        store.add(input("paste it", "{{clipboard}}", true)).unwrap();
+        store.add(input("old signature", "Old", false)).unwrap();
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+        let pipeline = SharedPipeline::new(PipelineConfig {
+            snippet_store: Some(store),
+            ..Default::default()
+        });
+        let snippets = pipeline.inner.lock().unwrap().dictation_snippets(None, true);
+        assert_eq!(snippets.snippets.len(), 2);
+        assert!(snippets.snippets.iter().all(|s| s.body.contains("{{")));
 
-    #[test]
-    fn test_pipeline_config_default() {
-        let config = PipelineConfig::default();
-        assert_eq!(config.max_duration_secs, 300.0);
-        assert_eq!(config.stt_provider, "groq");
-        assert_eq!(config.transcription_timeout, DEFAULT_TRANSCRIPTION_TIMEOUT);
-        assert_eq!(config.max_recording_bytes, MAX_WAV_SIZE_BYTES);
+        let expansion = pipeline.expand_snippets("My profile.", &snippets).unwrap();
+        assert_eq!(expansion.expanded.len(), 1);
+        assert_eq!(expansion.text(), "Profile: Default");
+
+        let untriggered = pipeline.expand_snippets("Nothing to expand.", &snippets).unwrap();
+        assert!(untriggered.is_empty());
     }
 
     #[test]
-    fn test_shared_pipeline_creation() {
-        let config = PipelineConfig {
-            stt_api_key: "test-key".to_string(),
+    fn test_background_renders_live_variables_empty() {
+        let mut inner = PipelineInner::new(PipelineConfig::default());
+        inner.previous_output = Some("Earlier text".to_string());
+        let prompts = PromptSections {
+            main_custom: Some("Profile {{profile}}, after: {{previous_output}}".to_string()),
             ..Default::default()
         };
-        let pipeline = SharedPipeline::new(config);
-        assert_eq!(pipeline.state(), PipelineState::Idle);
-        assert!(!pipeline.is_error());
+
+        let live = inner.render_prompts(prompts.clone(), None, true);
+        assert_eq!(live.main_prompt(), "Profile Default, after: Earlier text");
+        let background = inner.render_prompts(prompts, None, false);
+        assert_eq!(background.main_prompt(), "Profile Default, after: ");
     }
 
     #[test]
-    fn test_state_guards() {
-        assert!(PipelineState::Idle.can_start_recording());
-        assert!(PipelineState::Error.can_start_recording());
-        assert!(!PipelineState::Recording.can_start_recording());
-        assert!(!PipelineState::Transcribing.can_start_recording());
+    fn test_stt_vocabulary_follows_the_active_profile() {
+        let mut scoped = DictionaryEntry::new("Slackbot", vec!["slack bot".to_string()], None);
+        scoped.profile_id = Some("slack".to_string());
+        let inner = PipelineInner::new(PipelineConfig {
+            dictionary: vec![
+                DictionaryEntry::new("Claude", vec!["clawed".to_string()], None),
+                scoped,
+            ],
+            ..Default::default()
+        });
+        let slack = crate::llm::ProgramPromptProfile {
+            id: "slack".to_string(),
+            name: "Slack".to_string(),
+            program_paths: Vec::new(),
+            prompts: PromptSections::default(),
+            rewrite_llm_enabled: None,
+            stt_provider: None,
+            stt_model: None,
+            stt_timeout_seconds: None,
+            llm_provider: None,
+            llm_model: None,
+            llm_fallback_chain: None,
+            translation: None,
+            stt_language: None,
+        };
 
-        assert!(PipelineState::Recording.can_stop_recording());
-        assert!(!PipelineState::Idle.can_stop_recording());
+        let global = inner.stt_vocabulary(None);
+        let profile = inner.stt_vocabulary(Some(&slack));
+        assert_eq!(global.terms(), vec!["Claude"]);
+        assert!(profile.terms().contains(&"Slackbot".to_string()));
+        // Providers are cached per vocabulary, so each profile gets its own biasing.
+        assert_ne!(global.cache_key(), profile.cache_key());
+    }
 
-        assert!(PipelineState::Recording.can_cancel());
-        assert!(PipelineState::Transcribing.can_cancel());
-        assert!(!PipelineState::Idle.can_cancel());
+    #[test]
+    fn test_dictionary_replacements_are_logged() {
+        let mut scoped = DictionaryEntry::new("Slackbot", vec!["slack bot".to_string()], None);
+        scoped.profile_id = Some("slack".to_string());
+        let log_store = RequestLogStore::new();
+        let pipeline = SharedPipeline::new(PipelineConfig {
+            dictionary: vec![
+                DictionaryEntry::new("Claude", vec!["clawed".to_string()], None),
+                scoped,
+            ],
+            request_log_store: Some(log_store.clone()),
+            ..Default::default()
+        });
+
+        let dictionary = pipeline.inner.lock().unwrap().dictation_dictionary(None);
+        assert_eq!(dictionary.len(), 1);
+
+        log_store.start_request("groq".to_string(), None);
+        let text = pipeline.apply_dictionary("ask clawed, not the slack bot", &dictionary);
+        assert_eq!(text, "ask Claude, not the slack bot");
+        let replacements = log_store
+            .with_current(|log| log.dictionary_replacements.clone())
+            .unwrap();
+        assert_eq!(replacements.len(), 1);
+        assert_eq!(replacements[0].spoken, "clawed");
     }
 
     #[test]
-    fn test_force_reset() {
-        let config = PipelineConfig {
-            stt_api_key: "test-key".to_string(),
+    fn test_redictation_proposes_dictionary_suggestions() {
+        let store = DictionarySuggestionStore::in_memory();
+        let mut inner = PipelineInner::new(PipelineConfig {
+            dictionary_suggestions: Some(store.clone()),
             ..Default::default()
-        };
-        let pipeline = SharedPipeline::new(config);
+        });
+
+        inner.learn_from_redictation("email the clawed team about the launch", None);
+        // A dictation in another profile is not a re-dictation.
+        inner.learn_from_redictation("email the cloud team about the launch", Some("slack"));
+        assert!(store.list().unwrap().is_empty());
+
+        inner.learn_from_redictation("email the Claude team about the launch", Some("slack"));
+        let suggestions = store.list().unwrap();
+        assert_eq!(suggestions.len(), 1);
+        assert_eq!(suggestions[0].correction.dictionary_entry(), "cloud = Claude");
+        assert_eq!(suggestions[0].profile_id.as_deref(), Some("slack"));
+    }
 
-        // Force reset should always work
-        pipeline.force_reset();
-        assert_eq!(pipeline.state(), PipelineState::Idle);
+    #[test]
+    fn test_only_connection_failures_count_as_network_errors() {
+        assert!(PipelineError::Stt(SttError::Timeout).is_network_error());
+        assert!(PipelineError::Timeout(Duration::from_secs(10)).is_network_error());
+        assert!(!PipelineError::Stt(SttError::Api(ApiError::new("bad key").with_status(401))).is_network_error());
+        assert!(!PipelineError::NoProvider.is_network_error());
     }
 }
@@ -0,0 +1,523 @@
+//! Per-provider health tracking and circuit breaking.
+//!
+//! The pipeline records the outcome of every STT/LLM request here, keyed by provider and
+//! model. After `failure_threshold` consecutive provider faults (network errors, timeouts,
+//! 5xx, rate limits) the provider's circuit opens: the pipeline then tries it last (STT) or
+//! skips it (LLM) until `cooldown_secs` have passed, after which one trial request decides
+//! whether the circuit closes again.
+
+use crate::retry::RetryableError;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Settings for provider health tracking.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProviderHealthConfig {
+    /// Deprioritize/skip providers whose circuit is open. Health is tracked either way.
+    pub circuit_breaker_enabled: bool,
+    /// Consecutive failed requests that open a provider's circuit.
+    pub failure_threshold: u32,
+    /// How long an open circuit stays open before a trial request is let through.
+    pub cooldown_secs: u64,
+    /// Number of recent requests used for the success rate and latency.
+    pub window_size: usize,
+}
+
+impl Default for ProviderHealthConfig {
+    fn default() -> Self {
+        Self {
+            circuit_breaker_enabled: true,
+            failure_threshold: 3,
+            cooldown_secs: 30,
+            window_size: 20,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProviderKind {
+    Stt,
+    Llm,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CircuitState {
+    /// Requests flow normally.
+    Closed,
+    /// Too many recent failures; the provider is avoided until the cooldown ends.
+    Open,
+    /// Cooldown over; the next request decides whether the circuit closes or reopens.
+    HalfOpen,
+}
+
+/// Health of one provider/model, as shown in the UI.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProviderHealthSnapshot {
+    pub kind: ProviderKind,
+    pub provider: String,
+    pub model: Option<String>,
+    pub circuit: CircuitState,
+    /// Share of successful requests in the rolling window (0..=1).
+    pub success_rate: f32,
+    /// Requests in the rolling window.
+    pub samples: usize,
+    /// Mean latency of the successful requests in the window.
+    pub avg_latency_ms: Option<u64>,
+    pub consecutive_failures: u32,
+    pub last_error: Option<String>,
+    /// Time left until an open circuit lets a trial request through.
+    pub retry_in_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct HealthKey {
+    kind: ProviderKind,
+    provider: String,
+    model: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Sample {
+    success: bool,
+    latency: Duration,
+}
+
+#[derive(Debug, Default)]
+struct ProviderHealth {
+    samples: VecDeque<Sample>,
+    consecutive_failures: u32,
+    /// Set when the circuit opens; refreshed by every failure while it stays open.
+    opened_at: Option<Instant>,
+    /// Whether the half-open trial request is in flight; no other request is let through
+    /// until it finishes.
+    trial_in_flight: bool,
+    last_error: Option<String>,
+}
+
+impl ProviderHealth {
+    fn circuit(&self, config: &ProviderHealthConfig, now: Instant) -> CircuitState {
+        match self.opened_at {
+            None => CircuitState::Closed,
+            Some(opened_at) if now.duration_since(opened_at) < config.cooldown() => CircuitState::Open,
+            Some(_) => CircuitState::HalfOpen,
+        }
+    }
+
+    fn admits_requests(&self, config: &ProviderHealthConfig, now: Instant) -> bool {
+        match self.circuit(config, now) {
+            CircuitState::Closed => true,
+            CircuitState::Open => false,
+            CircuitState::HalfOpen => !self.trial_in_flight,
+        }
+    }
+
+    fn push(&mut self, sample: Sample, window_size: usize) {
+        self.samples.push_back(sample);
+        while self.samples.len() > window_size.max(1) {
+            self.samples.pop_front();
+        }
+    }
+}
+
+impl ProviderHealthConfig {
+    fn cooldown(&self) -> Duration {
+        Duration::from_secs(self.cooldown_secs)
+    }
+}
+
+#[derive(Debug, Default)]
+struct TrackerState {
+    config: ProviderHealthConfig,
+    providers: HashMap<HealthKey, ProviderHealth>,
+}
+
+/// Shared, cloneable health tracker (one per pipeline).
+#[derive(Debug, Clone, Default)]
+pub struct ProviderHealthTracker {
+    state: Arc<Mutex<TrackerState>>,
+}
+
+impl ProviderHealthTracker {
+    pub fn new(config: ProviderHealthConfig) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(TrackerState {
+                config,
+                providers: HashMap::new(),
+            })),
+        }
+    }
+
+    /// Replace the settings; recorded health is kept.
+    pub fn set_config(&self, config: ProviderHealthConfig) {
+        if let Ok(mut state) = self.state.lock() {
+            state.config = config;
+        }
+    }
+
+    fn key(kind: ProviderKind, provider: &str, model: Option<&str>) -> HealthKey {
+        HealthKey {
+            kind,
+            provider: provider.to_string(),
+            model: model.map(str::to_string),
+        }
+    }
+
+    /// Whether requests should go to this provider now.
+    ///
+    /// Always true with the circuit breaker disabled; otherwise false while its circuit is
+    /// open, and while a half-open circuit's trial request is in flight.
+    pub fn is_available(&self, kind: ProviderKind, provider: &str, model: Option<&str>) -> bool {
+        self.is_available_at(kind, provider, model, Instant::now())
+    }
+
+    fn is_available_at(
+        &self,
+        kind: ProviderKind,
+        provider: &str,
+        model: Option<&str>,
+        now: Instant,
+    ) -> bool {
+        let Ok(state) = self.state.lock() else {
+            return true;
+        };
+        if !state.config.circuit_breaker_enabled {
+            return true;
+        }
+        state
+            .providers
+            .get(&Self::key(kind, provider, model))
+            .is_none_or(|health| health.admits_requests(&state.config, now))
+    }
+
+    /// Note that a request to this provider is starting.
+    ///
+    /// Returns whether it is the half-open circuit's trial request; the trial ends when its
+    /// outcome is recorded (or `end_trial` is called).
+    fn begin_request_at(
+        &self,
+        kind: ProviderKind,
+        provider: &str,
+        model: Option<&str>,
+        now: Instant,
+    ) -> bool {
+        let Ok(mut state) = self.state.lock() else {
+            return false;
+        };
+        let config = state.config.clone();
+        let Some(health) = state.providers.get_mut(&Self::key(kind, provider, model)) else {
+            return false;
+        };
+        if health.trial_in_flight || health.circuit(&config, now) != CircuitState::HalfOpen {
+            return false;
+        }
+        health.trial_in_flight = true;
+        true
+    }
+
+    /// Let the next request through a half-open circuit, e.g. after the trial request was
+    /// cancelled or failed for a reason unrelated to the provider.
+    fn end_trial(&self, kind: ProviderKind, provider: &str, model: Option<&str>) {
+        if let Ok(mut state) = self.state.lock() {
+            if let Some(health) = state.providers.get_mut(&Self::key(kind, provider, model)) {
+                health.trial_in_flight = false;
+            }
+        }
+    }
+
+    pub fn record_success(&self, kind: ProviderKind, provider: &str, model: Option<&str>, latency: Duration) {
+        let Ok(mut state) = self.state.lock() else {
+            return;
+        };
+        let window_size = state.config.window_size;
+        let health = state.providers.entry(Self::key(kind, provider, model)).or_default();
+        health.push(Sample { success: true, latency }, window_size);
+        if health.opened_at.is_some() {
+            log::info!("Provider health: {} circuit closed after a successful request", provider);
+        }
+        health.consecutive_failures = 0;
+        health.opened_at = None;
+        health.trial_in_flight = false;
+    }
+
+    pub fn record_failure(
+        &self,
+        kind: ProviderKind,
+        provider: &str,
+        model: Option<&str>,
+        latency: Duration,
+        error: String,
+    ) {
+        self.record_failure_at(kind, provider, model, latency, error, Instant::now());
+    }
+
+    fn record_failure_at(
+        &self,
+        kind: ProviderKind,
+        provider: &str,
+        model: Option<&str>,
+        latency: Duration,
+        error: String,
+        now: Instant,
+    ) {
+        let Ok(mut state) = self.state.lock() else {
+            return;
+        };
+        let config = state.config.clone();
+        let health = state.providers.entry(Self::key(kind, provider, model)).or_default();
+        health.push(Sample { success: false, latency }, config.window_size);
+        health.consecutive_failures += 1;
+        health.last_error = Some(error);
+        health.trial_in_flight = false;
+
+        if health.consecutive_failures >= config.failure_threshold.max(1) {
+            if health.opened_at.is_none() {
+                log::warn!(
+                    "Provider health: {} circuit opened after {} consecutive failures",
+                    provider,
+                    health.consecutive_failures
+                );
+            }
+            health.opened_at = Some(now);
+        }
+    }
+
+    /// Run `request`, recording its latency and outcome.
+    ///
+    /// `request` is one logical request: wrap the whole retry loop, not each attempt, so a
+    /// request that needed retries counts once. Only provider faults count as failures;
+    /// errors such as a rejected request (4xx) or a cancelled one say nothing about the
+    /// provider's health and are not recorded. If the circuit is half-open, this request is
+    /// its trial (see `begin_request_at`).
+    pub async fn track<T, E: RetryableError>(
+        &self,
+        kind: ProviderKind,
+        provider: &str,
+        model: Option<&str>,
+        request: impl std::future::Future<Output = Result<T, E>>,
+    ) -> Result<T, E> {
+        let started = Instant::now();
+        let _trial = self
+            .begin_request_at(kind, provider, model, started)
+            .then(|| TrialGuard { tracker: self, kind, provider, model });
+        let result = request.await;
+        match &result {
+            Ok(_) => self.record_success(kind, provider, model, started.elapsed()),
+            Err(e) if e.is_provider_fault() => {
+                self.record_failure(kind, provider, model, started.elapsed(), e.to_string())
+            }
+            Err(_) => {}
+        }
+        result
+    }
+
+    /// Health of every provider that has handled a request, sorted by kind/provider/model.
+    pub fn snapshot(&self) -> Vec<ProviderHealthSnapshot> {
+        self.snapshot_at(Instant::now())
+    }
+
+    fn snapshot_at(&self, now: Instant) -> Vec<ProviderHealthSnapshot> {
+        let Ok(state) = self.state.lock() else {
+            return Vec::new();
+        };
+
+        let mut snapshots: Vec<_> = state
+            .providers
+            .iter()
+            .map(|(key, health)| {
+                let samples = health.samples.len();
+                let successes: Vec<Duration> = health
+                    .samples
+                    .iter()
+                    .filter(|s| s.success)
+                    .map(|s| s.latency)
+                    .collect();
+                let circuit = health.circuit(&state.config, now);
+
+                ProviderHealthSnapshot {
+                    kind: key.kind,
+                    provider: key.provider.clone(),
+                    model: key.model.clone(),
+                    circuit,
+                    success_rate: if samples == 0 {
+                        1.0
+                    } else {
+                        successes.len() as f32 / samples as f32
+                    },
+                    samples,
+                    avg_latency_ms: (!successes.is_empty()).then(|| {
+                        (successes.iter().sum::<Duration>() / successes.len() as u32).as_millis() as u64
+                    }),
+                    consecutive_failures: health.consecutive_failures,
+                    last_error: health.last_error.clone(),
+                    retry_in_ms: match (circuit, health.opened_at) {
+                        (CircuitState::Open, Some(opened_at)) => Some(
+                            state
+                                .config
+                                .cooldown()
+                                .saturating_sub(now.duration_since(opened_at))
+                                .as_millis() as u64,
+                        ),
+                        _ => None,
+                    },
+                }
+            })
+            .collect();
+
+        snapshots.sort_by(|a, b| {
+            (a.kind == ProviderKind::Llm, &a.provider, &a.model)
+                .cmp(&(b.kind == ProviderKind::Llm, &b.provider, &b.model))
+        });
+        snapshots
+    }
+
+    /// Forget all recorded health (closes every circuit).
+    pub fn reset(&self) {
+        if let Ok(mut state) = self.state.lock() {
+            state.providers.clear();
+        }
+    }
+}
+
+/// Ends a half-open trial when its request finishes or is dropped without an outcome.
+struct TrialGuard<'a> {
+    tracker: &'a ProviderHealthTracker,
+    kind: ProviderKind,
+    provider: &'a str,
+    model: Option<&'a str>,
+}
+
+impl Drop for TrialGuard<'_> {
+    fn drop(&mut self) {
+        self.tracker.end_trial(self.kind, self.provider, self.model);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stt::SttError;
+
+    const MS: Duration = Duration::from_millis(1);
+
+    fn tracker() -> ProviderHealthTracker {
+        ProviderHealthTracker::new(ProviderHealthConfig::default())
+    }
+
+    fn fail(tracker: &ProviderHealthTracker, times: u32, now: Instant) {
+        for _ in 0..times {
+            tracker.record_failure_at(ProviderKind::Stt, "groq", None, MS, "503".to_string(), now);
+        }
+    }
+
+    #[test]
+    fn test_circuit_opens_after_consecutive_failures() {
+        let tracker = tracker();
+        let now = Instant::now();
+
+        fail(&tracker, 2, now);
+        assert!(tracker.is_available_at(ProviderKind::Stt, "groq", None, now));
+
+        fail(&tracker, 1, now);
+        assert!(!tracker.is_available_at(ProviderKind::Stt, "groq", None, now));
+        // Other models and kinds are tracked separately.
+        assert!(tracker.is_available_at(ProviderKind::Stt, "groq", Some("whisper-large-v3"), now));
+        assert!(tracker.is_available_at(ProviderKind::Llm, "groq", None, now));
+    }
+
+    #[test]
+    fn test_half_open_after_cooldown_and_closes_on_success() {
+        let tracker = tracker();
+        let now = Instant::now();
+        fail(&tracker, 3, now);
+
+        let later = now + Duration::from_secs(31);
+        assert!(tracker.is_available_at(ProviderKind::Stt, "groq", None, later));
+        assert_eq!(tracker.snapshot_at(later)[0].circuit, CircuitState::HalfOpen);
+
+        tracker.record_success(ProviderKind::Stt, "groq", None, 200 * MS);
+        let snapshot = &tracker.snapshot_at(later)[0];
+        assert_eq!(snapshot.circuit, CircuitState::Closed);
+        assert_eq!(snapshot.consecutive_failures, 0);
+        assert_eq!(snapshot.samples, 4);
+        assert_eq!(snapshot.success_rate, 0.25);
+        assert_eq!(snapshot.avg_latency_ms, Some(200));
+    }
+
+    #[test]
+    fn test_failed_trial_reopens_circuit() {
+        let tracker = tracker();
+        let now = Instant::now();
+        fail(&tracker, 3, now);
+
+        let later = now + Duration::from_secs(31);
+        fail(&tracker, 1, later);
+        assert!(!tracker.is_available_at(ProviderKind::Stt, "groq", None, later));
+        assert_eq!(tracker.snapshot_at(later)[0].retry_in_ms, Some(30_000));
+    }
+
+    #[test]
+    fn test_half_open_admits_a_single_trial() {
+        let tracker = tracker();
+        let now = Instant::now();
+        fail(&tracker, 3, now);
+
+        let later = now + Duration::from_secs(31);
+        assert!(tracker.begin_request_at(ProviderKind::Stt, "groq", None, later));
+        assert!(!tracker.is_available_at(ProviderKind::Stt, "groq", None, later));
+        assert!(!tracker.begin_request_at(ProviderKind::Stt, "groq", None, later));
+
+        tracker.record_failure_at(ProviderKind::Stt, "groq", None, MS, "503".to_string(), later);
+        assert!(!tracker.is_available_at(ProviderKind::Stt, "groq", None, later));
+        assert_eq!(tracker.snapshot_at(later)[0].circuit, CircuitState::Open);
+    }
+
+    #[tokio::test]
+    async fn test_trial_without_outcome_lets_next_request_through() {
+        let tracker = ProviderHealthTracker::new(ProviderHealthConfig {
+            cooldown_secs: 0,
+            ..Default::default()
+        });
+        fail(&tracker, 3, Instant::now());
+
+        // A rejected request (4xx) says nothing about the provider; the next one is the trial.
+        let result: Result<(), SttError> = tracker
+            .track(ProviderKind::Stt, "groq", None, async {
+                assert!(!tracker.is_available(ProviderKind::Stt, "groq", None));
+                Err(SttError::Config("bad request".to_string()))
+            })
+            .await;
+        assert!(result.is_err());
+        assert!(tracker.is_available(ProviderKind::Stt, "groq", None));
+        assert_eq!(tracker.snapshot()[0].circuit, CircuitState::HalfOpen);
+    }
+
+    #[test]
+    fn test_disabled_circuit_breaker_still_tracks() {
+        let tracker = ProviderHealthTracker::new(ProviderHealthConfig {
+            circuit_breaker_enabled: false,
+            ..Default::default()
+        });
+        let now = Instant::now();
+        fail(&tracker, 5, now);
+
+        assert!(tracker.is_available_at(ProviderKind::Stt, "groq", None, now));
+        assert_eq!(tracker.snapshot_at(now)[0].circuit, CircuitState::Open);
+    }
+
+    #[test]
+    fn test_window_is_bounded() {
+        let tracker = ProviderHealthTracker::new(ProviderHealthConfig {
+            window_size: 3,
+            ..Default::default()
+        });
+        for _ in 0..10 {
+            tracker.record_success(ProviderKind::Llm, "openai", Some("gpt-4o-mini"), MS);
+        }
+        assert_eq!(tracker.snapshot()[0].samples, 3);
+    }
+}
@@ -7,6 +7,9 @@
 //! - Timing information
 //! - Errors if any
 
+use crate::dictionary::DictionaryReplacement;
+use crate::snippets::ExpandedSnippet;
+use crate::stt::{AudioEncoding, HallucinationDrop, Transcript};
 use chrono::{DateTime, Duration as ChronoDuration, Utc};
 use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue;
@@ -82,6 +85,9 @@ pub struct RequestLog {
     pub stt_provider: String,
     /// STT model used
     pub stt_model: Option<String>,
+    /// Request whose saved recording this log re-transcribes (offline retry queue).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry_of: Option<String>,
     /// LLM provider used (if formatting enabled)
     pub llm_provider: Option<String>,
     /// LLM model used
@@ -90,6 +96,12 @@ pub struct RequestLog {
     pub audio_duration_secs: Option<f32>,
     /// Audio file size in bytes
     pub audio_size_bytes: Option<usize>,
+    /// Encoding of the compressed upload (None = the WAV was uploaded as-is)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub audio_upload_encoding: Option<AudioEncoding>,
+    /// Total bytes uploaded after compression (compare with `audio_size_bytes`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub audio_upload_size_bytes: Option<usize>,
     /// Sample rate of the audio
     pub sample_rate: Option<u32>,
     /// Raw transcript from STT
@@ -107,6 +119,22 @@ pub struct RequestLog {
     /// JSON response received from STT provider (if available).
     #[serde(skip_serializing_if = "Option::is_none")]
     pub stt_response_json: Option<JsonValue>,
+    /// Parsed STT result with segments/word timings/confidence (if the provider returned any).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stt_transcript: Option<Transcript>,
+
+    /// Outcome of the hedged STT race (only when hedging was enabled for this request).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stt_hedge: Option<SttHedgeLog>,
+    /// Segments removed from the STT output as likely hallucinations.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hallucination_drops: Vec<HallucinationDrop>,
+    /// Personal dictionary replacements made in the transcript.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dictionary_replacements: Vec<DictionaryReplacement>,
+    /// Voice snippets expanded in the transcript.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub snippet_expansions: Vec<ExpandedSnippet>,
 
     /// Payload sent to LLM provider (if LLM rewrite attempted).
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -128,6 +156,32 @@ pub struct RequestLog {
     pub llm_duration_ms: Option<u64>,
 }
 
+/// Which request won a hedged STT race.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SttHedgeWinner {
+    /// The original request answered first.
+    Primary,
+    /// The delayed second request answered first.
+    Hedge,
+}
+
+/// Outcome of a hedged (raced) STT request.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SttHedgeLog {
+    pub winner: SttHedgeWinner,
+    /// Provider that produced the transcript.
+    pub provider: String,
+    /// Model override of the winning request (None = provider default).
+    pub model: Option<String>,
+    /// How long the primary had to answer before the hedge request was sent.
+    pub hedge_delay_ms: u64,
+    /// Whether the hedge request was sent (the primary may answer before the delay).
+    pub hedge_sent: bool,
+    /// Time from the primary request until the winning transcript arrived.
+    pub latency_ms: u64,
+}
+
 /// Status of a request
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "snake_case")]
@@ -151,15 +205,23 @@ impl RequestLog {
             completed_at: None,
             stt_provider,
             stt_model,
+            retry_of: None,
             llm_provider: None,
             llm_model: None,
             audio_duration_secs: None,
             audio_size_bytes: None,
+            audio_upload_encoding: None,
+            audio_upload_size_bytes: None,
             sample_rate: None,
             raw_transcript: None,
             formatted_transcript: None,
             stt_request_json: None,
             stt_response_json: None,
+            stt_transcript: None,
+            stt_hedge: None,
+            hallucination_drops: Vec::new(),
+            dictionary_replacements: Vec::new(),
+            snippet_expansions: Vec::new(),
             llm_request_json: None,
             llm_response_json: None,
             status: RequestStatus::InProgress,
@@ -240,6 +302,43 @@ impl RequestLog {
     }
 }
 
+tokio::task_local! {
+    /// Set while a request with its own log runs (see `RequestLogScratch::scope`).
+    static SCRATCH_LOG: Arc<Mutex<RequestLog>>;
+}
+
+/// A separate log for work running alongside the current request: one of several concurrent
+/// STT requests (hedge racers), so they don't overwrite each other's payloads in the current
+/// log, or a background retry, which must not write into the dictation the user is recording.
+#[derive(Debug, Clone)]
+pub struct RequestLogScratch(Arc<Mutex<RequestLog>>);
+
+impl Default for RequestLogScratch {
+    fn default() -> Self {
+        Self(Arc::new(Mutex::new(RequestLog::new(String::new(), None))))
+    }
+}
+
+impl RequestLogScratch {
+    /// A scratch log for a request of its own (e.g. a background retry).
+    pub fn for_request(stt_provider: String, stt_model: Option<String>) -> Self {
+        Self(Arc::new(Mutex::new(RequestLog::new(
+            stt_provider,
+            stt_model,
+        ))))
+    }
+
+    /// What the scratch log recorded so far.
+    pub fn snapshot(&self) -> RequestLog {
+        self.0.lock().unwrap().clone()
+    }
+
+    /// Run `future` with every `RequestLogStore::with_current` call inside it writing here.
+    pub async fn scope<F: std::future::Future>(&self, future: F) -> F::Output {
+        SCRATCH_LOG.scope(self.0.clone(), future).await
+    }
+}
+
 /// Thread-safe request log store
 #[derive(Debug, Clone)]
 pub struct RequestLogStore {
@@ -334,14 +433,38 @@ impl RequestLogStore {
     }
 
     /// Get the current request log for modification
+    ///
+    /// Inside `RequestLogScratch::scope` this is the scratch log instead.
     pub fn with_current<F, R>(&self, f: F) -> Option<R>
     where
         F: FnOnce(&mut RequestLog) -> R,
     {
+        if let Ok(scratch) = SCRATCH_LOG.try_with(Arc::clone) {
+            return Some(f(&mut scratch.lock().unwrap()));
+        }
         let mut current = self.current.lock().unwrap();
         current.as_mut().map(f)
     }
 
+    /// Copy what a scratch log recorded into the current log.
+    ///
+    /// Log entries are always kept; the STT request/response payloads (and the hedge race
+    /// they came from) only when `exchange` is set (the request whose transcript was used).
+    pub fn merge_scratch(&self, scratch: &RequestLogScratch, exchange: bool) {
+        let scratch = scratch.0.lock().unwrap();
+        self.with_current(|log| {
+            if exchange {
+                log.stt_request_json = scratch.stt_request_json.clone();
+                log.stt_response_json = scratch.stt_response_json.clone();
+                if scratch.stt_hedge.is_some() {
+                    log.stt_hedge = scratch.stt_hedge.clone();
+                }
+            }
+            log.entries.extend(scratch.entries.iter().cloned());
+            log.entries.sort_by_key(|entry| entry.timestamp);
+        });
+    }
+
     /// Complete the current request and store it
     pub fn complete_current(&self) {
         let mut current = self.current.lock().unwrap();
@@ -350,6 +473,12 @@ impl RequestLogStore {
         }
     }
 
+    /// Store a finished log that was recorded outside the current request (see
+    /// `RequestLogScratch::for_request`).
+    pub fn add_completed(&self, log: RequestLog) {
+        self.store_log(log);
+    }
+
     /// Store a completed log
     fn store_log(&self, log: RequestLog) {
         let mut logs = self.logs.lock().unwrap();
@@ -439,4 +568,32 @@ mod tests {
         assert_eq!(logs[0].id, id2); // Most recent first
         assert_eq!(logs[1].id, id1);
     }
+
+    #[tokio::test]
+    async fn test_scratch_logs_keep_racers_apart() {
+        let store = RequestLogStore::new();
+        store.start_request("openai".to_string(), None);
+        let (winner, loser) = (RequestLogScratch::default(), RequestLogScratch::default());
+
+        let write = |name: &'static str| {
+            let store = store.clone();
+            async move {
+                store.with_current(|log| {
+                    log.stt_request_json = Some(serde_json::json!({ "provider": name }));
+                    log.info(format!("{} sent", name));
+                });
+            }
+        };
+        winner.scope(write("winner")).await;
+        loser.scope(write("loser")).await;
+        assert!(store.with_current(|log| log.stt_request_json.is_none()).unwrap());
+
+        store.merge_scratch(&loser, false);
+        store.merge_scratch(&winner, true);
+        let log = store.with_current(|log| log.clone()).unwrap();
+        assert_eq!(log.stt_request_json.unwrap()["provider"], "winner");
+        let messages: Vec<&str> = log.entries.iter().map(|e| e.message.as_str()).collect();
+        assert_eq!(messages.len(), 2);
+        assert!(messages.contains(&"winner sent") && messages.contains(&"loser sent"));
+    }
 }
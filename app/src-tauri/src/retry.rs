@@ -0,0 +1,231 @@
+//! Retry utilities for STT and LLM providers with exponential backoff.
+
+use crate::api_error::ApiError;
+use std::time::Duration;
+
+/// Configuration for retry behavior
+#[derive(Debug, Clone)]
+pub struct RetryConfig {
+    /// Maximum number of retry attempts
+    pub max_retries: u32,
+    /// Initial delay before first retry (doubles with each attempt)
+    pub initial_delay: Duration,
+    /// Maximum delay between retries
+    pub max_delay: Duration,
+    /// Whether to retry on rate limit errors
+    pub retry_on_rate_limit: bool,
+    /// Longest server-requested wait (`Retry-After`) to honor; longer hints fail immediately
+    pub max_retry_after: Duration,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            initial_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(10),
+            retry_on_rate_limit: true,
+            max_retry_after: Duration::from_secs(30),
+        }
+    }
+}
+
+impl RetryConfig {
+    /// Create a new retry config with custom max retries
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn with_max_retries(max_retries: u32) -> Self {
+        Self {
+            max_retries,
+            ..Default::default()
+        }
+    }
+
+    /// Calculate the delay for a given attempt number (0-indexed)
+    pub fn delay_for_attempt(&self, attempt: u32) -> Duration {
+        let delay = self
+            .initial_delay
+            .saturating_mul(2u32.saturating_pow(attempt));
+        std::cmp::min(delay, self.max_delay)
+    }
+}
+
+/// Errors that `with_retry` can classify.
+pub trait RetryableError: std::fmt::Display {
+    /// Label used in retry log messages ("STT", "LLM").
+    const KIND: &'static str;
+
+    /// Connection-level failures that are always worth retrying (network errors, timeouts).
+    fn is_transient(&self) -> bool;
+
+    /// The structured error returned by the provider API, if any.
+    fn api_error(&self) -> Option<&ApiError>;
+
+    /// Whether the failure points at the provider being unhealthy (unreachable, overloaded,
+    /// rate limited), as opposed to a problem with this particular request.
+    fn is_provider_fault(&self) -> bool {
+        self.is_transient()
+            || self
+                .api_error()
+                .is_some_and(|e| e.is_transient_server_error() || e.is_rate_limited())
+    }
+}
+
+/// How long to wait before retrying after `error`, or `None` if it should not be retried.
+///
+/// API errors are judged by their HTTP status only; a server-provided `Retry-After` replaces
+/// the exponential backoff.
+fn retry_delay<E: RetryableError>(error: &E, config: &RetryConfig, attempt: u32) -> Option<Duration> {
+    let backoff = config.delay_for_attempt(attempt);
+    if error.is_transient() {
+        return Some(backoff);
+    }
+
+    let api_error = error.api_error()?;
+    let retryable = api_error.is_transient_server_error()
+        || (config.retry_on_rate_limit && api_error.is_rate_limited());
+    if !retryable {
+        return None;
+    }
+
+    match api_error.retry_after {
+        Some(hint) if hint > config.max_retry_after => None,
+        Some(hint) => Some(hint),
+        None => Some(backoff),
+    }
+}
+
+/// Determines if an error is retryable.
+///
+/// Note: this uses a default policy (including retrying rate-limit errors).
+/// If you need to respect a specific `RetryConfig`, use `with_retry`.
+#[cfg_attr(not(test), allow(dead_code))]
+pub fn is_retryable_error<E: RetryableError>(error: &E) -> bool {
+    retry_delay(error, &RetryConfig::default(), 0).is_some()
+}
+
+/// Execute an async function with retry logic
+pub async fn with_retry<F, Fut, T, E>(config: &RetryConfig, operation: F) -> Result<T, E>
+where
+    F: Fn() -> Fut,
+    Fut: std::future::Future<Output = Result<T, E>>,
+    E: RetryableError,
+{
+    let mut attempt = 0;
+    loop {
+        let error = match operation().await {
+            Ok(result) => return Ok(result),
+            Err(e) => e,
+        };
+
+        let delay = match retry_delay(&error, config, attempt) {
+            Some(delay) if attempt < config.max_retries => delay,
+            _ => return Err(error),
+        };
+
+        log::warn!(
+            "{} request failed (attempt {}/{}), retrying in {:?}: {}",
+            E::KIND,
+            attempt + 1,
+            config.max_retries + 1,
+            delay,
+            error
+        );
+
+        tokio::time::sleep(delay).await;
+        attempt += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::LlmError;
+    use crate::stt::SttError;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    #[test]
+    fn test_delay_calculation() {
+        let config = RetryConfig::default();
+
+        // Initial delay: 500ms
+        assert_eq!(config.delay_for_attempt(0), Duration::from_millis(500));
+        // Second attempt: 1000ms
+        assert_eq!(config.delay_for_attempt(1), Duration::from_millis(1000));
+        // Third attempt: 2000ms
+        assert_eq!(config.delay_for_attempt(2), Duration::from_millis(2000));
+        // Fourth attempt: 4000ms
+        assert_eq!(config.delay_for_attempt(3), Duration::from_millis(4000));
+    }
+
+    #[test]
+    fn test_max_delay_capping() {
+        let config = RetryConfig {
+            max_delay: Duration::from_secs(2),
+            ..Default::default()
+        };
+
+        // Should cap at max_delay
+        assert_eq!(config.delay_for_attempt(10), Duration::from_secs(2));
+    }
+
+    #[test]
+    fn test_is_retryable_error() {
+        let api = |status: u16| ApiError::new("API error").with_status(status);
+
+        assert!(is_retryable_error(&SttError::Timeout));
+        assert!(is_retryable_error(&SttError::Api(api(500))));
+        assert!(is_retryable_error(&SttError::Api(api(429))));
+        assert!(!is_retryable_error(&SttError::Api(api(400))));
+        assert!(!is_retryable_error(&SttError::Config("Invalid API key".to_string())));
+        assert!(!is_retryable_error(&SttError::Audio("Invalid audio format".to_string())));
+        assert!(is_retryable_error(&LlmError::Api(api(503))));
+        assert!(!is_retryable_error(&LlmError::NoApiKey("openai".to_string())));
+    }
+
+    #[test]
+    fn test_status_codes_in_message_text_are_ignored() {
+        let error = SttError::Api("Invalid request: expected 500 samples, got 429".into());
+        assert!(!is_retryable_error(&error));
+    }
+
+    #[test]
+    fn test_retry_after_overrides_backoff() {
+        let config = RetryConfig::default();
+        let hinted = SttError::Api(
+            ApiError::new("slow down")
+                .with_status(429)
+                .with_retry_after(Duration::from_secs(2)),
+        );
+        let too_long = SttError::Api(
+            ApiError::new("come back tomorrow")
+                .with_status(503)
+                .with_retry_after(Duration::from_secs(3600)),
+        );
+
+        assert_eq!(retry_delay(&hinted, &config, 0), Some(Duration::from_secs(2)));
+        assert_eq!(retry_delay(&too_long, &config, 0), None);
+    }
+
+    #[tokio::test]
+    async fn test_with_retry_recovers_from_rate_limit() {
+        let calls = AtomicU32::new(0);
+        let result = with_retry(&RetryConfig::default(), || {
+            let call = calls.fetch_add(1, Ordering::SeqCst);
+            async move {
+                if call == 0 {
+                    Err(LlmError::Api(
+                        ApiError::new("rate limited")
+                            .with_status(429)
+                            .with_retry_after(Duration::from_millis(10)),
+                    ))
+                } else {
+                    Ok("formatted")
+                }
+            }
+        })
+        .await;
+
+        assert_eq!(result.unwrap(), "formatted");
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+}
@@ -0,0 +1,300 @@
+//! Persistent queue of dictations that failed because the network was unavailable.
+//!
+//! The saved WAV for each request lives in `RecordingStore`; this queue only tracks which
+//! request ids should be re-transcribed once a provider is reachable again.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::RwLock;
+use std::time::Duration;
+
+/// Settings for automatically retrying queued dictations.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OfflineRetryConfig {
+    /// Queue dictations that failed on network errors and retry them when back online
+    pub enabled: bool,
+    /// Seconds between connectivity checks while the queue is non-empty
+    pub check_interval_secs: u64,
+    /// Give up on a dictation after this many automatic attempts
+    pub max_attempts: u32,
+    /// Copy the recovered text to the clipboard once it is ready
+    pub copy_to_clipboard: bool,
+}
+
+impl Default for OfflineRetryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            check_interval_secs: 15,
+            max_attempts: 5,
+            copy_to_clipboard: false,
+        }
+    }
+}
+
+impl OfflineRetryConfig {
+    pub fn check_interval(&self) -> Duration {
+        Duration::from_secs(self.check_interval_secs.max(1))
+    }
+}
+
+/// A dictation waiting to be re-transcribed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QueuedRetry {
+    pub request_id: String,
+    pub queued_at: DateTime<Utc>,
+    /// Program profile the dictation was recorded with (None = Default)
+    #[serde(default)]
+    pub profile_id: Option<String>,
+    /// Automatic attempts made so far (the original failure is not counted)
+    #[serde(default)]
+    pub attempts: u32,
+    #[serde(default)]
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+struct RetryQueueData {
+    entries: Vec<QueuedRetry>,
+}
+
+/// Manages loading and saving of the offline retry queue (oldest first).
+pub struct RetryQueue {
+    data: RwLock<RetryQueueData>,
+    file_path: Option<PathBuf>,
+}
+
+impl RetryQueue {
+    /// Create a queue persisted to `retry_queue.json` in the given app data directory
+    pub fn new(app_data_dir: PathBuf) -> Self {
+        let file_path = app_data_dir.join("retry_queue.json");
+
+        if let Some(parent) = file_path.parent() {
+            let _ = fs::create_dir_all(parent);
+        }
+
+        let data = fs::read_to_string(&file_path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+
+        Self {
+            data: RwLock::new(data),
+            file_path: Some(file_path),
+        }
+    }
+
+    /// Create a queue that is never written to disk
+    #[cfg(test)]
+    pub fn in_memory() -> Self {
+        Self {
+            data: RwLock::new(RetryQueueData::default()),
+            file_path: None,
+        }
+    }
+
+    fn save(&self) -> Result<(), String> {
+        let Some(file_path) = &self.file_path else {
+            return Ok(());
+        };
+
+        let data = self
+            .data
+            .read()
+            .map_err(|e| format!("Failed to read retry queue: {}", e))?;
+
+        let content = serde_json::to_string_pretty(&*data)
+            .map_err(|e| format!("Failed to serialize retry queue: {}", e))?;
+
+        fs::write(file_path, content)
+            .map_err(|e| format!("Failed to write retry queue file: {}", e))?;
+
+        Ok(())
+    }
+
+    /// Add a request to the end of the queue. Returns false if it was already queued.
+    pub fn enqueue(
+        &self,
+        request_id: &str,
+        profile_id: Option<String>,
+        error_message: String,
+    ) -> Result<bool, String> {
+        {
+            let mut data = self
+                .data
+                .write()
+                .map_err(|e| format!("Failed to write retry queue: {}", e))?;
+
+            if let Some(entry) = data.entries.iter_mut().find(|e| e.request_id == request_id) {
+                entry.last_error = Some(error_message);
+                return Ok(false);
+            }
+
+            data.entries.push(QueuedRetry {
+                request_id: request_id.to_string(),
+                queued_at: Utc::now(),
+                profile_id,
+                attempts: 0,
+                last_error: Some(error_message),
+            });
+        }
+        self.save()?;
+        Ok(true)
+    }
+
+    /// Record a failed automatic attempt.
+    ///
+    /// Returns the updated entry, or `None` if the request is not queued (anymore). Once
+    /// `max_attempts` is reached the entry is dropped from the queue and still returned so the
+    /// caller can mark the dictation as failed.
+    pub fn record_failure(
+        &self,
+        request_id: &str,
+        error_message: String,
+        max_attempts: u32,
+    ) -> Result<Option<QueuedRetry>, String> {
+        let updated = {
+            let mut data = self
+                .data
+                .write()
+                .map_err(|e| format!("Failed to write retry queue: {}", e))?;
+
+            let Some(index) = data.entries.iter().position(|e| e.request_id == request_id) else {
+                return Ok(None);
+            };
+
+            let entry = &mut data.entries[index];
+            entry.attempts += 1;
+            entry.last_error = Some(error_message);
+            let updated = entry.clone();
+
+            if updated.attempts >= max_attempts.max(1) {
+                data.entries.remove(index);
+            }
+            updated
+        };
+        self.save()?;
+        Ok(Some(updated))
+    }
+
+    /// Remove a request from the queue. Returns true if it was queued.
+    pub fn remove(&self, request_id: &str) -> Result<bool, String> {
+        let removed = {
+            let mut data = self
+                .data
+                .write()
+                .map_err(|e| format!("Failed to write retry queue: {}", e))?;
+
+            let before = data.entries.len();
+            data.entries.retain(|e| e.request_id != request_id);
+            data.entries.len() != before
+        };
+        if removed {
+            self.save()?;
+        }
+        Ok(removed)
+    }
+
+    /// Drop every queued request
+    pub fn clear(&self) -> Result<(), String> {
+        {
+            let mut data = self
+                .data
+                .write()
+                .map_err(|e| format!("Failed to write retry queue: {}", e))?;
+            data.entries.clear();
+        }
+        self.save()
+    }
+
+    /// All queued requests, oldest first
+    pub fn entries(&self) -> Result<Vec<QueuedRetry>, String> {
+        let data = self
+            .data
+            .read()
+            .map_err(|e| format!("Failed to read retry queue: {}", e))?;
+        Ok(data.entries.clone())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.read().map(|d| d.entries.is_empty()).unwrap_or(true)
+    }
+}
+
+/// Endpoint used to check whether a cloud STT provider is reachable again.
+///
+/// Returns `None` for providers that run locally and need no network.
+pub fn connectivity_probe_url(stt_provider: &str) -> Option<&'static str> {
+    match stt_provider {
+        "openai" => Some("https://api.openai.com"),
+        "groq" => Some("https://api.groq.com"),
+        "deepgram" => Some("https://api.deepgram.com"),
+        "assemblyai" => Some("https://api.assemblyai.com"),
+        _ => None,
+    }
+}
+
+/// Cheap reachability check: any HTTP response (even 404) means the host is back.
+pub async fn probe_connectivity(client: &reqwest::Client, url: &str) -> bool {
+    client
+        .head(url)
+        .timeout(Duration::from_secs(5))
+        .send()
+        .await
+        .is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_enqueue_is_idempotent_and_ordered() {
+        let queue = RetryQueue::in_memory();
+        assert!(queue.enqueue("a", None, "network down".to_string()).unwrap());
+        assert!(queue.enqueue("b", None, "network down".to_string()).unwrap());
+        assert!(!queue.enqueue("a", None, "still down".to_string()).unwrap());
+
+        let entries = queue.entries().unwrap();
+        let ids: Vec<_> = entries.iter().map(|e| e.request_id.as_str()).collect();
+        assert_eq!(ids, vec!["a", "b"]);
+        assert_eq!(entries[0].last_error.as_deref(), Some("still down"));
+    }
+
+    #[test]
+    fn test_record_failure_drops_entry_after_max_attempts() {
+        let queue = RetryQueue::in_memory();
+        queue.enqueue("a", None, "network down".to_string()).unwrap();
+
+        let first = queue.record_failure("a", "timeout".to_string(), 2).unwrap().unwrap();
+        assert_eq!(first.attempts, 1);
+        assert!(!queue.is_empty());
+
+        let second = queue.record_failure("a", "timeout".to_string(), 2).unwrap().unwrap();
+        assert_eq!(second.attempts, 2);
+        assert!(queue.is_empty());
+
+        assert!(queue.record_failure("a", "timeout".to_string(), 2).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_queue_persists_across_instances() {
+        let dir = std::env::temp_dir().join(format!("retry-queue-test-{}", uuid::Uuid::new_v4()));
+        {
+            let queue = RetryQueue::new(dir.clone());
+            queue.enqueue("a", Some("slack".to_string()), "network down".to_string()).unwrap();
+        }
+
+        let reloaded = RetryQueue::new(dir.clone());
+        let entry = &reloaded.entries().unwrap()[0];
+        assert_eq!(entry.request_id, "a");
+        assert_eq!(entry.profile_id.as_deref(), Some("slack"));
+        assert!(reloaded.remove("a").unwrap());
+        assert!(RetryQueue::new(dir.clone()).is_empty());
+
+        let _ = fs::remove_dir_all(dir);
+    }
+}
@@ -1,7 +1,9 @@
 use serde::{Deserialize, Serialize};
 use std::str::FromStr;
 
-use crate::llm::PromptSections;
+use crate::llm::{LlmFallbackEntry, PromptSections};
+use crate::stt::SttLanguage;
+use crate::translation::TranslationConfig;
 
 #[cfg(desktop)]
 use tauri_plugin_global_shortcut::Shortcut;
@@ -22,6 +24,9 @@ pub const DEFAULT_HOLD_KEY: &str = "Backquote";
 /// Default key for paste last transcription (Ctrl+Alt+.)
 pub const DEFAULT_PASTE_LAST_KEY: &str = "Period";
 
+/// Default key for transforming the selected text by voice instruction (Ctrl+Alt+/)
+pub const DEFAULT_TRANSFORM_KEY: &str = "Slash";
+
 // ============================================================================
 // DEFAULT VAD SETTINGS - Voice Activity Detection
 // ============================================================================
@@ -101,6 +106,17 @@ impl HotkeyConfig {
         }
     }
 
+    /// Create default transform-selection hotkey config
+    pub fn default_transform() -> Self {
+        Self {
+            modifiers: DEFAULT_HOTKEY_MODIFIERS
+                .iter()
+                .map(|s| s.to_string())
+                .collect(),
+            key: DEFAULT_TRANSFORM_KEY.to_string(),
+        }
+    }
+
     /// Convert to shortcut string format like "ctrl+alt+Space"
     /// Note: modifiers must be lowercase for the parser to recognize them
     pub fn to_shortcut_string(&self) -> String {
@@ -258,6 +274,17 @@ pub struct RewriteProgramPromptProfile {
     pub llm_provider: Option<String>,
     #[serde(default)]
     pub llm_model: Option<String>,
+    /// Optional per-profile LLM fallback chain (falls back to global `llm_fallback_chain`)
+    #[serde(default)]
+    pub llm_fallback_chain: Option<Vec<LlmFallbackEntry>>,
+
+    /// Optional per-profile translation mode (falls back to global `translation`)
+    #[serde(default)]
+    pub translation: Option<TranslationConfig>,
+
+    /// Optional per-profile spoken language (falls back to global `stt_language`)
+    #[serde(default)]
+    pub stt_language: Option<SttLanguage>,
 }
 
 fn deserialize_program_paths<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
@@ -0,0 +1,575 @@
+//! Voice snippets: spoken trigger phrases that expand to stored text.
+//!
+//! Matching happens right after STT and is deterministic: a dictation that consists only of a
+//! trigger is replaced by the snippet body without an LLM rewrite, and (for multi-word
+//! triggers) a trigger spoken inside a longer dictation is expanded in place. Triggers are
+//! compared word by word, ignoring case, punctuation, filler words and small misspellings.
+//!
+//! Bodies may contain the prompt template variables (`{{date}}`, `{{clipboard}}`, ...), which
+//! are filled when the dictation is processed.
+
+use crate::llm::{template_variables, PROMPT_VARIABLES};
+use crate::text_normalize::{edit_distance, normalize_word, word_ranges};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::ops::Range;
+use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
+use uuid::Uuid;
+
+/// Spoken filler ignored when comparing a dictation with a trigger.
+const FILLER_WORDS: &[&str] = &["um", "uh", "erm", "er", "ah", "hmm", "please"];
+
+/// Punctuation STT adds after a trigger; dropped when the body brings its own ending.
+const TRAILING_PUNCTUATION: &[char] = &['.', ',', ';', ':', '!', '?'];
+
+/// A stored snippet.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Snippet {
+    pub id: String,
+    /// Spoken phrases that insert the snippet (e.g. "insert meeting link").
+    pub triggers: Vec<String>,
+    /// Text inserted for the trigger (may contain template variables).
+    pub body: String,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Also expand multi-word triggers spoken inside a longer dictation.
+    #[serde(default = "default_true")]
+    pub inline: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Fields of a snippet as created or edited in the UI.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct SnippetInput {
+    pub triggers: Vec<String>,
+    pub body: String,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    #[serde(default = "default_true")]
+    pub inline: bool,
+}
+
+impl SnippetInput {
+    /// Trimmed triggers and a checked body, or why the input can't be saved.
+    fn validate(self) -> Result<Self, String> {
+        let mut triggers: Vec<String> = Vec::new();
+        for trigger in self.triggers {
+            let trigger = trigger.trim().to_string();
+            if words(&trigger).is_empty() {
+                continue;
+            }
+            if !triggers.iter().any(|t| t.eq_ignore_ascii_case(&trigger)) {
+                triggers.push(trigger);
+            }
+        }
+        if triggers.is_empty() {
+            return Err("A snippet needs at least one trigger phrase".to_string());
+        }
+        if self.body.trim().is_empty() {
+            return Err("Snippet text cannot be empty".to_string());
+        }
+
+        let unknown: Vec<String> = template_variables(&self.body)
+            .into_iter()
+            .filter(|name| !PROMPT_VARIABLES.contains(&name.as_str()))
+            .collect();
+        if !unknown.is_empty() {
+            return Err(format!(
+                "Unknown snippet variable(s): {}",
+                unknown.join(", ")
+            ));
+        }
+
+        Ok(Self { triggers, ..self })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+struct SnippetData {
+    snippets: Vec<Snippet>,
+}
+
+/// Manages loading and saving of voice snippets.
+///
+/// Clones share the same data (the pipeline keeps one to read the current snippets).
+#[derive(Debug, Clone)]
+pub struct SnippetStore {
+    data: Arc<RwLock<SnippetData>>,
+    file_path: Option<PathBuf>,
+}
+
+impl SnippetStore {
+    /// Create a store persisted to `snippets.json` in the given app data directory
+    pub fn new(app_data_dir: PathBuf) -> Self {
+        let file_path = app_data_dir.join("snippets.json");
+
+        if let Some(parent) = file_path.parent() {
+            let _ = fs::create_dir_all(parent);
+        }
+
+        let data = fs::read_to_string(&file_path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+
+        Self {
+            data: Arc::new(RwLock::new(data)),
+            file_path: Some(file_path),
+        }
+    }
+
+    /// Create a store that is never written to disk
+    #[cfg(test)]
+    pub fn in_memory() -> Self {
+        Self {
+            data: Arc::new(RwLock::new(SnippetData::default())),
+            file_path: None,
+        }
+    }
+
+    fn save(&self) -> Result<(), String> {
+        let Some(file_path) = &self.file_path else {
+            return Ok(());
+        };
+
+        let data = self
+            .data
+            .read()
+            .map_err(|e| format!("Failed to read snippets: {}", e))?;
+
+        let content = serde_json::to_string_pretty(&*data)
+            .map_err(|e| format!("Failed to serialize snippets: {}", e))?;
+
+        fs::write(file_path, content)
+            .map_err(|e| format!("Failed to write snippets file: {}", e))?;
+
+        Ok(())
+    }
+
+    /// All snippets, in creation order
+    pub fn list(&self) -> Result<Vec<Snippet>, String> {
+        let data = self
+            .data
+            .read()
+            .map_err(|e| format!("Failed to read snippets: {}", e))?;
+        Ok(data.snippets.clone())
+    }
+
+    /// Snippets that take part in matching
+    pub fn enabled(&self) -> Vec<Snippet> {
+        self.list()
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|s| s.enabled)
+            .collect()
+    }
+
+    /// Add a snippet
+    pub fn add(&self, input: SnippetInput) -> Result<Snippet, String> {
+        let input = input.validate()?;
+        let snippet = Snippet {
+            id: Uuid::new_v4().to_string(),
+            triggers: input.triggers,
+            body: input.body,
+            enabled: input.enabled,
+            inline: input.inline,
+        };
+
+        {
+            let mut data = self
+                .data
+                .write()
+                .map_err(|e| format!("Failed to write snippets: {}", e))?;
+            data.snippets.push(snippet.clone());
+        }
+
+        self.save()?;
+        Ok(snippet)
+    }
+
+    /// Replace the fields of an existing snippet
+    pub fn update(&self, id: &str, input: SnippetInput) -> Result<Snippet, String> {
+        let input = input.validate()?;
+
+        let updated = {
+            let mut data = self
+                .data
+                .write()
+                .map_err(|e| format!("Failed to write snippets: {}", e))?;
+            let snippet = data
+                .snippets
+                .iter_mut()
+                .find(|s| s.id == id)
+                .ok_or_else(|| format!("Snippet not found: {}", id))?;
+            snippet.triggers = input.triggers;
+            snippet.body = input.body;
+            snippet.enabled = input.enabled;
+            snippet.inline = input.inline;
+            snippet.clone()
+        };
+
+        self.save()?;
+        Ok(updated)
+    }
+
+    /// Delete a snippet. Returns false if it didn't exist.
+    pub fn delete(&self, id: &str) -> Result<bool, String> {
+        let removed = {
+            let mut data = self
+                .data
+                .write()
+                .map_err(|e| format!("Failed to write snippets: {}", e))?;
+            let before = data.snippets.len();
+            data.snippets.retain(|s| s.id != id);
+            data.snippets.len() != before
+        };
+
+        if removed {
+            self.save()?;
+        }
+        Ok(removed)
+    }
+}
+
+/// A snippet expansion, as recorded in the request log.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExpandedSnippet {
+    pub snippet_id: String,
+    /// The trigger that matched.
+    pub trigger: String,
+    /// What was actually said (the replaced part of the transcript).
+    pub spoken: String,
+    /// The whole dictation was the trigger.
+    pub full_match: bool,
+}
+
+/// Result of matching snippets against a transcript.
+#[derive(Debug, Clone, Default)]
+pub struct SnippetExpansion {
+    pub expanded: Vec<ExpandedSnippet>,
+    /// The whole dictation was a trigger: `text()` is the snippet body.
+    pub full_match: bool,
+    source: String,
+    replacements: Vec<(Range<usize>, String)>,
+}
+
+impl SnippetExpansion {
+    pub fn is_empty(&self) -> bool {
+        self.replacements.is_empty()
+    }
+
+    /// The transcript with the snippet bodies in place of their triggers.
+    pub fn text(&self) -> String {
+        self.replace_with(|_, body| body.to_string())
+    }
+
+    /// The transcript with markers in place of the triggers, for the LLM rewrite.
+    pub fn marked_text(&self) -> String {
+        self.replace_with(|index, _| marker(index))
+    }
+
+    /// Put the snippet bodies back into a rewrite of `marked_text()`.
+    ///
+    /// None when the rewrite lost, duplicated or invented a marker.
+    pub fn restore_markers(&self, rewritten: &str) -> Option<String> {
+        if rewritten.matches("[[snippet").count() != self.replacements.len() {
+            return None;
+        }
+
+        let mut restored = rewritten.to_string();
+        for (index, (_, body)) in self.replacements.iter().enumerate() {
+            let marker = marker(index);
+            if restored.matches(&marker).count() != 1 {
+                return None;
+            }
+            restored = restored.replacen(&marker, body, 1);
+        }
+        Some(restored)
+    }
+
+    fn replace_with(&self, replacement: impl Fn(usize, &str) -> String) -> String {
+        let mut text = String::with_capacity(self.source.len());
+        let mut last = 0;
+        for (index, (range, body)) in self.replacements.iter().enumerate() {
+            text.push_str(&self.source[last..range.start]);
+            text.push_str(&replacement(index, body));
+            last = range.end;
+        }
+        text.push_str(&self.source[last..]);
+        text
+    }
+}
+
+fn marker(index: usize) -> String {
+    format!("[[snippet{}]]", index + 1)
+}
+
+/// A word of the transcript: lowercased, without punctuation, with its position.
+struct Word {
+    text: String,
+    range: Range<usize>,
+}
+
+fn words(text: &str) -> Vec<Word> {
+    word_ranges(text)
+        .into_iter()
+        .map(|range| Word {
+            text: normalize_word(&text[range.clone()]),
+            range,
+        })
+        .filter(|word| !word.text.is_empty() && !FILLER_WORDS.contains(&word.text.as_str()))
+        .collect()
+}
+
+/// Whether a spoken word matches a trigger word, allowing a typo per 4 letters (max 2).
+fn similar(spoken: &str, trigger: &str) -> bool {
+    if spoken == trigger {
+        return true;
+    }
+    let len = trigger.chars().count();
+    if len < 4 || spoken.chars().count().abs_diff(len) > 2 {
+        return false;
+    }
+    let allowed = (len / 4).min(2);
+    let spoken: Vec<char> = spoken.chars().collect();
+    let trigger: Vec<char> = trigger.chars().collect();
+    edit_distance(&spoken, &trigger) <= allowed
+}
+
+fn matches_at(spoken: &[Word], trigger: &[String]) -> bool {
+    spoken.len() >= trigger.len()
+        && spoken
+            .iter()
+            .zip(trigger)
+            .all(|(word, expected)| similar(&word.text, expected))
+}
+
+/// Expand the snippets triggered in `text`.
+///
+/// A dictation that is exactly one trigger becomes that snippet's body. Otherwise inline
+/// snippets with multi-word triggers are expanded where they were spoken (longest trigger
+/// first, left to right).
+pub fn expand_snippets(text: &str, snippets: &[Snippet]) -> SnippetExpansion {
+    let mut expansion = SnippetExpansion {
+        source: text.to_string(),
+        ..Default::default()
+    };
+    let spoken = words(text);
+    if spoken.is_empty() {
+        return expansion;
+    }
+
+    // (snippet, trigger, trigger words), longest trigger first.
+    let mut triggers: Vec<(&Snippet, &str, Vec<String>)> = snippets
+        .iter()
+        .filter(|s| s.enabled)
+        .flat_map(|s| {
+            s.triggers.iter().map(move |trigger| {
+                let trigger_words = words(trigger).into_iter().map(|w| w.text).collect();
+                (s, trigger.as_str(), trigger_words)
+            })
+        })
+        .filter(|(_, _, trigger_words): &(_, _, Vec<String>)| !trigger_words.is_empty())
+        .collect();
+    triggers.sort_by_key(|(_, _, trigger_words)| std::cmp::Reverse(trigger_words.len()));
+
+    if let Some((snippet, trigger, _)) = triggers.iter().find(|(_, _, trigger_words)| {
+        trigger_words.len() == spoken.len() && matches_at(&spoken, trigger_words)
+    }) {
+        expansion.expanded.push(ExpandedSnippet {
+            snippet_id: snippet.id.clone(),
+            trigger: trigger.to_string(),
+            spoken: text.trim().to_string(),
+            full_match: true,
+        });
+        expansion.full_match = true;
+        expansion.replacements.push((0..text.len(), snippet.body.clone()));
+        return expansion;
+    }
+
+    let mut index = 0;
+    while index < spoken.len() {
+        let found = triggers.iter().find(|(snippet, _, trigger_words)| {
+            snippet.inline
+                && trigger_words.len() >= 2
+                && matches_at(&spoken[index..], trigger_words)
+        });
+        let Some((snippet, trigger, trigger_words)) = found else {
+            index += 1;
+            continue;
+        };
+
+        let first = &spoken[index];
+        let last = &spoken[index + trigger_words.len() - 1];
+        let mut range = first.range.start..last.range.end;
+
+        // "... my address." -> "... 1 Main St.\n" rather than "1 Main St.\n."
+        let body_has_ending = snippet
+            .body
+            .trim_end_matches(' ')
+            .ends_with(|c: char| TRAILING_PUNCTUATION.contains(&c) || c == '\n');
+        if body_has_ending && text[range.end..].starts_with(TRAILING_PUNCTUATION) {
+            range.end += 1;
+        }
+
+        expansion.expanded.push(ExpandedSnippet {
+            snippet_id: snippet.id.clone(),
+            trigger: trigger.to_string(),
+            spoken: text[first.range.start..last.range.end].to_string(),
+            full_match: false,
+        });
+        expansion.replacements.push((range, snippet.body.clone()));
+        index += trigger_words.len();
+    }
+
+    expansion
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snippet(id: &str, triggers: &[&str], body: &str) -> Snippet {
+        Snippet {
+            id: id.to_string(),
+            triggers: triggers.iter().map(|t| t.to_string()).collect(),
+            body: body.to_string(),
+            enabled: true,
+            inline: true,
+        }
+    }
+
+    fn snippets() -> Vec<Snippet> {
+        vec![
+            snippet(
+                "link",
+                &["insert meeting link"],
+                "https://meet.example.com/alex",
+            ),
+            snippet("sign", &["sign off", "signature"], "Best regards,\nAlex"),
+        ]
+    }
+
+    #[test]
+    fn test_full_utterance_match() {
+        let expansion = expand_snippets("Um, insert the meeting link.", &snippets());
+        assert!(expansion.is_empty());
+
+        let expansion = expand_snippets("Um, insert meting link.", &snippets());
+        assert!(expansion.full_match);
+        assert_eq!(expansion.text(), "https://meet.example.com/alex");
+        assert_eq!(expansion.expanded[0].snippet_id, "link");
+        assert_eq!(expansion.expanded[0].spoken, "Um, insert meting link.");
+
+        // Single-word triggers only match the whole dictation.
+        assert!(expand_snippets("Signature.", &snippets()).full_match);
+        assert!(expand_snippets("Check my signature please.", &snippets()).is_empty());
+    }
+
+    #[test]
+    fn test_inline_expansion() {
+        let text = "Thanks for your time. Sign off.";
+        let expansion = expand_snippets(text, &snippets());
+        assert!(!expansion.full_match);
+        assert_eq!(
+            expansion.text(),
+            "Thanks for your time. Best regards,\nAlex."
+        );
+        assert_eq!(
+            expansion.marked_text(),
+            "Thanks for your time. [[snippet1]]."
+        );
+        assert_eq!(expansion.expanded[0].spoken, "Sign off");
+
+        // A body with its own ending swallows the punctuation after the trigger.
+        let mut dotted = snippets();
+        dotted[1].body = "Cheers.".to_string();
+        assert_eq!(
+            expand_snippets(text, &dotted).text(),
+            "Thanks for your time. Cheers."
+        );
+
+        let mut not_inline = snippets();
+        not_inline[1].inline = false;
+        assert!(expand_snippets(text, &not_inline).is_empty());
+    }
+
+    #[test]
+    fn test_restore_markers() {
+        let expansion = expand_snippets(
+            "join here insert meeting link and then sign off",
+            &snippets(),
+        );
+        assert_eq!(
+            expansion.marked_text(),
+            "join here [[snippet1]] and then [[snippet2]]"
+        );
+        assert_eq!(
+            expansion
+                .restore_markers("Join here: [[snippet1]], and then [[snippet2]]")
+                .as_deref(),
+            Some("Join here: https://meet.example.com/alex, and then Best regards,\nAlex")
+        );
+        assert_eq!(
+            expansion.restore_markers("Join here and then [[snippet2]]"),
+            None
+        );
+        assert_eq!(
+            expansion.restore_markers("[[snippet1]] [[snippet1]] [[snippet2]]"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_store_crud_and_validation() {
+        let store = SnippetStore::in_memory();
+        let added = store
+            .add(SnippetInput {
+                triggers: vec![
+                    " sign off ".to_string(),
+                    "Sign Off".to_string(),
+                    "  ".to_string(),
+                ],
+                body: "Best,\n{{user_name}}".to_string(),
+                enabled: true,
+                inline: true,
+            })
+            .unwrap();
+        assert_eq!(added.triggers, vec!["sign off"]);
+
+        let invalid = SnippetInput {
+            triggers: vec!["today".to_string()],
+            body: "{{today}}".to_string(),
+            enabled: true,
+            inline: false,
+        };
+        assert!(store.add(invalid.clone()).unwrap_err().contains("today"));
+        assert!(store
+            .add(SnippetInput {
+                triggers: vec!["...".to_string()],
+                ..invalid.clone()
+            })
+            .is_err());
+
+        let updated = store
+            .update(
+                &added.id,
+                SnippetInput {
+                    body: "{{date}}".to_string(),
+                    enabled: false,
+                    ..invalid
+                },
+            )
+            .unwrap();
+        assert_eq!(updated.triggers, vec!["today"]);
+        assert!(store.enabled().is_empty());
+
+        assert!(store.delete(&added.id).unwrap());
+        assert!(!store.delete(&added.id).unwrap());
+        assert!(store.list().unwrap().is_empty());
+    }
+}
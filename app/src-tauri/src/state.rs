@@ -1,4 +1,5 @@
 use std::sync::atomic::AtomicBool;
+use std::sync::Mutex;
 
 #[derive(Default)]
 pub struct AppState {
@@ -13,4 +14,8 @@ pub struct AppState {
     pub paste_key_held: AtomicBool,
     /// Tracks if toggle key is currently held down (for debouncing - action happens on release)
     pub toggle_key_held: AtomicBool,
+    /// Tracks if transform-selection key is currently held down (action happens on release)
+    pub transform_key_held: AtomicBool,
+    /// Text selected when the transform hotkey started the current recording
+    pub transform_selection: Mutex<Option<String>>,
 }
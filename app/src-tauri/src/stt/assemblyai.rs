@@ -0,0 +1,364 @@
+//! AssemblyAI STT provider implementation.
+//!
+//! AssemblyAI transcription is asynchronous: the audio is uploaded, a transcript job is
+//! created for the returned URL, and the job is polled until it completes.
+
+use super::transcript::json_f32;
+use super::{
+    AudioEncoding, AudioFormat, SttError, SttLanguage, SttProvider, Transcript, TranscriptWord,
+    Vocabulary,
+};
+use async_trait::async_trait;
+use crate::api_error::ApiError;
+use crate::request_log::RequestLogStore;
+use serde_json::json;
+use std::time::{Duration, Instant};
+
+const DEFAULT_BASE_URL: &str = "https://api.assemblyai.com/v2";
+
+/// AssemblyAI accepts at most this many `word_boost` entries...
+const WORD_BOOST_MAX_TERMS: usize = 1000;
+/// ...each of at most this many words.
+const WORD_BOOST_MAX_WORDS: usize = 6;
+
+/// AssemblyAI API provider for speech-to-text
+pub struct AssemblyAiSttProvider {
+    client: reqwest::Client,
+    api_key: String,
+    model: String,
+    word_boost: Vec<String>,
+    language: SttLanguage,
+    base_url: String,
+    poll_interval: Duration,
+    max_poll_duration: Duration,
+    request_log_store: Option<RequestLogStore>,
+}
+
+impl AssemblyAiSttProvider {
+    /// Create a new AssemblyAI STT provider
+    ///
+    /// # Arguments
+    /// * `api_key` - AssemblyAI API key
+    /// * `model` - Speech model to use (e.g., "universal")
+    pub fn new(api_key: String, model: Option<String>) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(60))
+            .build()
+            .expect("Failed to create HTTP client");
+
+        Self::with_client(client, api_key, model)
+    }
+
+    /// Create a new provider with a custom HTTP client
+    pub fn with_client(client: reqwest::Client, api_key: String, model: Option<String>) -> Self {
+        Self {
+            client,
+            api_key,
+            model: model.unwrap_or_else(|| "universal".to_string()),
+            word_boost: Vec::new(),
+            language: SttLanguage::Auto,
+            base_url: DEFAULT_BASE_URL.to_string(),
+            poll_interval: Duration::from_millis(500),
+            max_poll_duration: Duration::from_secs(300),
+            request_log_store: None,
+        }
+    }
+
+    /// Boost recognition of these words/phrases (e.g. personal dictionary terms).
+    ///
+    /// Entries that AssemblyAI would reject (too many words) are dropped.
+    pub fn with_word_boost(mut self, terms: Vec<String>) -> Self {
+        self.word_boost = Self::sanitize_word_boost(terms);
+        self
+    }
+
+    /// Boost the personal dictionary terms, most important first so they survive the limit.
+    pub fn with_vocabulary(self, vocabulary: Vocabulary) -> Self {
+        self.with_word_boost(vocabulary.prioritized_terms())
+    }
+
+    /// Spoken language (`language_code`, or language detection limited to the candidates)
+    pub fn with_language(mut self, language: SttLanguage) -> Self {
+        self.language = language.normalized();
+        self
+    }
+
+    /// Override the API base URL (e.g. a local stand-in server)
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    pub fn with_request_log_store(mut self, store: Option<RequestLogStore>) -> Self {
+        self.request_log_store = store;
+        self
+    }
+
+    fn sanitize_word_boost(terms: Vec<String>) -> Vec<String> {
+        terms
+            .into_iter()
+            .map(|t| t.trim().to_string())
+            .filter(|t| !t.is_empty() && t.split_whitespace().count() <= WORD_BOOST_MAX_WORDS)
+            .take(WORD_BOOST_MAX_TERMS)
+            .collect()
+    }
+
+    /// Body for `POST /transcript`.
+    fn transcript_request_body(&self, audio_url: &str) -> serde_json::Value {
+        let mut body = json!({
+            "audio_url": audio_url,
+            "speech_model": self.model,
+            "punctuate": true,
+            "format_text": true,
+        });
+
+        if !self.word_boost.is_empty() {
+            body["word_boost"] = json!(self.word_boost);
+            body["boost_param"] = json!("high");
+        }
+
+        match &self.language {
+            SttLanguage::Fixed { language } => {
+                body["language_code"] = json!(language);
+            }
+            SttLanguage::Candidates { languages } => {
+                body["language_detection"] = json!(true);
+                body["language_detection_options"] = json!({ "expected_languages": languages });
+            }
+            SttLanguage::Auto => {
+                body["language_detection"] = json!(true);
+            }
+        }
+
+        body
+    }
+
+    /// Build a `Transcript` from a completed transcript job (word times are in milliseconds).
+    fn transcript_from_result(result: &serde_json::Value) -> Transcript {
+        let words = result["words"]
+            .as_array()
+            .map(|words| {
+                words
+                    .iter()
+                    .map(|w| TranscriptWord {
+                        word: w["text"].as_str().unwrap_or("").to_string(),
+                        start: json_f32(&w["start"]).unwrap_or(0.0) / 1000.0,
+                        end: json_f32(&w["end"]).unwrap_or(0.0) / 1000.0,
+                        confidence: json_f32(&w["confidence"]),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Transcript {
+            text: result["text"].as_str().unwrap_or("").to_string(),
+            segments: Vec::new(),
+            words,
+            language: result["language_code"].as_str().map(|s| s.to_string()),
+            confidence: json_f32(&result["confidence"]),
+        }
+    }
+
+    async fn check_response(response: reqwest::Response) -> Result<serde_json::Value, SttError> {
+        if !response.status().is_success() {
+            let error = ApiError::from_response("AssemblyAI API error", response).await;
+            return Err(SttError::Api(error));
+        }
+
+        Ok(response.json().await?)
+    }
+
+    fn map_send_error(e: reqwest::Error) -> SttError {
+        if e.is_timeout() {
+            SttError::Timeout
+        } else {
+            SttError::Network(e)
+        }
+    }
+
+    async fn upload(&self, audio: &[u8]) -> Result<String, SttError> {
+        let response = self
+            .client
+            .post(format!("{}/upload", self.base_url))
+            .header("authorization", &self.api_key)
+            .header("content-type", "application/octet-stream")
+            .body(audio.to_vec())
+            .send()
+            .await
+            .map_err(Self::map_send_error)?;
+
+        let result = Self::check_response(response).await?;
+        result["upload_url"]
+            .as_str()
+            .map(|s| s.to_string())
+            .ok_or_else(|| SttError::Api("AssemblyAI upload response missing upload_url".into()))
+    }
+
+    async fn create_transcript(&self, audio_url: &str) -> Result<String, SttError> {
+        let response = self
+            .client
+            .post(format!("{}/transcript", self.base_url))
+            .header("authorization", &self.api_key)
+            .json(&self.transcript_request_body(audio_url))
+            .send()
+            .await
+            .map_err(Self::map_send_error)?;
+
+        let result = Self::check_response(response).await?;
+        result["id"]
+            .as_str()
+            .map(|s| s.to_string())
+            .ok_or_else(|| SttError::Api("AssemblyAI transcript response missing id".into()))
+    }
+
+    async fn poll_transcript(&self, id: &str) -> Result<serde_json::Value, SttError> {
+        let started = Instant::now();
+
+        loop {
+            let response = self
+                .client
+                .get(format!("{}/transcript/{}", self.base_url, id))
+                .header("authorization", &self.api_key)
+                .send()
+                .await
+                .map_err(Self::map_send_error)?;
+
+            let result = Self::check_response(response).await?;
+            match result["status"].as_str() {
+                Some("completed") => return Ok(result),
+                Some("error") => {
+                    let error = result["error"].as_str().unwrap_or("unknown error");
+                    return Err(SttError::Api(
+                        format!("AssemblyAI transcription failed: {}", error).into(),
+                    ));
+                }
+                _ => {}
+            }
+
+            if started.elapsed() >= self.max_poll_duration {
+                return Err(SttError::Timeout);
+            }
+
+            tokio::time::sleep(self.poll_interval).await;
+        }
+    }
+}
+
+#[async_trait]
+impl SttProvider for AssemblyAiSttProvider {
+    async fn transcribe(&self, audio: &[u8], format: &AudioFormat) -> Result<String, SttError> {
+        self.transcribe_detailed(audio, format).await.map(|t| t.text)
+    }
+
+    async fn transcribe_detailed(
+        &self,
+        audio: &[u8],
+        format: &AudioFormat,
+    ) -> Result<Transcript, SttError> {
+        if let Some(store) = &self.request_log_store {
+            let request_json = json!({
+                "provider": "assemblyai",
+                "endpoint": format!("{}/transcript", self.base_url),
+                "upload": {
+                    "endpoint": format!("{}/upload", self.base_url),
+                    "encoding": format.encoding,
+                    "bytes": audio.len(),
+                    "data": "<binary audio omitted>",
+                },
+                "body": self.transcript_request_body("<upload_url>"),
+            });
+
+            store.with_current(|log| {
+                log.stt_request_json = Some(request_json);
+            });
+        }
+
+        let audio_url = self.upload(audio).await?;
+        let transcript_id = self.create_transcript(&audio_url).await?;
+        log::debug!("AssemblyAI: polling transcript {}", transcript_id);
+
+        let result = self.poll_transcript(&transcript_id).await?;
+
+        if let Some(store) = &self.request_log_store {
+            let result_for_log = result.clone();
+            store.with_current(|log| {
+                log.stt_response_json = Some(result_for_log);
+            });
+        }
+
+        Ok(Self::transcript_from_result(&result))
+    }
+
+    fn name(&self) -> &'static str {
+        "assemblyai"
+    }
+
+    /// Uploads are raw bytes; AssemblyAI detects the container itself.
+    fn accepts_encoding(&self, encoding: AudioEncoding) -> bool {
+        encoding.is_file_format()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_provider_creation() {
+        let provider = AssemblyAiSttProvider::new("test-key".to_string(), None);
+        assert_eq!(provider.name(), "assemblyai");
+        assert_eq!(provider.model, "universal");
+    }
+
+    #[test]
+    fn test_word_boost_sanitized() {
+        let provider = AssemblyAiSttProvider::new("test-key".to_string(), None).with_word_boost(vec![
+            "Tauri".to_string(),
+            "  ".to_string(),
+            "one two three four five six seven".to_string(),
+        ]);
+        assert_eq!(provider.word_boost, vec!["Tauri"]);
+    }
+
+    #[test]
+    fn test_transcript_from_result() {
+        let result = json!({
+            "status": "completed",
+            "text": "Hello Tauri.",
+            "language_code": "en_us",
+            "confidence": 0.93,
+            "words": [
+                { "text": "Hello", "start": 250, "end": 650, "confidence": 0.99 },
+                { "text": "Tauri.", "start": 730, "end": 1100, "confidence": 0.51 }
+            ]
+        });
+
+        let transcript = AssemblyAiSttProvider::transcript_from_result(&result);
+        assert_eq!(transcript.text, "Hello Tauri.");
+        assert_eq!(transcript.language.as_deref(), Some("en_us"));
+        assert_eq!(transcript.words.len(), 2);
+        assert_eq!(transcript.words[0].start, 0.25);
+        assert_eq!(transcript.low_confidence_words().len(), 1);
+    }
+
+    #[test]
+    fn test_transcript_request_body() {
+        let provider = AssemblyAiSttProvider::new("test-key".to_string(), Some("nano".to_string()));
+        let body = provider.transcript_request_body("https://cdn.example/audio");
+        assert_eq!(body["speech_model"], "nano");
+        assert!(body.get("word_boost").is_none());
+
+        let provider = provider.with_word_boost(vec!["Pipecat".to_string()]);
+        let body = provider.transcript_request_body("https://cdn.example/audio");
+        assert_eq!(body["word_boost"], json!(["Pipecat"]));
+        assert_eq!(body["language_detection"], true);
+
+        let provider = provider.with_language(SttLanguage::Fixed {
+            language: "German".to_string(),
+        });
+        let body = provider.transcript_request_body("https://cdn.example/audio");
+        assert_eq!(body["language_code"], "de");
+        assert!(body.get("language_detection").is_none());
+    }
+}
@@ -1,18 +1,73 @@
 //! Deepgram STT provider implementation.
+//!
+//! Batch transcription posts a finished WAV to `/v1/listen`. Streaming sessions use the
+//! live WebSocket endpoint instead (see `deepgram_live`).
 
-use super::{AudioFormat, SttError, SttProvider};
+use super::deepgram_live::{run_live_session, LiveSessionConfig, DEFAULT_FINALIZE_TIMEOUT};
+use super::transcript::json_f32;
+use super::{
+    AudioChunkReceiver, AudioEncoding, AudioFormat, SttError, SttLanguage, SttProvider, SttStream,
+    Transcript, TranscriptWord, Vocabulary,
+};
 use async_trait::async_trait;
+use crate::api_error::ApiError;
 use crate::request_log::RequestLogStore;
 use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION, CONTENT_TYPE};
 use reqwest::Url;
 use serde_json::json;
 use std::time::Duration;
 
+const DEFAULT_LIVE_BASE_URL: &str = "wss://api.deepgram.com/v1/listen";
+
+/// Milliseconds of silence after which Deepgram sends `UtteranceEnd`.
+const UTTERANCE_END_MS: u32 = 1000;
+
+/// Most vocabulary terms sent per request (keeps the query string to a sane length; Deepgram
+/// also caps keyterm prompts at 500 tokens).
+const VOCABULARY_MAX_TERMS: usize = 100;
+
+/// Build a `Transcript` from a Deepgram `alternatives[n]` object (REST and live share the shape).
+pub(super) fn transcript_from_alternative(
+    alternative: &serde_json::Value,
+    language: Option<&str>,
+) -> Transcript {
+    let words = alternative["words"]
+        .as_array()
+        .map(|words| {
+            words
+                .iter()
+                .map(|w| TranscriptWord {
+                    word: w["punctuated_word"]
+                        .as_str()
+                        .or_else(|| w["word"].as_str())
+                        .unwrap_or("")
+                        .to_string(),
+                    start: json_f32(&w["start"]).unwrap_or(0.0),
+                    end: json_f32(&w["end"]).unwrap_or(0.0),
+                    confidence: json_f32(&w["confidence"]),
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Transcript {
+        text: alternative["transcript"].as_str().unwrap_or("").to_string(),
+        segments: Vec::new(),
+        words,
+        language: language.map(|l| l.to_string()),
+        confidence: json_f32(&alternative["confidence"]),
+    }
+}
+
 /// Deepgram API provider for speech-to-text
 pub struct DeepgramSttProvider {
     client: reqwest::Client,
     api_key: String,
     model: String,
+    live_base_url: String,
+    live_finalize_timeout: Duration,
+    language: SttLanguage,
+    vocabulary: Vocabulary,
     request_log_store: Option<RequestLogStore>,
 }
 
@@ -30,6 +85,79 @@ impl DeepgramSttProvider {
             .append_pair("model", &self.model)
             .append_pair("smart_format", "true")
             .append_pair("punctuate", "true");
+        self.append_language_params(&mut url, false);
+        self.append_vocabulary_params(&mut url);
+
+        Ok(url)
+    }
+
+    /// Nova-3 takes `keyterm` prompts (phrases allowed); older models take single-word
+    /// `keywords`. Terms are sent most important first, up to `VOCABULARY_MAX_TERMS`.
+    fn append_vocabulary_params(&self, url: &mut Url) {
+        let keyterm = self.model.starts_with("nova-3");
+        let mut pairs = url.query_pairs_mut();
+        for term in self
+            .vocabulary
+            .prioritized()
+            .into_iter()
+            .filter(|t| keyterm || t.word_count() == 1)
+            .take(VOCABULARY_MAX_TERMS)
+        {
+            pairs.append_pair(if keyterm { "keyterm" } else { "keywords" }, &term.term);
+        }
+    }
+
+    /// `language=xx` for a fixed language; otherwise `detect_language` (restricted to the
+    /// candidates when given). Live sessions can't detect, so they use the preferred candidate.
+    fn append_language_params(&self, url: &mut Url, live: bool) {
+        let mut pairs = url.query_pairs_mut();
+        match &self.language {
+            SttLanguage::Fixed { language } => {
+                pairs.append_pair("language", language);
+            }
+            SttLanguage::Candidates { languages } if live => {
+                if let Some(preferred) = languages.first() {
+                    pairs.append_pair("language", preferred);
+                }
+            }
+            SttLanguage::Candidates { languages } => {
+                for language in languages {
+                    pairs.append_pair("detect_language", language);
+                }
+            }
+            SttLanguage::Auto if live => {}
+            SttLanguage::Auto => {
+                pairs.append_pair("detect_language", "true");
+            }
+        }
+    }
+
+    /// Build the live (WebSocket) /v1/listen URL for raw PCM16 audio.
+    ///
+    /// Uses the same formatting options as `listen_url`, plus interim results and
+    /// utterance-end events so the transcript is ready as soon as recording stops.
+    fn live_url(&self, format: &AudioFormat) -> Result<Url, SttError> {
+        if !matches!(format.encoding, AudioEncoding::Pcm16) {
+            return Err(SttError::Config(
+                "Deepgram live streaming requires PCM16 audio".to_string(),
+            ));
+        }
+
+        let mut url = Url::parse(&self.live_base_url)
+            .map_err(|e| SttError::Config(format!("Invalid Deepgram live URL: {}", e)))?;
+
+        url.query_pairs_mut()
+            .append_pair("model", &self.model)
+            .append_pair("smart_format", "true")
+            .append_pair("punctuate", "true")
+            .append_pair("encoding", "linear16")
+            .append_pair("sample_rate", &format.sample_rate.to_string())
+            .append_pair("channels", &format.channels.to_string())
+            .append_pair("interim_results", "true")
+            .append_pair("utterance_end_ms", &UTTERANCE_END_MS.to_string())
+            .append_pair("vad_events", "true");
+        self.append_language_params(&mut url, true);
+        self.append_vocabulary_params(&mut url);
 
         Ok(url)
     }
@@ -49,6 +177,10 @@ impl DeepgramSttProvider {
             client,
             api_key,
             model: model.unwrap_or_else(|| "nova-2".to_string()),
+            live_base_url: DEFAULT_LIVE_BASE_URL.to_string(),
+            live_finalize_timeout: DEFAULT_FINALIZE_TIMEOUT,
+            language: SttLanguage::Auto,
+            vocabulary: Vocabulary::default(),
             request_log_store: None,
         }
     }
@@ -60,10 +192,40 @@ impl DeepgramSttProvider {
             client,
             api_key,
             model: model.unwrap_or_else(|| "nova-2".to_string()),
+            live_base_url: DEFAULT_LIVE_BASE_URL.to_string(),
+            live_finalize_timeout: DEFAULT_FINALIZE_TIMEOUT,
+            language: SttLanguage::Auto,
+            vocabulary: Vocabulary::default(),
             request_log_store: None,
         }
     }
 
+    /// Override the live WebSocket endpoint (e.g. a local stand-in server)
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn with_live_base_url(mut self, url: impl Into<String>) -> Self {
+        self.live_base_url = url.into();
+        self
+    }
+
+    /// Override how long a live session waits for the flushed result after `Finalize`
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn with_live_finalize_timeout(mut self, timeout: Duration) -> Self {
+        self.live_finalize_timeout = timeout;
+        self
+    }
+
+    /// Dictionary terms to bias recognition towards (`keyterm` / `keywords`)
+    pub fn with_vocabulary(mut self, vocabulary: Vocabulary) -> Self {
+        self.vocabulary = vocabulary;
+        self
+    }
+
+    /// Spoken language (`language` / `detect_language` query parameters)
+    pub fn with_language(mut self, language: SttLanguage) -> Self {
+        self.language = language.normalized();
+        self
+    }
+
     pub fn with_request_log_store(mut self, store: Option<RequestLogStore>) -> Self {
         self.request_log_store = store;
         self
@@ -72,14 +234,22 @@ impl DeepgramSttProvider {
 
 #[async_trait]
 impl SttProvider for DeepgramSttProvider {
-    async fn transcribe(&self, audio: &[u8], _format: &AudioFormat) -> Result<String, SttError> {
+    async fn transcribe(&self, audio: &[u8], format: &AudioFormat) -> Result<String, SttError> {
+        self.transcribe_detailed(audio, format).await.map(|t| t.text)
+    }
+
+    async fn transcribe_detailed(
+        &self,
+        audio: &[u8],
+        format: &AudioFormat,
+    ) -> Result<Transcript, SttError> {
         if let Some(store) = &self.request_log_store {
             let url = self.listen_url()?;
             let request_json = json!({
                 "provider": "deepgram",
                 "endpoint": url.as_str(),
                 "headers": {
-                    "content-type": "audio/wav",
+                    "content-type": format.encoding.mime_type(),
                     // Authorization intentionally omitted.
                 },
                 "body": {
@@ -101,7 +271,7 @@ impl SttProvider for DeepgramSttProvider {
         );
         headers.insert(
             CONTENT_TYPE,
-            HeaderValue::from_static("audio/wav"),
+            HeaderValue::from_static(format.encoding.mime_type()),
         );
 
         let url = self.listen_url()?;
@@ -116,15 +286,8 @@ impl SttProvider for DeepgramSttProvider {
             .map_err(|e| if e.is_timeout() { SttError::Timeout } else { SttError::Network(e) })?;
 
         if !response.status().is_success() {
-            let status = response.status();
-            let error_text = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "Unknown error".to_string());
-            return Err(SttError::Api(format!(
-                "Deepgram API error ({}): {}",
-                status, error_text
-            )));
+            let error = ApiError::from_response("Deepgram API error", response).await;
+            return Err(SttError::Api(error));
         }
 
         let result: serde_json::Value = response.json().await?;
@@ -137,20 +300,44 @@ impl SttProvider for DeepgramSttProvider {
         }
 
         // Deepgram response structure:
-        // { "results": { "channels": [{ "alternatives": [{ "transcript": "..." }] }] } }
-        let text = result["results"]["channels"]
-            .get(0)
-            .and_then(|ch| ch["alternatives"].get(0))
-            .and_then(|alt| alt["transcript"].as_str())
-            .unwrap_or("")
-            .to_string();
-
-        Ok(text)
+        // { "results": { "channels": [{ "detected_language": "en",
+        //     "alternatives": [{ "transcript": "...", "confidence": 0.98, "words": [...] }] }] } }
+        let channel = &result["results"]["channels"][0];
+        Ok(transcript_from_alternative(
+            &channel["alternatives"][0],
+            channel["detected_language"].as_str(),
+        ))
     }
 
     fn name(&self) -> &'static str {
         "deepgram"
     }
+
+    /// Batch requests are sniffed from the body; raw PCM would need encoding parameters.
+    fn accepts_encoding(&self, encoding: AudioEncoding) -> bool {
+        encoding.is_file_format()
+    }
+
+    fn supports_streaming(&self) -> bool {
+        true
+    }
+
+    fn open_stream(
+        &self,
+        format: &AudioFormat,
+        audio_rx: AudioChunkReceiver,
+    ) -> Result<SttStream, SttError> {
+        let config = LiveSessionConfig {
+            url: self.live_url(format)?,
+            api_key: self.api_key.clone(),
+            finalize_timeout: self.live_finalize_timeout,
+            request_log_store: self.request_log_store.clone(),
+        };
+
+        Ok(SttStream::spawn("deepgram", move |events_tx, cancel_token| {
+            run_live_session(config, audio_rx, events_tx, cancel_token)
+        }))
+    }
 }
 
 #[cfg(test)]
@@ -169,4 +356,251 @@ mod tests {
         let provider = DeepgramSttProvider::new("test-key".to_string(), Some("nova-2-general".to_string()));
         assert_eq!(provider.model, "nova-2-general");
     }
+
+    #[test]
+    fn test_transcript_from_alternative() {
+        let alternative = json!({
+            "transcript": "hello tauri",
+            "confidence": 0.91,
+            "words": [
+                { "word": "hello", "punctuated_word": "Hello", "start": 0.1, "end": 0.4, "confidence": 0.99 },
+                { "word": "tauri", "start": 0.5, "end": 0.9, "confidence": 0.42 }
+            ]
+        });
+
+        let transcript = transcript_from_alternative(&alternative, Some("en"));
+        assert_eq!(transcript.text, "hello tauri");
+        assert_eq!(transcript.confidence, Some(0.91));
+        assert_eq!(transcript.language.as_deref(), Some("en"));
+        assert_eq!(transcript.words[0].word, "Hello");
+        assert_eq!(transcript.low_confidence_words()[0].word, "tauri");
+    }
+
+    #[test]
+    fn test_live_url_params() {
+        let provider = DeepgramSttProvider::new("test-key".to_string(), None);
+        let url = provider
+            .live_url(&AudioFormat {
+                sample_rate: 48000,
+                channels: 1,
+                encoding: AudioEncoding::Pcm16,
+            })
+            .unwrap();
+
+        assert!(url.as_str().starts_with("wss://api.deepgram.com/v1/listen?"));
+        let query = url.query().unwrap();
+        assert!(query.contains("encoding=linear16"));
+        assert!(query.contains("sample_rate=48000"));
+        assert!(query.contains("interim_results=true"));
+        assert!(query.contains("utterance_end_ms=1000"));
+    }
+
+    #[test]
+    fn test_language_params() {
+        let provider = DeepgramSttProvider::new("test-key".to_string(), None);
+        assert!(provider.listen_url().unwrap().query().unwrap().contains("detect_language=true"));
+
+        let provider = provider.with_language(SttLanguage::Candidates {
+            languages: vec!["en".to_string(), "Spanish".to_string()],
+        });
+        let query = provider.listen_url().unwrap().query().unwrap().to_string();
+        assert!(query.contains("detect_language=en&detect_language=es"));
+
+        let live = provider
+            .live_url(&AudioFormat {
+                sample_rate: 16000,
+                channels: 1,
+                encoding: AudioEncoding::Pcm16,
+            })
+            .unwrap();
+        assert!(live.query().unwrap().contains("language=en"));
+        assert!(!live.query().unwrap().contains("detect_language"));
+    }
+
+    #[test]
+    fn test_vocabulary_params_depend_on_model() {
+        let vocabulary = Vocabulary::parse_dictionary("Tauri\nant row pick = Anthropic\nNew York");
+
+        let provider = DeepgramSttProvider::new("test-key".to_string(), None)
+            .with_vocabulary(vocabulary.clone());
+        let query = provider.listen_url().unwrap().query().unwrap().to_string();
+        assert!(query.contains("keywords=Anthropic&keywords=Tauri"));
+        assert!(!query.contains("New"));
+
+        let provider = DeepgramSttProvider::new("test-key".to_string(), Some("nova-3".to_string()))
+            .with_vocabulary(vocabulary);
+        let query = provider.listen_url().unwrap().query().unwrap().to_string();
+        assert!(query.contains("keyterm=Anthropic&keyterm=Tauri&keyterm=New+York"));
+    }
+
+    #[test]
+    fn test_live_url_rejects_wav() {
+        let provider = DeepgramSttProvider::new("test-key".to_string(), None);
+        let result = provider.live_url(&AudioFormat {
+            sample_rate: 16000,
+            channels: 1,
+            encoding: AudioEncoding::Wav,
+        });
+        assert!(matches!(result, Err(SttError::Config(_))));
+    }
+
+    /// What the live stand-in saw from the client.
+    struct StandInLog {
+        audio_bytes: usize,
+        auth_header: Option<String>,
+        close_stream: bool,
+    }
+
+    /// Read the `Authorization` header off the upgrade request without consuming it.
+    async fn peek_auth_header(tcp: &tokio::net::TcpStream) -> Option<String> {
+        let mut buf = [0u8; 4096];
+        loop {
+            let n = tcp.peek(&mut buf).await.unwrap();
+            let head = String::from_utf8_lossy(&buf[..n]);
+            if head.contains("\r\n\r\n") {
+                return head.lines().find_map(|line| {
+                    let (name, value) = line.split_once(':')?;
+                    name.eq_ignore_ascii_case("authorization")
+                        .then(|| value.trim().to_string())
+                });
+            }
+            tokio::task::yield_now().await;
+        }
+    }
+
+    /// Stand-in for Deepgram's live endpoint: counts audio bytes until `Finalize`, then
+    /// replies with an interim result followed (if `flush` is set) by the flushed final result.
+    async fn spawn_live_stand_in(flush: bool) -> (String, tokio::task::JoinHandle<StandInLog>) {
+        use futures_util::{SinkExt, StreamExt};
+        use tokio_tungstenite::tungstenite::Message;
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let handle = tokio::spawn(async move {
+            let (tcp, _) = listener.accept().await.unwrap();
+            let auth_header = peek_auth_header(&tcp).await;
+            let mut socket = tokio_tungstenite::accept_async(tcp).await.unwrap();
+
+            let mut audio_bytes = 0;
+            while let Some(Ok(message)) = socket.next().await {
+                match message {
+                    Message::Binary(data) => audio_bytes += data.len(),
+                    Message::Text(text) if text.as_str().contains("Finalize") => break,
+                    _ => {}
+                }
+            }
+
+            let replies: &[(&str, bool)] = if flush {
+                &[("hello", false), ("Hello world.", true)]
+            } else {
+                &[("hello", false)]
+            };
+            for (transcript, is_final) in replies {
+                let result = json!({
+                    "type": "Results",
+                    "is_final": is_final,
+                    "from_finalize": is_final,
+                    "channel": { "alternatives": [{ "transcript": transcript }] }
+                });
+                socket.send(Message::text(result.to_string())).await.unwrap();
+            }
+
+            // Drain until the client closes the stream.
+            let mut close_stream = false;
+            while let Some(Ok(message)) = socket.next().await {
+                match message {
+                    Message::Text(text) if text.as_str().contains("CloseStream") => {
+                        close_stream = true;
+                        break;
+                    }
+                    Message::Close(_) => break,
+                    _ => {}
+                }
+            }
+
+            StandInLog {
+                audio_bytes,
+                auth_header,
+                close_stream,
+            }
+        });
+
+        (format!("ws://{}/v1/listen", addr), handle)
+    }
+
+    fn pcm_format() -> AudioFormat {
+        AudioFormat {
+            sample_rate: 16000,
+            channels: 1,
+            encoding: AudioEncoding::Pcm16,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_live_session_against_stand_in() {
+        let (url, server) = spawn_live_stand_in(true).await;
+        let store = RequestLogStore::new();
+        store.start_request("deepgram".to_string(), None);
+
+        let provider = DeepgramSttProvider::new("test-key".to_string(), None)
+            .with_live_base_url(url)
+            .with_request_log_store(Some(store.clone()));
+
+        let (audio_tx, audio_rx) = super::super::audio_chunk_channel();
+        let mut stream = provider.open_stream(&pcm_format(), audio_rx).unwrap();
+        let mut events = stream.take_events().unwrap();
+
+        audio_tx.send(vec![0; 160]).unwrap();
+        audio_tx.send(vec![0; 160]).unwrap();
+        drop(audio_tx);
+
+        let transcript = stream.finish().await.unwrap();
+        assert_eq!(transcript.text, "Hello world.");
+
+        assert_eq!(
+            events.recv().await,
+            Some(super::super::TranscriptEvent::Partial {
+                text: "hello".to_string()
+            })
+        );
+        assert_eq!(
+            events.recv().await,
+            Some(super::super::TranscriptEvent::Final {
+                text: "Hello world.".to_string()
+            })
+        );
+
+        let log = server.await.unwrap();
+        assert_eq!(log.audio_bytes, 640);
+        assert_eq!(log.auth_header.as_deref(), Some("Token test-key"));
+        assert!(log.close_stream);
+
+        let (request_json, response_json) = store
+            .with_current(|log| (log.stt_request_json.clone(), log.stt_response_json.clone()))
+            .unwrap();
+        assert_eq!(request_json.unwrap()["mode"], "live");
+        assert_eq!(response_json.unwrap()["transcript"], "Hello world.");
+    }
+
+    #[tokio::test]
+    async fn test_live_session_gives_up_waiting_for_finalize() {
+        let (url, server) = spawn_live_stand_in(false).await;
+        let provider = DeepgramSttProvider::new("test-key".to_string(), None)
+            .with_live_base_url(url)
+            .with_live_finalize_timeout(Duration::from_millis(100));
+
+        let (audio_tx, audio_rx) = super::super::audio_chunk_channel();
+        let stream = provider.open_stream(&pcm_format(), audio_rx).unwrap();
+        audio_tx.send(vec![0; 160]).unwrap();
+        drop(audio_tx);
+
+        // The server never flushes: the session closes the stream and keeps the interim result.
+        let transcript = tokio::time::timeout(Duration::from_secs(5), stream.finish())
+            .await
+            .expect("session should stop waiting after the finalize timeout")
+            .unwrap();
+        assert_eq!(transcript.text, "hello");
+        assert!(server.await.unwrap().close_stream);
+    }
 }
@@ -0,0 +1,325 @@
+//! Deepgram live (WebSocket) streaming session.
+//!
+//! Protocol summary (https://developers.deepgram.com/docs/live-streaming-audio):
+//! - Binary frames carry raw linear16 audio.
+//! - `Results` messages carry interim (`is_final: false`) and final transcripts.
+//! - `UtteranceEnd` marks a gap in speech (requires `interim_results=true`).
+//! - Sending `Finalize` flushes buffered audio; the flushed result has `from_finalize: true`.
+//! - Sending `CloseStream` asks the server to close the socket after sending remaining results.
+
+use super::deepgram::transcript_from_alternative;
+use super::{AudioChunkReceiver, SttError, Transcript, TranscriptEvent};
+use crate::api_error::ApiError;
+use crate::request_log::RequestLogStore;
+use futures_util::{SinkExt, StreamExt};
+use reqwest::Url;
+use serde_json::json;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::time::Instant;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::http::HeaderValue;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_util::sync::CancellationToken;
+
+/// How long to wait for the flushed result after sending `Finalize`.
+pub(super) const DEFAULT_FINALIZE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Everything a live session needs, cloned out of the provider.
+pub(super) struct LiveSessionConfig {
+    pub url: Url,
+    pub api_key: String,
+    pub finalize_timeout: Duration,
+    pub request_log_store: Option<RequestLogStore>,
+}
+
+/// A parsed server message we care about.
+#[derive(Debug, PartialEq)]
+enum LiveMessage {
+    Results {
+        transcript: Transcript,
+        is_final: bool,
+        from_finalize: bool,
+    },
+    UtteranceEnd,
+    Metadata,
+    Other,
+}
+
+fn parse_live_message(text: &str) -> Result<(LiveMessage, serde_json::Value), SttError> {
+    let value: serde_json::Value = serde_json::from_str(text)
+        .map_err(|e| SttError::Api(format!("Invalid Deepgram live message: {}", e).into()))?;
+
+    let message = match value["type"].as_str() {
+        Some("Results") => LiveMessage::Results {
+            transcript: transcript_from_alternative(&value["channel"]["alternatives"][0], None),
+            is_final: value["is_final"].as_bool().unwrap_or(false),
+            from_finalize: value["from_finalize"].as_bool().unwrap_or(false),
+        },
+        Some("UtteranceEnd") => LiveMessage::UtteranceEnd,
+        Some("Metadata") => LiveMessage::Metadata,
+        Some("Error") => {
+            let description = value["description"]
+                .as_str()
+                .or_else(|| value["message"].as_str())
+                .unwrap_or("unknown error");
+            let error = ApiError {
+                code: value["variant"].as_str().map(str::to_string),
+                ..ApiError::new(format!("Deepgram live API error: {}", description))
+            };
+            return Err(SttError::Api(error));
+        }
+        _ => LiveMessage::Other,
+    };
+
+    Ok((message, value))
+}
+
+/// Join finalized segments into the full transcript.
+///
+/// Words are concatenated in order; the overall confidence is the mean of the segments'.
+fn join_segments(segments: Vec<Transcript>) -> Transcript {
+    let confidences: Vec<f32> = segments.iter().filter_map(|s| s.confidence).collect();
+    let confidence = (!confidences.is_empty())
+        .then(|| confidences.iter().sum::<f32>() / confidences.len() as f32);
+
+    let mut texts: Vec<String> = Vec::new();
+    let mut words = Vec::new();
+    for segment in segments {
+        let text = segment.text.trim();
+        if !text.is_empty() {
+            texts.push(text.to_string());
+        }
+        words.extend(segment.words);
+    }
+
+    Transcript {
+        text: texts.join(" "),
+        words,
+        confidence,
+        ..Default::default()
+    }
+}
+
+/// Drive a live session until the audio channel closes and the final transcript is in.
+pub(super) async fn run_live_session(
+    config: LiveSessionConfig,
+    mut audio_rx: AudioChunkReceiver,
+    events_tx: mpsc::UnboundedSender<TranscriptEvent>,
+    cancel_token: CancellationToken,
+) -> Result<Transcript, SttError> {
+    if let Some(store) = &config.request_log_store {
+        let request_json = json!({
+            "provider": "deepgram",
+            "mode": "live",
+            "endpoint": config.url.as_str(),
+            "headers": {
+                // Authorization intentionally omitted.
+            },
+            "body": {
+                "data": "<streamed audio omitted>",
+            }
+        });
+
+        store.with_current(|log| {
+            log.stt_request_json = Some(request_json);
+        });
+    }
+
+    let mut request = config
+        .url
+        .as_str()
+        .into_client_request()
+        .map_err(|e| SttError::Config(format!("Invalid Deepgram live URL: {}", e)))?;
+    request.headers_mut().insert(
+        "Authorization",
+        HeaderValue::from_str(&format!("Token {}", config.api_key))
+            .map_err(|e| SttError::Config(format!("Invalid API key format: {}", e)))?,
+    );
+
+    let (socket, _response) = tokio::select! {
+        biased;
+
+        _ = cancel_token.cancelled() => return Err(SttError::Cancelled),
+
+        result = tokio_tungstenite::connect_async(request) => result.map_err(|e| {
+            // A rejected upgrade (bad key, rate limit) carries the HTTP status.
+            let status = match &e {
+                tokio_tungstenite::tungstenite::Error::Http(response) => Some(response.status().as_u16()),
+                _ => None,
+            };
+            SttError::Api(ApiError {
+                status,
+                ..ApiError::new(format!("Deepgram live connection failed: {}", e))
+            })
+        })?,
+    };
+    log::info!("Deepgram live: connected");
+
+    let (mut write, mut read) = socket.split();
+
+    let mut final_segments: Vec<Transcript> = Vec::new();
+    // Latest interim hypothesis not yet covered by a final result.
+    let mut pending_interim: Option<Transcript> = None;
+    let mut logged_messages: Vec<serde_json::Value> = Vec::new();
+    let mut audio_bytes_sent: usize = 0;
+    let mut audio_done = false;
+    // Set once `Finalize` is sent; the server may never answer it.
+    let mut finalize_deadline: Option<Instant> = None;
+
+    loop {
+        tokio::select! {
+            biased;
+
+            _ = cancel_token.cancelled() => {
+                let _ = write.send(Message::Close(None)).await;
+                return Err(SttError::Cancelled);
+            }
+
+            chunk = audio_rx.recv(), if !audio_done => match chunk {
+                Some(samples) => {
+                    let bytes: Vec<u8> = samples.iter().flat_map(|s| s.to_le_bytes()).collect();
+                    audio_bytes_sent += bytes.len();
+                    write
+                        .send(Message::Binary(bytes.into()))
+                        .await
+                        .map_err(|e| SttError::Api(format!("Deepgram live send failed: {}", e).into()))?;
+                }
+                None => {
+                    // Recording stopped: flush whatever Deepgram still has buffered.
+                    audio_done = true;
+                    write
+                        .send(Message::text(json!({ "type": "Finalize" }).to_string()))
+                        .await
+                        .map_err(|e| SttError::Api(format!("Deepgram live send failed: {}", e).into()))?;
+                    finalize_deadline = Some(Instant::now() + config.finalize_timeout);
+                }
+            },
+
+            _ = tokio::time::sleep_until(finalize_deadline.unwrap_or_else(Instant::now)), if finalize_deadline.is_some() => {
+                log::warn!(
+                    "Deepgram live: no flushed result within {:?} of Finalize, closing the stream",
+                    config.finalize_timeout
+                );
+                let _ = write
+                    .send(Message::text(json!({ "type": "CloseStream" }).to_string()))
+                    .await;
+                break;
+            }
+
+            message = read.next() => {
+                let text = match message {
+                    Some(Ok(Message::Text(text))) => text,
+                    Some(Ok(Message::Close(_))) | None => break,
+                    Some(Ok(_)) => continue,
+                    Some(Err(e)) => {
+                        return Err(SttError::Api(format!("Deepgram live receive failed: {}", e).into()));
+                    }
+                };
+
+                let (message, value) = parse_live_message(text.as_str())?;
+                match message {
+                    LiveMessage::Results { transcript, is_final, from_finalize } => {
+                        logged_messages.push(value);
+                        if is_final {
+                            pending_interim = None;
+                            if !transcript.text.trim().is_empty() {
+                                let _ = events_tx.send(TranscriptEvent::Final { text: transcript.text.clone() });
+                                final_segments.push(transcript);
+                            }
+                        } else {
+                            let _ = events_tx.send(TranscriptEvent::Partial { text: transcript.text.clone() });
+                            pending_interim = Some(transcript);
+                        }
+
+                        // The flushed result is the last one we need.
+                        if audio_done && from_finalize && is_final {
+                            let _ = write
+                                .send(Message::text(json!({ "type": "CloseStream" }).to_string()))
+                                .await;
+                            break;
+                        }
+                    }
+                    LiveMessage::UtteranceEnd => {
+                        logged_messages.push(value);
+                        log::debug!("Deepgram live: utterance end");
+                    }
+                    LiveMessage::Metadata => {
+                        logged_messages.push(value);
+                    }
+                    LiveMessage::Other => {}
+                }
+            }
+        }
+    }
+
+    // If the socket closed before the trailing audio was finalized, keep the last hypothesis
+    // rather than dropping the end of the dictation.
+    if let Some(interim) = pending_interim.filter(|t| !t.text.trim().is_empty()) {
+        log::warn!("Deepgram live: using unfinalized interim result for trailing audio");
+        final_segments.push(interim);
+    }
+
+    let transcript = join_segments(final_segments);
+
+    if let Some(store) = &config.request_log_store {
+        let response_json = json!({
+            "mode": "live",
+            "audio_bytes_sent": audio_bytes_sent,
+            "messages": logged_messages,
+            "transcript": transcript.text,
+        });
+        store.with_current(|log| {
+            log.stt_response_json = Some(response_json);
+        });
+    }
+
+    log::info!(
+        "Deepgram live: session complete ({} bytes sent, {} chars)",
+        audio_bytes_sent,
+        transcript.text.len()
+    );
+
+    Ok(transcript)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_results_message() {
+        let (message, _) = parse_live_message(
+            r#"{"type":"Results","is_final":true,"from_finalize":true,"channel":{"alternatives":[{"transcript":"hello world","confidence":0.9}]}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            message,
+            LiveMessage::Results {
+                transcript: Transcript {
+                    text: "hello world".to_string(),
+                    confidence: Some(0.9),
+                    ..Default::default()
+                },
+                is_final: true,
+                from_finalize: true,
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_error_message() {
+        let result = parse_live_message(r#"{"type":"Error","description":"bad audio"}"#);
+        assert!(matches!(result, Err(SttError::Api(e)) if e.message.contains("bad audio")));
+    }
+
+    #[test]
+    fn test_join_segments_skips_empty() {
+        let segments = vec![
+            Transcript::from_text("Hello."),
+            Transcript::from_text(" "),
+            Transcript::from_text("World."),
+        ];
+        assert_eq!(join_segments(segments).text, "Hello. World.");
+    }
+}
@@ -1,10 +1,14 @@
 //! Groq Whisper API STT provider implementation.
 
-use super::{AudioFormat, SttError, SttProvider};
+use super::language::transcribe_in_language;
+use super::openai::{
+    post_audio_transcription, TranscriptionsRequest, VERBOSE_TIMESTAMP_GRANULARITIES,
+};
+use super::{
+    AudioEncoding, AudioFormat, SttError, SttLanguage, SttProvider, Transcript, Vocabulary,
+};
 use async_trait::async_trait;
 use crate::request_log::RequestLogStore;
-use reqwest::multipart;
-use serde_json::json;
 use std::time::Duration;
 
 /// Groq Whisper API provider for speech-to-text
@@ -13,6 +17,8 @@ pub struct GroqSttProvider {
     api_key: String,
     model: String,
     default_prompt: Option<String>,
+    language: SttLanguage,
+    vocabulary: Vocabulary,
     request_log_store: Option<RequestLogStore>,
 }
 
@@ -36,6 +42,8 @@ impl GroqSttProvider {
             api_key,
             model: model.unwrap_or_else(|| "whisper-large-v3-turbo".to_string()),
             default_prompt,
+            language: SttLanguage::Auto,
+            vocabulary: Vocabulary::default(),
             request_log_store: None,
         }
     }
@@ -53,10 +61,24 @@ impl GroqSttProvider {
             api_key,
             model: model.unwrap_or_else(|| "whisper-large-v3-turbo".to_string()),
             default_prompt,
+            language: SttLanguage::Auto,
+            vocabulary: Vocabulary::default(),
             request_log_store: None,
         }
     }
 
+    /// Spoken language hint (`language` form field)
+    pub fn with_language(mut self, language: SttLanguage) -> Self {
+        self.language = language.normalized();
+        self
+    }
+
+    /// Dictionary terms to bias recognition towards (appended to the prompt)
+    pub fn with_vocabulary(mut self, vocabulary: Vocabulary) -> Self {
+        self.vocabulary = vocabulary;
+        self
+    }
+
     pub fn with_request_log_store(mut self, store: Option<RequestLogStore>) -> Self {
         self.request_log_store = store;
         self
@@ -71,94 +93,105 @@ impl GroqSttProvider {
         let clamped: String = trimmed.chars().take(Self::PROMPT_MAX_CHARS).collect();
         Some(clamped)
     }
+
+    async fn transcribe_audio(
+        &self,
+        audio: &[u8],
+        format: &AudioFormat,
+        language: Option<String>,
+    ) -> Result<Transcript, SttError> {
+        let extra_fields: Vec<(String, String)> = language
+            .map(|language| ("language".to_string(), language))
+            .into_iter()
+            .collect();
+        // verbose_json adds segments (with avg_logprob/no_speech_prob), words and language.
+        let request = TranscriptionsRequest {
+            provider: "groq",
+            api_label: "Groq",
+            endpoint: "https://api.groq.com/openai/v1/audio/transcriptions",
+            api_key: Some(&self.api_key),
+            model: &self.model,
+            prompt: self
+                .vocabulary
+                .whisper_prompt(self.default_prompt.as_deref(), Self::PROMPT_MAX_CHARS)
+                .as_deref()
+                .and_then(Self::clamp_prompt),
+            response_format: Some("verbose_json"),
+            timestamp_granularities: VERBOSE_TIMESTAMP_GRANULARITIES,
+            extra_fields: &extra_fields,
+        };
+
+        let result = post_audio_transcription(
+            &self.client,
+            self.request_log_store.as_ref(),
+            &request,
+            audio,
+            format,
+        )
+        .await?;
+
+        Ok(Transcript::from_verbose_json(&result))
+    }
 }
 
 #[async_trait]
 impl SttProvider for GroqSttProvider {
-    async fn transcribe(&self, audio: &[u8], _format: &AudioFormat) -> Result<String, SttError> {
-        if let Some(store) = &self.request_log_store {
-            let prompt = self
-                .default_prompt
-                .as_deref()
-                .and_then(Self::clamp_prompt);
-            let request_json = json!({
-                "provider": "groq",
-                "endpoint": "https://api.groq.com/openai/v1/audio/transcriptions",
-                "content_type": "multipart/form-data",
-                "fields": {
-                    "model": self.model,
-                    "prompt": prompt,
-                },
-                "file": {
-                    "name": "audio.wav",
-                    "mime": "audio/wav",
-                    "bytes": audio.len(),
-                    "data": "<binary audio omitted>",
-                }
-            });
-
-            store.with_current(|log| {
-                log.stt_request_json = Some(request_json);
-            });
-        }
-
-        let part = multipart::Part::bytes(audio.to_vec())
-            .file_name("audio.wav")
-            .mime_str("audio/wav")
-            .map_err(|e| SttError::Audio(format!("Failed to create multipart: {}", e)))?;
-
-        let mut form = multipart::Form::new()
-            .part("file", part)
-            .text("model", self.model.clone());
-
-        if let Some(prompt) = self
-            .default_prompt
-            .as_deref()
-            .and_then(Self::clamp_prompt)
-        {
-            form = form.text("prompt", prompt);
-        }
-
-        let response = self
-            .client
-            .post("https://api.groq.com/openai/v1/audio/transcriptions")
-            .bearer_auth(&self.api_key)
-            .multipart(form)
-            .send()
-            .await
-            .map_err(|e| if e.is_timeout() { SttError::Timeout } else { SttError::Network(e) })?;
-
-        if !response.status().is_success() {
-            let status = response.status();
-            let error_text = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "Unknown error".to_string());
-            return Err(SttError::Api(format!(
-                "Groq API error ({}): {}",
-                status, error_text
-            )));
-        }
+    async fn transcribe(&self, audio: &[u8], format: &AudioFormat) -> Result<String, SttError> {
+        self.transcribe_detailed(audio, format).await.map(|t| t.text)
+    }
 
-        let result: serde_json::Value = response.json().await?;
+    async fn transcribe_detailed(
+        &self,
+        audio: &[u8],
+        format: &AudioFormat,
+    ) -> Result<Transcript, SttError> {
+        transcribe_in_language(&self.language, |language| {
+            self.transcribe_audio(audio, format, language)
+        })
+        .await
+    }
 
-        if let Some(store) = &self.request_log_store {
-            let result_for_log = result.clone();
-            store.with_current(|log| {
-                log.stt_response_json = Some(result_for_log);
-            });
-        }
-        let text = result["text"]
-            .as_str()
-            .unwrap_or("")
-            .to_string();
+    /// Groq serves translations with `whisper-large-v3` only (not the turbo variant).
+    fn supports_translation(&self) -> bool {
+        self.model == "whisper-large-v3"
+    }
 
-        Ok(text)
+    async fn translate_detailed(
+        &self,
+        audio: &[u8],
+        format: &AudioFormat,
+    ) -> Result<Transcript, SttError> {
+        let request = TranscriptionsRequest {
+            provider: "groq",
+            api_label: "Groq",
+            endpoint: "https://api.groq.com/openai/v1/audio/translations",
+            api_key: Some(&self.api_key),
+            model: &self.model,
+            prompt: self.default_prompt.as_deref().and_then(Self::clamp_prompt),
+            response_format: Some("verbose_json"),
+            timestamp_granularities: &[],
+            extra_fields: &[],
+        };
+
+        let result = post_audio_transcription(
+            &self.client,
+            self.request_log_store.as_ref(),
+            &request,
+            audio,
+            format,
+        )
+        .await?;
+
+        Ok(Transcript::from_verbose_json(&result))
     }
 
     fn name(&self) -> &'static str {
         "groq"
     }
+
+    fn accepts_encoding(&self, encoding: AudioEncoding) -> bool {
+        encoding.is_file_format()
+    }
 }
 
 #[cfg(test)]
@@ -182,6 +215,19 @@ mod tests {
         assert_eq!(provider.model, "whisper-large-v3-turbo");
     }
 
+    #[test]
+    fn test_translation_requires_large_v3() {
+        let turbo = GroqSttProvider::new("test-key".to_string(), None, None);
+        assert!(!turbo.supports_translation());
+
+        let large = GroqSttProvider::new(
+            "test-key".to_string(),
+            Some("whisper-large-v3".to_string()),
+            None,
+        );
+        assert!(large.supports_translation());
+    }
+
     #[test]
     fn test_prompt_clamping() {
         let long = "x".repeat(GroqSttProvider::PROMPT_MAX_CHARS + 10);
@@ -0,0 +1,374 @@
+//! Hallucination filtering for STT output.
+//!
+//! Whisper-style models invent text for silence and noise ("Thank you.", "Thanks for
+//! watching!") and sometimes loop on a phrase. This filter drops:
+//! - segments the provider itself scores as non-speech (`no_speech_prob`/`avg_logprob`, the
+//!   same rule Whisper uses to skip silent windows),
+//! - immediate repeats of the previous segment,
+//! - segments that are exactly a blocklisted phrase.
+//!
+//! Providers without segments are filtered per sentence (blocklist and repeats only).
+
+use super::{Transcript, TranscriptSegment};
+use serde::{Deserialize, Serialize};
+
+/// Settings for the hallucination filter.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HallucinationFilterConfig {
+    pub enabled: bool,
+    /// Segments with a higher no-speech probability are treated as silence...
+    pub no_speech_prob_threshold: f32,
+    /// ...when their average token log-probability is also below this.
+    pub avg_logprob_threshold: f32,
+    /// Drop a segment that repeats the previous one word for word (single words such as
+    /// "No. No." are left alone).
+    pub drop_repeats: bool,
+    /// Segments matching one of these phrases (ignoring case and punctuation) are dropped.
+    pub blocklist: Vec<String>,
+}
+
+impl Default for HallucinationFilterConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            no_speech_prob_threshold: 0.6,
+            avg_logprob_threshold: -1.0,
+            drop_repeats: true,
+            blocklist: [
+                "Thanks for watching!",
+                "Thank you for watching.",
+                "Thanks for watching and see you next time.",
+                "Please subscribe.",
+                "Subscribe to my channel.",
+                "Like and subscribe.",
+                "Don't forget to like and subscribe.",
+                "Subtitles by the Amara.org community",
+            ]
+            .into_iter()
+            .map(String::from)
+            .collect(),
+        }
+    }
+}
+
+/// Why a piece of the transcript was dropped.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "reason", rename_all = "snake_case")]
+pub enum HallucinationReason {
+    /// The provider scored the segment as non-speech.
+    NoSpeech {
+        no_speech_prob: f32,
+        avg_logprob: Option<f32>,
+    },
+    /// The segment repeated the previous one.
+    Repeat,
+    /// The segment matched a blocklisted phrase.
+    Blocklist { phrase: String },
+}
+
+/// A segment (or sentence) removed by the filter.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HallucinationDrop {
+    pub text: String,
+    #[serde(flatten)]
+    pub reason: HallucinationReason,
+    /// Segment times in seconds (absent for sentence-level drops).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end: Option<f32>,
+}
+
+/// Lowercase, strip punctuation and collapse whitespace, for phrase comparisons.
+fn normalize_phrase(text: &str) -> String {
+    text.chars()
+        .map(|c| if c.is_alphanumeric() || c.is_whitespace() { c } else { ' ' })
+        .collect::<String>()
+        .to_lowercase()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Split text after sentence-ending punctuation, keeping the punctuation.
+fn split_sentences(text: &str) -> Vec<String> {
+    let mut sentences = Vec::new();
+    let mut current = String::new();
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        current.push(c);
+        if matches!(c, '.' | '!' | '?') && chars.peek().is_none_or(|n| n.is_whitespace()) {
+            let sentence = current.trim();
+            if !sentence.is_empty() {
+                sentences.push(sentence.to_string());
+            }
+            current.clear();
+        }
+    }
+
+    let rest = current.trim();
+    if !rest.is_empty() {
+        sentences.push(rest.to_string());
+    }
+    sentences
+}
+
+struct Filter<'a> {
+    config: &'a HallucinationFilterConfig,
+    blocklist: Vec<(String, &'a str)>,
+    previous: Option<String>,
+}
+
+impl<'a> Filter<'a> {
+    fn new(config: &'a HallucinationFilterConfig) -> Self {
+        Self {
+            config,
+            blocklist: config
+                .blocklist
+                .iter()
+                .map(|phrase| (normalize_phrase(phrase), phrase.as_str()))
+                .filter(|(normalized, _)| !normalized.is_empty())
+                .collect(),
+            previous: None,
+        }
+    }
+
+    /// Why `text` should be dropped, if it should. Kept text becomes the repeat reference.
+    fn check(&mut self, text: &str, segment: Option<&TranscriptSegment>) -> Option<HallucinationReason> {
+        let normalized = normalize_phrase(text);
+        if normalized.is_empty() {
+            return None;
+        }
+
+        if let Some(no_speech_prob) = segment.and_then(|s| s.no_speech_prob) {
+            let avg_logprob = segment.and_then(|s| s.avg_logprob);
+            if no_speech_prob > self.config.no_speech_prob_threshold
+                && avg_logprob.is_none_or(|p| p < self.config.avg_logprob_threshold)
+            {
+                return Some(HallucinationReason::NoSpeech {
+                    no_speech_prob,
+                    avg_logprob,
+                });
+            }
+        }
+
+        if let Some((_, phrase)) = self.blocklist.iter().find(|(b, _)| *b == normalized) {
+            return Some(HallucinationReason::Blocklist {
+                phrase: phrase.to_string(),
+            });
+        }
+
+        if self.config.drop_repeats
+            && normalized.contains(' ')
+            && self.previous.as_deref() == Some(normalized.as_str())
+        {
+            return Some(HallucinationReason::Repeat);
+        }
+
+        self.previous = Some(normalized);
+        None
+    }
+}
+
+/// Remove hallucinated segments from `transcript`.
+///
+/// Returns the filtered transcript and what was dropped. The text is rebuilt from the kept
+/// segments (or sentences) only when something was dropped; words inside dropped segments are
+/// removed too.
+pub fn filter_hallucinations(
+    transcript: Transcript,
+    config: &HallucinationFilterConfig,
+) -> (Transcript, Vec<HallucinationDrop>) {
+    if !config.enabled || transcript.text.trim().is_empty() {
+        return (transcript, Vec::new());
+    }
+
+    let mut filter = Filter::new(config);
+    let mut drops = Vec::new();
+
+    if transcript.segments.is_empty() {
+        let mut kept = Vec::new();
+        for sentence in split_sentences(&transcript.text) {
+            match filter.check(&sentence, None) {
+                Some(reason) => drops.push(HallucinationDrop {
+                    text: sentence,
+                    reason,
+                    start: None,
+                    end: None,
+                }),
+                None => kept.push(sentence),
+            }
+        }
+
+        if drops.is_empty() {
+            return (transcript, drops);
+        }
+        // Without segment times there is no telling which words belonged to a dropped sentence.
+        let filtered = Transcript {
+            text: kept.join(" "),
+            words: Vec::new(),
+            ..transcript
+        };
+        return (filtered, drops);
+    }
+
+    let mut kept: Vec<TranscriptSegment> = Vec::new();
+    for segment in transcript.segments.iter() {
+        match filter.check(&segment.text, Some(segment)) {
+            Some(reason) => drops.push(HallucinationDrop {
+                text: segment.text.clone(),
+                reason,
+                start: Some(segment.start),
+                end: Some(segment.end),
+            }),
+            None => kept.push(segment.clone()),
+        }
+    }
+
+    if drops.is_empty() {
+        return (transcript, drops);
+    }
+
+    let in_dropped_segment = |start: f32, end: f32| {
+        drops.iter().any(|d| {
+            let (Some(drop_start), Some(drop_end)) = (d.start, d.end) else {
+                return false;
+            };
+            let midpoint = (start + end) / 2.0;
+            midpoint >= drop_start && midpoint <= drop_end
+        })
+    };
+
+    let filtered = Transcript {
+        text: kept
+            .iter()
+            .map(|s| s.text.trim())
+            .filter(|t| !t.is_empty())
+            .collect::<Vec<_>>()
+            .join(" "),
+        words: transcript
+            .words
+            .into_iter()
+            .filter(|w| !in_dropped_segment(w.start, w.end))
+            .collect(),
+        segments: kept,
+        ..transcript
+    };
+    (filtered, drops)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stt::TranscriptWord;
+
+    fn segment(text: &str, start: f32, no_speech_prob: f32, avg_logprob: f32) -> TranscriptSegment {
+        TranscriptSegment {
+            text: text.to_string(),
+            start,
+            end: start + 1.0,
+            avg_logprob: Some(avg_logprob),
+            no_speech_prob: Some(no_speech_prob),
+        }
+    }
+
+    #[test]
+    fn test_drops_non_speech_and_repeated_segments() {
+        let transcript = Transcript {
+            text: "Send the report. Send the report. Thank you.".to_string(),
+            segments: vec![
+                segment("Send the report.", 0.0, 0.01, -0.2),
+                segment("Send the report.", 1.0, 0.02, -0.3),
+                segment("Thank you.", 2.0, 0.9, -1.4),
+            ],
+            words: vec![
+                TranscriptWord { word: "Send".to_string(), start: 0.1, end: 0.3, confidence: None },
+                TranscriptWord { word: "Thank".to_string(), start: 2.1, end: 2.4, confidence: None },
+            ],
+            ..Default::default()
+        };
+
+        let (filtered, drops) = filter_hallucinations(transcript, &HallucinationFilterConfig::default());
+        assert_eq!(filtered.text, "Send the report.");
+        assert_eq!(filtered.segments.len(), 1);
+        assert_eq!(filtered.words.len(), 1);
+        assert_eq!(drops[0].reason, HallucinationReason::Repeat);
+        assert!(matches!(drops[1].reason, HallucinationReason::NoSpeech { .. }));
+    }
+
+    #[test]
+    fn test_verbose_json_no_speech_segment_is_dropped() {
+        // A silent recording as Whisper's verbose_json reports it.
+        let response = serde_json::json!({
+            "text": " Thank you.",
+            "segments": [
+                { "id": 0, "start": 0.0, "end": 2.0, "text": " Thank you.", "avg_logprob": -1.3, "no_speech_prob": 0.92 }
+            ]
+        });
+        let transcript = Transcript::from_verbose_json(&response);
+
+        let (filtered, drops) = filter_hallucinations(transcript, &HallucinationFilterConfig::default());
+        assert_eq!(filtered.text, "");
+        assert_eq!(
+            drops[0].reason,
+            HallucinationReason::NoSpeech { no_speech_prob: 0.92, avg_logprob: Some(-1.3) }
+        );
+    }
+
+    #[test]
+    fn test_confident_speech_is_kept_despite_no_speech_prob() {
+        let transcript = Transcript {
+            text: "Yes.".to_string(),
+            segments: vec![segment("Yes.", 0.0, 0.8, -0.3)],
+            ..Default::default()
+        };
+
+        let (filtered, drops) = filter_hallucinations(transcript, &HallucinationFilterConfig::default());
+        assert_eq!(filtered.text, "Yes.");
+        assert!(drops.is_empty());
+    }
+
+    #[test]
+    fn test_blocklist_applies_per_sentence_without_segments() {
+        let transcript = Transcript::from_text("See you at noon. Thanks for watching!");
+
+        let (filtered, drops) = filter_hallucinations(transcript, &HallucinationFilterConfig::default());
+        assert_eq!(filtered.text, "See you at noon.");
+        assert_eq!(
+            drops[0].reason,
+            HallucinationReason::Blocklist { phrase: "Thanks for watching!".to_string() }
+        );
+    }
+
+    #[test]
+    fn test_single_word_repeats_are_kept() {
+        let transcript = Transcript::from_text("No. No. Not that one.");
+
+        let (filtered, drops) = filter_hallucinations(transcript, &HallucinationFilterConfig::default());
+        assert_eq!(filtered.text, "No. No. Not that one.");
+        assert!(drops.is_empty());
+    }
+
+    #[test]
+    fn test_disabled_filter_keeps_everything() {
+        let config = HallucinationFilterConfig {
+            enabled: false,
+            ..Default::default()
+        };
+        let transcript = Transcript::from_text("Thanks for watching!");
+
+        let (filtered, drops) = filter_hallucinations(transcript.clone(), &config);
+        assert_eq!(filtered, transcript);
+        assert!(drops.is_empty());
+    }
+
+    #[test]
+    fn test_split_sentences_keeps_decimals() {
+        assert_eq!(
+            split_sentences("It costs 3.50 today. Really?"),
+            vec!["It costs 3.50 today.", "Really?"]
+        );
+    }
+}
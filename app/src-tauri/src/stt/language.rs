@@ -0,0 +1,188 @@
+//! Spoken-language selection for STT requests.
+//!
+//! Without a hint, Whisper guesses the language from the first seconds of audio and short
+//! utterances are easily misdetected. Providers map this setting onto their own parameters
+//! (`language`, `detect_language`, `language_code`, `set_language`).
+
+use super::{SttError, Transcript};
+use crate::translation::normalize_language;
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+
+/// Which language(s) the speaker is expected to use.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum SttLanguage {
+    /// Let the provider detect the language.
+    #[default]
+    Auto,
+    /// Always transcribe in this language (ISO 639-1 code or name).
+    Fixed { language: String },
+    /// Detect the language, but only among these (first = preferred).
+    Candidates { languages: Vec<String> },
+}
+
+impl SttLanguage {
+    /// Normalized form: codes are ISO 639-1, blanks dropped, and a single candidate becomes
+    /// a fixed language.
+    pub fn normalized(&self) -> Self {
+        match self {
+            Self::Auto => Self::Auto,
+            Self::Fixed { language } if language.trim().is_empty() => Self::Auto,
+            Self::Fixed { language } => Self::Fixed {
+                language: normalize_language(language),
+            },
+            Self::Candidates { languages } => {
+                let mut codes: Vec<String> = Vec::new();
+                for code in languages
+                    .iter()
+                    .filter(|l| !l.trim().is_empty())
+                    .map(|l| normalize_language(l))
+                {
+                    if !codes.contains(&code) {
+                        codes.push(code);
+                    }
+                }
+
+                match codes.len() {
+                    0 => Self::Auto,
+                    1 => Self::Fixed {
+                        language: codes.remove(0),
+                    },
+                    _ => Self::Candidates { languages: codes },
+                }
+            }
+        }
+    }
+
+    /// The language to request explicitly, if any.
+    pub fn fixed(&self) -> Option<&str> {
+        match self {
+            Self::Fixed { language } => Some(language),
+            _ => None,
+        }
+    }
+
+    /// Allowed languages when detecting among candidates (empty otherwise).
+    pub fn candidates(&self) -> &[String] {
+        match self {
+            Self::Candidates { languages } => languages,
+            _ => &[],
+        }
+    }
+
+    /// Whether a detected language is acceptable. Unknown detections are accepted.
+    pub fn accepts(&self, detected: Option<&str>) -> bool {
+        let Some(detected) = detected.filter(|d| !d.trim().is_empty()) else {
+            return true;
+        };
+        let detected = normalize_language(detected);
+
+        match self {
+            Self::Auto => true,
+            Self::Fixed { language } => normalize_language(language) == detected,
+            Self::Candidates { languages } => languages
+                .iter()
+                .any(|l| normalize_language(l) == detected),
+        }
+    }
+
+    /// Stable key for provider caches (providers are built per language setting).
+    pub fn cache_key(&self) -> String {
+        match self.normalized() {
+            Self::Auto => "auto".to_string(),
+            Self::Fixed { language } => language,
+            Self::Candidates { languages } => languages.join("|"),
+        }
+    }
+}
+
+/// Transcribe with `language` for providers that accept a single `language` parameter only.
+///
+/// With candidates, the first request auto-detects; if the detected language is not one of
+/// the candidates, the audio is transcribed again in the preferred (first) candidate.
+pub async fn transcribe_in_language<F, Fut>(
+    language: &SttLanguage,
+    mut transcribe: F,
+) -> Result<Transcript, SttError>
+where
+    F: FnMut(Option<String>) -> Fut,
+    Fut: Future<Output = Result<Transcript, SttError>>,
+{
+    let transcript = transcribe(language.fixed().map(str::to_string)).await?;
+
+    match language.candidates().first() {
+        Some(preferred) if !language.accepts(transcript.language.as_deref()) => {
+            log::info!(
+                "STT detected language {:?} outside of the allowed languages; retrying in '{}'",
+                transcript.language,
+                preferred
+            );
+            transcribe(Some(preferred.clone())).await
+        }
+        _ => Ok(transcript),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidates(languages: &[&str]) -> SttLanguage {
+        SttLanguage::Candidates {
+            languages: languages.iter().map(|l| l.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_normalized() {
+        assert_eq!(
+            candidates(&["English", "en-US", " "]).normalized(),
+            SttLanguage::Fixed {
+                language: "en".to_string()
+            }
+        );
+        assert_eq!(
+            candidates(&["en", "German"]).normalized(),
+            candidates(&["en", "de"])
+        );
+        assert_eq!(
+            SttLanguage::Fixed {
+                language: String::new()
+            }
+            .normalized(),
+            SttLanguage::Auto
+        );
+    }
+
+    #[test]
+    fn test_accepts_detected_language_names() {
+        let language = candidates(&["en", "de"]);
+        // Whisper's verbose_json reports full names.
+        assert!(language.accepts(Some("english")));
+        assert!(!language.accepts(Some("welsh")));
+        assert!(language.accepts(None));
+        assert!(SttLanguage::Auto.accepts(Some("welsh")));
+    }
+
+    #[tokio::test]
+    async fn test_transcribe_in_language_retries_outside_candidates() {
+        let language = candidates(&["en", "pt"]);
+        let mut requested = Vec::new();
+
+        let transcript = transcribe_in_language(&language, |lang| {
+            requested.push(lang.clone());
+            async move {
+                Ok(Transcript {
+                    language: Some(lang.unwrap_or_else(|| "welsh".to_string())),
+                    ..Transcript::from_text("hello")
+                })
+            }
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(requested, vec![None, Some("en".to_string())]);
+        assert_eq!(transcript.language.as_deref(), Some("en"));
+    }
+}
@@ -3,25 +3,43 @@
 //! This module provides a trait-based abstraction for STT providers,
 //! allowing easy switching between different speech recognition services.
 
+mod assemblyai;
 mod deepgram;
+mod deepgram_live;
 mod groq;
+mod hallucination;
+mod language;
 mod openai;
-mod retry;
+mod openai_compatible;
+mod streaming;
+mod transcript;
+mod vocabulary;
 
 #[cfg(feature = "local-whisper")]
 mod whisper;
 
+pub use assemblyai::AssemblyAiSttProvider;
 pub use deepgram::DeepgramSttProvider;
 pub use groq::GroqSttProvider;
+pub use hallucination::{filter_hallucinations, HallucinationDrop, HallucinationFilterConfig};
+pub use language::SttLanguage;
 pub use openai::OpenAiSttProvider;
-pub use retry::{with_retry, RetryConfig};
-#[allow(unused_imports)]
-pub use retry::is_retryable_error;
+pub use openai_compatible::{OpenAiCompatibleSttConfig, OpenAiCompatibleSttProvider};
+pub use crate::retry::{with_retry, RetryConfig};
+pub use streaming::{
+    audio_chunk_channel, f32_to_pcm16, start_streaming_session, AudioChunkReceiver,
+    AudioChunkSender, SttStream, TranscriptEvent,
+};
+pub use transcript::{Transcript, TranscriptSegment, TranscriptWord};
+pub use vocabulary::Vocabulary;
 
 #[cfg(feature = "local-whisper")]
 pub use whisper::{LocalWhisperConfig, LocalWhisperProvider, WhisperModel};
 
+use crate::api_error::ApiError;
+use crate::retry::RetryableError;
 use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 
 /// Audio format information for STT processing
@@ -46,11 +64,44 @@ impl Default for AudioFormat {
 }
 
 /// Supported audio encoding formats
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum AudioEncoding {
+    #[default]
     Wav,
-    #[cfg_attr(not(test), allow(dead_code))]
+    /// Raw little-endian 16-bit PCM (live streaming only).
     Pcm16,
+    /// Lossless FLAC.
+    Flac,
+    /// Opus in an Ogg container (lossy).
+    OggOpus,
+}
+
+impl AudioEncoding {
+    /// MIME type sent with uploads in this encoding.
+    pub fn mime_type(self) -> &'static str {
+        match self {
+            Self::Wav => "audio/wav",
+            Self::Pcm16 => "audio/L16",
+            Self::Flac => "audio/flac",
+            Self::OggOpus => "audio/ogg",
+        }
+    }
+
+    /// File name sent with multipart uploads (providers detect the format from the extension).
+    pub fn file_name(self) -> &'static str {
+        match self {
+            Self::Wav => "audio.wav",
+            Self::Pcm16 => "audio.pcm",
+            Self::Flac => "audio.flac",
+            Self::OggOpus => "audio.ogg",
+        }
+    }
+
+    /// Whether this is a self-describing file format that batch transcription APIs accept.
+    pub fn is_file_format(self) -> bool {
+        !matches!(self, Self::Pcm16)
+    }
 }
 
 /// Errors that can occur during STT operations
@@ -60,7 +111,7 @@ pub enum SttError {
     Network(#[from] reqwest::Error),
 
     #[error("API error: {0}")]
-    Api(String),
+    Api(ApiError),
 
     #[error("Audio processing error: {0}")]
     Audio(String),
@@ -70,6 +121,24 @@ pub enum SttError {
 
     #[error("Timeout: transcription took too long")]
     Timeout,
+
+    #[error("Transcription cancelled")]
+    Cancelled,
+}
+
+impl RetryableError for SttError {
+    const KIND: &'static str = "STT";
+
+    fn is_transient(&self) -> bool {
+        matches!(self, SttError::Network(_) | SttError::Timeout)
+    }
+
+    fn api_error(&self) -> Option<&ApiError> {
+        match self {
+            SttError::Api(error) => Some(error),
+            _ => None,
+        }
+    }
 }
 
 /// Trait for Speech-to-Text providers
@@ -85,9 +154,74 @@ pub trait SttProvider: Send + Sync {
     /// The transcribed text, or an error if transcription fails
     async fn transcribe(&self, audio: &[u8], format: &AudioFormat) -> Result<String, SttError>;
 
+    /// Transcribe audio data, keeping segments/word timings/confidence when available.
+    ///
+    /// The default wraps `transcribe` in a text-only `Transcript`. Providers whose API
+    /// returns richer results override this (and implement `transcribe` on top of it).
+    async fn transcribe_detailed(
+        &self,
+        audio: &[u8],
+        format: &AudioFormat,
+    ) -> Result<Transcript, SttError> {
+        Ok(Transcript::from_text(self.transcribe(audio, format).await?))
+    }
+
+    /// Whether `translate_detailed` is available for the configured model.
+    fn supports_translation(&self) -> bool {
+        false
+    }
+
+    /// Transcribe audio and translate it into English in one request (Whisper's translate task).
+    ///
+    /// Only called when `supports_translation` returns `true`.
+    async fn translate_detailed(
+        &self,
+        _audio: &[u8],
+        _format: &AudioFormat,
+    ) -> Result<Transcript, SttError> {
+        Err(SttError::Config(format!(
+            "STT provider '{}' does not support translation",
+            self.name()
+        )))
+    }
+
     /// Get the name of this provider
     #[cfg_attr(not(test), allow(dead_code))]
     fn name(&self) -> &'static str;
+
+    /// Whether batch requests may send audio in `encoding`.
+    ///
+    /// Providers that return `false` are sent the WAV recording instead of a compressed copy.
+    fn accepts_encoding(&self, encoding: AudioEncoding) -> bool {
+        matches!(encoding, AudioEncoding::Wav)
+    }
+
+    /// Whether this provider has a native live-streaming API.
+    ///
+    /// Providers that return `false` are streamed through a batch adapter
+    /// (see `start_streaming_session`).
+    fn supports_streaming(&self) -> bool {
+        false
+    }
+
+    /// Open a native live-streaming session.
+    ///
+    /// # Arguments
+    /// * `format` - Sample rate/channels of the incoming PCM16 chunks
+    /// * `audio_rx` - Audio chunks pushed from the capture thread; closed when capture stops
+    ///
+    /// This must not block: connecting happens inside the spawned session driver, while
+    /// early audio waits in the channel. Only called when `supports_streaming` returns `true`.
+    fn open_stream(
+        &self,
+        _format: &AudioFormat,
+        _audio_rx: AudioChunkReceiver,
+    ) -> Result<SttStream, SttError> {
+        Err(SttError::Config(format!(
+            "STT provider '{}' does not support live streaming",
+            self.name()
+        )))
+    }
 }
 
 /// Registry for managing multiple STT providers
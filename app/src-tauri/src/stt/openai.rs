@@ -4,19 +4,143 @@
 //! - Legacy Whisper API (whisper-1) - uses /v1/audio/transcriptions
 //! - Audio chat models (e.g., gpt-4o-audio-preview) - uses /v1/responses with audio input
 
-use super::{AudioFormat, SttError, SttProvider};
+use super::language::transcribe_in_language;
+use super::{
+    AudioEncoding, AudioFormat, SttError, SttLanguage, SttProvider, Transcript, Vocabulary,
+};
 use async_trait::async_trait;
+use crate::api_error::ApiError;
 use crate::request_log::RequestLogStore;
+use crate::translation::language_display_name;
 use reqwest::multipart;
 use serde_json::json;
 use std::time::Duration;
 
+/// A multipart request to an OpenAI-style `/audio/transcriptions` endpoint.
+pub(super) struct TranscriptionsRequest<'a> {
+    /// Provider id recorded in the request log.
+    pub provider: &'a str,
+    /// Human-readable API name used in error messages.
+    pub api_label: &'a str,
+    pub endpoint: &'a str,
+    /// Bearer token; omitted from the request when `None`.
+    pub api_key: Option<&'a str>,
+    pub model: &'a str,
+    pub prompt: Option<String>,
+    /// `response_format` form field (e.g. "verbose_json"); server default when `None`.
+    pub response_format: Option<&'a str>,
+    /// `timestamp_granularities[]` values (only meaningful with "verbose_json").
+    pub timestamp_granularities: &'a [&'a str],
+    /// Additional form fields sent verbatim (e.g. `language`, `temperature`).
+    pub extra_fields: &'a [(String, String)],
+}
+
+/// Timestamp granularities requested alongside `verbose_json`.
+pub(super) const VERBOSE_TIMESTAMP_GRANULARITIES: &[&str] = &["segment", "word"];
+
+/// Post an audio file to an OpenAI-style transcription endpoint and return the JSON response.
+///
+/// Shared by the OpenAI provider and the generic OpenAI-compatible provider.
+pub(super) async fn post_audio_transcription(
+    client: &reqwest::Client,
+    request_log_store: Option<&RequestLogStore>,
+    request: &TranscriptionsRequest<'_>,
+    audio: &[u8],
+    format: &AudioFormat,
+) -> Result<serde_json::Value, SttError> {
+    if let Some(store) = request_log_store {
+        let mut fields = json!({
+            "model": request.model,
+            "prompt": request.prompt,
+        });
+        if let Some(response_format) = request.response_format {
+            fields["response_format"] = json!(response_format);
+        }
+        if !request.timestamp_granularities.is_empty() {
+            fields["timestamp_granularities[]"] = json!(request.timestamp_granularities);
+        }
+        for (key, value) in request.extra_fields {
+            fields[key.as_str()] = json!(value);
+        }
+
+        let request_json = json!({
+            "provider": request.provider,
+            "endpoint": request.endpoint,
+            "content_type": "multipart/form-data",
+            "fields": fields,
+            "file": {
+                "name": format.encoding.file_name(),
+                "mime": format.encoding.mime_type(),
+                "bytes": audio.len(),
+                "data": "<binary audio omitted>",
+            }
+        });
+
+        store.with_current(|log| {
+            log.stt_request_json = Some(request_json);
+        });
+    }
+
+    let part = multipart::Part::bytes(audio.to_vec())
+        .file_name(format.encoding.file_name())
+        .mime_str(format.encoding.mime_type())
+        .map_err(|e| SttError::Audio(format!("Failed to create multipart: {}", e)))?;
+
+    let mut form = multipart::Form::new()
+        .part("file", part)
+        .text("model", request.model.to_string());
+
+    if let Some(prompt) = &request.prompt {
+        form = form.text("prompt", prompt.clone());
+    }
+
+    if let Some(response_format) = request.response_format {
+        form = form.text("response_format", response_format.to_string());
+    }
+
+    for granularity in request.timestamp_granularities {
+        form = form.text("timestamp_granularities[]", granularity.to_string());
+    }
+
+    for (key, value) in request.extra_fields {
+        form = form.text(key.clone(), value.clone());
+    }
+
+    let mut builder = client.post(request.endpoint).multipart(form);
+    if let Some(api_key) = request.api_key {
+        builder = builder.bearer_auth(api_key);
+    }
+
+    let response = builder
+        .send()
+        .await
+        .map_err(|e| if e.is_timeout() { SttError::Timeout } else { SttError::Network(e) })?;
+
+    if !response.status().is_success() {
+        let error = ApiError::from_response(&format!("{} API error", request.api_label), response).await;
+        return Err(SttError::Api(error));
+    }
+
+    let result: serde_json::Value = response.json().await?;
+
+    if let Some(store) = request_log_store {
+        let result_for_log = result.clone();
+        store.with_current(|log| {
+            log.stt_response_json = Some(result_for_log);
+        });
+    }
+
+    Ok(result)
+}
+
 /// OpenAI STT provider for speech-to-text
 pub struct OpenAiSttProvider {
     client: reqwest::Client,
     api_key: String,
     model: String,
     default_prompt: Option<String>,
+    language: SttLanguage,
+    vocabulary: Vocabulary,
     request_log_store: Option<RequestLogStore>,
 }
 
@@ -46,6 +170,8 @@ impl OpenAiSttProvider {
                 .map(str::trim)
                 .filter(|s| !s.is_empty())
                 .map(|s| s.to_string()),
+            language: SttLanguage::Auto,
+            vocabulary: Vocabulary::default(),
             request_log_store: None,
         }
     }
@@ -67,10 +193,24 @@ impl OpenAiSttProvider {
                 .map(str::trim)
                 .filter(|s| !s.is_empty())
                 .map(|s| s.to_string()),
+            language: SttLanguage::Auto,
+            vocabulary: Vocabulary::default(),
             request_log_store: None,
         }
     }
 
+    /// Spoken language hint (`language` form field / audio-chat instruction)
+    pub fn with_language(mut self, language: SttLanguage) -> Self {
+        self.language = language.normalized();
+        self
+    }
+
+    /// Dictionary terms to bias recognition towards (appended to the transcription prompt)
+    pub fn with_vocabulary(mut self, vocabulary: Vocabulary) -> Self {
+        self.vocabulary = vocabulary;
+        self
+    }
+
     pub fn with_request_log_store(mut self, store: Option<RequestLogStore>) -> Self {
         self.request_log_store = store;
         self
@@ -107,81 +247,105 @@ impl OpenAiSttProvider {
         Some(prompt.to_string())
     }
 
+    /// Whether the model supports `verbose_json` (segments + word timestamps).
+    ///
+    /// Only `whisper-1` does; the `*-transcribe` models return `json`/`text` only.
+    fn supports_verbose_json(&self) -> bool {
+        self.model == "whisper-1"
+    }
+
+    /// Only `whisper-1` is served by the translation endpoint.
+    fn supports_translation_endpoint(&self) -> bool {
+        self.model == "whisper-1"
+    }
+
     /// Transcribe using the dedicated OpenAI transcription endpoint.
     async fn transcribe_audio_transcriptions(
         &self,
         audio: &[u8],
+        format: &AudioFormat,
         prompt: Option<&str>,
-    ) -> Result<String, SttError> {
-        if let Some(store) = &self.request_log_store {
-            let prompt = self.clamp_prompt_for_model(prompt);
-            let request_json = json!({
-                "provider": "openai",
-                "endpoint": "https://api.openai.com/v1/audio/transcriptions",
-                "content_type": "multipart/form-data",
-                "fields": {
-                    "model": self.model,
-                    "prompt": prompt,
-                },
-                "file": {
-                    "name": "audio.wav",
-                    "mime": "audio/wav",
-                    "bytes": audio.len(),
-                    "data": "<binary audio omitted>",
-                }
-            });
-
-            store.with_current(|log| {
-                log.stt_request_json = Some(request_json);
-            });
-        }
-
-        let part = multipart::Part::bytes(audio.to_vec())
-            .file_name("audio.wav")
-            .mime_str("audio/wav")
-            .map_err(|e| SttError::Audio(format!("Failed to create multipart: {}", e)))?;
-
-        let mut form = multipart::Form::new()
-            .part("file", part)
-            .text("model", self.model.clone());
-
-        if let Some(prompt) = self.clamp_prompt_for_model(prompt) {
-            form = form.text("prompt", prompt);
-        }
-
-        let response = self
-            .client
-            .post("https://api.openai.com/v1/audio/transcriptions")
-            .bearer_auth(&self.api_key)
-            .multipart(form)
-            .send()
-            .await
-            .map_err(|e| if e.is_timeout() { SttError::Timeout } else { SttError::Network(e) })?;
-
-        if !response.status().is_success() {
-            let status = response.status();
-            let error_text = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "Unknown error".to_string());
-            return Err(SttError::Api(format!(
-                "OpenAI Whisper API error ({}): {}",
-                status, error_text
-            )));
-        }
+        language: Option<String>,
+    ) -> Result<Transcript, SttError> {
+        let verbose = self.supports_verbose_json();
+        let extra_fields: Vec<(String, String)> = language
+            .map(|language| ("language".to_string(), language))
+            .into_iter()
+            .collect();
+        let request = TranscriptionsRequest {
+            provider: "openai",
+            api_label: "OpenAI Whisper",
+            endpoint: "https://api.openai.com/v1/audio/transcriptions",
+            api_key: Some(&self.api_key),
+            model: &self.model,
+            prompt: self.clamp_prompt_for_model(
+                self.vocabulary
+                    .whisper_prompt(prompt, Self::WHISPER_PROMPT_MAX_CHARS)
+                    .as_deref(),
+            ),
+            response_format: verbose.then_some("verbose_json"),
+            timestamp_granularities: if verbose { VERBOSE_TIMESTAMP_GRANULARITIES } else { &[] },
+            extra_fields: &extra_fields,
+        };
+
+        let result = post_audio_transcription(
+            &self.client,
+            self.request_log_store.as_ref(),
+            &request,
+            audio,
+            format,
+        )
+        .await?;
+
+        Ok(Transcript::from_verbose_json(&result))
+    }
 
-        let result: serde_json::Value = response.json().await?;
+    /// Translate speech into English using the OpenAI translation endpoint.
+    async fn translate_audio(
+        &self,
+        audio: &[u8],
+        format: &AudioFormat,
+    ) -> Result<Transcript, SttError> {
+        // The translation endpoint returns segments but has no word-level timestamps.
+        let request = TranscriptionsRequest {
+            provider: "openai",
+            api_label: "OpenAI Whisper",
+            endpoint: "https://api.openai.com/v1/audio/translations",
+            api_key: Some(&self.api_key),
+            model: &self.model,
+            prompt: self.clamp_prompt_for_model(self.default_prompt.as_deref()),
+            response_format: Some("verbose_json"),
+            timestamp_granularities: &[],
+            extra_fields: &[],
+        };
+
+        let result = post_audio_transcription(
+            &self.client,
+            self.request_log_store.as_ref(),
+            &request,
+            audio,
+            format,
+        )
+        .await?;
+
+        Ok(Transcript::from_verbose_json(&result))
+    }
 
-        if let Some(store) = &self.request_log_store {
-            let result_for_log = result.clone();
-            store.with_current(|log| {
-                log.stt_response_json = Some(result_for_log);
-            });
+    /// Language hint for the audio-chat (Responses API) path, which has no `language` field.
+    fn language_instruction(&self) -> Option<String> {
+        let names: Vec<String> = match &self.language {
+            SttLanguage::Auto => return None,
+            SttLanguage::Fixed { language } => vec![language.clone()],
+            SttLanguage::Candidates { languages } => languages.clone(),
         }
-
-        let text = result["text"].as_str().unwrap_or("").to_string();
-
-        Ok(text)
+        .iter()
+        .map(|l| language_display_name(l))
+        .collect();
+
+        Some(match names.as_slice() {
+            [name] => format!("The speech is in {}.", name),
+            _ => format!("The speech is in one of: {}.", names.join(", ")),
+        })
     }
 
     fn extract_responses_output_text(value: &serde_json::Value) -> Result<String, SttError> {
@@ -192,7 +356,7 @@ impl OpenAiSttProvider {
         let output = value
             .get("output")
             .and_then(|v| v.as_array())
-            .ok_or_else(|| SttError::Api("Responses API returned no 'output' array".to_string()))?;
+            .ok_or_else(|| SttError::Api("Responses API returned no 'output' array".into()))?;
 
         for item in output {
             if item.get("type").and_then(|t| t.as_str()) != Some("message") {
@@ -211,7 +375,7 @@ impl OpenAiSttProvider {
                             .get("refusal")
                             .and_then(|r| r.as_str())
                             .unwrap_or("");
-                        return Err(SttError::Api(format!("OpenAI refusal: {}", refusal)));
+                        return Err(SttError::Api(format!("OpenAI refusal: {}", refusal).into()));
                     }
                     Some("output_text") => {
                         if let Some(text) = part.get("text").and_then(|t| t.as_str()) {
@@ -224,7 +388,7 @@ impl OpenAiSttProvider {
         }
 
         Err(SttError::Api(
-            "Responses API returned no output_text content".to_string(),
+            "Responses API returned no output_text content".into(),
         ))
     }
 
@@ -244,6 +408,14 @@ impl OpenAiSttProvider {
             instruction.push_str("\n\nContext/prompt: ");
             instruction.push_str(&prompt);
         }
+        if let Some(hint) = self.language_instruction() {
+            instruction.push_str("\n\n");
+            instruction.push_str(&hint);
+        }
+        if !self.vocabulary.is_empty() {
+            instruction.push_str("\n\nSpell these terms exactly as written: ");
+            instruction.push_str(&self.vocabulary.prioritized_terms().join(", "));
+        }
 
         let request_body = json!({
             "model": self.model,
@@ -317,15 +489,8 @@ impl OpenAiSttProvider {
             .map_err(|e| if e.is_timeout() { SttError::Timeout } else { SttError::Network(e) })?;
 
         if !response.status().is_success() {
-            let status = response.status();
-            let error_text = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "Unknown error".to_string());
-            return Err(SttError::Api(format!(
-                "OpenAI GPT-4o API error ({}): {}",
-                status, error_text
-            )));
+            let error = ApiError::from_response("OpenAI GPT-4o API error", response).await;
+            return Err(SttError::Api(error));
         }
 
         let result: serde_json::Value = response.json().await?;
@@ -346,27 +511,70 @@ impl OpenAiSttProvider {
     pub async fn transcribe_with_prompt(
         &self,
         audio: &[u8],
-        _format: &AudioFormat,
+        format: &AudioFormat,
         prompt: Option<&str>,
     ) -> Result<String, SttError> {
+        self.transcribe_detailed_with_prompt(audio, format, prompt)
+            .await
+            .map(|t| t.text)
+    }
+
+    /// Transcribe with an optional prompt, keeping segment/word detail when available.
+    async fn transcribe_detailed_with_prompt(
+        &self,
+        audio: &[u8],
+        format: &AudioFormat,
+        prompt: Option<&str>,
+    ) -> Result<Transcript, SttError> {
         if self.uses_transcriptions_endpoint() {
-            self.transcribe_audio_transcriptions(audio, prompt).await
+            transcribe_in_language(&self.language, |language| {
+                self.transcribe_audio_transcriptions(audio, format, prompt, language)
+            })
+            .await
         } else {
-            self.transcribe_responses_audio(audio, prompt).await
+            self.transcribe_responses_audio(audio, prompt)
+                .await
+                .map(Transcript::from_text)
         }
     }
 }
 
 #[async_trait]
 impl SttProvider for OpenAiSttProvider {
-    async fn transcribe(&self, audio: &[u8], _format: &AudioFormat) -> Result<String, SttError> {
-        self.transcribe_with_prompt(audio, _format, self.default_prompt.as_deref())
+    async fn transcribe(&self, audio: &[u8], format: &AudioFormat) -> Result<String, SttError> {
+        self.transcribe_detailed(audio, format).await.map(|t| t.text)
+    }
+
+    async fn transcribe_detailed(
+        &self,
+        audio: &[u8],
+        format: &AudioFormat,
+    ) -> Result<Transcript, SttError> {
+        self.transcribe_detailed_with_prompt(audio, format, self.default_prompt.as_deref())
             .await
     }
 
+    fn supports_translation(&self) -> bool {
+        self.supports_translation_endpoint()
+    }
+
+    async fn translate_detailed(
+        &self,
+        audio: &[u8],
+        format: &AudioFormat,
+    ) -> Result<Transcript, SttError> {
+        self.translate_audio(audio, format).await
+    }
+
     fn name(&self) -> &'static str {
         "openai"
     }
+
+    /// The Responses API only takes WAV/MP3 `input_audio`.
+    fn accepts_encoding(&self, encoding: AudioEncoding) -> bool {
+        encoding.is_file_format()
+            && (self.uses_transcriptions_endpoint() || matches!(encoding, AudioEncoding::Wav))
+    }
 }
 
 #[cfg(test)]
@@ -424,4 +632,32 @@ mod tests {
         );
         assert!(provider.uses_transcriptions_endpoint());
     }
+
+    #[test]
+    fn test_translation_support() {
+        let provider =
+            OpenAiSttProvider::new("test-key".to_string(), Some("whisper-1".to_string()), None);
+        assert!(provider.supports_translation());
+
+        let provider = OpenAiSttProvider::new(
+            "test-key".to_string(),
+            Some("gpt-4o-transcribe".to_string()),
+            None,
+        );
+        assert!(!provider.supports_translation());
+    }
+
+    #[test]
+    fn test_language_instruction_for_audio_chat_models() {
+        let provider = OpenAiSttProvider::new("test-key".to_string(), None, None);
+        assert!(provider.language_instruction().is_none());
+
+        let provider = provider.with_language(SttLanguage::Candidates {
+            languages: vec!["en".to_string(), "de".to_string()],
+        });
+        assert_eq!(
+            provider.language_instruction().as_deref(),
+            Some("The speech is in one of: English, German.")
+        );
+    }
 }
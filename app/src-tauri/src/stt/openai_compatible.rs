@@ -0,0 +1,242 @@
+//! Generic OpenAI-compatible STT provider.
+//!
+//! Targets self-hosted servers that expose `/v1/audio/transcriptions` with the OpenAI
+//! multipart contract (faster-whisper-server, whisper.cpp's `server`, LocalAI, ...).
+
+use super::openai::{
+    post_audio_transcription, TranscriptionsRequest, VERBOSE_TIMESTAMP_GRANULARITIES,
+};
+use super::{AudioEncoding, AudioFormat, SttError, SttProvider, Transcript, Vocabulary};
+use async_trait::async_trait;
+use crate::request_log::RequestLogStore;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+/// User settings for the OpenAI-compatible STT endpoint.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OpenAiCompatibleSttConfig {
+    /// Server base URL, e.g. "http://localhost:8000/v1".
+    pub base_url: String,
+    /// Optional bearer token. Most local servers don't need one.
+    pub api_key: Option<String>,
+    /// Model name to send (falls back to "whisper-1" when unset).
+    pub model: Option<String>,
+    /// Extra multipart form fields sent with every request (e.g. `language`).
+    pub extra_fields: BTreeMap<String, String>,
+}
+
+impl OpenAiCompatibleSttConfig {
+    /// Whether enough is configured to make requests.
+    pub fn is_configured(&self) -> bool {
+        !self.base_url.trim().is_empty()
+    }
+}
+
+/// STT provider for any OpenAI-compatible transcription server
+pub struct OpenAiCompatibleSttProvider {
+    client: reqwest::Client,
+    endpoint: String,
+    api_key: Option<String>,
+    model: String,
+    extra_fields: Vec<(String, String)>,
+    default_prompt: Option<String>,
+    vocabulary: Vocabulary,
+    request_log_store: Option<RequestLogStore>,
+}
+
+impl OpenAiCompatibleSttProvider {
+    /// Whisper-based servers only look at the first 224 prompt tokens; vocabulary terms are
+    /// added while the prompt stays within this many characters.
+    const PROMPT_MAX_CHARS: usize = 224;
+
+    /// Create a new OpenAI-compatible STT provider
+    ///
+    /// # Arguments
+    /// * `config` - Endpoint settings
+    /// * `model` - Model override (takes precedence over `config.model`)
+    /// * `default_prompt` - Optional transcription prompt (`prompt` form field)
+    pub fn new(
+        config: &OpenAiCompatibleSttConfig,
+        model: Option<String>,
+        default_prompt: Option<String>,
+    ) -> Result<Self, SttError> {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(120)) // Local CPU inference can be slow
+            .build()
+            .expect("Failed to create HTTP client");
+
+        Self::with_client(client, config, model, default_prompt)
+    }
+
+    /// Create a new provider with a custom HTTP client
+    pub fn with_client(
+        client: reqwest::Client,
+        config: &OpenAiCompatibleSttConfig,
+        model: Option<String>,
+        default_prompt: Option<String>,
+    ) -> Result<Self, SttError> {
+        if !config.is_configured() {
+            return Err(SttError::Config(
+                "OpenAI-compatible STT requires a base URL".to_string(),
+            ));
+        }
+
+        Ok(Self {
+            client,
+            endpoint: Self::transcriptions_endpoint(&config.base_url),
+            api_key: config
+                .api_key
+                .as_deref()
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(|s| s.to_string()),
+            model: model
+                .or_else(|| config.model.clone())
+                .filter(|m| !m.trim().is_empty())
+                .unwrap_or_else(|| "whisper-1".to_string()),
+            extra_fields: config
+                .extra_fields
+                .iter()
+                .filter(|(k, _)| !k.trim().is_empty())
+                .map(|(k, v)| (k.trim().to_string(), v.clone()))
+                .collect(),
+            default_prompt: default_prompt
+                .as_deref()
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(|s| s.to_string()),
+            vocabulary: Vocabulary::default(),
+            request_log_store: None,
+        })
+    }
+
+    /// Dictionary terms to bias recognition towards (appended to the prompt)
+    pub fn with_vocabulary(mut self, vocabulary: Vocabulary) -> Self {
+        self.vocabulary = vocabulary;
+        self
+    }
+
+    pub fn with_request_log_store(mut self, store: Option<RequestLogStore>) -> Self {
+        self.request_log_store = store;
+        self
+    }
+
+    /// Request `verbose_json` (segments/words/language) unless the user chose a format.
+    fn requests_verbose_json(&self) -> bool {
+        !self.extra_fields.iter().any(|(k, _)| k == "response_format")
+    }
+
+    /// Accept either a base URL ("http://host/v1") or the full transcription endpoint.
+    fn transcriptions_endpoint(base_url: &str) -> String {
+        let base = base_url.trim().trim_end_matches('/');
+        if base.ends_with("/audio/transcriptions") {
+            base.to_string()
+        } else {
+            format!("{}/audio/transcriptions", base)
+        }
+    }
+}
+
+#[async_trait]
+impl SttProvider for OpenAiCompatibleSttProvider {
+    async fn transcribe(&self, audio: &[u8], format: &AudioFormat) -> Result<String, SttError> {
+        self.transcribe_detailed(audio, format).await.map(|t| t.text)
+    }
+
+    async fn transcribe_detailed(
+        &self,
+        audio: &[u8],
+        format: &AudioFormat,
+    ) -> Result<Transcript, SttError> {
+        let verbose = self.requests_verbose_json();
+        let request = TranscriptionsRequest {
+            provider: "openai-compatible",
+            api_label: "OpenAI-compatible",
+            endpoint: &self.endpoint,
+            api_key: self.api_key.as_deref(),
+            model: &self.model,
+            prompt: self
+                .vocabulary
+                .whisper_prompt(self.default_prompt.as_deref(), Self::PROMPT_MAX_CHARS),
+            response_format: verbose.then_some("verbose_json"),
+            timestamp_granularities: if verbose { VERBOSE_TIMESTAMP_GRANULARITIES } else { &[] },
+            extra_fields: &self.extra_fields,
+        };
+
+        let result = post_audio_transcription(
+            &self.client,
+            self.request_log_store.as_ref(),
+            &request,
+            audio,
+            format,
+        )
+        .await?;
+
+        Ok(Transcript::from_verbose_json(&result))
+    }
+
+    fn name(&self) -> &'static str {
+        "openai-compatible"
+    }
+
+    /// Servers that can't decode the upload (e.g. whisper.cpp without `--convert`) reject it
+    /// with an error, which lets the fallback chain take over.
+    fn accepts_encoding(&self, encoding: AudioEncoding) -> bool {
+        encoding.is_file_format()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(base_url: &str) -> OpenAiCompatibleSttConfig {
+        OpenAiCompatibleSttConfig {
+            base_url: base_url.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_provider_creation() {
+        let provider =
+            OpenAiCompatibleSttProvider::new(&config("http://localhost:8000/v1/"), None, None)
+                .unwrap();
+        assert_eq!(provider.name(), "openai-compatible");
+        assert_eq!(provider.model, "whisper-1");
+        assert_eq!(provider.endpoint, "http://localhost:8000/v1/audio/transcriptions");
+        assert!(provider.api_key.is_none());
+    }
+
+    #[test]
+    fn test_full_endpoint_url_accepted() {
+        let provider = OpenAiCompatibleSttProvider::new(
+            &config("http://127.0.0.1:8080/v1/audio/transcriptions"),
+            None,
+            None,
+        )
+        .unwrap();
+        assert_eq!(provider.endpoint, "http://127.0.0.1:8080/v1/audio/transcriptions");
+    }
+
+    #[test]
+    fn test_model_override_precedence() {
+        let mut cfg = config("http://localhost:8000/v1");
+        cfg.model = Some("Systran/faster-whisper-small".to_string());
+
+        let provider = OpenAiCompatibleSttProvider::new(&cfg, None, None).unwrap();
+        assert_eq!(provider.model, "Systran/faster-whisper-small");
+
+        let provider =
+            OpenAiCompatibleSttProvider::new(&cfg, Some("large-v3".to_string()), None).unwrap();
+        assert_eq!(provider.model, "large-v3");
+    }
+
+    #[test]
+    fn test_missing_base_url_rejected() {
+        let result = OpenAiCompatibleSttProvider::new(&config("  "), None, None);
+        assert!(matches!(result, Err(SttError::Config(_))));
+    }
+}
@@ -0,0 +1,269 @@
+//! Streaming transcription sessions.
+//!
+//! A streaming session is fed mono PCM16 chunks straight from the capture thread while the
+//! user is still speaking, and yields partial/final transcript events as they arrive.
+//!
+//! Providers with a native live API override `SttProvider::open_stream`. Every other provider
+//! is driven through a batch adapter that buffers the chunks and calls `transcribe` once the
+//! audio channel closes, so callers can treat all providers the same way.
+
+use super::{AudioEncoding, AudioFormat, SttError, SttProvider, Transcript};
+use hound::{WavSpec, WavWriter};
+use serde::Serialize;
+use std::future::Future;
+use std::io::Cursor;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+
+/// A chunk of mono 16-bit PCM samples, at the capture device's sample rate.
+pub type AudioChunk = Vec<i16>;
+
+/// Sending half of an audio chunk channel (held by the capture thread).
+pub type AudioChunkSender = mpsc::UnboundedSender<AudioChunk>;
+
+/// Receiving half of an audio chunk channel (held by the streaming session).
+pub type AudioChunkReceiver = mpsc::UnboundedReceiver<AudioChunk>;
+
+/// Create a new channel for pushing capture audio into a streaming session.
+///
+/// The channel is unbounded so the realtime audio callback never blocks; a session is
+/// only ever as long as a single dictation.
+pub fn audio_chunk_channel() -> (AudioChunkSender, AudioChunkReceiver) {
+    mpsc::unbounded_channel()
+}
+
+/// Convert f32 samples (-1.0..=1.0) to PCM16, matching the WAV encoder's conversion.
+pub fn f32_to_pcm16(samples: &[f32]) -> AudioChunk {
+    samples
+        .iter()
+        .map(|&s| (s.clamp(-1.0, 1.0) * i16::MAX as f32) as i16)
+        .collect()
+}
+
+/// Transcript events emitted by a streaming session.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TranscriptEvent {
+    /// Interim hypothesis for the segment currently being spoken. May still change.
+    Partial { text: String },
+    /// A finalized segment. Segments arrive in order and are never revised.
+    Final { text: String },
+}
+
+/// Handle to a running streaming transcription session.
+///
+/// Dropping the handle cancels the session.
+pub struct SttStream {
+    provider: &'static str,
+    events: Option<mpsc::UnboundedReceiver<TranscriptEvent>>,
+    task: Option<tauri::async_runtime::JoinHandle<Result<Transcript, SttError>>>,
+    cancel_token: CancellationToken,
+}
+
+impl SttStream {
+    /// Spawn a session driver.
+    ///
+    /// The driver owns its audio receiver, publishes events through the given sender, and
+    /// returns the full transcript once the audio channel closes. It should return
+    /// `SttError::Cancelled` promptly when the token is cancelled.
+    pub fn spawn<F, Fut>(provider: &'static str, driver: F) -> Self
+    where
+        F: FnOnce(mpsc::UnboundedSender<TranscriptEvent>, CancellationToken) -> Fut,
+        Fut: Future<Output = Result<Transcript, SttError>> + Send + 'static,
+    {
+        let (events_tx, events_rx) = mpsc::unbounded_channel();
+        let cancel_token = CancellationToken::new();
+        let task = tauri::async_runtime::spawn(driver(events_tx, cancel_token.clone()));
+
+        Self {
+            provider,
+            events: Some(events_rx),
+            task: Some(task),
+            cancel_token,
+        }
+    }
+
+    /// Name of the provider driving this session.
+    pub fn provider_name(&self) -> &'static str {
+        self.provider
+    }
+
+    /// Take the transcript event receiver (for UI forwarding). Can only be taken once.
+    pub fn take_events(&mut self) -> Option<mpsc::UnboundedReceiver<TranscriptEvent>> {
+        self.events.take()
+    }
+
+    /// Wait for the session to produce its full transcript.
+    ///
+    /// The audio sender must be dropped (i.e. capture stopped) before this can complete.
+    pub async fn finish(mut self) -> Result<Transcript, SttError> {
+        let Some(task) = self.task.take() else {
+            return Err(SttError::Config(
+                "Streaming session already finished".to_string(),
+            ));
+        };
+
+        match task.await {
+            Ok(result) => result,
+            Err(e) => Err(SttError::Api(format!("Streaming session task failed: {}", e).into())),
+        }
+    }
+}
+
+impl Drop for SttStream {
+    fn drop(&mut self) {
+        // Harmless once the driver has returned; stops it early otherwise.
+        self.cancel_token.cancel();
+    }
+}
+
+/// Start a streaming session for any provider.
+///
+/// Uses the provider's native live API when it has one, otherwise falls back to buffering
+/// the audio and running a single batch `transcribe` call at the end.
+pub fn start_streaming_session(
+    provider: Arc<dyn SttProvider>,
+    format: AudioFormat,
+    audio_rx: AudioChunkReceiver,
+) -> Result<SttStream, SttError> {
+    if provider.supports_streaming() {
+        return provider.open_stream(&format, audio_rx);
+    }
+
+    Ok(start_batch_adapter(provider, format, audio_rx))
+}
+
+/// Drive a batch-only provider as a streaming session.
+fn start_batch_adapter(
+    provider: Arc<dyn SttProvider>,
+    format: AudioFormat,
+    mut audio_rx: AudioChunkReceiver,
+) -> SttStream {
+    SttStream::spawn(provider.name(), move |events_tx, cancel_token| async move {
+        let mut samples: Vec<i16> = Vec::new();
+        loop {
+            tokio::select! {
+                biased;
+
+                _ = cancel_token.cancelled() => return Err(SttError::Cancelled),
+
+                chunk = audio_rx.recv() => match chunk {
+                    Some(chunk) => samples.extend_from_slice(&chunk),
+                    None => break,
+                },
+            }
+        }
+
+        let wav = encode_pcm16_wav(&samples, format.sample_rate)?;
+        let wav_format = AudioFormat {
+            sample_rate: format.sample_rate,
+            channels: 1,
+            encoding: AudioEncoding::Wav,
+        };
+
+        let transcript = tokio::select! {
+            biased;
+
+            _ = cancel_token.cancelled() => return Err(SttError::Cancelled),
+
+            result = provider.transcribe_detailed(&wav, &wav_format) => result?,
+        };
+
+        let _ = events_tx.send(TranscriptEvent::Final {
+            text: transcript.text.clone(),
+        });
+        Ok(transcript)
+    })
+}
+
+/// Encode mono PCM16 samples as a WAV file.
+fn encode_pcm16_wav(samples: &[i16], sample_rate: u32) -> Result<Vec<u8>, SttError> {
+    let spec = WavSpec {
+        channels: 1,
+        sample_rate,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+
+    let mut cursor = Cursor::new(Vec::new());
+    {
+        let mut writer =
+            WavWriter::new(&mut cursor, spec).map_err(|e| SttError::Audio(e.to_string()))?;
+        for &sample in samples {
+            writer
+                .write_sample(sample)
+                .map_err(|e| SttError::Audio(e.to_string()))?;
+        }
+        writer
+            .finalize()
+            .map_err(|e| SttError::Audio(e.to_string()))?;
+    }
+
+    Ok(cursor.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+
+    struct LengthProvider;
+
+    #[async_trait]
+    impl SttProvider for LengthProvider {
+        async fn transcribe(&self, audio: &[u8], format: &AudioFormat) -> Result<String, SttError> {
+            assert_eq!(format.channels, 1);
+            Ok(format!("{} bytes", audio.len()))
+        }
+
+        fn name(&self) -> &'static str {
+            "length"
+        }
+    }
+
+    #[test]
+    fn test_f32_to_pcm16_clamps() {
+        assert_eq!(f32_to_pcm16(&[0.0, 1.0, -1.0, 2.0]), vec![0, i16::MAX, -i16::MAX, i16::MAX]);
+    }
+
+    #[test]
+    fn test_transcript_event_serialization() {
+        let json = serde_json::to_value(TranscriptEvent::Partial {
+            text: "hel".to_string(),
+        })
+        .unwrap();
+        assert_eq!(json["kind"], "partial");
+        assert_eq!(json["text"], "hel");
+    }
+
+    #[tokio::test]
+    async fn test_batch_adapter_transcribes_buffered_audio() {
+        let (audio_tx, audio_rx) = audio_chunk_channel();
+        let mut stream = start_streaming_session(
+            Arc::new(LengthProvider),
+            AudioFormat {
+                sample_rate: 16000,
+                channels: 1,
+                encoding: AudioEncoding::Pcm16,
+            },
+            audio_rx,
+        )
+        .unwrap();
+        let mut events = stream.take_events().unwrap();
+
+        audio_tx.send(vec![0; 100]).unwrap();
+        audio_tx.send(vec![0; 60]).unwrap();
+        drop(audio_tx);
+
+        // 44-byte WAV header + 160 samples * 2 bytes
+        let transcript = stream.finish().await.unwrap();
+        assert_eq!(transcript.text, "364 bytes");
+        assert_eq!(
+            events.recv().await,
+            Some(TranscriptEvent::Final {
+                text: "364 bytes".to_string()
+            })
+        );
+    }
+}
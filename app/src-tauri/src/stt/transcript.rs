@@ -0,0 +1,248 @@
+//! Detailed transcription results.
+//!
+//! Providers that return more than plain text (segments, word timings, confidences,
+//! detected language) expose it through `SttProvider::transcribe_detailed`.
+
+use serde::{Deserialize, Serialize};
+
+/// Words below this confidence are flagged as low-confidence in history.
+pub const LOW_CONFIDENCE_THRESHOLD: f32 = 0.6;
+
+/// A transcription result with optional timing/confidence detail.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Transcript {
+    /// Full transcript text.
+    pub text: String,
+    /// Segments (phrases/sentences) with timings, when the provider returns them.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub segments: Vec<TranscriptSegment>,
+    /// Individual words with timings, when the provider returns them.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub words: Vec<TranscriptWord>,
+    /// Detected (or requested) language code, e.g. "en".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    /// Overall confidence (0.0..=1.0), when the provider reports one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<f32>,
+}
+
+/// A timed segment of the transcript. Times are in seconds from the start of the audio.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TranscriptSegment {
+    pub text: String,
+    pub start: f32,
+    pub end: f32,
+    /// Whisper average token log-probability for the segment.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub avg_logprob: Option<f32>,
+    /// Whisper probability that the segment contains no speech.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub no_speech_prob: Option<f32>,
+}
+
+/// A timed word. Times are in seconds from the start of the audio.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TranscriptWord {
+    pub word: String,
+    pub start: f32,
+    pub end: f32,
+    /// Per-word confidence (0.0..=1.0), when the provider reports one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<f32>,
+}
+
+impl Transcript {
+    /// A transcript with text only (providers without detail).
+    pub fn from_text(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Whether anything beyond the plain text is present.
+    pub fn has_details(&self) -> bool {
+        !self.segments.is_empty()
+            || !self.words.is_empty()
+            || self.language.is_some()
+            || self.confidence.is_some()
+    }
+
+    /// Words whose confidence is below `LOW_CONFIDENCE_THRESHOLD`.
+    pub fn low_confidence_words(&self) -> Vec<&TranscriptWord> {
+        self.words
+            .iter()
+            .filter(|w| w.confidence.is_some_and(|c| c < LOW_CONFIDENCE_THRESHOLD))
+            .collect()
+    }
+
+    /// Join the transcripts of consecutive audio chunks, in order.
+    ///
+    /// Each part is `(start_secs, transcript)`; segment/word times are shifted by the chunk's
+    /// start so they stay relative to the whole recording.
+    pub fn stitch(parts: Vec<(f32, Transcript)>) -> Self {
+        let confidences: Vec<f32> = parts.iter().filter_map(|(_, t)| t.confidence).collect();
+        let mut stitched = Self {
+            confidence: (!confidences.is_empty())
+                .then(|| confidences.iter().sum::<f32>() / confidences.len() as f32),
+            ..Default::default()
+        };
+
+        let mut texts: Vec<String> = Vec::new();
+        for (offset, part) in parts {
+            let text = part.text.trim();
+            if !text.is_empty() {
+                texts.push(text.to_string());
+            }
+            stitched.segments.extend(part.segments.into_iter().map(|mut s| {
+                s.start += offset;
+                s.end += offset;
+                s
+            }));
+            stitched.words.extend(part.words.into_iter().map(|mut w| {
+                w.start += offset;
+                w.end += offset;
+                w
+            }));
+            if stitched.language.is_none() {
+                stitched.language = part.language;
+            }
+        }
+
+        stitched.text = texts.join(" ");
+        stitched
+    }
+
+    /// Parse an OpenAI-style `verbose_json` response (OpenAI, Groq, whisper servers).
+    ///
+    /// Plain `json` responses (only `text`) parse to a text-only transcript.
+    pub fn from_verbose_json(value: &serde_json::Value) -> Self {
+        let segments = value["segments"]
+            .as_array()
+            .map(|segments| {
+                segments
+                    .iter()
+                    .map(|s| TranscriptSegment {
+                        text: s["text"].as_str().unwrap_or("").trim().to_string(),
+                        start: json_f32(&s["start"]).unwrap_or(0.0),
+                        end: json_f32(&s["end"]).unwrap_or(0.0),
+                        avg_logprob: json_f32(&s["avg_logprob"]),
+                        no_speech_prob: json_f32(&s["no_speech_prob"]),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let words = value["words"]
+            .as_array()
+            .map(|words| {
+                words
+                    .iter()
+                    .map(|w| TranscriptWord {
+                        word: w["word"].as_str().unwrap_or("").trim().to_string(),
+                        start: json_f32(&w["start"]).unwrap_or(0.0),
+                        end: json_f32(&w["end"]).unwrap_or(0.0),
+                        confidence: json_f32(&w["probability"]),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Self {
+            text: value["text"].as_str().unwrap_or("").to_string(),
+            segments,
+            words,
+            language: value["language"]
+                .as_str()
+                .filter(|s| !s.is_empty())
+                .map(|s| s.to_string()),
+            confidence: None,
+        }
+    }
+}
+
+/// Read a JSON number as f32.
+pub(crate) fn json_f32(value: &serde_json::Value) -> Option<f32> {
+    value.as_f64().map(|v| v as f32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_from_verbose_json() {
+        let value = json!({
+            "text": " Hello world.",
+            "language": "english",
+            "segments": [
+                { "id": 0, "start": 0.0, "end": 1.5, "text": " Hello world.", "avg_logprob": -0.2, "no_speech_prob": 0.01 }
+            ],
+            "words": [
+                { "word": "Hello", "start": 0.0, "end": 0.6 },
+                { "word": "world.", "start": 0.7, "end": 1.4 }
+            ]
+        });
+
+        let transcript = Transcript::from_verbose_json(&value);
+        assert_eq!(transcript.text, " Hello world.");
+        assert_eq!(transcript.language.as_deref(), Some("english"));
+        assert_eq!(transcript.segments.len(), 1);
+        assert_eq!(transcript.segments[0].text, "Hello world.");
+        assert_eq!(transcript.segments[0].no_speech_prob, Some(0.01));
+        assert_eq!(transcript.words.len(), 2);
+        assert!(transcript.has_details());
+    }
+
+    #[test]
+    fn test_plain_json_has_no_details() {
+        let transcript = Transcript::from_verbose_json(&json!({ "text": "hi" }));
+        assert_eq!(transcript, Transcript::from_text("hi"));
+        assert!(!transcript.has_details());
+    }
+
+    #[test]
+    fn test_stitch_offsets_times() {
+        let first = Transcript {
+            text: " Hello.".to_string(),
+            words: vec![TranscriptWord { word: "Hello.".to_string(), start: 0.2, end: 0.6, confidence: None }],
+            language: Some("en".to_string()),
+            confidence: Some(0.8),
+            ..Default::default()
+        };
+        let second = Transcript {
+            text: "World.".to_string(),
+            words: vec![TranscriptWord { word: "World.".to_string(), start: 0.1, end: 0.5, confidence: None }],
+            confidence: Some(0.6),
+            ..Default::default()
+        };
+
+        let stitched = Transcript::stitch(vec![(0.0, first), (60.0, second)]);
+        assert_eq!(stitched.text, "Hello. World.");
+        assert_eq!(stitched.words[1].start, 60.1);
+        assert_eq!(stitched.language.as_deref(), Some("en"));
+        assert!((stitched.confidence.unwrap() - 0.7).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_low_confidence_words() {
+        let transcript = Transcript {
+            text: "ant row pick".to_string(),
+            words: vec![
+                TranscriptWord { word: "ant".to_string(), confidence: Some(0.4), ..Default::default() },
+                TranscriptWord { word: "row".to_string(), confidence: Some(0.9), ..Default::default() },
+                TranscriptWord { word: "pick".to_string(), confidence: None, ..Default::default() },
+            ],
+            ..Default::default()
+        };
+
+        let low: Vec<&str> = transcript
+            .low_confidence_words()
+            .iter()
+            .map(|w| w.word.as_str())
+            .collect();
+        assert_eq!(low, vec!["ant"]);
+    }
+}
@@ -0,0 +1,211 @@
+//! Vocabulary biasing: personal dictionary terms passed to the STT provider.
+//!
+//! The dictionary is free-form text written for the LLM. It is parsed into terms here so the
+//! STT layer can spell names correctly before the LLM ever sees them. Each provider has its
+//! own mechanism (Deepgram `keyterm`/`keywords`, Whisper `prompt`, AssemblyAI `word_boost`,
+//! whisper.cpp initial prompt) and its own size limit, so terms carry a priority.
+
+/// A term the speaker uses, with the misrecognitions it should replace.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct VocabularyTerm {
+    /// Correct spelling, e.g. "Anthropic"
+    pub term: String,
+    /// Spoken/misheard forms from "spoken = Written" mappings, e.g. "ant row pick"
+    pub sounds_like: Vec<String>,
+}
+
+impl VocabularyTerm {
+    /// Higher is more important when a provider can't take every term.
+    ///
+    /// Explicit mappings document a known misrecognition; names and acronyms (uppercase or
+    /// digits) are next, as generic lowercase words are usually transcribed correctly anyway.
+    fn priority(&self) -> u8 {
+        if !self.sounds_like.is_empty() {
+            2
+        } else if self
+            .term
+            .chars()
+            .any(|c| c.is_uppercase() || c.is_ascii_digit())
+        {
+            1
+        } else {
+            0
+        }
+    }
+
+    pub fn word_count(&self) -> usize {
+        self.term.split_whitespace().count()
+    }
+}
+
+/// Dictionary terms in the order they were written.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Vocabulary {
+    terms: Vec<VocabularyTerm>,
+}
+
+impl Vocabulary {
+    /// Longer lines are descriptions for the LLM, not terms.
+    const MAX_TERM_WORDS: usize = 4;
+
+    /// Parse the personal dictionary prompt section.
+    ///
+    /// Only lines under an `### Entries` heading are considered when one exists. Mappings
+    /// ("spoken = Written", "spoken -> Written") contribute their right-hand side; long
+    /// free-form descriptions are skipped and duplicates (ignoring case) are merged.
+    pub fn parse_dictionary(text: &str) -> Self {
+        let entries = match text.find("### Entries") {
+            Some(idx) => text[idx..].split_once('\n').map(|(_, rest)| rest).unwrap_or(""),
+            None => text,
+        };
+
+        let mut terms: Vec<VocabularyTerm> = Vec::new();
+        for parsed in entries.lines().filter_map(Self::parse_dictionary_line) {
+            match terms.iter_mut().find(|t| t.term.eq_ignore_ascii_case(&parsed.term)) {
+                Some(existing) => existing.sounds_like.extend(parsed.sounds_like),
+                None => terms.push(parsed),
+            }
+        }
+
+        Self { terms }
+    }
+
+    /// Parse one dictionary entry line ("spoken = Written", "spoken -> Written" or a term).
+    ///
+    /// `None` for blank lines, headings and long free-form descriptions.
+    pub fn parse_dictionary_line(line: &str) -> Option<VocabularyTerm> {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+
+        let line = line.trim_start_matches(['-', '*']).trim();
+        let (spoken, written) = match line
+            .rsplit_once("->")
+            .or_else(|| line.rsplit_once('='))
+        {
+            Some((spoken, written)) => (Some(spoken), written),
+            None => (None, line),
+        };
+        let unquote = |s: &str| s.trim().trim_matches(['"', '\'']).trim().to_string();
+        let term = unquote(written);
+        let spoken = spoken.map(unquote).filter(|s| !s.is_empty());
+
+        if term.is_empty() || term.split_whitespace().count() > Self::MAX_TERM_WORDS {
+            return None;
+        }
+        Some(VocabularyTerm {
+            term,
+            sounds_like: spoken.into_iter().collect(),
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    /// Fingerprint of the terms, to cache providers per vocabulary.
+    pub fn cache_key(&self) -> String {
+        use std::hash::{DefaultHasher, Hash, Hasher};
+
+        if self.is_empty() {
+            return "none".to_string();
+        }
+        let mut hasher = DefaultHasher::new();
+        self.hash(&mut hasher);
+        format!("{:016x}", hasher.finish())
+    }
+
+    /// Terms in dictionary order.
+    pub fn terms(&self) -> Vec<String> {
+        self.terms.iter().map(|t| t.term.clone()).collect()
+    }
+
+    /// Terms, most important first (dictionary order within the same priority).
+    pub fn prioritized(&self) -> Vec<&VocabularyTerm> {
+        let mut terms: Vec<&VocabularyTerm> = self.terms.iter().collect();
+        terms.sort_by_key(|t| std::cmp::Reverse(t.priority()));
+        terms
+    }
+
+    /// Spellings, most important first.
+    pub fn prioritized_terms(&self) -> Vec<String> {
+        self.prioritized().into_iter().map(|t| t.term.clone()).collect()
+    }
+
+    /// Build a Whisper-style prompt: `base` followed by as many terms as fit in `max_chars`.
+    ///
+    /// Whisper has no dedicated vocabulary field; listing the spellings in the prompt makes
+    /// the decoder prefer them. The base prompt is kept intact and lower-priority terms are
+    /// dropped first. Returns `None` when there is neither a base prompt nor any term.
+    pub fn whisper_prompt(&self, base: Option<&str>, max_chars: usize) -> Option<String> {
+        let base = base.map(str::trim).filter(|s| !s.is_empty());
+        let mut prompt = base.unwrap_or_default().to_string();
+        let mut listed = 0usize;
+
+        for term in self.prioritized() {
+            let separator = match (listed, prompt.is_empty()) {
+                (0, true) => "",
+                (0, false) => " ",
+                _ => ", ",
+            };
+            // Room for the closing period.
+            let added = separator.chars().count() + term.term.chars().count() + 1;
+            if prompt.chars().count() + added > max_chars {
+                continue;
+            }
+            prompt.push_str(separator);
+            prompt.push_str(&term.term);
+            listed += 1;
+        }
+
+        if listed > 0 {
+            prompt.push('.');
+        }
+        (!prompt.is_empty()).then_some(prompt)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DICTIONARY: &str = "## Personal Dictionary\nIntro text\n\n### Entries:\n\
+        tangerine\n- ant row pick = Anthropic\nLLM\n\"kube cuddle\" -> kubectl\n\
+        The name 'Claude' should always be capitalized\nAnthropic";
+
+    #[test]
+    fn test_parse_dictionary() {
+        let vocabulary = Vocabulary::parse_dictionary(DICTIONARY);
+        assert_eq!(vocabulary.terms(), vec!["tangerine", "Anthropic", "LLM", "kubectl"]);
+        assert_eq!(vocabulary.terms[1].sounds_like, vec!["ant row pick"]);
+        assert_eq!(vocabulary.terms[3].sounds_like, vec!["kube cuddle"]);
+    }
+
+    #[test]
+    fn test_prioritized_terms() {
+        let vocabulary = Vocabulary::parse_dictionary(DICTIONARY);
+        assert_eq!(
+            vocabulary.prioritized_terms(),
+            vec!["Anthropic", "kubectl", "LLM", "tangerine"]
+        );
+    }
+
+    #[test]
+    fn test_whisper_prompt_fits_budget() {
+        let vocabulary = Vocabulary::parse_dictionary(DICTIONARY);
+        assert_eq!(
+            vocabulary.whisper_prompt(None, 224).as_deref(),
+            Some("Anthropic, kubectl, LLM, tangerine.")
+        );
+        assert_eq!(
+            vocabulary.whisper_prompt(Some("Meeting notes."), 34).as_deref(),
+            Some("Meeting notes. Anthropic, kubectl.")
+        );
+        assert_eq!(
+            Vocabulary::default().whisper_prompt(Some(" Hi "), 224).as_deref(),
+            Some("Hi")
+        );
+        assert!(Vocabulary::default().whisper_prompt(None, 224).is_none());
+    }
+}
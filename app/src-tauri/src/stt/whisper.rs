@@ -15,11 +15,17 @@
 //! - medium: ~1.5GB, high accuracy
 //! - large: ~2.9GB, highest accuracy
 
-use super::{AudioFormat, SttError, SttProvider};
+use super::{
+    AudioFormat, SttError, SttLanguage, SttProvider, Transcript, TranscriptSegment, TranscriptWord,
+    Vocabulary,
+};
 use async_trait::async_trait;
-use std::path::PathBuf;
-use std::sync::Arc;
-use whisper_rs::{FullParams, SamplingStrategy, WhisperContext, WhisperContextParameters};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock, Weak};
+use whisper_rs::{
+    FullParams, SamplingStrategy, WhisperContext, WhisperContextParameters, WhisperState,
+};
 
 /// Available Whisper model sizes
 #[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
@@ -152,13 +158,51 @@ impl Default for LocalWhisperConfig {
     }
 }
 
+/// Loaded models by file path, shared by every provider using the same model.
+///
+/// Providers are cached per language and vocabulary (each profile can get its own), while the
+/// language and initial prompt only matter per request. Sharing the context keeps one copy
+/// of the model in memory; it is freed once the last provider using it is dropped.
+fn shared_context(model_path: &Path) -> Result<Arc<WhisperContext>, SttError> {
+    static CONTEXTS: OnceLock<Mutex<HashMap<PathBuf, Weak<WhisperContext>>>> = OnceLock::new();
+
+    let mut contexts = CONTEXTS
+        .get_or_init(Default::default)
+        .lock()
+        .map_err(|e| SttError::Audio(format!("Whisper model cache lock poisoned: {}", e)))?;
+    contexts.retain(|_, ctx| ctx.strong_count() > 0);
+    if let Some(ctx) = contexts.get(model_path).and_then(Weak::upgrade) {
+        return Ok(ctx);
+    }
+
+    // Loading under the lock keeps two providers from reading the same model at once.
+    let ctx = WhisperContext::new_with_params(
+        model_path
+            .to_str()
+            .ok_or_else(|| SttError::Audio("Invalid model path encoding".to_string()))?,
+        WhisperContextParameters::default(),
+    )
+    .map_err(|e| SttError::Audio(format!("Failed to load Whisper model: {}", e)))?;
+    let ctx = Arc::new(ctx);
+    contexts.insert(model_path.to_path_buf(), Arc::downgrade(&ctx));
+    Ok(ctx)
+}
+
 /// Local Whisper STT provider
 pub struct LocalWhisperProvider {
     ctx: Arc<WhisperContext>,
     config: LocalWhisperConfig,
+    /// Languages to choose from when `config.language` is unset (empty = any language).
+    candidates: Vec<String>,
+    /// Dictionary terms passed as the decoder's initial prompt.
+    initial_prompt: Option<String>,
 }
 
 impl LocalWhisperProvider {
+    /// whisper.cpp keeps at most 224 prompt tokens; stay well within that, like the
+    /// hosted Whisper APIs.
+    const INITIAL_PROMPT_MAX_CHARS: usize = 224;
+
     /// Create a new provider with the given model path
     pub fn new(model_path: PathBuf) -> Result<Self, SttError> {
         Self::with_config(LocalWhisperConfig {
@@ -176,22 +220,31 @@ impl LocalWhisperProvider {
             )));
         }
 
-        let ctx_params = WhisperContextParameters::default();
-
-        let ctx = WhisperContext::new_with_params(
-            config.model_path.to_str().ok_or_else(|| {
-                SttError::Audio("Invalid model path encoding".to_string())
-            })?,
-            ctx_params,
-        )
-        .map_err(|e| SttError::Audio(format!("Failed to load Whisper model: {}", e)))?;
+        let ctx = shared_context(&config.model_path)?;
 
         Ok(Self {
-            ctx: Arc::new(ctx),
+            ctx,
             config,
+            candidates: Vec::new(),
+            initial_prompt: None,
         })
     }
 
+    /// Dictionary terms to bias recognition towards (initial prompt)
+    pub fn with_vocabulary(mut self, vocabulary: Vocabulary) -> Self {
+        self.initial_prompt = vocabulary.whisper_prompt(None, Self::INITIAL_PROMPT_MAX_CHARS);
+        self
+    }
+
+    /// Spoken language (`set_language`); candidates are chosen with whisper.cpp's language
+    /// detection before decoding.
+    pub fn with_language(mut self, language: SttLanguage) -> Self {
+        let language = language.normalized();
+        self.config.language = language.fixed().map(str::to_string);
+        self.candidates = language.candidates().to_vec();
+        self
+    }
+
     /// Check if a model file exists at the given path
     pub fn model_exists(model_path: &PathBuf) -> bool {
         model_path.exists() && model_path.is_file()
@@ -203,22 +256,36 @@ impl LocalWhisperProvider {
             d.join("tangerine-voice").join("models")
         })
     }
-}
 
-#[async_trait]
-impl SttProvider for LocalWhisperProvider {
-    async fn transcribe(&self, audio: &[u8], _format: &AudioFormat) -> Result<String, SttError> {
+    /// English-only (`*.en`) models cannot run the translate task.
+    fn is_english_only_model(&self) -> bool {
+        self.config
+            .model_path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .is_some_and(|n| n.contains(".en."))
+    }
+
+    /// Run whisper.cpp on the recording, optionally with the translate (to English) task.
+    async fn run_inference(&self, audio: &[u8], translate: bool) -> Result<Transcript, SttError> {
         // Decode WAV to f32 samples
         let samples = decode_wav_to_f32_mono_16khz(audio)?;
 
         if samples.is_empty() {
-            return Ok(String::new());
+            return Ok(Transcript::default());
         }
 
         // Clone what we need for the blocking task
         let ctx = self.ctx.clone();
-        let language = self.config.language.clone();
-        let translate = self.config.translate;
+        // The configured language describes plain transcription (default "en"); a translation
+        // requested per dictation has to auto-detect the spoken language instead.
+        let language = if translate && !self.config.translate {
+            None
+        } else {
+            self.config.language.clone()
+        };
+        let candidates = self.candidates.clone();
+        let initial_prompt = self.initial_prompt.clone();
         let n_threads = self.config.n_threads;
 
         // whisper-rs is synchronous, so we use spawn_blocking
@@ -227,16 +294,27 @@ impl SttProvider for LocalWhisperProvider {
                 .create_state()
                 .map_err(|e| SttError::Audio(format!("Failed to create Whisper state: {}", e)))?;
 
+            // whisper.cpp defaults to English, so auto-detection must be requested explicitly.
+            let language = match language {
+                Some(lang) => lang,
+                None if !candidates.is_empty() => {
+                    detect_among_candidates(&mut state, &samples, &candidates, n_threads)
+                }
+                None => "auto".to_string(),
+            };
+
             let mut params = FullParams::new(SamplingStrategy::Greedy { best_of: 1 });
 
             // Set language
-            if let Some(lang) = &language {
-                params.set_language(Some(lang));
-            }
+            params.set_language(Some(&language));
 
             // Set translate mode
             params.set_translate(translate);
 
+            if let Some(prompt) = &initial_prompt {
+                params.set_initial_prompt(prompt);
+            }
+
             // Set thread count
             if n_threads > 0 {
                 params.set_n_threads(n_threads as i32);
@@ -248,36 +326,192 @@ impl SttProvider for LocalWhisperProvider {
             params.set_print_realtime(false);
             params.set_print_timestamps(false);
 
+            // Per-token timestamps (used to build word timings)
+            params.set_token_timestamps(true);
+
             // Run inference
             state
                 .full(params, &samples)
                 .map_err(|e| SttError::Audio(format!("Whisper inference failed: {}", e)))?;
 
             // Collect results
-            let num_segments = state.full_n_segments().map_err(|e| {
-                SttError::Audio(format!("Failed to get segment count: {}", e))
-            })?;
-
             let mut text = String::new();
-            for i in 0..num_segments {
-                if let Ok(segment_text) = state.full_get_segment_text(i) {
-                    text.push_str(&segment_text);
+            let mut segments = Vec::new();
+            let mut tokens: Vec<WhisperTokenTiming> = Vec::new();
+            for segment in state.as_iter() {
+                let Ok(segment_text) = segment.to_str_lossy() else {
+                    continue;
+                };
+                text.push_str(&segment_text);
+
+                // Timestamps are reported in centiseconds.
+                segments.push(TranscriptSegment {
+                    text: segment_text.trim().to_string(),
+                    start: segment.start_timestamp() as f32 / 100.0,
+                    end: segment.end_timestamp() as f32 / 100.0,
+                    avg_logprob: None,
+                    no_speech_prob: Some(segment.no_speech_probability()),
+                });
+
+                // Mean text-token log-probability, as Whisper reports `avg_logprob`.
+                let mut logprob_sum = 0.0f32;
+                let mut logprob_count = 0usize;
+
+                for j in 0..segment.n_tokens() {
+                    let Some(token) = segment.get_token(j) else {
+                        continue;
+                    };
+                    let Ok(token_text) = token.to_str_lossy() else {
+                        continue;
+                    };
+                    let data = token.token_data();
+                    if !is_special_token(&token_text) {
+                        logprob_sum += data.plog;
+                        logprob_count += 1;
+                    }
+                    tokens.push(WhisperTokenTiming {
+                        text: token_text.into_owned(),
+                        start: data.t0 as f32 / 100.0,
+                        end: data.t1 as f32 / 100.0,
+                        probability: data.p,
+                    });
+                }
+
+                if let Some(segment) = segments.last_mut().filter(|_| logprob_count > 0) {
+                    segment.avg_logprob = Some(logprob_sum / logprob_count as f32);
                 }
             }
 
-            Ok::<String, SttError>(text.trim().to_string())
+            let language =
+                whisper_rs::get_lang_str(state.full_lang_id_from_state()).map(|l| l.to_string());
+
+            Ok::<Transcript, SttError>(Transcript {
+                text: text.trim().to_string(),
+                segments,
+                words: merge_tokens_into_words(&tokens),
+                language,
+                confidence: None,
+            })
         })
         .await
         .map_err(|e| SttError::Audio(format!("Task join error: {}", e)))??;
 
         Ok(result)
     }
+}
+
+#[async_trait]
+impl SttProvider for LocalWhisperProvider {
+    async fn transcribe(&self, audio: &[u8], format: &AudioFormat) -> Result<String, SttError> {
+        self.transcribe_detailed(audio, format).await.map(|t| t.text)
+    }
+
+    async fn transcribe_detailed(
+        &self,
+        audio: &[u8],
+        _format: &AudioFormat,
+    ) -> Result<Transcript, SttError> {
+        self.run_inference(audio, self.config.translate).await
+    }
+
+    fn supports_translation(&self) -> bool {
+        !self.is_english_only_model()
+    }
+
+    async fn translate_detailed(
+        &self,
+        audio: &[u8],
+        _format: &AudioFormat,
+    ) -> Result<Transcript, SttError> {
+        self.run_inference(audio, true).await
+    }
 
     fn name(&self) -> &'static str {
         "local-whisper"
     }
 }
 
+/// Pick the most probable of `candidates` with whisper.cpp's language detection.
+///
+/// Falls back to the first (preferred) candidate if detection fails.
+fn detect_among_candidates(
+    state: &mut WhisperState,
+    samples: &[f32],
+    candidates: &[String],
+    n_threads: u32,
+) -> String {
+    let threads = if n_threads > 0 {
+        n_threads as usize
+    } else {
+        std::thread::available_parallelism().map_or(1, |n| n.get())
+    };
+
+    let probabilities = state
+        .pcm_to_mel(samples, threads)
+        .and_then(|_| state.lang_detect(0, threads))
+        .map(|(_, probabilities)| probabilities)
+        .unwrap_or_default();
+
+    candidates
+        .iter()
+        .filter_map(|candidate| {
+            let id = whisper_rs::get_lang_id(candidate)?;
+            Some((candidate, *probabilities.get(usize::try_from(id).ok()?)?))
+        })
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(candidate, _)| candidate.clone())
+        .unwrap_or_else(|| candidates[0].clone())
+}
+
+/// Timing/probability for one whisper.cpp token (times in seconds).
+struct WhisperTokenTiming {
+    text: String,
+    start: f32,
+    end: f32,
+    probability: f32,
+}
+
+/// Special tokens (`[_BEG_]`, `<|endoftext|>`, ...) carry no text.
+fn is_special_token(text: &str) -> bool {
+    text.starts_with("[_") || text.starts_with("<|")
+}
+
+/// Merge whisper.cpp sub-word tokens into words.
+///
+/// A token starting with whitespace begins a new word; special tokens (`[_BEG_]`,
+/// `<|endoftext|>`, ...) are skipped. Word confidence is the lowest token probability.
+fn merge_tokens_into_words(tokens: &[WhisperTokenTiming]) -> Vec<TranscriptWord> {
+    let mut words: Vec<TranscriptWord> = Vec::new();
+
+    for token in tokens {
+        if is_special_token(&token.text) {
+            continue;
+        }
+
+        let starts_word = token.text.starts_with(char::is_whitespace) || words.is_empty();
+        let piece = token.text.trim();
+        if piece.is_empty() {
+            continue;
+        }
+
+        match words.last_mut() {
+            Some(word) if !starts_word => {
+                word.word.push_str(piece);
+                word.end = token.end;
+                word.confidence = word.confidence.map(|c| c.min(token.probability));
+            }
+            _ => words.push(TranscriptWord {
+                word: piece.to_string(),
+                start: token.start,
+                end: token.end,
+                confidence: Some(token.probability),
+            }),
+        }
+    }
+
+    words
+}
+
 /// Decode WAV audio to f32 samples, converting to mono 16kHz if needed
 fn decode_wav_to_f32_mono_16khz(wav_bytes: &[u8]) -> Result<Vec<f32>, SttError> {
     use std::io::Cursor;
@@ -350,6 +584,29 @@ mod tests {
         assert!(!WhisperModel::LargeV3.is_english_only());
     }
 
+    #[test]
+    fn test_merge_tokens_into_words() {
+        let token = |text: &str, start: f32, end: f32, probability: f32| WhisperTokenTiming {
+            text: text.to_string(),
+            start,
+            end,
+            probability,
+        };
+        let tokens = vec![
+            token("[_BEG_]", 0.0, 0.0, 1.0),
+            token(" Hel", 0.0, 0.2, 0.9),
+            token("lo", 0.2, 0.4, 0.7),
+            token(" world", 0.5, 0.9, 0.95),
+        ];
+
+        let words = merge_tokens_into_words(&tokens);
+        assert_eq!(words.len(), 2);
+        assert_eq!(words[0].word, "Hello");
+        assert_eq!(words[0].end, 0.4);
+        assert_eq!(words[0].confidence, Some(0.7));
+        assert_eq!(words[1].word, "world");
+    }
+
     #[test]
     fn test_all_models() {
         let models = WhisperModel::all();
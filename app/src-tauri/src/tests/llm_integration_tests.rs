@@ -6,7 +6,7 @@
 
 use crate::llm::{
     format_text, AnthropicLlmProvider, LlmProvider, OllamaLlmProvider, OpenAiLlmProvider,
-    PromptSections,
+    OutputGuardrail, PromptSections,
 };
 
 #[test]
@@ -156,7 +156,7 @@ async fn test_format_text_integration() {
     let provider = OpenAiLlmProvider::new(api_key);
     let prompts = PromptSections::default();
 
-    let result = format_text(&provider, "um hello there uh how are you", &prompts).await;
+    let result = format_text(&provider, "um hello there uh how are you", &prompts, &OutputGuardrail::default()).await;
 
     assert!(result.is_ok(), "format_text failed: {:?}", result);
     let formatted = result.unwrap();
@@ -170,7 +170,7 @@ async fn test_format_text_empty_input() {
     let provider = OpenAiLlmProvider::new("test_key".to_string());
     let prompts = PromptSections::default();
 
-    let result = format_text(&provider, "", &prompts).await;
+    let result = format_text(&provider, "", &prompts, &OutputGuardrail::default()).await;
     assert!(result.is_ok());
     assert_eq!(result.unwrap(), "");
 }
@@ -181,7 +181,7 @@ async fn test_format_text_whitespace_input() {
     let provider = OpenAiLlmProvider::new("test_key".to_string());
     let prompts = PromptSections::default();
 
-    let result = format_text(&provider, "   \n\t   ", &prompts).await;
+    let result = format_text(&provider, "   \n\t   ", &prompts, &OutputGuardrail::default()).await;
     assert!(result.is_ok());
     assert_eq!(result.unwrap(), "");
 }
@@ -66,6 +66,7 @@ fn test_pipeline_config_custom() {
             initial_delay: Duration::from_millis(500),
             max_delay: Duration::from_secs(30),
             retry_on_rate_limit: true,
+            max_retry_after: Duration::from_secs(60),
         },
         ..Default::default()
     };
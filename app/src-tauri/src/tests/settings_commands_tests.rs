@@ -25,6 +25,14 @@ fn test_default_paste_last_hotkey() {
     assert!(hotkey.modifiers.contains(&"alt".to_string()));
 }
 
+#[test]
+fn test_default_transform_hotkey() {
+    let hotkey = HotkeyConfig::default_transform();
+    assert_eq!(hotkey.key, "Slash");
+    assert!(hotkey.modifiers.contains(&"ctrl".to_string()));
+    assert!(hotkey.modifiers.contains(&"alt".to_string()));
+}
+
 #[test]
 fn test_to_shortcut_string() {
     let hotkey = HotkeyConfig {
@@ -5,8 +5,8 @@
 //! when you have `GROQ_API_KEY`, `OPENAI_API_KEY`, or `DEEPGRAM_API_KEY` set.
 
 use crate::stt::{
-    AudioEncoding, AudioFormat, DeepgramSttProvider, GroqSttProvider, OpenAiSttProvider,
-    SttProvider,
+    AssemblyAiSttProvider, AudioEncoding, AudioFormat, DeepgramSttProvider, GroqSttProvider,
+    OpenAiSttProvider, SttProvider,
 };
 
 #[test]
@@ -27,6 +27,12 @@ fn test_deepgram_provider_implements_trait() {
     assert_eq!(provider.name(), "deepgram");
 }
 
+#[test]
+fn test_assemblyai_provider_implements_trait() {
+    let provider = AssemblyAiSttProvider::new("test_key".to_string(), None);
+    assert_eq!(provider.name(), "assemblyai");
+}
+
 #[test]
 fn test_groq_provider_with_custom_model() {
     let provider = GroqSttProvider::new(
@@ -0,0 +1,94 @@
+//! Word splitting and comparison shared by the personal dictionary, voice snippets, dictionary
+//! learning and the LLM output guardrail.
+//!
+//! Words are runs of letters, digits and apostrophes (straight or typographic), so "don't"
+//! stays one word and punctuation never sticks to one.
+
+use std::ops::Range;
+
+/// Whether `c` can be part of a word.
+pub fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '\'' || c == '\u{2019}'
+}
+
+/// Words of `text` with their byte ranges.
+pub fn word_ranges(text: &str) -> Vec<Range<usize>> {
+    let mut found = Vec::new();
+    let mut start: Option<usize> = None;
+    for (index, c) in text
+        .char_indices()
+        .chain(std::iter::once((text.len(), ' ')))
+    {
+        match (start, is_word_char(c)) {
+            (None, true) => start = Some(index),
+            (Some(begin), false) => {
+                found.push(begin..index);
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    found
+}
+
+/// A word as compared: lowercase, with straight apostrophes and none around it.
+///
+/// Empty for a word made of apostrophes only.
+pub fn normalize_word(word: &str) -> String {
+    word.to_lowercase()
+        .replace('\u{2019}', "'")
+        .trim_matches('\'')
+        .to_string()
+}
+
+/// The normalized words of `text` (see `normalize_word`).
+pub fn words(text: &str) -> Vec<String> {
+    word_ranges(text)
+        .into_iter()
+        .map(|range| normalize_word(&text[range]))
+        .filter(|word| !word.is_empty())
+        .collect()
+}
+
+/// Levenshtein distance between two sequences (of characters, words, ...).
+pub fn edit_distance<T: PartialEq>(a: &[T], b: &[T]) -> usize {
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+
+    for (i, a_item) in a.iter().enumerate() {
+        current[0] = i + 1;
+        for (j, b_item) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_item != b_item);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_words() {
+        let text = "Don\u{2019}t ship 'v2' -- yet!";
+        let ranges: Vec<&str> = word_ranges(text).into_iter().map(|r| &text[r]).collect();
+        assert_eq!(ranges, vec!["Don\u{2019}t", "ship", "'v2'", "yet"]);
+        assert_eq!(words(text), vec!["don't", "ship", "v2", "yet"]);
+        assert!(words("'' ...").is_empty());
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        let chars = |s: &str| s.chars().collect::<Vec<_>>();
+        assert_eq!(edit_distance(&chars("kitten"), &chars("sitting")), 3);
+        assert_eq!(edit_distance(&chars("slack"), &chars("")), 5);
+
+        let a = words("the quick brown fox");
+        let b = words("The quick red fox jumps.");
+        assert_eq!(edit_distance(&a, &b), 2);
+        assert_eq!(edit_distance(&a, &[]), 4);
+    }
+}
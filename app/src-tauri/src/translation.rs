@@ -0,0 +1,187 @@
+//! Speech translation mode: speak in one language, get text in another.
+//!
+//! Whisper's `translate` task (OpenAI, Groq, whisper.cpp) only produces English, so it is used
+//! when the target is English and the STT provider supports it. Any other target is handled
+//! by the LLM stage with an extra translation prompt section.
+
+use serde::{Deserialize, Serialize};
+
+/// Translation settings (global in `PipelineConfig`, overridable per program profile).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TranslationConfig {
+    pub enabled: bool,
+    /// Spoken language as an ISO 639-1 code or name (`None` = detect automatically)
+    pub source_language: Option<String>,
+    /// Language of the output text as an ISO 639-1 code or name (e.g. "en", "Spanish")
+    pub target_language: String,
+}
+
+impl Default for TranslationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            source_language: None,
+            target_language: "en".to_string(),
+        }
+    }
+}
+
+/// Where a dictation gets translated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TranslationRoute {
+    /// Plain transcription.
+    None,
+    /// The STT provider's translate endpoint/task (English output only).
+    Stt,
+    /// Transcribe as usual, then translate in the LLM stage.
+    Llm,
+}
+
+/// Common ISO 639-1 codes and their English names, for prompts and matching.
+const LANGUAGE_NAMES: &[(&str, &str)] = &[
+    ("ar", "Arabic"),
+    ("cs", "Czech"),
+    ("da", "Danish"),
+    ("de", "German"),
+    ("el", "Greek"),
+    ("en", "English"),
+    ("es", "Spanish"),
+    ("fi", "Finnish"),
+    ("fr", "French"),
+    ("he", "Hebrew"),
+    ("hi", "Hindi"),
+    ("hu", "Hungarian"),
+    ("id", "Indonesian"),
+    ("it", "Italian"),
+    ("ja", "Japanese"),
+    ("ko", "Korean"),
+    ("nl", "Dutch"),
+    ("no", "Norwegian"),
+    ("pl", "Polish"),
+    ("pt", "Portuguese"),
+    ("ro", "Romanian"),
+    ("ru", "Russian"),
+    ("sv", "Swedish"),
+    ("th", "Thai"),
+    ("tr", "Turkish"),
+    ("uk", "Ukrainian"),
+    ("vi", "Vietnamese"),
+    ("zh", "Chinese"),
+];
+
+/// Normalize a language code or name to its ISO 639-1 code when known ("en-US" -> "en",
+/// "German" -> "de"); unknown values are returned trimmed and lowercased.
+pub fn normalize_language(language: &str) -> String {
+    let lower = language.trim().to_lowercase();
+    let primary = lower.split(['-', '_']).next().unwrap_or_default();
+
+    LANGUAGE_NAMES
+        .iter()
+        .find(|(code, name)| *code == primary || name.eq_ignore_ascii_case(&lower))
+        .map(|(code, _)| code.to_string())
+        .unwrap_or(lower)
+}
+
+/// Human-readable language name for prompts ("de" -> "German"); unknown values pass through.
+pub fn language_display_name(language: &str) -> String {
+    let code = normalize_language(language);
+    LANGUAGE_NAMES
+        .iter()
+        .find(|(c, _)| *c == code)
+        .map(|(_, name)| name.to_string())
+        .unwrap_or_else(|| language.trim().to_string())
+}
+
+impl TranslationConfig {
+    fn source(&self) -> Option<&str> {
+        self.source_language
+            .as_deref()
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+    }
+
+    /// Whether dictations should be translated at all.
+    ///
+    /// Disabled, missing a target, or source == target all mean plain transcription.
+    pub fn is_active(&self) -> bool {
+        if !self.enabled || self.target_language.trim().is_empty() {
+            return false;
+        }
+        let target = normalize_language(&self.target_language);
+        self.source()
+            .is_none_or(|source| normalize_language(source) != target)
+    }
+
+    pub fn targets_english(&self) -> bool {
+        normalize_language(&self.target_language) == "en"
+    }
+
+    /// Pick where to translate, given whether the primary STT provider supports translation.
+    pub fn route(&self, stt_supports_translation: bool) -> TranslationRoute {
+        if !self.is_active() {
+            TranslationRoute::None
+        } else if self.targets_english() && stt_supports_translation {
+            TranslationRoute::Stt
+        } else {
+            TranslationRoute::Llm
+        }
+    }
+
+    /// Prompt section appended to the LLM system prompt when translating in the LLM stage.
+    pub fn prompt_section(&self) -> String {
+        let target = language_display_name(&self.target_language);
+        let source = match self.source() {
+            Some(source) => format!("The speaker is talking in {}.", language_display_name(source)),
+            None => "The speaker may be talking in any language.".to_string(),
+        };
+
+        format!(
+            "## Translation\n\
+             {source} Translate the text into {target}.\n\
+             - Apply all other rules first, then output ONLY the {target} translation\n\
+             - Keep names, code, URLs and dictionary terms as written\n\
+             - If the text is already in {target}, output it unchanged"
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(source: Option<&str>, target: &str) -> TranslationConfig {
+        TranslationConfig {
+            enabled: true,
+            source_language: source.map(str::to_string),
+            target_language: target.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_normalize_language() {
+        assert_eq!(normalize_language("en-US"), "en");
+        assert_eq!(normalize_language(" German "), "de");
+        assert_eq!(normalize_language("pt_BR"), "pt");
+        assert_eq!(normalize_language("Klingon"), "klingon");
+        assert_eq!(language_display_name("es"), "Spanish");
+        assert_eq!(language_display_name("Klingon"), "Klingon");
+    }
+
+    #[test]
+    fn test_route() {
+        assert_eq!(TranslationConfig::default().route(true), TranslationRoute::None);
+        assert_eq!(config(Some("de"), "English").route(true), TranslationRoute::Stt);
+        assert_eq!(config(None, "en").route(false), TranslationRoute::Llm);
+        assert_eq!(config(Some("en"), "es").route(true), TranslationRoute::Llm);
+        // Same language on both sides: nothing to translate.
+        assert_eq!(config(Some("en-GB"), "en").route(true), TranslationRoute::None);
+    }
+
+    #[test]
+    fn test_prompt_section_names_languages() {
+        let section = config(Some("de"), "es").prompt_section();
+        assert!(section.contains("talking in German"));
+        assert!(section.contains("into Spanish"));
+    }
+}
@@ -379,6 +379,52 @@ impl VadFrameProcessor {
     }
 }
 
+/// Find the middle of each pause between speech segments.
+///
+/// `samples` is mono audio at `sample_rate`; the returned offsets index into it. Only pauses
+/// long enough to end a speech segment (the VAD hangover) are reported.
+pub fn find_pause_midpoints(samples: &[f32], sample_rate: u32, config: VadConfig) -> Vec<usize> {
+    let sample_rate = sample_rate.max(1);
+    let frame_ms = config.frame_duration_ms.max(1) as usize;
+    let hangover_ms = config.hangover_frames as usize * frame_ms;
+    let speech_start_ms = config.speech_frames_threshold as usize * frame_ms;
+
+    // Resample in one-second blocks rather than per VAD frame; long recordings have a lot of frames.
+    let mut processor = VadFrameProcessor::new(config, 16000);
+    let mut processed_16k: usize = 0;
+    let mut pause_start_ms: Option<usize> = None;
+    let mut midpoints_ms = Vec::new();
+
+    for block in samples.chunks(sample_rate as usize) {
+        let resampled = resample_to_16khz(block, sample_rate);
+        for frame in resampled.chunks(160) {
+            processed_16k += frame.len();
+            let now_ms = processed_16k / 16;
+
+            for event in processor.process(frame) {
+                match event {
+                    VadEvent::SpeechEnd => {
+                        pause_start_ms = Some(now_ms.saturating_sub(hangover_ms));
+                    }
+                    VadEvent::SpeechStart { .. } => {
+                        if let Some(start) = pause_start_ms.take() {
+                            let end = now_ms.saturating_sub(speech_start_ms).max(start);
+                            midpoints_ms.push((start + end) / 2);
+                        }
+                    }
+                    VadEvent::None => {}
+                }
+            }
+        }
+    }
+
+    midpoints_ms
+        .into_iter()
+        .map(|ms| ms * sample_rate as usize / 1000)
+        .filter(|&offset| offset < samples.len())
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -445,4 +491,10 @@ mod tests {
         let processor = VadFrameProcessor::new(VadConfig::default(), 44100);
         assert!(!processor.is_speaking());
     }
+
+    #[test]
+    fn test_find_pause_midpoints_in_silence() {
+        let silence = vec![0.0f32; 48000 * 3];
+        assert!(find_pause_midpoints(&silence, 48000, VadConfig::default()).is_empty());
+    }
 }
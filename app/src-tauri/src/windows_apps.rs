@@ -1,6 +1,7 @@
 // Windows foreground process + window enumeration helpers.
 //
-// These are used for per-program prompt profiles.
+// These are used for per-program prompt profiles and the `{{app_name}}`/`{{window_title}}`
+// prompt variables.
 
 #[cfg(target_os = "windows")]
 mod imp {
@@ -63,6 +64,29 @@ mod imp {
         }
     }
 
+    pub fn get_foreground_window_title() -> Option<String> {
+        unsafe {
+            let hwnd = GetForegroundWindow();
+            if hwnd.0.is_null() {
+                return None;
+            }
+
+            let title_len = GetWindowTextLengthW(hwnd);
+            if title_len == 0 {
+                return None;
+            }
+
+            let mut title_buf: Vec<u16> = vec![0; (title_len as usize) + 1];
+            let copied = GetWindowTextW(hwnd, &mut title_buf);
+            if copied == 0 {
+                return None;
+            }
+
+            let title = String::from_utf16_lossy(&title_buf[..copied as usize]).trim().to_string();
+            (!title.is_empty()).then_some(title)
+        }
+    }
+
     pub fn list_open_windows() -> Vec<OpenWindowInfo> {
         unsafe extern "system" fn enum_proc(hwnd: HWND, lparam: LPARAM) -> BOOL {
             // Safety: caller passes a valid mutable Vec pointer via LPARAM.
@@ -114,7 +138,9 @@ mod imp {
 }
 
 #[cfg(target_os = "windows")]
-pub use imp::{get_foreground_process_path, list_open_windows, OpenWindowInfo};
+pub use imp::{
+    get_foreground_process_path, get_foreground_window_title, list_open_windows, OpenWindowInfo,
+};
 
 #[cfg(not(target_os = "windows"))]
 mod imp_stub {
@@ -128,10 +154,16 @@ mod imp_stub {
         None
     }
 
+    pub fn get_foreground_window_title() -> Option<String> {
+        None
+    }
+
     pub fn list_open_windows() -> Vec<OpenWindowInfo> {
         Vec::new()
     }
 }
 
 #[cfg(not(target_os = "windows"))]
-pub use imp_stub::{get_foreground_process_path, list_open_windows, OpenWindowInfo};
+pub use imp_stub::{
+    get_foreground_process_path, get_foreground_window_title, list_open_windows, OpenWindowInfo,
+};
@@ -8,6 +8,7 @@ import {
   Title,
   Tooltip,
 } from "@mantine/core";
+import { notifications } from "@mantine/notifications";
 import { useQuery } from "@tanstack/react-query";
 import {
   CircleHelp,
@@ -37,6 +38,7 @@ import {
   DEFAULT_HOLD_HOTKEY,
   DEFAULT_PASTE_LAST_HOTKEY,
   DEFAULT_TOGGLE_HOTKEY,
+  DEFAULT_TRANSFORM_HOTKEY,
 } from "./lib/hotkeyDefaults";
 import { applyAccentColor } from "./lib/accentColor";
 import {
@@ -133,6 +135,8 @@ function InstructionsCard() {
   const holdHotkey = settings?.hold_hotkey ?? DEFAULT_HOLD_HOTKEY;
   const pasteLastHotkey =
     settings?.paste_last_hotkey ?? DEFAULT_PASTE_LAST_HOTKEY;
+  const transformHotkey =
+    settings?.transform_hotkey ?? DEFAULT_TRANSFORM_HOTKEY;
 
   return (
     <div className="instructions-card animate-in">
@@ -153,6 +157,11 @@ function InstructionsCard() {
           <HotkeyDisplay config={pasteLastHotkey} />
           <span className="instruction-desc">Paste last result</span>
         </div>
+        <div className="instruction-method">
+          <span className="instruction-label">Transform:</span>
+          <HotkeyDisplay config={transformHotkey} />
+          <span className="instruction-desc">Rewrite selected text</span>
+        </div>
       </div>
     </div>
   );
@@ -646,6 +655,30 @@ function AccentColorSync() {
   return null;
 }
 
+function PipelineNoticeListener() {
+  useEffect(() => {
+    let unlisten: (() => void) | undefined;
+
+    const setup = async () => {
+      unlisten = await tauriAPI.onPipelineNotice((notice) => {
+        notifications.show({
+          title: "Dictation",
+          message: notice.message,
+          color: "yellow",
+        });
+      });
+    };
+
+    setup();
+
+    return () => {
+      unlisten?.();
+    };
+  }, []);
+
+  return null;
+}
+
 export default function App() {
   const [activeView, setActiveView] = useState<View>("home");
   const [settingsGuideOpen, setSettingsGuideOpen] = useState(false);
@@ -691,6 +724,7 @@ export default function App() {
   return (
     <div className="app-layout">
       <AccentColorSync />
+      <PipelineNoticeListener />
       <Sidebar
         activeView={activeView}
         onViewChange={(view) => {
@@ -144,12 +144,13 @@ interface GroupedHistory {
     id: string;
     text: string;
     timestamp: string;
-    status?: "in_progress" | "success" | "error";
+    status?: "in_progress" | "success" | "error" | "queued";
     error_message?: string | null;
     stt_provider?: string | null;
     stt_model?: string | null;
     llm_provider?: string | null;
     llm_model?: string | null;
+    low_confidence_words?: Array<{ word: string; confidence?: number }>;
   }>;
 }
 
@@ -158,12 +159,13 @@ function groupHistoryByDate(
     id: string;
     text: string;
     timestamp: string;
-    status?: "in_progress" | "success" | "error";
+    status?: "in_progress" | "success" | "error" | "queued";
     error_message?: string | null;
     stt_provider?: string | null;
     stt_model?: string | null;
     llm_provider?: string | null;
     llm_model?: string | null;
+    low_confidence_words?: Array<{ word: string; confidence?: number }>;
   }>
 ): GroupedHistory[] {
   const groups: Record<string, GroupedHistory> = {};
@@ -263,7 +265,7 @@ function buildAnalysisPrompt(
     id: string;
     text: string;
     timestamp: string;
-    status?: "in_progress" | "success" | "error";
+    status?: "in_progress" | "success" | "error" | "queued";
   }>,
   options?: {
     includeFromLastHours?: number | null;
@@ -1566,6 +1568,26 @@ export function HistoryFeed() {
                           Transcribing…
                         </Text>
                       </Group>
+                    ) : (entry.status ?? "success") === "queued" ? (
+                      <Group gap={8} wrap="nowrap" style={{ minWidth: 0 }}>
+                        <Text size="sm" c="orange">
+                          Waiting for network
+                        </Text>
+                        <Text
+                          size="sm"
+                          c="dimmed"
+                          style={{
+                            flex: 1,
+                            minWidth: 0,
+                            whiteSpace: "nowrap",
+                            overflow: "hidden",
+                            textOverflow: "ellipsis",
+                          }}
+                          title={entry.error_message ?? undefined}
+                        >
+                          Retries automatically when back online
+                        </Text>
+                      </Group>
                     ) : (entry.status ?? "success") === "error" ? (
                       <Group gap={8} wrap="nowrap" style={{ minWidth: 0 }}>
                         <Text size="sm" c="red">
@@ -1604,6 +1626,20 @@ export function HistoryFeed() {
                         }
                       >
                         {entry.text?.trim() ? entry.text : "No transcript"}
+                        {(entry.low_confidence_words?.length ?? 0) > 0 && (
+                          <Text
+                            component="span"
+                            size="xs"
+                            c="dimmed"
+                            display="block"
+                            title="Words the speech-to-text provider was unsure about"
+                          >
+                            Unsure:{" "}
+                            {entry.low_confidence_words
+                              ?.map((w) => w.word)
+                              .join(", ")}
+                          </Text>
+                        )}
                       </Text>
                     )}
                   </div>
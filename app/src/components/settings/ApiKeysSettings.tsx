@@ -44,6 +44,13 @@ const API_KEYS: ApiKeyConfig[] = [
     storeKey: "deepgram_api_key",
     getKeyUrl: "https://console.deepgram.com/project",
   },
+  {
+    id: "assemblyai",
+    label: "AssemblyAI",
+    placeholder: "Enter API key",
+    storeKey: "assemblyai_api_key",
+    getKeyUrl: "https://www.assemblyai.com/app/api-keys",
+  },
   {
     id: "anthropic",
     label: "Anthropic",
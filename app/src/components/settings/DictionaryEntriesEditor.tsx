@@ -0,0 +1,229 @@
+import {
+  Accordion,
+  ActionIcon,
+  Button,
+  Group,
+  Switch,
+  TagsInput,
+  Text,
+  TextInput,
+  Tooltip,
+} from "@mantine/core";
+import { Plus, Trash2 } from "lucide-react";
+import { useEffect, useState } from "react";
+import { useSettings, useUpdateDictionaryEntries } from "../../lib/queries";
+import type { DictionaryEntry } from "../../lib/tauri";
+
+function createId(): string {
+  // `crypto.randomUUID()` is available in modern browsers; keep a fallback for safety.
+  // This only needs to be unique enough for local settings.
+  return (
+    globalThis.crypto?.randomUUID?.() ??
+    `id_${Date.now()}_${Math.random().toString(16).slice(2)}`
+  );
+}
+
+interface DictionaryEntryRowProps {
+  entry: DictionaryEntry;
+  disabled: boolean;
+  onChange: (entry: DictionaryEntry) => void;
+  onDelete: () => void;
+}
+
+function DictionaryEntryRow({
+  entry,
+  disabled,
+  onChange,
+  onDelete,
+}: DictionaryEntryRowProps) {
+  const [term, setTerm] = useState(entry.term);
+
+  useEffect(() => {
+    setTerm(entry.term);
+  }, [entry.term]);
+
+  return (
+    <div
+      style={{
+        padding: "10px 0",
+        borderBottom: "1px solid var(--border-default)",
+      }}
+    >
+      <Group wrap="nowrap" align="flex-end">
+        <TextInput
+          label="Term"
+          placeholder="e.g. Anthropic"
+          value={term}
+          onChange={(e) => setTerm(e.currentTarget.value)}
+          onBlur={() => {
+            const trimmed = term.trim();
+            if (trimmed !== entry.term) {
+              onChange({ ...entry, term: trimmed });
+            }
+          }}
+          styles={{ label: { fontSize: 12 } }}
+          style={{ flex: 1 }}
+        />
+        <TagsInput
+          label="Spoken as"
+          placeholder="e.g. ant row pic"
+          value={entry.spoken}
+          onChange={(spoken) => onChange({ ...entry, spoken })}
+          styles={{ label: { fontSize: 12 } }}
+          style={{ flex: 2 }}
+        />
+        <Tooltip label="Delete entry" withArrow>
+          <ActionIcon
+            variant="subtle"
+            color="gray"
+            onClick={onDelete}
+            aria-label="Delete entry"
+            disabled={disabled}
+            mb={4}
+          >
+            <Trash2 size={16} />
+          </ActionIcon>
+        </Tooltip>
+      </Group>
+      <Group gap="lg" mt={8}>
+        <Tooltip
+          label="Also fix the capitalization of the term itself. Leave off for common words like “cursor”."
+          withArrow
+          multiline
+          w={260}
+        >
+          <Switch
+            label="Match the term"
+            size="xs"
+            color="gray"
+            checked={entry.match_term}
+            onChange={(e) =>
+              onChange({ ...entry, match_term: e.currentTarget.checked })
+            }
+          />
+        </Tooltip>
+        <Switch
+          label="Case-sensitive"
+          size="xs"
+          color="gray"
+          checked={entry.case_sensitive}
+          onChange={(e) =>
+            onChange({ ...entry, case_sensitive: e.currentTarget.checked })
+          }
+        />
+        <Switch
+          label="Whole words"
+          size="xs"
+          color="gray"
+          checked={entry.whole_word}
+          onChange={(e) =>
+            onChange({ ...entry, whole_word: e.currentTarget.checked })
+          }
+        />
+      </Group>
+    </div>
+  );
+}
+
+export interface DictionaryEntriesEditorProps {
+  sectionKey: string;
+  // Profile whose entries are edited (null = Default, whose entries apply to every profile).
+  profileId: string | null;
+}
+
+export function DictionaryEntriesEditor({
+  sectionKey,
+  profileId,
+}: DictionaryEntriesEditorProps) {
+  const { data: settings } = useSettings();
+  const updateDictionaryEntries = useUpdateDictionaryEntries();
+
+  const allEntries = settings?.dictionary_entries ?? [];
+  const entries = allEntries.filter(
+    (entry) => (entry.profile_id ?? null) === profileId
+  );
+
+  const updateEntry = (next: DictionaryEntry) => {
+    updateDictionaryEntries.mutate(
+      allEntries.map((entry) => (entry.id === next.id ? next : entry))
+    );
+  };
+
+  const deleteEntry = (id: string) => {
+    updateDictionaryEntries.mutate(
+      allEntries.filter((entry) => entry.id !== id)
+    );
+  };
+
+  const addEntry = () => {
+    updateDictionaryEntries.mutate([
+      ...allEntries,
+      {
+        id: createId(),
+        term: "",
+        spoken: [],
+        case_sensitive: false,
+        match_term: false,
+        whole_word: true,
+        profile_id: profileId,
+      },
+    ]);
+  };
+
+  return (
+    <Accordion.Item value={sectionKey}>
+      <Accordion.Control>
+        <div>
+          <p className="settings-label">Dictionary Entries</p>
+          <p className="settings-description">
+            Terms and the spoken forms replaced by them
+          </p>
+        </div>
+      </Accordion.Control>
+      <Accordion.Panel>
+        <Text size="xs" c="dimmed" mb="sm">
+          Spoken forms are replaced in every transcript, also without the LLM
+          rewrite. The terms are listed in the Personal Dictionary section and
+          sent to the STT provider as vocabulary.
+          {profileId !== null &&
+            " Entries of the Default profile apply here too."}
+        </Text>
+
+        {entries.length === 0 ? (
+          <div style={{ fontSize: 12, opacity: 0.7, paddingBottom: 8 }}>
+            No entries yet.
+          </div>
+        ) : null}
+
+        {entries.map((entry) => (
+          <DictionaryEntryRow
+            key={entry.id}
+            entry={entry}
+            disabled={updateDictionaryEntries.isPending}
+            onChange={updateEntry}
+            onDelete={() => deleteEntry(entry.id)}
+          />
+        ))}
+
+        <div
+          style={{
+            display: "flex",
+            marginTop: 16,
+            justifyContent: "flex-end",
+          }}
+        >
+          <Button
+            variant="light"
+            color="gray"
+            size="xs"
+            leftSection={<Plus size={14} />}
+            onClick={addEntry}
+            disabled={updateDictionaryEntries.isPending}
+          >
+            Add entry
+          </Button>
+        </div>
+      </Accordion.Panel>
+    </Accordion.Item>
+  );
+}
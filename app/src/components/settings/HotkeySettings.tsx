@@ -5,6 +5,7 @@ import {
   DEFAULT_HOLD_HOTKEY,
   DEFAULT_PASTE_LAST_HOTKEY,
   DEFAULT_TOGGLE_HOTKEY,
+  DEFAULT_TRANSFORM_HOTKEY,
 } from "../../lib/hotkeyDefaults";
 import {
   useResetHotkeysToDefaults,
@@ -12,6 +13,7 @@ import {
   useUpdateHoldHotkey,
   useUpdatePasteLastHotkey,
   useUpdateToggleHotkey,
+  useUpdateTransformHotkey,
 } from "../../lib/queries";
 import type { HotkeyConfig } from "../../lib/tauri";
 import { HotkeyInput } from "../HotkeyInput";
@@ -19,7 +21,7 @@ import { HotkeyInput } from "../HotkeyInput";
 const GLOBAL_ONLY_TOOLTIP =
   "This setting can only be changed in the Default profile";
 
-type RecordingInput = "toggle" | "hold" | "paste_last" | null;
+type RecordingInput = "toggle" | "hold" | "paste_last" | "transform" | null;
 
 export function HotkeySettings({
   editingProfileId,
@@ -31,6 +33,7 @@ export function HotkeySettings({
   const updateToggleHotkey = useUpdateToggleHotkey();
   const updateHoldHotkey = useUpdateHoldHotkey();
   const updatePasteLastHotkey = useUpdatePasteLastHotkey();
+  const updateTransformHotkey = useUpdateTransformHotkey();
   const resetHotkeys = useResetHotkeysToDefaults();
 
   // Track which input is currently recording (only one at a time)
@@ -44,6 +47,7 @@ export function HotkeySettings({
     updateToggleHotkey.error ||
     updateHoldHotkey.error ||
     updatePasteLastHotkey.error ||
+    updateTransformHotkey.error ||
     resetHotkeys.error;
 
   const errorMessage =
@@ -87,6 +91,10 @@ export function HotkeySettings({
     updatePasteLastHotkey.mutate(config);
   };
 
+  const handleTransformHotkeyChange = (config: HotkeyConfig) => {
+    updateTransformHotkey.mutate(config);
+  };
+
   const content = (
     <>
       {showError && (
@@ -138,6 +146,19 @@ export function HotkeySettings({
         />
       </div>
 
+      <div style={{ marginTop: 20 }}>
+        <HotkeyInput
+          label="Transform Selection"
+          description="Select text, press, speak an instruction, press again to replace the selection"
+          value={settings?.transform_hotkey ?? DEFAULT_TRANSFORM_HOTKEY}
+          onChange={handleTransformHotkeyChange}
+          disabled={isLoading || updateTransformHotkey.isPending}
+          isRecording={recordingInput === "transform"}
+          onStartRecording={() => setRecordingInput("transform")}
+          onStopRecording={() => setRecordingInput(null)}
+        />
+      </div>
+
       <div
         style={{
           marginTop: 24,
@@ -44,6 +44,7 @@ import {
 } from "../../lib/tauri";
 import { LLM_MODELS, STT_MODELS } from "../../lib/modelOptions";
 import { HintSelect } from "../HintSelect";
+import { DictionaryEntriesEditor } from "./DictionaryEntriesEditor";
 import { PromptSectionEditor } from "./PromptSectionEditor";
 
 const INHERIT_TOOLTIP = "Inheriting from Default profile";
@@ -2421,7 +2422,7 @@ export function PromptSettings({
           <PromptSectionEditor
             sectionKey={`${activeProfileId}-dictionary-prompt`}
             title="Personal Dictionary"
-            description="Custom word mappings for technical terms"
+            description="Instructions for the dictionary entries below"
             enabled={localSections!.dictionary.enabled}
             initialContent={localSections!.dictionary.content}
             defaultContent={defaultSections?.dictionary ?? ""}
@@ -2489,6 +2490,11 @@ export function PromptSettings({
               updateRewriteProgramPromptProfiles.isPending
             }
           />
+
+          <DictionaryEntriesEditor
+            sectionKey={`${activeProfileId}-dictionary-entries`}
+            profileId={isDefaultScope ? null : activeProfileId}
+          />
         </Accordion>
       </div>
     </>
@@ -1,6 +1,7 @@
 export { ApiKeysSettings } from "./ApiKeysSettings";
 export { AudioSettings } from "./AudioSettings";
 export { DataSettings } from "./DataSettings";
+export { DictionaryEntriesEditor } from "./DictionaryEntriesEditor";
 export { HotkeySettings } from "./HotkeySettings";
 export type { PromptSectionEditorProps } from "./PromptSectionEditor";
 export { PromptSectionEditor } from "./PromptSectionEditor";
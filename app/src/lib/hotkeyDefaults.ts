@@ -17,6 +17,9 @@ export const DEFAULT_HOLD_KEY = "Backquote";
 /** Default key for paste last transcription (Ctrl+Alt+.) */
 export const DEFAULT_PASTE_LAST_KEY = "Period";
 
+/** Default key for transforming the selected text (Ctrl+Alt+/) */
+export const DEFAULT_TRANSFORM_KEY = "Slash";
+
 // ============================================================================
 
 /** Default toggle hotkey config */
@@ -36,3 +39,9 @@ export const DEFAULT_PASTE_LAST_HOTKEY: HotkeyConfig = {
 	modifiers: DEFAULT_HOTKEY_MODIFIERS,
 	key: DEFAULT_PASTE_LAST_KEY,
 };
+
+/** Default transform selection hotkey config */
+export const DEFAULT_TRANSFORM_HOTKEY: HotkeyConfig = {
+	modifiers: DEFAULT_HOTKEY_MODIFIERS,
+	key: DEFAULT_TRANSFORM_KEY,
+};
@@ -24,6 +24,11 @@ export const STT_MODELS: Record<string, ModelOption[]> = {
     { value: "enhanced", label: "Enhanced" },
     { value: "base", label: "Base" },
   ],
+  assemblyai: [
+    { value: "universal", label: "Universal" },
+    { value: "slam-1", label: "Slam-1" },
+    { value: "nano", label: "Nano" },
+  ],
   whisper: [], // Local whisper has its own model management
 };
 
@@ -5,6 +5,7 @@ import {
   audioSettingsTestAPI,
   type CleanupPromptSections,
   configAPI,
+  type DictionaryEntry,
   type HotkeyConfig,
   llmAPI,
   logsAPI,
@@ -97,6 +98,7 @@ export function useUpdateToggleHotkey() {
           toggle: settings.toggle_hotkey,
           hold: settings.hold_hotkey,
           paste_last: settings.paste_last_hotkey,
+          transform: settings.transform_hotkey,
         },
         "toggle"
       );
@@ -127,6 +129,7 @@ export function useUpdateHoldHotkey() {
           toggle: settings.toggle_hotkey,
           hold: settings.hold_hotkey,
           paste_last: settings.paste_last_hotkey,
+          transform: settings.transform_hotkey,
         },
         "hold"
       );
@@ -157,6 +160,7 @@ export function useUpdatePasteLastHotkey() {
           toggle: settings.toggle_hotkey,
           hold: settings.hold_hotkey,
           paste_last: settings.paste_last_hotkey,
+          transform: settings.transform_hotkey,
         },
         "paste_last"
       );
@@ -173,6 +177,37 @@ export function useUpdatePasteLastHotkey() {
   });
 }
 
+export function useUpdateTransformHotkey() {
+  const queryClient = useQueryClient();
+  return useMutation({
+    mutationFn: async (hotkey: HotkeyConfig) => {
+      // Get current settings for validation
+      const settings = await tauriAPI.getSettings();
+
+      // Validate no duplicate
+      const error = validateHotkeyNotDuplicate(
+        hotkey,
+        {
+          toggle: settings.toggle_hotkey,
+          hold: settings.hold_hotkey,
+          paste_last: settings.paste_last_hotkey,
+          transform: settings.transform_hotkey,
+        },
+        "transform"
+      );
+      if (error) throw new Error(error);
+
+      // Save and re-register
+      await tauriAPI.updateTransformHotkey(hotkey);
+      await tauriAPI.unregisterShortcuts();
+      await tauriAPI.registerShortcuts();
+    },
+    onSuccess: () => {
+      queryClient.invalidateQueries({ queryKey: ["settings"] });
+    },
+  });
+}
+
 export function useUpdateSelectedMic() {
   const queryClient = useQueryClient();
   return useMutation({
@@ -626,6 +661,46 @@ export function useUpdateRewriteProgramPromptProfiles() {
   });
 }
 
+export function useUpdateDictionaryEntries() {
+  const queryClient = useQueryClient();
+  return useMutation({
+    mutationFn: async (entries: DictionaryEntry[]) => {
+      await tauriAPI.updateDictionaryEntries(entries);
+      // Replacements, the LLM dictionary section and STT vocabulary all come from the entries.
+      await configAPI.syncPipelineConfig();
+    },
+    onMutate: async (entries: DictionaryEntry[]) => {
+      // Optimistically update the settings cache so edits don't snap back while saving.
+      await queryClient.cancelQueries({ queryKey: ["settings"] });
+
+      const previousSettings = queryClient.getQueryData<AppSettings>([
+        "settings",
+      ]);
+
+      if (previousSettings) {
+        queryClient.setQueryData<AppSettings>(["settings"], {
+          ...previousSettings,
+          dictionary_entries: entries,
+        });
+      }
+
+      return { previousSettings };
+    },
+    onError: (error, _entries, context) => {
+      console.error("updateDictionaryEntries failed:", error);
+      if (context?.previousSettings) {
+        queryClient.setQueryData<AppSettings>(
+          ["settings"],
+          context.previousSettings
+        );
+      }
+    },
+    onSettled: () => {
+      queryClient.invalidateQueries({ queryKey: ["settings"] });
+    },
+  });
+}
+
 export function useResetHotkeysToDefaults() {
   const queryClient = useQueryClient();
   return useMutation({
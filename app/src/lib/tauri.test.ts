@@ -90,6 +90,7 @@ describe("createHotkeyDuplicateSchema", () => {
 		toggle: { modifiers: ["ctrl", "alt"], key: "Space" },
 		hold: { modifiers: ["ctrl", "alt"], key: "Backquote" },
 		paste_last: { modifiers: ["ctrl", "alt"], key: "Period" },
+		transform: { modifiers: ["ctrl", "alt"], key: "Slash" },
 	};
 
 	it("allows a unique hotkey when editing toggle", () => {
@@ -142,6 +143,7 @@ describe("validateHotkeyNotDuplicate", () => {
 		toggle: { modifiers: ["ctrl", "alt"], key: "Space" },
 		hold: { modifiers: ["ctrl", "alt"], key: "Backquote" },
 		paste_last: { modifiers: ["ctrl", "alt"], key: "Period" },
+		transform: { modifiers: ["ctrl", "alt"], key: "Slash" },
 	};
 
 	it("returns null for a unique hotkey", () => {
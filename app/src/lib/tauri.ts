@@ -31,16 +31,50 @@ export const HotkeyConfigSchema = z.object({
   key: z.string().min(1, "Key is required"),
 });
 
+export interface TranscriptWord {
+  word: string;
+  start: number;
+  end: number;
+  confidence?: number;
+}
+
+export interface TranscriptSegment {
+  text: string;
+  start: number;
+  end: number;
+  avg_logprob?: number;
+  no_speech_prob?: number;
+}
+
+// Detailed STT result (timings/confidence/language), when the provider returns it.
+export interface Transcript {
+  text: string;
+  segments?: TranscriptSegment[];
+  words?: TranscriptWord[];
+  language?: string;
+  confidence?: number;
+}
+
 interface HistoryEntry {
   id: string;
   timestamp: string;
   text: string;
-  status?: "in_progress" | "success" | "error";
+  // "queued": failed on a network error, retried automatically once back online.
+  status?: "in_progress" | "success" | "error" | "queued";
   error_message?: string | null;
   stt_provider?: string | null;
   stt_model?: string | null;
   llm_provider?: string | null;
   llm_model?: string | null;
+  stt_transcript?: Transcript | null;
+  // Spoken language reported by the STT provider (ISO 639-1 code when known).
+  language?: string | null;
+  // Words the STT provider reported with low confidence (worth double-checking).
+  low_confidence_words?: TranscriptWord[];
+  // Raw STT text, when the output differs from it.
+  stt_text?: string | null;
+  // Program profile active for the dictation (absent = Default).
+  profile_id?: string | null;
 }
 
 export interface PromptSection {
@@ -76,6 +110,13 @@ export interface RewriteProgramPromptProfile {
   stt_timeout_seconds?: number | null;
   llm_provider?: string | null;
   llm_model?: string | null;
+  // Per-profile LLM fallback chain (null = inherit the global `llm_fallback_chain` setting)
+  llm_fallback_chain?: LlmFallbackEntry[] | null;
+
+  // Per-profile translation mode (null = inherit the global `translation` setting)
+  translation?: TranslationConfig | null;
+  // Per-profile spoken language (null = inherit the global `stt_language` setting)
+  stt_language?: SttLanguage | null;
 
   // Per-profile overrides for UI (Option 1: override-or-inherit)
   // NOTE: These are persisted in settings.json as part of the profile object.
@@ -91,6 +132,106 @@ export interface RewriteProgramPromptProfile {
   output_hit_enter?: boolean | null;
 }
 
+// Persisted under the `translation` settings key.
+export interface TranslationConfig {
+  enabled: boolean;
+  // ISO 639-1 code or language name; null = detect automatically.
+  source_language: string | null;
+  // Whisper's translate task only outputs English; other targets are translated by the LLM.
+  target_language: string;
+}
+
+// Persisted under the `stt_language` settings key. Languages are ISO 639-1 codes or names;
+// candidates are tried in order of preference.
+export type SttLanguage =
+  | { mode: "auto" }
+  | { mode: "fixed"; language: string }
+  | { mode: "candidates"; languages: string[] };
+
+// An extra LLM provider tried when the ones before it fail (model null = provider default).
+export interface LlmFallbackEntry {
+  provider: string;
+  model: string | null;
+}
+
+// Persisted under the `llm_output_guardrail` settings key (off by default). Rewrites that fail
+// these checks fall back to the raw transcript ("fallback") or are retried once with a stricter
+// prompt. Prompts with a custom main prompt are never checked.
+export interface OutputGuardrail {
+  enabled: boolean;
+  action: "fallback" | "retry_strict";
+  // Output/transcript length ratio (characters)
+  min_length_ratio: number;
+  max_length_ratio: number;
+  // Share of the output's words that also appear in the transcript
+  min_token_overlap: number;
+  // Word-level edit distance relative to the longer text
+  max_edit_ratio: number;
+}
+
+const DEFAULT_OUTPUT_GUARDRAIL: OutputGuardrail = {
+  enabled: false,
+  action: "fallback",
+  min_length_ratio: 0.3,
+  max_length_ratio: 1.8,
+  min_token_overlap: 0.6,
+  max_edit_ratio: 0.7,
+};
+
+// Persisted under the `openai_compatible_stt` settings key (STT provider "openai-compatible").
+export interface OpenAiCompatibleSttConfig {
+  // e.g. "http://localhost:8000/v1" (faster-whisper-server, LocalAI)
+  base_url: string;
+  api_key: string | null;
+  model: string | null;
+  // Extra multipart form fields sent with every request (e.g. `language`)
+  extra_fields: Record<string, string>;
+}
+
+// Persisted under the `stt_fallback_chain` settings key: STT providers tried in order when
+// the effective provider fails or times out.
+export interface SttFallbackEntry {
+  provider: string;
+  model: string | null;
+}
+
+// Persisted under the `stt_chunking` settings key. Long recordings are split at pauses and
+// the chunks transcribed concurrently.
+export interface SttChunkingConfig {
+  enabled: boolean;
+  max_chunk_secs: number;
+  max_concurrency: number;
+}
+
+const DEFAULT_STT_CHUNKING: SttChunkingConfig = {
+  enabled: true,
+  max_chunk_secs: 60,
+  max_concurrency: 4,
+};
+
+// Persisted under the `openai_compatible_llm` settings key (LLM provider "openai-compatible").
+export interface OpenAiCompatibleLlmConfig {
+  // e.g. "http://localhost:1234/v1" (LM Studio) or "https://openrouter.ai/api/v1"
+  base_url: string;
+  api_key: string | null;
+  model: string | null;
+  headers: Record<string, string>;
+}
+
+// Payload of `llm-rewrite-delta` events: the new text and everything streamed so far
+// (raw model output; the final transcript is trimmed).
+export interface LlmRewriteDelta {
+  delta: string;
+  text: string;
+}
+
+// Payload of `pipeline-notice` events: something about a dictation the user should know,
+// even though it completed.
+export interface PipelineNotice {
+  kind: "streamed_output_replaced" | "llm_output_rejected";
+  message: string;
+}
+
 export type PlayingAudioHandling = "none" | "mute" | "pause" | "mute_and_pause";
 
 export type AudioCue = "tangerine" | "maraca" | "clave" | "tambourine";
@@ -110,6 +251,8 @@ export type OutputMode = "paste" | "paste_and_clipboard" | "clipboard";
 
 export type TranscriptionRetentionUnit = "days" | "hours";
 
+export type AudioUploadEncoding = "wav" | "flac" | "ogg_opus";
+
 export type RequestLogsRetentionMode = "amount" | "time";
 
 export type SettingsGuideState = "pending" | "skipped" | "completed";
@@ -142,6 +285,7 @@ export interface AppSettings {
   toggle_hotkey: HotkeyConfig;
   hold_hotkey: HotkeyConfig;
   paste_last_hotkey: HotkeyConfig;
+  transform_hotkey: HotkeyConfig;
   selected_mic_id: string | null;
   sound_enabled: boolean;
   audio_cue: AudioCue;
@@ -155,8 +299,24 @@ export interface AppSettings {
   stt_model: string | null;
   // Global STT prompt (applies to all transcriptions when supported by the selected provider/model)
   stt_transcription_prompt: string | null;
+  openai_compatible_stt: OpenAiCompatibleSttConfig;
+  // Providers tried in order when STT fails or times out.
+  stt_fallback_chain: SttFallbackEntry[];
+  stt_chunking: SttChunkingConfig;
+  // Stream audio to the STT provider while recording (providers that support it).
+  stt_streaming_enabled: boolean;
   llm_provider: string | null;
   llm_model: string | null;
+  // Providers tried in order when the LLM step fails (within the same timeout budget).
+  llm_fallback_chain: LlmFallbackEntry[];
+  // Checks applied to LLM rewrites before they replace the transcript.
+  llm_output_guardrail: OutputGuardrail;
+  // Available to custom prompts as {{user_name}}
+  user_name: string | null;
+  // System prompt for the transform-selection hotkey; null uses the built-in default.
+  transform_prompt: string | null;
+  // Personal dictionary: replaced in the transcript and added to the LLM dictionary section.
+  dictionary_entries: DictionaryEntry[];
 
   // Optional per-provider reasoning/thinking knobs.
   // These are ignored unless the selected provider/model supports them.
@@ -164,6 +324,7 @@ export interface AppSettings {
   anthropic_thinking_budget: number | null;
   gemini_thinking_budget: number | null;
   gemini_thinking_level: "minimal" | "low" | "medium" | "high" | null;
+  openai_compatible_llm: OpenAiCompatibleLlmConfig;
 
   playing_audio_handling: PlayingAudioHandling;
   stt_timeout_seconds: number | null;
@@ -171,6 +332,10 @@ export interface AppSettings {
   widget_position: WidgetPosition;
   output_mode: OutputMode;
   output_hit_enter: boolean;
+  // Stream the LLM rewrite (the overlay receives `llm-rewrite-delta` events).
+  llm_streaming_enabled: boolean;
+  // Paste sentences while the rewrite streams instead of waiting for the full text.
+  output_streaming_chunks: boolean;
 
   // Hallucination protection (quiet-audio gate)
   quiet_audio_gate_enabled: boolean;
@@ -189,6 +354,8 @@ export interface AppSettings {
   audio_highpass_enabled: boolean;
   audio_agc_enabled: boolean;
   audio_noise_suppression_enabled: boolean;
+  // Compressed upload format for batch STT providers that accept it.
+  audio_upload_encoding: AudioUploadEncoding;
 
   // How many recordings/history entries to retain
   max_saved_recordings: number;
@@ -295,6 +462,24 @@ function normalizeAnthropicThinkingBudget(value: unknown): number | null {
   return Math.min(32768, n);
 }
 
+function normalizeLlmFallbackChain(value: unknown): LlmFallbackEntry[] {
+  if (!Array.isArray(value)) return [];
+  return value
+    .filter(
+      (entry): entry is { provider: string; model?: unknown } =>
+        typeof entry?.provider === "string" && entry.provider.trim() !== ""
+    )
+    .map((entry) => ({
+      provider: entry.provider,
+      model: typeof entry.model === "string" ? entry.model : null,
+    }));
+}
+
+function normalizeSttFallbackChain(value: unknown): SttFallbackEntry[] {
+  // Same shape as the LLM chain.
+  return normalizeLlmFallbackChain(value);
+}
+
 function normalizeNoiseGateThresholdDbfs(value: unknown): number | null {
   if (value == null) return null;
   if (typeof value !== "number" || !Number.isFinite(value)) return null;
@@ -401,6 +586,11 @@ export const defaultPasteLastHotkey: HotkeyConfig = {
   key: "Period",
 };
 
+export const defaultTransformHotkey: HotkeyConfig = {
+  modifiers: DEFAULT_HOTKEY_MODIFIERS,
+  key: "Slash",
+};
+
 // ============================================================================
 // Store helpers
 // ============================================================================
@@ -438,12 +628,13 @@ export function hotkeyIsSameAs(a: HotkeyConfig, b: HotkeyConfig): boolean {
   );
 }
 
-type HotkeyType = "toggle" | "hold" | "paste_last";
+type HotkeyType = "toggle" | "hold" | "paste_last" | "transform";
 
 const HOTKEY_LABELS: Record<HotkeyType, string> = {
   toggle: "toggle",
   hold: "hold",
   paste_last: "paste last",
+  transform: "transform selection",
 };
 
 /**
@@ -478,6 +669,7 @@ export function validateHotkeyNotDuplicate(
     toggle: HotkeyConfig;
     hold: HotkeyConfig;
     paste_last: HotkeyConfig;
+    transform: HotkeyConfig;
   },
   excludeType: HotkeyType
 ): string | null {
@@ -589,10 +781,44 @@ export const tauriAPI = {
           : null;
       const llm_model =
         typeof (p as any).llm_model === "string" ? (p as any).llm_model : null;
+      const llm_fallback_chain = Array.isArray((p as any).llm_fallback_chain)
+        ? normalizeLlmFallbackChain((p as any).llm_fallback_chain)
+        : null;
       const rewrite_llm_enabled =
         typeof (p as any).rewrite_llm_enabled === "boolean"
           ? (p as any).rewrite_llm_enabled
           : null;
+      const translation_raw = (p as any).translation;
+      const translation: TranslationConfig | null =
+        translation_raw && typeof translation_raw === "object"
+          ? {
+              enabled: translation_raw.enabled === true,
+              source_language:
+                typeof translation_raw.source_language === "string"
+                  ? translation_raw.source_language
+                  : null,
+              target_language:
+                typeof translation_raw.target_language === "string"
+                  ? translation_raw.target_language
+                  : "en",
+            }
+          : null;
+      const stt_language_raw = (p as any).stt_language;
+      const stt_language: SttLanguage | null =
+        stt_language_raw?.mode === "auto"
+          ? { mode: "auto" }
+          : stt_language_raw?.mode === "fixed" &&
+            typeof stt_language_raw.language === "string"
+          ? { mode: "fixed", language: stt_language_raw.language }
+          : stt_language_raw?.mode === "candidates" &&
+            Array.isArray(stt_language_raw.languages)
+          ? {
+              mode: "candidates",
+              languages: stt_language_raw.languages.filter(
+                (x: unknown) => typeof x === "string"
+              ),
+            }
+          : null;
 
       const sound_enabled =
         typeof (p as any).sound_enabled === "boolean"
@@ -651,6 +877,9 @@ export const tauriAPI = {
         stt_timeout_seconds,
         llm_provider,
         llm_model,
+        llm_fallback_chain,
+        translation,
+        stt_language,
         sound_enabled,
         playing_audio_handling,
         overlay_mode,
@@ -677,6 +906,9 @@ export const tauriAPI = {
       paste_last_hotkey:
         (await store.get<HotkeyConfig>("paste_last_hotkey")) ??
         defaultPasteLastHotkey,
+      transform_hotkey:
+        (await store.get<HotkeyConfig>("transform_hotkey")) ??
+        defaultTransformHotkey,
       selected_mic_id:
         (await store.get<string | null>("selected_mic_id")) ?? null,
       sound_enabled: (await store.get<boolean>("sound_enabled")) ?? true,
@@ -695,8 +927,41 @@ export const tauriAPI = {
       stt_model: (await store.get<string | null>("stt_model")) ?? null,
       stt_transcription_prompt:
         (await store.get<string | null>("stt_transcription_prompt")) ?? null,
+      openai_compatible_stt: {
+        base_url: "",
+        api_key: null,
+        model: null,
+        extra_fields: {},
+        ...((await store.get<Partial<OpenAiCompatibleSttConfig>>(
+          "openai_compatible_stt"
+        )) ?? {}),
+      },
+      stt_fallback_chain: normalizeSttFallbackChain(
+        await store.get("stt_fallback_chain")
+      ),
+      stt_chunking: {
+        ...DEFAULT_STT_CHUNKING,
+        ...((await store.get<Partial<SttChunkingConfig>>("stt_chunking")) ??
+          {}),
+      },
+      stt_streaming_enabled:
+        (await store.get<boolean>("stt_streaming_enabled")) ?? false,
       llm_provider: (await store.get<string | null>("llm_provider")) ?? null,
       llm_model: (await store.get<string | null>("llm_model")) ?? null,
+      llm_fallback_chain: normalizeLlmFallbackChain(
+        await store.get("llm_fallback_chain")
+      ),
+      llm_output_guardrail: {
+        ...DEFAULT_OUTPUT_GUARDRAIL,
+        ...((await store.get<Partial<OutputGuardrail>>(
+          "llm_output_guardrail"
+        )) ?? {}),
+      },
+      user_name: (await store.get<string | null>("user_name")) ?? null,
+      transform_prompt:
+        (await store.get<string | null>("transform_prompt")) ?? null,
+      dictionary_entries:
+        (await store.get<DictionaryEntry[]>("dictionary_entries")) ?? [],
       openai_reasoning_effort: normalizeOpenAiReasoningEffort(
         await store.get("openai_reasoning_effort")
       ),
@@ -709,6 +974,15 @@ export const tauriAPI = {
       gemini_thinking_level: normalizeGeminiThinkingLevel(
         await store.get("gemini_thinking_level")
       ),
+      openai_compatible_llm: {
+        base_url: "",
+        api_key: null,
+        model: null,
+        headers: {},
+        ...((await store.get<Partial<OpenAiCompatibleLlmConfig>>(
+          "openai_compatible_llm"
+        )) ?? {}),
+      },
       playing_audio_handling: normalizePlayingAudioHandling(
         (await store.get("playing_audio_handling")) ??
           // Legacy key for migration:
@@ -724,6 +998,10 @@ export const tauriAPI = {
         (await store.get<WidgetPosition>("widget_position")) ?? "bottom-center",
       output_mode: normalizeOutputMode(await store.get("output_mode")),
       output_hit_enter: (await store.get<boolean>("output_hit_enter")) ?? false,
+      llm_streaming_enabled:
+        (await store.get<boolean>("llm_streaming_enabled")) ?? false,
+      output_streaming_chunks:
+        (await store.get<boolean>("output_streaming_chunks")) ?? false,
 
       quiet_audio_gate_enabled:
         (await store.get<boolean>("quiet_audio_gate_enabled")) ?? true,
@@ -759,6 +1037,8 @@ export const tauriAPI = {
         (await store.get<boolean>("audio_agc_enabled")) ?? false,
       audio_noise_suppression_enabled:
         (await store.get<boolean>("audio_noise_suppression_enabled")) ?? false,
+      audio_upload_encoding:
+        (await store.get<AudioUploadEncoding>("audio_upload_encoding")) ?? "wav",
 
       max_saved_recordings: normalizeMaxSavedRecordings(
         await store.get("max_saved_recordings")
@@ -850,6 +1130,12 @@ export const tauriAPI = {
     await store.save();
   },
 
+  async updateTransformHotkey(hotkey: HotkeyConfig): Promise<void> {
+    const store = await getStore();
+    await store.set("transform_hotkey", hotkey);
+    await store.save();
+  },
+
   async updateSelectedMic(micId: string | null): Promise<void> {
     const store = await getStore();
     await store.set("selected_mic_id", micId);
@@ -922,6 +1208,32 @@ export const tauriAPI = {
     await store.save();
   },
 
+  async updateOpenAiCompatibleStt(
+    config: OpenAiCompatibleSttConfig
+  ): Promise<void> {
+    const store = await getStore();
+    await store.set("openai_compatible_stt", config);
+    await store.save();
+  },
+
+  async updateSttFallbackChain(chain: SttFallbackEntry[]): Promise<void> {
+    const store = await getStore();
+    await store.set("stt_fallback_chain", chain);
+    await store.save();
+  },
+
+  async updateSttChunking(chunking: SttChunkingConfig): Promise<void> {
+    const store = await getStore();
+    await store.set("stt_chunking", chunking);
+    await store.save();
+  },
+
+  async updateSttStreamingEnabled(enabled: boolean): Promise<void> {
+    const store = await getStore();
+    await store.set("stt_streaming_enabled", enabled);
+    await store.save();
+  },
+
   async updateLLMProvider(provider: string | null): Promise<void> {
     const store = await getStore();
     await store.set("llm_provider", provider);
@@ -934,6 +1246,36 @@ export const tauriAPI = {
     await store.save();
   },
 
+  async updateLlmFallbackChain(chain: LlmFallbackEntry[]): Promise<void> {
+    const store = await getStore();
+    await store.set("llm_fallback_chain", chain);
+    await store.save();
+  },
+
+  async updateLlmOutputGuardrail(guardrail: OutputGuardrail): Promise<void> {
+    const store = await getStore();
+    await store.set("llm_output_guardrail", guardrail);
+    await store.save();
+  },
+
+  async updateUserName(name: string | null): Promise<void> {
+    const store = await getStore();
+    await store.set("user_name", name?.trim() ? name.trim() : null);
+    await store.save();
+  },
+
+  async updateTransformPrompt(prompt: string | null): Promise<void> {
+    const store = await getStore();
+    await store.set("transform_prompt", prompt?.trim() ? prompt : null);
+    await store.save();
+  },
+
+  async updateDictionaryEntries(entries: DictionaryEntry[]): Promise<void> {
+    const store = await getStore();
+    await store.set("dictionary_entries", entries);
+    await store.save();
+  },
+
   async updateOpenAiReasoningEffort(
     effort: OpenAiReasoningEffort | null
   ): Promise<void> {
@@ -990,6 +1332,14 @@ export const tauriAPI = {
     await store.save();
   },
 
+  async updateOpenAiCompatibleLlm(
+    config: OpenAiCompatibleLlmConfig
+  ): Promise<void> {
+    const store = await getStore();
+    await store.set("openai_compatible_llm", config);
+    await store.save();
+  },
+
   async updatePlayingAudioHandling(
     handling: PlayingAudioHandling
   ): Promise<void> {
@@ -1038,6 +1388,18 @@ export const tauriAPI = {
     await store.save();
   },
 
+  async updateLlmStreamingEnabled(enabled: boolean): Promise<void> {
+    const store = await getStore();
+    await store.set("llm_streaming_enabled", enabled);
+    await store.save();
+  },
+
+  async updateOutputStreamingChunks(enabled: boolean): Promise<void> {
+    const store = await getStore();
+    await store.set("output_streaming_chunks", enabled);
+    await store.save();
+  },
+
   async updateQuietAudioGateEnabled(enabled: boolean): Promise<void> {
     const store = await getStore();
     await store.set("quiet_audio_gate_enabled", enabled);
@@ -1124,6 +1486,12 @@ export const tauriAPI = {
     await store.save();
   },
 
+  async updateAudioUploadEncoding(encoding: AudioUploadEncoding): Promise<void> {
+    const store = await getStore();
+    await store.set("audio_upload_encoding", encoding);
+    await store.save();
+  },
+
   async updateMaxSavedRecordings(max: number): Promise<void> {
     const store = await getStore();
     await store.set("max_saved_recordings", normalizeMaxSavedRecordings(max));
@@ -1243,6 +1611,7 @@ export const tauriAPI = {
     await store.set("toggle_hotkey", defaultToggleHotkey);
     await store.set("hold_hotkey", defaultHoldHotkey);
     await store.set("paste_last_hotkey", defaultPasteLastHotkey);
+    await store.set("transform_hotkey", defaultTransformHotkey);
     await store.save();
   },
 
@@ -1263,6 +1632,14 @@ export const tauriAPI = {
     return invoke("get_history", { limit });
   },
 
+  // Correct an entry's text; returns the dictionary suggestions learned from the edit.
+  async updateHistoryEntry(
+    id: string,
+    text: string
+  ): Promise<DictionarySuggestion[]> {
+    return invoke("update_history_entry", { id, text });
+  },
+
   async deleteHistoryEntry(id: string): Promise<boolean> {
     return invoke("delete_history_entry", { id });
   },
@@ -1297,6 +1674,24 @@ export const tauriAPI = {
     );
   },
 
+  // Streamed LLM rewrite text (emitted by Rust while the rewrite is generated)
+  async onLlmRewriteDelta(
+    callback: (payload: LlmRewriteDelta) => void
+  ): Promise<UnlistenFn> {
+    return listen<LlmRewriteDelta>("llm-rewrite-delta", (event) => {
+      callback(event.payload);
+    });
+  },
+
+  // Notices about completed dictations (e.g. streamed text that had to be replaced)
+  async onPipelineNotice(
+    callback: (notice: PipelineNotice) => void
+  ): Promise<UnlistenFn> {
+    return listen<PipelineNotice>("pipeline-notice", (event) => {
+      callback(event.payload);
+    });
+  },
+
   // History sync between windows
   async emitHistoryChanged(): Promise<void> {
     return emit("history-changed", {});
@@ -1347,6 +1742,34 @@ export interface LlmProviderInfo {
   models: string[];
 }
 
+// Prompt sections as the backend takes them (null = default text for that section).
+export interface PromptConfigPayload {
+  main_custom: string | null;
+  advanced_enabled: boolean;
+  advanced_custom: string | null;
+  dictionary_enabled: boolean;
+  dictionary_custom: string | null;
+}
+
+// Values for the {{variable}} placeholders in custom prompts (null renders as "").
+export interface PromptContext {
+  app_name: string | null;
+  window_title: string | null;
+  date: string | null;
+  time: string | null;
+  profile: string | null;
+  user_name: string | null;
+  previous_output: string | null;
+  clipboard: string | null;
+}
+
+export interface PromptPreviewResponse {
+  prompt: string;
+  unknown_variables: string[];
+  available_variables: string[];
+  context: PromptContext;
+}
+
 export interface LlmCompleteResponse {
   output: string;
   provider_used: string;
@@ -1363,6 +1786,18 @@ export const llmAPI = {
       profile_id: params.profileId ?? null,
     }),
 
+  // Render the system prompt with the current variable values. Pass `prompts` to preview
+  // unsaved edits; otherwise the profile's (or Default) saved prompts are used.
+  previewSystemPrompt: (params: {
+    profileId?: string | null;
+    prompts?: PromptConfigPayload | null;
+  }) =>
+    invoke<PromptPreviewResponse>("preview_llm_system_prompt", {
+      prompts: params.prompts ?? null,
+      // Rust param name is `profile_id`
+      profile_id: params.profileId ?? null,
+    }),
+
   complete: (params: {
     provider: string;
     model?: string | null;
@@ -1402,8 +1837,60 @@ export const sttAPI = {
     invoke<string>("pipeline_retry_transcription", {
       requestId: params.requestId,
     }),
+
+  getProviderHealth: () =>
+    invoke<ProviderHealthSnapshot[]>("pipeline_get_provider_health"),
+
+  resetProviderHealth: () => invoke<void>("pipeline_reset_provider_health"),
+
+  // Dictations waiting to be retried after a network failure (oldest first).
+  getOfflineRetryQueue: () =>
+    invoke<QueuedRetry[]>("offline_retry_queue_list"),
+
+  // Stop retrying a queued dictation automatically (its history entry becomes an error).
+  removeFromOfflineRetryQueue: (params: { requestId: string }) =>
+    invoke<boolean>("offline_retry_queue_remove", {
+      requestId: params.requestId,
+    }),
 };
 
+export interface QueuedRetry {
+  request_id: string;
+  queued_at: string;
+  attempts: number;
+  last_error: string | null;
+}
+
+// Persisted under the `offline_retry` settings key.
+export interface OfflineRetryConfig {
+  enabled: boolean;
+  check_interval_secs: number;
+  max_attempts: number;
+  copy_to_clipboard: boolean;
+}
+
+// Payload of the `offline-retry-completed` event.
+export interface OfflineRetryCompletedEvent {
+  request_id: string;
+  text: string;
+  copied_to_clipboard: boolean;
+}
+
+export interface ProviderHealthSnapshot {
+  kind: "stt" | "llm";
+  provider: string;
+  model: string | null;
+  circuit: "closed" | "open" | "half_open";
+  // Share of successful requests in the rolling window (0..1).
+  success_rate: number;
+  samples: number;
+  avg_latency_ms: number | null;
+  consecutive_failures: number;
+  last_error: string | null;
+  // Time until an open circuit lets a trial request through.
+  retry_in_ms: number | null;
+}
+
 export interface AudioLevelStats {
   duration_secs: number;
   rms: number;
@@ -1435,6 +1922,7 @@ export interface DefaultSectionsResponse {
   main: string;
   advanced: string;
   dictionary: string;
+  transform: string;
 }
 
 interface ProviderInfo {
@@ -1475,12 +1963,45 @@ export interface LogEntry {
   details: string | null;
 }
 
+export interface SttHedgeLog {
+  winner: "primary" | "hedge";
+  provider: string;
+  model: string | null;
+  hedge_delay_ms: number;
+  hedge_sent: boolean;
+  latency_ms: number;
+}
+
+export interface HallucinationDrop {
+  text: string;
+  reason: "no_speech" | "repeat" | "blocklist";
+  no_speech_prob?: number;
+  avg_logprob?: number | null;
+  phrase?: string;
+  start?: number;
+  end?: number;
+}
+
+export interface ExpandedSnippet {
+  snippet_id: string;
+  trigger: string;
+  spoken: string;
+  full_match: boolean;
+}
+
+export interface DictionaryReplacement {
+  spoken: string;
+  term: string;
+}
+
 export interface RequestLog {
   id: string;
   started_at: string;
   ended_at: string | null;
   stt_provider: string;
   stt_model: string | null;
+  // Request whose saved recording this log re-transcribes (offline retry queue).
+  retry_of?: string;
   llm_provider: string | null;
   llm_model: string | null;
   raw_transcript: string | null;
@@ -1495,6 +2016,15 @@ export interface RequestLog {
   // Binary audio is redacted and represented with placeholders.
   stt_request_json?: unknown;
   stt_response_json?: unknown;
+  stt_transcript?: Transcript;
+  stt_hedge?: SttHedgeLog;
+  hallucination_drops?: HallucinationDrop[];
+  dictionary_replacements?: DictionaryReplacement[];
+  snippet_expansions?: ExpandedSnippet[];
+  // Recording size, and the uploaded size when the audio was compressed.
+  audio_size_bytes?: number | null;
+  audio_upload_encoding?: AudioUploadEncoding;
+  audio_upload_size_bytes?: number;
   llm_request_json?: unknown;
   llm_response_json?: unknown;
 }
@@ -1511,6 +2041,85 @@ export const logsAPI = {
   clearRequestLogs: () => invoke<void>("clear_request_logs"),
 };
 
+// ============================================================================
+// Voice Snippets API
+// ============================================================================
+
+export interface Snippet {
+  id: string;
+  // Spoken phrases that insert the snippet (matched loosely).
+  triggers: string[];
+  // Inserted text; may use the prompt variables ({{date}}, {{clipboard}}, ...).
+  body: string;
+  enabled: boolean;
+  // Also expand multi-word triggers spoken inside a longer dictation.
+  inline: boolean;
+}
+
+export type SnippetInput = Omit<Snippet, "id">;
+
+export const snippetsAPI = {
+  listSnippets: () => invoke<Snippet[]>("list_snippets"),
+
+  addSnippet: (snippet: SnippetInput) =>
+    invoke<Snippet>("add_snippet", { snippet }),
+
+  updateSnippet: (id: string, snippet: SnippetInput) =>
+    invoke<Snippet>("update_snippet", { id, snippet }),
+
+  deleteSnippet: (id: string) => invoke<boolean>("delete_snippet", { id }),
+};
+
+// ============================================================================
+// Personal Dictionary
+// ============================================================================
+
+export interface DictionaryEntry {
+  id: string;
+  // Canonical spelling, e.g. "Anthropic".
+  term: string;
+  // Spoken or misheard forms replaced by the term, e.g. "ant row pic".
+  spoken: string[];
+  // Match the spoken forms with exact case.
+  case_sensitive: boolean;
+  // Also match the term itself in any case and fix its capitalization (off by default, so a
+  // common word like "cursor" is left alone).
+  match_term: boolean;
+  // Only match whole words.
+  whole_word: boolean;
+  // Profile the entry is limited to (null = all profiles).
+  profile_id: string | null;
+}
+
+// ============================================================================
+// Dictionary Suggestions API (auto-learning dictionary)
+// ============================================================================
+
+export interface DictionarySuggestion {
+  id: string;
+  // Misrecognized words, e.g. "ant row pic".
+  heard: string;
+  // Correct spelling, e.g. "Anthropic".
+  correct: string;
+  // Profile the entry is learned in (null = Default).
+  profile_id: string | null;
+  source: "history_edit" | "redictation";
+  times_seen: number;
+  created_at: string;
+}
+
+export const dictionaryAPI = {
+  listSuggestions: () =>
+    invoke<DictionarySuggestion[]>("list_dictionary_suggestions"),
+
+  // Adds "heard" as a spoken form of the "correct" dictionary entry; false if it was already there.
+  approveSuggestion: (id: string) =>
+    invoke<boolean>("approve_dictionary_suggestion", { id }),
+
+  rejectSuggestion: (id: string) =>
+    invoke<boolean>("reject_dictionary_suggestion", { id }),
+};
+
 // ============================================================================
 // Recordings API (playback)
 // ============================================================================